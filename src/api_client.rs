@@ -0,0 +1,72 @@
+use reqwest::Client;
+use std::time::Duration;
+
+/// Default per-request timeout for real-time translation calls
+pub const DEFAULT_DEEPL_TIMEOUT: Duration = Duration::from_millis(5000);
+/// Default per-request timeout for summarization; minutes take a while to generate
+pub const DEFAULT_SUMMARY_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Timeout and connection pool tuning for an outbound API client
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientSettings {
+    pub timeout: Duration,
+    pub pool_max_idle_per_host: usize,
+    pub pool_idle_timeout: Duration,
+    pub tcp_keepalive: Duration,
+}
+
+impl ClientSettings {
+    pub fn with_timeout(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            pool_max_idle_per_host: 16,
+            pool_idle_timeout: Duration::from_secs(90),
+            tcp_keepalive: Duration::from_secs(60),
+        }
+    }
+
+    pub fn build(&self) -> Result<Client, reqwest::Error> {
+        Client::builder()
+            .timeout(self.timeout)
+            .pool_max_idle_per_host(self.pool_max_idle_per_host)
+            .pool_idle_timeout(self.pool_idle_timeout)
+            .tcp_keepalive(self.tcp_keepalive)
+            .build()
+    }
+}
+
+/// Timeout from an env var holding milliseconds; zero or unparsable values use the default
+pub fn timeout_from_env(var: &str, default: Duration) -> Duration {
+    std::env::var(var)
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .filter(|ms| *ms > 0)
+        .map(Duration::from_millis)
+        .unwrap_or(default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    #[tokio::test]
+    async fn test_configured_timeout_is_applied() {
+        // A server that accepts connections but never answers
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut held = Vec::new();
+            while let Ok((socket, _)) = listener.accept().await {
+                held.push(socket);
+            }
+        });
+
+        let client = ClientSettings::with_timeout(Duration::from_millis(150)).build().unwrap();
+        let started = std::time::Instant::now();
+        let err = client.get(format!("http://{}/", addr)).send().await.unwrap_err();
+
+        assert!(err.is_timeout());
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}
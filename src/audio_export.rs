@@ -0,0 +1,179 @@
+use std::error::Error;
+use std::io::Cursor;
+use std::ops::Range;
+use std::path::Path;
+use hound::{WavReader, WavWriter};
+use twilight_http::Client as HttpClient;
+use twilight_model::guild::PremiumTier;
+use twilight_model::http::attachment::Attachment;
+use twilight_model::id::Id;
+use twilight_model::id::marker::{ChannelMarker, GuildMarker};
+use crate::error::AppError;
+
+/// Upload limit for guilds without boosts, or when the tier can't be fetched
+pub const DEFAULT_UPLOAD_LIMIT: u64 = 25 * 1024 * 1024;
+
+/// Canonical WAV header size written by hound for PCM files
+const WAV_HEADER_LEN: u64 = 44;
+
+/// Room left for multipart encoding overhead so parts don't land exactly on the limit
+const UPLOAD_HEADROOM: u64 = 64 * 1024;
+
+/// Per-attachment upload limit for a guild's boost tier
+pub fn upload_limit(tier: PremiumTier) -> u64 {
+    match tier {
+        PremiumTier::Tier2 => 50 * 1024 * 1024,
+        PremiumTier::Tier3 => 100 * 1024 * 1024,
+        _ => DEFAULT_UPLOAD_LIMIT,
+    }
+}
+
+/// Byte ranges of the PCM data for each part, so every part's WAV fits in `limit`.
+/// Parts end on whole frames (`block_align` bytes) to keep channels and samples intact.
+pub fn split_points(data_len: u64, limit: u64, block_align: u64) -> Vec<Range<u64>> {
+    let block_align = block_align.max(1);
+    let budget = limit.saturating_sub(WAV_HEADER_LEN + UPLOAD_HEADROOM);
+    let part_len = (budget / block_align * block_align).max(block_align);
+
+    (0..data_len)
+        .step_by(part_len as usize)
+        .map(|start| start..(start + part_len).min(data_len))
+        .collect()
+}
+
+/// Read a 16-bit WAV and split it into in-memory WAV parts of at most `limit` bytes each
+pub fn split_wav(path: &Path, limit: u64) -> Result<Vec<Vec<u8>>, AppError> {
+    let mut reader = WavReader::open(path)?;
+    let spec = reader.spec();
+    if spec.bits_per_sample != 16 || spec.sample_format != hound::SampleFormat::Int {
+        return Err(AppError::Encoding(format!("Can't split {}: only 16-bit PCM is supported", path.display())));
+    }
+    let samples = reader.samples::<i16>().collect::<Result<Vec<_>, _>>()?;
+
+    let block_align = spec.channels as u64 * 2;
+    let data_len = samples.len() as u64 * 2;
+    let mut parts = Vec::new();
+    for range in split_points(data_len, limit, block_align) {
+        let mut buffer = Cursor::new(Vec::new());
+        let mut writer = WavWriter::new(&mut buffer, spec)?;
+        for sample in &samples[(range.start / 2) as usize..(range.end / 2) as usize] {
+            writer.write_sample(*sample)?;
+        }
+        writer.finalize()?;
+        parts.push(buffer.into_inner());
+    }
+    Ok(parts)
+}
+
+/// The guild's upload limit, falling back to the default if the guild can't be fetched
+pub async fn guild_upload_limit(http: &HttpClient, guild_id: Id<GuildMarker>) -> u64 {
+    match http.guild(guild_id).await {
+        Ok(response) => match response.model().await {
+            Ok(guild) => upload_limit(guild.premium_tier),
+            Err(_) => DEFAULT_UPLOAD_LIMIT,
+        },
+        Err(e) => {
+            eprintln!("[WARN] Couldn't fetch guild {} for its upload limit: {}", guild_id, e);
+            DEFAULT_UPLOAD_LIMIT
+        }
+    }
+}
+
+/// Upload a recording to a channel, splitting WAVs that exceed the guild's
+/// upload limit into time-ordered parts (one per message). Returns the number of parts posted.
+pub async fn export_recording(
+    http: &HttpClient,
+    guild_id: Id<GuildMarker>,
+    channel_id: Id<ChannelMarker>,
+    path: &Path,
+) -> Result<usize, Box<dyn Error + Send + Sync>> {
+    let limit = guild_upload_limit(http, guild_id).await;
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "recording.wav".to_string());
+    let size = tokio::fs::metadata(path).await?.len();
+
+    if size <= limit {
+        let attachment = Attachment::from_bytes(file_name.clone(), tokio::fs::read(path).await?, 0);
+        let content = format!("🎧 `{}`", file_name);
+        http.create_message(channel_id).content(&content).attachments(&[attachment]).await?;
+        return Ok(1);
+    }
+
+    if path.extension().and_then(|ext| ext.to_str()) != Some("wav") {
+        return Err(format!("{} is {} bytes, over the {} byte upload limit", file_name, size, limit).into());
+    }
+
+    let owned_path = path.to_path_buf();
+    let parts = tokio::task::spawn_blocking(move || split_wav(&owned_path, limit)).await??;
+    let stem = file_name.trim_end_matches(".wav");
+    println!("[INFO] Splitting {} ({} bytes) into {} parts for upload", file_name, size, parts.len());
+
+    let total = parts.len();
+    for (index, part) in parts.into_iter().enumerate() {
+        let part_name = format!("{}_part{}of{}.wav", stem, index + 1, total);
+        let content = format!("🎧 `{}` (part {}/{})", file_name, index + 1, total);
+        let attachment = Attachment::from_bytes(part_name, part, 0);
+        http.create_message(channel_id).content(&content).attachments(&[attachment]).await?;
+    }
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_points() {
+        const MB: u64 = 1024 * 1024;
+
+        // Fits in one part
+        assert_eq!(split_points(10 * MB, 25 * MB, 2), vec![0..10 * MB]);
+
+        // 60MB of stereo 16-bit audio against the 25MB limit: three parts, frame-aligned
+        let parts = split_points(60 * MB, 25 * MB, 4);
+        assert_eq!(parts.len(), 3);
+        assert_eq!(parts[0].start, 0);
+        assert_eq!(parts.last().unwrap().end, 60 * MB);
+        for window in parts.windows(2) {
+            assert_eq!(window[0].end, window[1].start);
+        }
+        for part in &parts {
+            assert_eq!(part.start % 4, 0);
+            assert!(part.end - part.start + WAV_HEADER_LEN <= 25 * MB);
+        }
+
+        assert!(split_points(0, 25 * MB, 2).is_empty());
+        assert_eq!(upload_limit(PremiumTier::None), DEFAULT_UPLOAD_LIMIT);
+        assert_eq!(upload_limit(PremiumTier::Tier3), 100 * MB);
+    }
+
+    #[test]
+    fn test_split_wav_parts_are_valid() {
+        let path = std::env::temp_dir().join(format!("split_test_{}.wav", std::process::id()));
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: 48000,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = WavWriter::create(&path, spec).unwrap();
+        for i in 0..100_000 {
+            writer.write_sample((i % 1000) as i16).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        let limit = 100 * 1024;
+        let parts = split_wav(&path, limit).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(parts.len(), 6);
+        let mut total = 0;
+        for part in &parts {
+            assert!(part.len() as u64 <= limit);
+            total += WavReader::new(Cursor::new(part)).unwrap().len();
+        }
+        assert_eq!(total, 100_000);
+    }
+}
@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Notify;
+use twilight_model::channel::message::EmojiReactionType;
+use twilight_model::id::Id;
+use twilight_model::id::marker::{MessageMarker, UserMarker};
+
+/// Reaction participants use to object to a recording
+pub const CONSENT_OBJECTION_EMOJI: &str = "❌";
+
+/// Seconds participants have to object when the guild hasn't configured a window
+pub const DEFAULT_CONSENT_WINDOW_SECS: u64 = 15;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConsentOutcome {
+    /// The window passed without objections
+    Proceed,
+    /// Someone objected; recording must not start
+    Objected(Id<UserMarker>),
+}
+
+struct ConsentWindow {
+    objector: Option<Id<UserMarker>>,
+    notify: Arc<Notify>,
+}
+
+/// Open consent prompts, keyed by the announcement message
+pub struct ConsentTracker {
+    bot_user_id: Id<UserMarker>,
+    windows: Mutex<HashMap<Id<MessageMarker>, ConsentWindow>>,
+}
+
+impl ConsentTracker {
+    pub fn new(bot_user_id: Id<UserMarker>) -> Self {
+        Self {
+            bot_user_id,
+            windows: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Start accepting objections on an announcement message. The window stays open
+    /// until the returned prompt is waited on or dropped, so an error between opening
+    /// and waiting doesn't leave it behind.
+    pub fn open(&self, message_id: Id<MessageMarker>) -> PendingConsent<'_> {
+        let notify = Arc::new(Notify::new());
+        self.windows.lock().unwrap().insert(message_id, ConsentWindow {
+            objector: None,
+            notify: notify.clone(),
+        });
+        PendingConsent { tracker: self, message_id, notify }
+    }
+
+    /// Handle a reaction; returns true if it was an objection to an open prompt.
+    /// The bot's own ❌ (added so people can click it) doesn't count.
+    pub async fn record_reaction(
+        &self,
+        message_id: Id<MessageMarker>,
+        user_id: Id<UserMarker>,
+        emoji: &EmojiReactionType,
+    ) -> bool {
+        let is_objection = matches!(emoji, EmojiReactionType::Unicode { name } if name == CONSENT_OBJECTION_EMOJI);
+        if !is_objection || user_id == self.bot_user_id {
+            return false;
+        }
+
+        let mut windows = self.windows.lock().unwrap();
+        let Some(window) = windows.get_mut(&message_id) else {
+            return false;
+        };
+        window.objector.get_or_insert(user_id);
+        window.notify.notify_one();
+        true
+    }
+
+    fn close(&self, message_id: Id<MessageMarker>) -> Option<Id<UserMarker>> {
+        self.windows.lock().unwrap().remove(&message_id).and_then(|window| window.objector)
+    }
+
+    #[cfg(test)]
+    fn open_count(&self) -> usize {
+        self.windows.lock().unwrap().len()
+    }
+}
+
+/// A consent prompt accepting objections; closed when waited on or dropped
+pub struct PendingConsent<'a> {
+    tracker: &'a ConsentTracker,
+    message_id: Id<MessageMarker>,
+    notify: Arc<Notify>,
+}
+
+impl PendingConsent<'_> {
+    /// Wait for the window to pass, returning early on the first objection
+    pub async fn wait(self, window: Duration) -> ConsentOutcome {
+        let _ = tokio::time::timeout(window, self.notify.notified()).await;
+        match self.tracker.close(self.message_id) {
+            Some(user_id) => ConsentOutcome::Objected(user_id),
+            None => ConsentOutcome::Proceed,
+        }
+    }
+}
+
+impl Drop for PendingConsent<'_> {
+    fn drop(&mut self) {
+        self.tracker.close(self.message_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn objection() -> EmojiReactionType {
+        EmojiReactionType::Unicode { name: CONSENT_OBJECTION_EMOJI.to_string() }
+    }
+
+    #[tokio::test]
+    async fn test_window_without_objection_proceeds() {
+        let bot: Id<UserMarker> = Id::new(1);
+        let tracker = ConsentTracker::new(bot);
+        let message = Id::new(100);
+        let prompt = tracker.open(message);
+
+        // The bot's own reaction and other emoji aren't objections
+        assert!(!tracker.record_reaction(message, bot, &objection()).await);
+        let thumbs_up = EmojiReactionType::Unicode { name: "👍".to_string() };
+        assert!(!tracker.record_reaction(message, Id::new(2), &thumbs_up).await);
+
+        assert_eq!(prompt.wait(Duration::from_millis(20)).await, ConsentOutcome::Proceed);
+
+        // Reactions after the window closed are ignored
+        assert!(!tracker.record_reaction(message, Id::new(2), &objection()).await);
+        assert_eq!(tracker.open_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_abandoned_prompt_is_closed() {
+        let tracker = ConsentTracker::new(Id::new(1));
+        let message = Id::new(100);
+        // e.g. adding the ❌ reaction failed and the prompt was never waited on
+        drop(tracker.open(message));
+        assert_eq!(tracker.open_count(), 0);
+        assert!(!tracker.record_reaction(message, Id::new(2), &objection()).await);
+    }
+
+    #[tokio::test]
+    async fn test_objection_aborts_early() {
+        let tracker = Arc::new(ConsentTracker::new(Id::new(1)));
+        let message = Id::new(100);
+        let prompt = tracker.open(message);
+
+        let objecting = tracker.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            objecting.record_reaction(message, Id::new(3), &objection()).await;
+        });
+
+        let outcome = tokio::time::timeout(
+            Duration::from_secs(1),
+            prompt.wait(Duration::from_secs(60)),
+        ).await.unwrap();
+        assert_eq!(outcome, ConsentOutcome::Objected(Id::new(3)));
+    }
+}
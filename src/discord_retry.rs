@@ -0,0 +1,111 @@
+use std::future::Future;
+use std::time::Duration;
+use twilight_http::Client as HttpClient;
+use twilight_http::api_error::ApiError;
+use twilight_http::error::ErrorType;
+use twilight_model::id::Id;
+use twilight_model::id::marker::ChannelMarker;
+
+/// Tries per message before giving up, including the first
+pub const MAX_SEND_ATTEMPTS: u32 = 4;
+/// Wait before the first retry of a 5xx; doubled for each further retry
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(1);
+/// Longest rate limit wait honored; anything longer gives up instead of stalling the caller
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+/// How long to wait before sending again after a failed attempt (1-based), or None
+/// to give up. 429s wait as long as Discord asks and 5xx back off. Anything without
+/// a response is not retried: the message may already have been posted, and
+/// sending it again would duplicate it.
+pub fn retry_delay(status: Option<u16>, retry_after_secs: Option<f64>, attempt: u32) -> Option<Duration> {
+    if attempt >= MAX_SEND_ATTEMPTS {
+        return None;
+    }
+    let backoff = RETRY_BASE_DELAY * 2u32.pow(attempt - 1);
+    match status {
+        Some(429) => {
+            let wait = retry_after_secs
+                .filter(|secs| secs.is_finite() && *secs >= 0.0)
+                .map(Duration::from_secs_f64)
+                .unwrap_or(backoff);
+            (wait <= MAX_RETRY_AFTER).then_some(wait)
+        }
+        Some(status) if status >= 500 => Some(backoff),
+        _ => None,
+    }
+}
+
+fn retry_delay_for(error: &twilight_http::Error, attempt: u32) -> Option<Duration> {
+    match error.kind() {
+        ErrorType::Response { error, status, .. } => {
+            let retry_after = match error {
+                ApiError::Ratelimited(ratelimited) => Some(ratelimited.retry_after),
+                _ => None,
+            };
+            retry_delay(Some(status.get()), retry_after, attempt)
+        }
+        ErrorType::ServiceUnavailable { .. } => retry_delay(Some(503), None, attempt),
+        _ => None,
+    }
+}
+
+/// Run a Discord request, sending it again after rate limits and server errors.
+/// `send` builds a fresh request for each attempt; `what` names it in the log.
+pub async fn send_resilient<T, F, Fut>(what: &str, mut send: F) -> Result<T, twilight_http::Error>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, twilight_http::Error>>,
+{
+    let mut attempt = 1;
+    loop {
+        let error = match send().await {
+            Ok(response) => return Ok(response),
+            Err(e) => e,
+        };
+        let Some(delay) = retry_delay_for(&error, attempt) else {
+            return Err(error);
+        };
+        eprintln!(
+            "[WARN] {} failed (attempt {}/{}), retrying in {:?}: {}",
+            what, attempt, MAX_SEND_ATTEMPTS, delay, error
+        );
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+/// Post a message, retrying rate limits and transient Discord errors so meeting
+/// results aren't lost to a single failed request
+pub async fn send_message_resilient(
+    http: &HttpClient,
+    channel_id: Id<ChannelMarker>,
+    content: &str,
+) -> Result<(), twilight_http::Error> {
+    let what = format!("Sending message to channel {}", channel_id);
+    send_resilient(&what, || async { http.create_message(channel_id).content(content).await.map(drop) }).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_delay() {
+        // Rate limited: wait exactly as long as Discord says
+        assert_eq!(retry_delay(Some(429), Some(2.5), 1), Some(Duration::from_millis(2500)));
+        assert_eq!(retry_delay(Some(429), None, 2), Some(Duration::from_secs(2)));
+        assert_eq!(retry_delay(Some(429), Some(600.0), 1), None);
+
+        // Server errors back off
+        assert_eq!(retry_delay(Some(502), None, 1), Some(Duration::from_secs(1)));
+        assert_eq!(retry_delay(Some(500), None, 3), Some(Duration::from_secs(4)));
+        assert_eq!(retry_delay(Some(503), None, MAX_SEND_ATTEMPTS), None);
+
+        // Without a response the message may have gone through; resending could post it twice
+        assert_eq!(retry_delay(None, None, 1), None);
+
+        // Client errors won't succeed on a retry
+        assert_eq!(retry_delay(Some(403), None, 1), None);
+        assert_eq!(retry_delay(Some(400), Some(1.0), 1), None);
+    }
+}
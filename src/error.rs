@@ -0,0 +1,163 @@
+use std::time::Duration;
+use thiserror::Error;
+
+/// Why a translation failed, so callers can tell the user something useful
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TranslationFailure {
+    /// DeepL couldn't be reached
+    Network,
+    /// Rate limited or server-side trouble; worth retrying
+    Transient,
+    /// Missing or invalid API key; retrying can't help
+    Auth,
+    /// The request itself was rejected
+    BadRequest,
+    /// Character quota for the billing period is used up
+    QuotaExceeded,
+    /// Short-circuited while DeepL keeps failing
+    Unavailable,
+    UnsupportedLanguage,
+    Other,
+}
+
+impl TranslationFailure {
+    /// Whether DeepL itself is down or overloaded, as opposed to rejecting this
+    /// request or the bot's key. Only these count toward the circuit breaker.
+    pub fn is_outage(self) -> bool {
+        matches!(self, TranslationFailure::Network | TranslationFailure::Transient)
+    }
+}
+
+/// Classify a DeepL HTTP status
+pub fn classify_deepl_status(status: u16) -> TranslationFailure {
+    match status {
+        429 | 500..=599 => TranslationFailure::Transient,
+        401 | 403 => TranslationFailure::Auth,
+        400 => TranslationFailure::BadRequest,
+        456 => TranslationFailure::QuotaExceeded,
+        _ => TranslationFailure::Other,
+    }
+}
+
+/// Errors from the bot's transcription, translation, summarization, voice and audio
+/// layers. Parsers and validators of user or config input return the message as a
+/// `String` instead, and the Discord-facing glue (command handlers, webhooks,
+/// transcript hooks) keeps boxed errors.
+#[derive(Debug, Error)]
+pub enum AppError {
+    #[error("Whisper model not found at: {0}")]
+    ModelMissing(String),
+
+    #[error("Transcription failed: {0}")]
+    Transcription(String),
+
+    #[error("Transcription timed out after {}s", .0.as_secs_f32())]
+    TranscriptionTimeout(Duration),
+
+    #[error("Transcription cancelled")]
+    TranscriptionCancelled,
+
+    #[error("{message}")]
+    Translation {
+        failure: TranslationFailure,
+        /// HTTP status DeepL answered with, if it answered
+        status: Option<u16>,
+        message: String,
+    },
+
+    #[error("Summarization failed: {0}")]
+    Summarization(String),
+
+    #[error("Voice connection failed: {0}")]
+    Voice(String),
+
+    #[error("Audio encoding failed: {0}")]
+    Encoding(String),
+
+    /// A `/transcribe_url` link or file that can't be used; the message is for the user
+    #[error("{0}")]
+    RemoteAudio(String),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+impl AppError {
+    pub fn translation(failure: TranslationFailure, status: Option<u16>, message: impl Into<String>) -> Self {
+        AppError::Translation { failure, status, message: message.into() }
+    }
+
+    /// The translation failure kind, if this is a translation error
+    pub fn translation_failure(&self) -> Option<TranslationFailure> {
+        match self {
+            AppError::Translation { failure, .. } => Some(*failure),
+            _ => None,
+        }
+    }
+}
+
+impl From<whisper_rs::WhisperError> for AppError {
+    fn from(e: whisper_rs::WhisperError) -> Self {
+        AppError::Transcription(e.to_string())
+    }
+}
+
+impl From<hound::Error> for AppError {
+    fn from(e: hound::Error) -> Self {
+        match e {
+            hound::Error::IoError(e) => AppError::Io(e),
+            other => AppError::Transcription(other.to_string()),
+        }
+    }
+}
+
+impl From<tokio::task::JoinError> for AppError {
+    fn from(e: tokio::task::JoinError) -> Self {
+        AppError::Transcription(format!("worker thread failed: {}", e))
+    }
+}
+
+impl From<audiopus::Error> for AppError {
+    fn from(e: audiopus::Error) -> Self {
+        AppError::Encoding(e.to_string())
+    }
+}
+
+impl From<songbird::error::JoinError> for AppError {
+    fn from(e: songbird::error::JoinError) -> Self {
+        AppError::Voice(e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_underlying_errors_map_to_variants() {
+        let io = std::io::Error::new(std::io::ErrorKind::NotFound, "gone");
+        assert!(matches!(AppError::from(io), AppError::Io(_)));
+
+        let wav = hound::Error::IoError(std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "eof"));
+        assert!(matches!(AppError::from(wav), AppError::Io(_)));
+        assert!(matches!(AppError::from(hound::Error::FormatError("bad header")), AppError::Transcription(_)));
+        assert!(matches!(AppError::from(whisper_rs::WhisperError::InvalidThreadCount), AppError::Transcription(_)));
+
+        let quota = AppError::translation(classify_deepl_status(456), Some(456), "DeepL API quota exceeded (456)");
+        assert_eq!(quota.translation_failure(), Some(TranslationFailure::QuotaExceeded));
+        assert_eq!(quota.to_string(), "DeepL API quota exceeded (456)");
+        assert_eq!(AppError::Summarization("x".to_string()).translation_failure(), None);
+
+        let timeout = AppError::TranscriptionTimeout(Duration::from_secs(2));
+        assert_eq!(timeout.to_string(), "Transcription timed out after 2s");
+
+        // Still usable wherever a boxed error is expected
+        let boxed: Box<dyn std::error::Error + Send + Sync> = AppError::Voice("no channel".to_string()).into();
+        assert_eq!(boxed.to_string(), "Voice connection failed: no channel");
+
+        assert_eq!(
+            AppError::RemoteAudio("The file is larger than 50 MB".to_string()).to_string(),
+            "The file is larger than 50 MB"
+        );
+    }
+}
@@ -0,0 +1,87 @@
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+use twilight_model::id::Id;
+use twilight_model::id::marker::{GuildMarker, UserMarker};
+
+use crate::error::AppError;
+
+/// Where `/feedback` corrections are appended unless `FEEDBACK_LOG_PATH` is set
+pub const DEFAULT_FEEDBACK_LOG: &str = "./feedback.jsonl";
+
+/// One reported transcription mistake, a line of the feedback log
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeedbackEntry {
+    pub timestamp: String,
+    /// None when reported from a DM
+    pub guild_id: Option<Id<GuildMarker>>,
+    pub user_id: Id<UserMarker>,
+    pub wrong: String,
+    pub correct: String,
+}
+
+impl FeedbackEntry {
+    pub fn new(guild_id: Option<Id<GuildMarker>>, user_id: Id<UserMarker>, wrong: &str, correct: &str) -> Self {
+        Self {
+            timestamp: Local::now().to_rfc3339(),
+            guild_id,
+            user_id,
+            wrong: wrong.trim().to_string(),
+            correct: correct.trim().to_string(),
+        }
+    }
+}
+
+/// Append-only JSONL log of transcription corrections, mined later for vocabulary
+pub struct FeedbackLog {
+    path: PathBuf,
+    /// Keeps concurrent reports from interleaving within a line
+    write_lock: Mutex<()>,
+}
+
+impl FeedbackLog {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into(), write_lock: Mutex::new(()) }
+    }
+
+    pub async fn append(&self, entry: &FeedbackEntry) -> Result<(), AppError> {
+        let mut line = serde_json::to_string(entry).map_err(|e| AppError::Io(e.into()))?;
+        line.push('\n');
+
+        let _guard = self.write_lock.lock().await;
+        if let Some(parent) = self.path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let mut file = tokio::fs::OpenOptions::new().create(true).append(true).open(&self.path).await?;
+        file.write_all(line.as_bytes()).await?;
+        file.flush().await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_entries_are_appended_as_json_lines() {
+        let path = std::env::temp_dir().join(format!("feedback_{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let log = FeedbackLog::new(&path);
+
+        let first = FeedbackEntry::new(Some(Id::new(1)), Id::new(10), " diggy gizzy ", "Diggy-Gizzy");
+        let second = FeedbackEntry::new(None, Id::new(20), "kubernetis", "Kubernetes");
+        log.append(&first).await.unwrap();
+        log.append(&second).await.unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].contains(r#""guild_id":"1","user_id":"10","wrong":"diggy gizzy","correct":"Diggy-Gizzy""#));
+        assert_eq!(serde_json::from_str::<FeedbackEntry>(lines[1]).unwrap(), second);
+        assert_eq!(second.guild_id, None);
+    }
+}
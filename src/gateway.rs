@@ -0,0 +1,155 @@
+use twilight_gateway::Intents;
+use twilight_gateway::error::ReceiveMessageErrorType;
+use twilight_model::gateway::CloseCode;
+use twilight_model::guild::Permissions;
+
+/// Consecutive failed reconnects before the process exits so an orchestrator can restart it
+pub const MAX_CONSECUTIVE_FATAL_ERRORS: u32 = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GatewayErrorKind {
+    /// One bad message or a dropped connection; the shard carries on by itself
+    Recoverable,
+    /// The shard couldn't reconnect, or Discord refused the session for good
+    Fatal,
+}
+
+/// Classify an error returned while receiving from the gateway
+pub fn classify_receive_error(kind: &ReceiveMessageErrorType) -> GatewayErrorKind {
+    match kind {
+        ReceiveMessageErrorType::Reconnect => GatewayErrorKind::Fatal,
+        _ => GatewayErrorKind::Recoverable,
+    }
+}
+
+/// Classify a gateway close code; unknown codes are assumed to allow reconnecting
+pub fn classify_close_code(code: Option<u16>) -> GatewayErrorKind {
+    match code.map(CloseCode::try_from) {
+        Some(Ok(code)) if !code.can_reconnect() => GatewayErrorKind::Fatal,
+        _ => GatewayErrorKind::Recoverable,
+    }
+}
+
+/// Intents that must be switched on in the Developer Portal before Discord accepts them
+const PRIVILEGED_INTENTS: Intents = Intents::GUILD_MEMBERS
+    .union(Intents::GUILD_PRESENCES)
+    .union(Intents::MESSAGE_CONTENT);
+
+/// Permissions the bot uses: joining and speaking in voice, seeing and posting in text
+/// channels, translation embeds, transcript and minutes files, and reacting for consent
+/// and `/record_emoji`
+pub const REQUIRED_PERMISSIONS: Permissions = Permissions::CONNECT
+    .union(Permissions::SPEAK)
+    .union(Permissions::VIEW_CHANNEL)
+    .union(Permissions::SEND_MESSAGES)
+    .union(Permissions::EMBED_LINKS)
+    .union(Permissions::ATTACH_FILES)
+    .union(Permissions::ADD_REACTIONS);
+
+/// Comma-separated flag names, e.g. `GUILD_MEMBERS, MESSAGE_CONTENT`
+fn flag_names<'a>(names: impl Iterator<Item = (&'a str, impl Sized)>) -> String {
+    names.map(|(name, _)| name).collect::<Vec<_>>().join(", ")
+}
+
+/// Startup log line listing the permissions to grant and an invite URL carrying them
+pub fn permissions_notice(application_id: u64) -> String {
+    format!(
+        "The bot needs these permissions in each server: {}. Invite URL: https://discord.com/oauth2/authorize?client_id={}&scope=bot%20applications.commands&permissions={}",
+        flag_names(REQUIRED_PERMISSIONS.iter_names()),
+        application_id,
+        REQUIRED_PERMISSIONS.bits()
+    )
+}
+
+/// How to fix a close code that won't go away by reconnecting, or None if there's no advice
+pub fn close_code_guidance(code: u16, intents: Intents) -> Option<String> {
+    let guidance = match CloseCode::try_from(code).ok()? {
+        CloseCode::AuthenticationFailed => {
+            "The bot token was rejected. Check DISCORD_TOKEN against the Bot page of the Developer Portal.".to_string()
+        }
+        CloseCode::DisallowedIntents => format!(
+            "Discord refused the privileged intents {}. Enable them under Bot > Privileged Gateway Intents in the Developer Portal, then restart.",
+            flag_names((intents & PRIVILEGED_INTENTS).iter_names())
+        ),
+        CloseCode::InvalidIntents => "The requested gateway intents are invalid; this is a bug in the bot's configuration.".to_string(),
+        CloseCode::ShardingRequired => "The bot is in too many servers for one shard and needs sharding.".to_string(),
+        CloseCode::InvalidApiVersion => "The gateway API version is no longer supported; update the bot.".to_string(),
+        _ => return None,
+    };
+    Some(guidance)
+}
+
+/// Counts fatal errors in a row; any successfully received event resets it
+#[derive(Debug, Default)]
+pub struct FatalErrorStreak {
+    consecutive: u32,
+}
+
+impl FatalErrorStreak {
+    /// Record a fatal error; returns true once the limit is reached
+    pub fn record(&mut self) -> bool {
+        self.consecutive += 1;
+        self.consecutive >= MAX_CONSECUTIVE_FATAL_ERRORS
+    }
+
+    pub fn reset(&mut self) {
+        self.consecutive = 0;
+    }
+
+    pub fn count(&self) -> u32 {
+        self.consecutive
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_classification() {
+        assert_eq!(classify_receive_error(&ReceiveMessageErrorType::Reconnect), GatewayErrorKind::Fatal);
+        assert_eq!(
+            classify_receive_error(&ReceiveMessageErrorType::Deserializing { event: "{}".to_string() }),
+            GatewayErrorKind::Recoverable
+        );
+
+        // Authentication and intent problems won't fix themselves
+        assert_eq!(classify_close_code(Some(4004)), GatewayErrorKind::Fatal);
+        assert_eq!(classify_close_code(Some(4014)), GatewayErrorKind::Fatal);
+        // Timeouts, rate limits, normal closes and unknown codes are retried
+        assert_eq!(classify_close_code(Some(4009)), GatewayErrorKind::Recoverable);
+        assert_eq!(classify_close_code(Some(4008)), GatewayErrorKind::Recoverable);
+        assert_eq!(classify_close_code(Some(1000)), GatewayErrorKind::Recoverable);
+        assert_eq!(classify_close_code(None), GatewayErrorKind::Recoverable);
+    }
+
+    #[test]
+    fn test_close_code_guidance() {
+        let intents = Intents::GUILDS | Intents::GUILD_MEMBERS | Intents::GUILD_VOICE_STATES;
+        let disallowed = close_code_guidance(4014, intents).unwrap();
+        assert!(disallowed.contains("GUILD_MEMBERS"));
+        assert!(!disallowed.contains("GUILD_VOICE_STATES"));
+        assert!(close_code_guidance(4004, intents).unwrap().contains("DISCORD_TOKEN"));
+        // Codes that reconnect on their own, and unknown codes, have no advice
+        assert_eq!(close_code_guidance(4009, intents), None);
+        assert_eq!(close_code_guidance(1000, intents), None);
+
+        let notice = permissions_notice(123);
+        assert!(notice.contains("ADD_REACTIONS, VIEW_CHANNEL, SEND_MESSAGES, EMBED_LINKS, ATTACH_FILES, CONNECT, SPEAK."));
+        assert!(notice.contains(&format!("client_id=123&scope=bot%20applications.commands&permissions={}", REQUIRED_PERMISSIONS.bits())));
+    }
+
+    #[test]
+    fn test_fatal_streak_limit() {
+        let mut streak = FatalErrorStreak::default();
+        for _ in 1..MAX_CONSECUTIVE_FATAL_ERRORS {
+            assert!(!streak.record());
+        }
+        streak.reset();
+        assert_eq!(streak.count(), 0);
+        for _ in 1..MAX_CONSECUTIVE_FATAL_ERRORS {
+            assert!(!streak.record());
+        }
+        assert!(streak.record());
+    }
+}
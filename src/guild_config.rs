@@ -0,0 +1,783 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Weak};
+use tokio::sync::RwLock;
+use twilight_http::request::channel::reaction::RequestReactionType;
+use twilight_model::channel::message::EmojiReactionType;
+use twilight_model::id::Id;
+use twilight_model::id::marker::{ChannelMarker, EmojiMarker, GuildMarker, RoleMarker};
+use std::time::Duration;
+use crate::consent::DEFAULT_CONSENT_WINDOW_SECS;
+use crate::persist::{self, DebouncedSave};
+use crate::redaction::{DEFAULT_PROFANITY, RedactionOptions};
+use crate::summarizer;
+use crate::voice_translator::UtteranceTuning;
+
+pub const DEFAULT_CONTROL_EMOJI: &str = "🔴";
+
+/// What the bot posts that can be routed to a dedicated text channel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputKind {
+    Minutes,
+    Translations,
+}
+
+/// Editors often write a file in several steps; wait this long before reloading
+const RELOAD_DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Most vocabulary terms a guild can register; they all go into whisper's prompt
+pub const MAX_VOCAB_ENTRIES: usize = 100;
+
+/// A name or term that comes up in this guild's meetings
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VocabEntry {
+    pub term: String,
+    /// Fixed translation, applied through a DeepL glossary
+    #[serde(default)]
+    pub translation: Option<VocabTranslation>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VocabTranslation {
+    /// Language codes as used by `/translate_set` (`ja`, `en`, `ko`)
+    pub source_lang: String,
+    pub target_lang: String,
+    pub text: String,
+}
+
+/// Whisper prompt from the guild's free-form prompt followed by its vocabulary
+pub fn vocabulary_prompt(prompt: Option<&str>, terms: &[&str]) -> Option<String> {
+    let prompt = prompt.map(str::trim).filter(|prompt| !prompt.is_empty());
+    let terms: Vec<&str> = terms.iter().map(|term| term.trim()).filter(|term| !term.is_empty()).collect();
+    match (prompt, terms.is_empty()) {
+        (None, true) => None,
+        (Some(prompt), true) => Some(prompt.to_string()),
+        (None, false) => Some(format!("{}.", terms.join(", "))),
+        (Some(prompt), false) => Some(format!("{} {}.", prompt, terms.join(", "))),
+    }
+}
+
+fn default_control_emoji() -> String {
+    DEFAULT_CONTROL_EMOJI.to_string()
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GuildConfig {
+    /// Unicode emoji used for the recording control reaction,
+    /// or the custom emoji's name when `control_emoji_id` is set
+    #[serde(default = "default_control_emoji")]
+    pub control_emoji: String,
+    /// Custom guild emoji used for the recording control reaction
+    #[serde(default)]
+    pub control_emoji_id: Option<Id<EmojiMarker>>,
+    #[serde(default)]
+    pub control_emoji_animated: bool,
+    /// Mask profanity, emails and phone numbers in transcripts before posting or summarizing
+    #[serde(default)]
+    pub redact_transcripts: bool,
+    /// Words masked when redaction is on, besides [`crate::redaction::DEFAULT_PROFANITY`]
+    #[serde(default)]
+    pub redact_words: Vec<String>,
+    /// Role a member needs to start recordings; everyone may record when unset
+    #[serde(default)]
+    pub required_role_id: Option<Id<RoleMarker>>,
+    /// Announce recordings and give participants time to object before starting
+    #[serde(default)]
+    pub consent_prompt: bool,
+    #[serde(default)]
+    pub consent_window_secs: Option<u64>,
+    /// Names and terms that come up in this guild's meetings, passed to whisper
+    /// as an initial prompt to bias recognition
+    #[serde(default)]
+    pub transcription_prompt: Option<String>,
+    /// Whisper model (`base` or `fast`) for all transcriptions in this guild;
+    /// unset uses `base` for recordings and `fast` for translation
+    #[serde(default)]
+    pub transcription_model: Option<String>,
+    /// Where this guild's recordings are written instead of `./recordings/{guild_id}`
+    #[serde(default)]
+    pub recording_dir: Option<String>,
+    /// Keep WAVs after transcription instead of deleting them; unset follows `KEEP_RECORDINGS`
+    #[serde(default)]
+    pub keep_recordings: Option<bool>,
+    /// Post the minutes and transcript as one Markdown file; unset follows `MINUTES_AS_FILE`
+    #[serde(default)]
+    pub minutes_as_file: Option<bool>,
+    /// Terms fed to whisper's prompt and, with a translation, to a DeepL glossary
+    #[serde(default)]
+    pub vocabulary: Vec<VocabEntry>,
+    /// Custom minutes prompt with a `{transcript}` placeholder; unset uses the built-in one
+    #[serde(default)]
+    pub minutes_template: Option<String>,
+    /// Real-time translation's pause and minimum speech length; unset uses the defaults
+    #[serde(default)]
+    pub translation_tuning: Option<UtteranceTuning>,
+    /// Text channel the minutes are posted in instead of the voice channel chat
+    #[serde(default)]
+    pub minutes_channel_id: Option<Id<ChannelMarker>>,
+    /// Text channel live translations are posted in instead of the voice channel chat
+    #[serde(default)]
+    pub translation_channel_id: Option<Id<ChannelMarker>>,
+}
+
+impl Default for GuildConfig {
+    fn default() -> Self {
+        Self {
+            control_emoji: default_control_emoji(),
+            control_emoji_id: None,
+            control_emoji_animated: false,
+            redact_transcripts: false,
+            redact_words: Vec::new(),
+            required_role_id: None,
+            consent_prompt: false,
+            consent_window_secs: None,
+            transcription_prompt: None,
+            transcription_model: None,
+            recording_dir: None,
+            keep_recordings: None,
+            minutes_as_file: None,
+            vocabulary: Vec::new(),
+            minutes_template: None,
+            translation_tuning: None,
+            minutes_channel_id: None,
+            translation_channel_id: None,
+        }
+    }
+}
+
+impl GuildConfig {
+    /// Check whether a reaction is this guild's recording control emoji.
+    /// Custom emoji are compared by id, since names can change.
+    pub fn matches_control_emoji(&self, emoji: &EmojiReactionType) -> bool {
+        match (self.control_emoji_id, emoji) {
+            (Some(control_id), EmojiReactionType::Custom { id, .. }) => *id == control_id,
+            (None, EmojiReactionType::Unicode { name }) => *name == self.control_emoji,
+            _ => false,
+        }
+    }
+
+    /// Reaction to add to the control message
+    pub fn control_reaction(&self) -> RequestReactionType<'_> {
+        match self.control_emoji_id {
+            Some(id) => RequestReactionType::Custom {
+                id,
+                name: Some(&self.control_emoji),
+            },
+            None => RequestReactionType::Unicode {
+                name: &self.control_emoji,
+            },
+        }
+    }
+
+    /// Redaction settings to apply to transcripts, if this guild opted in
+    pub fn redaction_options(&self) -> Option<RedactionOptions> {
+        self.redact_transcripts.then(|| RedactionOptions {
+            words: DEFAULT_PROFANITY
+                .iter()
+                .map(|word| word.to_string())
+                .chain(self.redact_words.iter().cloned())
+                .collect(),
+            mask_emails: true,
+            mask_phones: true,
+        })
+    }
+
+    /// Whether this guild keeps its recordings, given the bot-wide default
+    pub fn keeps_recordings(&self, default: bool) -> bool {
+        self.keep_recordings.unwrap_or(default)
+    }
+
+    /// Whether this guild gets its minutes as a single file, given the bot-wide default
+    pub fn posts_minutes_as_file(&self, default: bool) -> bool {
+        self.minutes_as_file.unwrap_or(default)
+    }
+
+    /// Channel to post `kind` in: the configured one, or `fallback` (the voice channel chat)
+    pub fn output_channel(&self, kind: OutputKind, fallback: Id<ChannelMarker>) -> Id<ChannelMarker> {
+        let configured = match kind {
+            OutputKind::Minutes => self.minutes_channel_id,
+            OutputKind::Translations => self.translation_channel_id,
+        };
+        configured.unwrap_or(fallback)
+    }
+
+    /// How long to wait for objections before recording, if the consent prompt is on
+    pub fn consent_window(&self) -> Option<Duration> {
+        self.consent_prompt.then(|| {
+            Duration::from_secs(self.consent_window_secs.unwrap_or(DEFAULT_CONSENT_WINDOW_SECS))
+        })
+    }
+
+    /// Initial prompt for whisper: the guild's prompt and vocabulary, if any
+    pub fn initial_prompt(&self) -> Option<String> {
+        let terms: Vec<&str> = self.vocabulary.iter().map(|entry| entry.term.as_str()).collect();
+        vocabulary_prompt(self.transcription_prompt.as_deref(), &terms)
+    }
+
+    /// `(term, translation)` pairs for a DeepL glossary from `source_lang` to `target_lang`
+    pub fn glossary_entries(&self, source_lang: &str, target_lang: &str) -> Vec<(String, String)> {
+        self.vocabulary
+            .iter()
+            .filter_map(|entry| {
+                let translation = entry.translation.as_ref()?;
+                (translation.source_lang.eq_ignore_ascii_case(source_lang)
+                    && translation.target_lang.eq_ignore_ascii_case(target_lang))
+                    .then(|| (entry.term.clone(), translation.text.clone()))
+            })
+            .collect()
+    }
+
+    /// Emoji as it should appear in message content
+    pub fn control_emoji_display(&self) -> String {
+        match self.control_emoji_id {
+            Some(id) if self.control_emoji_animated => format!("<a:{}:{}>", self.control_emoji, id),
+            Some(id) => format!("<:{}:{}>", self.control_emoji, id),
+            None => self.control_emoji.clone(),
+        }
+    }
+}
+
+/// Whether a member with `roles` may start recordings in a guild with `guild_config`
+pub fn has_required_role(roles: &[Id<RoleMarker>], guild_config: &GuildConfig) -> bool {
+    match guild_config.required_role_id {
+        Some(required) => roles.contains(&required),
+        None => true,
+    }
+}
+
+/// Parse a custom emoji mention like `<:name:123>` or `<a:name:123>`
+/// into `(name, id, animated)`
+pub fn parse_custom_emoji(input: &str) -> Option<(String, Id<EmojiMarker>, bool)> {
+    let inner = input.trim().strip_prefix('<')?.strip_suffix('>')?;
+    let (animated, rest) = match inner.strip_prefix("a:") {
+        Some(rest) => (true, rest),
+        None => (false, inner.strip_prefix(':')?),
+    };
+    let (name, id) = rest.split_once(':')?;
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return None;
+    }
+    let id = id.parse::<u64>().ok().and_then(Id::new_checked)?;
+    Some((name.to_string(), id, animated))
+}
+
+/// Basic sanity check for a Unicode emoji supplied by a user
+pub fn is_valid_unicode_emoji(emoji: &str) -> bool {
+    let emoji = emoji.trim();
+    !emoji.is_empty()
+        && emoji.chars().count() <= 8
+        && !emoji.chars().any(|c| c.is_ascii_alphanumeric() || c.is_whitespace() || c == '<' || c == ':')
+}
+
+pub struct GuildConfigManager {
+    configs: Arc<RwLock<HashMap<Id<GuildMarker>, GuildConfig>>>,
+    file_path: String,
+    /// Keeps the file watcher alive once `watch` has been called
+    watcher: std::sync::Mutex<Option<RecommendedWatcher>>,
+    saves: DebouncedSave,
+}
+
+impl GuildConfigManager {
+    pub fn new(file_path: &str) -> Self {
+        let configs = Self::load_from_file(file_path);
+        Self {
+            configs: Arc::new(RwLock::new(configs)),
+            file_path: file_path.to_string(),
+            watcher: std::sync::Mutex::new(None),
+            saves: DebouncedSave::default(),
+        }
+    }
+
+    /// Re-read the config file. Returns whether anything changed; a file that
+    /// doesn't parse is rejected and the current config is kept, as is a file
+    /// read while command changes are still waiting to be saved.
+    pub async fn reload(&self) -> Result<bool, Box<dyn Error + Send + Sync>> {
+        let content = match tokio::fs::read_to_string(&self.file_path).await {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::from("{}"),
+            Err(e) => return Err(e.into()),
+        };
+        let loaded: HashMap<Id<GuildMarker>, GuildConfig> = serde_json::from_str(&content)?;
+
+        // Commands mark changes dirty while holding this lock, so checking under it
+        // means no edit can slip in between the check and the swap
+        let mut configs = self.configs.write().await;
+        if self.saves.has_unsaved_changes() {
+            return Err("changes made through commands haven't been saved yet".into());
+        }
+        if *configs == loaded {
+            return Ok(false);
+        }
+        *configs = loaded;
+        Ok(true)
+    }
+
+    /// Hot-reload the config when the file is edited outside the bot
+    pub fn watch(self: &Arc<Self>) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let path = Path::new(&self.file_path);
+        let file_name = path.file_name().ok_or("Guild config path has no file name")?.to_os_string();
+        let dir = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            let Ok(event) = event else { return };
+            let touches_config = matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+                && event.paths.iter().any(|p| p.file_name() == Some(file_name.as_os_str()));
+            if touches_config {
+                let _ = tx.send(());
+            }
+        })?;
+        // Watch the directory so editors that replace the file (write + rename) are seen too
+        watcher.watch(&dir, RecursiveMode::NonRecursive)?;
+        *self.watcher.lock().unwrap() = Some(watcher);
+
+        let manager: Weak<Self> = Arc::downgrade(self);
+        tokio::spawn(async move {
+            while rx.recv().await.is_some() {
+                tokio::time::sleep(RELOAD_DEBOUNCE).await;
+                while rx.try_recv().is_ok() {}
+
+                let Some(manager) = manager.upgrade() else { break };
+                match manager.reload().await {
+                    Ok(true) => println!("[INFO] Reloaded guild config from {}", manager.file_path),
+                    Ok(false) => {}
+                    Err(e) => eprintln!("[WARN] Ignoring edit to {}: {}", manager.file_path, e),
+                }
+            }
+        });
+
+        println!("[INFO] Watching {} for changes", self.file_path);
+        Ok(())
+    }
+
+    fn load_from_file(path: &str) -> HashMap<Id<GuildMarker>, GuildConfig> {
+        persist::load_json_or_default(Path::new(path))
+    }
+
+    async fn save_to_file(&self) {
+        let result = self.saves.save(Path::new(&self.file_path), || async {
+            serde_json::to_string_pretty(&*self.configs.read().await)
+        }).await;
+        match result {
+            Ok(_) => {}
+            Err(e) => eprintln!("[ERROR] Failed to save guild config to {}: {}", self.file_path, e),
+        }
+    }
+
+    /// Save changes in the background at most once per `interval`
+    pub fn spawn_saver(self: &Arc<Self>, interval: Duration) {
+        let manager = self.clone();
+        tokio::spawn(async move {
+            manager.saves.run(interval, || manager.save_to_file()).await;
+        });
+    }
+
+    /// Write pending changes now, e.g. on shutdown
+    pub async fn flush(&self) {
+        self.save_to_file().await;
+    }
+
+    /// Get a guild's config, falling back to defaults when none is stored
+    pub async fn get_guild_config(&self, guild_id: Id<GuildMarker>) -> GuildConfig {
+        let configs = self.configs.read().await;
+        configs.get(&guild_id).cloned().unwrap_or_default()
+    }
+
+    pub async fn set_control_emoji(&self, guild_id: Id<GuildMarker>, emoji: &str) {
+        {
+            let mut configs = self.configs.write().await;
+            let config = configs.entry(guild_id).or_default();
+            config.control_emoji = emoji.trim().to_string();
+            config.control_emoji_id = None;
+            config.control_emoji_animated = false;
+            self.saves.mark_dirty();
+        }
+    }
+
+    pub async fn set_custom_control_emoji(
+        &self,
+        guild_id: Id<GuildMarker>,
+        name: &str,
+        emoji_id: Id<EmojiMarker>,
+        animated: bool,
+    ) {
+        {
+            let mut configs = self.configs.write().await;
+            let config = configs.entry(guild_id).or_default();
+            config.control_emoji = name.to_string();
+            config.control_emoji_id = Some(emoji_id);
+            config.control_emoji_animated = animated;
+            self.saves.mark_dirty();
+        }
+    }
+
+    /// Limit recording to members with `role_id`, or let anyone record with None
+    pub async fn set_required_role(&self, guild_id: Id<GuildMarker>, role_id: Option<Id<RoleMarker>>) {
+        {
+            let mut configs = self.configs.write().await;
+            configs.entry(guild_id).or_default().required_role_id = role_id;
+            self.saves.mark_dirty();
+        }
+    }
+
+    /// Route `kind` to a text channel, or back to the voice channel chat with None
+    pub async fn set_output_channel(&self, guild_id: Id<GuildMarker>, kind: OutputKind, channel_id: Option<Id<ChannelMarker>>) {
+        {
+            let mut configs = self.configs.write().await;
+            let config = configs.entry(guild_id).or_default();
+            match kind {
+                OutputKind::Minutes => config.minutes_channel_id = channel_id,
+                OutputKind::Translations => config.translation_channel_id = channel_id,
+            }
+            self.saves.mark_dirty();
+        }
+    }
+
+    pub async fn set_transcription_model(&self, guild_id: Id<GuildMarker>, model: Option<String>) {
+        {
+            let mut configs = self.configs.write().await;
+            configs.entry(guild_id).or_default().transcription_model = model;
+            self.saves.mark_dirty();
+        }
+    }
+
+    pub async fn set_consent_prompt(&self, guild_id: Id<GuildMarker>, enabled: bool, window_secs: Option<u64>) {
+        {
+            let mut configs = self.configs.write().await;
+            let config = configs.entry(guild_id).or_default();
+            config.consent_prompt = enabled;
+            if window_secs.is_some() {
+                config.consent_window_secs = window_secs;
+            }
+            self.saves.mark_dirty();
+        }
+    }
+
+    /// Add a vocabulary term, replacing an existing entry for the same term
+    pub async fn add_vocab(&self, guild_id: Id<GuildMarker>, entry: VocabEntry) -> Result<(), String> {
+        {
+            let mut configs = self.configs.write().await;
+            let vocabulary = &mut configs.entry(guild_id).or_default().vocabulary;
+            match vocabulary.iter().position(|existing| existing.term.eq_ignore_ascii_case(&entry.term)) {
+                Some(index) => vocabulary[index] = entry,
+                None if vocabulary.len() >= MAX_VOCAB_ENTRIES => {
+                    return Err(format!("This server already has {} vocabulary terms", MAX_VOCAB_ENTRIES));
+                }
+                None => vocabulary.push(entry),
+            }
+            self.saves.mark_dirty();
+        }
+        Ok(())
+    }
+
+    /// Remove a vocabulary term; returns false if it wasn't registered
+    pub async fn remove_vocab(&self, guild_id: Id<GuildMarker>, term: &str) -> bool {
+        let mut configs = self.configs.write().await;
+        let vocabulary = &mut configs.entry(guild_id).or_default().vocabulary;
+        let before = vocabulary.len();
+        vocabulary.retain(|entry| !entry.term.eq_ignore_ascii_case(term.trim()));
+        let removed = vocabulary.len() != before;
+        if removed {
+            self.saves.mark_dirty();
+        }
+        removed
+    }
+
+    /// Use a custom minutes prompt, or the built-in one with None
+    pub async fn set_minutes_template(&self, guild_id: Id<GuildMarker>, template: Option<String>) -> Result<(), String> {
+        if let Some(template) = &template {
+            summarizer::validate_prompt_template(template)?;
+        }
+        {
+            let mut configs = self.configs.write().await;
+            configs.entry(guild_id).or_default().minutes_template = template;
+            self.saves.mark_dirty();
+        }
+        Ok(())
+    }
+
+    /// Change the real-time translation thresholds, keeping the current value for any
+    /// that's None. Returns the thresholds now in effect.
+    pub async fn set_translation_tuning(
+        &self,
+        guild_id: Id<GuildMarker>,
+        silence_ms: Option<u64>,
+        min_speech_ms: Option<u64>,
+    ) -> Result<UtteranceTuning, String> {
+        let tuning = {
+            let mut configs = self.configs.write().await;
+            let config = configs.entry(guild_id).or_default();
+            let current = config.translation_tuning.unwrap_or_default();
+            let tuning = UtteranceTuning::new(
+                silence_ms.unwrap_or(current.silence_ms),
+                min_speech_ms.unwrap_or(current.min_speech_ms),
+            )?;
+            config.translation_tuning = Some(tuning);
+            self.saves.mark_dirty();
+            tuning
+        };
+        Ok(tuning)
+    }
+
+    /// Turn transcript redaction on or off; `words` replaces the word list when given
+    pub async fn set_redaction(&self, guild_id: Id<GuildMarker>, enabled: bool, words: Option<Vec<String>>) {
+        {
+            let mut configs = self.configs.write().await;
+            let config = configs.entry(guild_id).or_default();
+            config.redact_transcripts = enabled;
+            if let Some(words) = words {
+                config.redact_words = words;
+            }
+            self.saves.mark_dirty();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn temp_config_path(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!("guild_config_{}_{}.json", name, std::process::id()))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    #[tokio::test]
+    async fn test_save_and_load() {
+        let path = temp_config_path("save_load");
+        let guild_id = Id::new(1);
+
+        let manager = GuildConfigManager::new(&path);
+        manager.set_control_emoji(guild_id, "🎙️").await;
+        manager.set_required_role(guild_id, Some(Id::new(20))).await;
+        manager.flush().await;
+
+        let reloaded = GuildConfigManager::new(&path);
+        let config = reloaded.get_guild_config(guild_id).await;
+        assert_eq!(config.control_emoji, "🎙️");
+        assert_eq!(config.required_role_id, Some(Id::new(20)));
+        assert_eq!(config, manager.get_guild_config(guild_id).await);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_translation_tuning_is_validated_and_saved() {
+        let path = temp_config_path("tuning");
+        let guild_id = Id::new(1);
+
+        let manager = GuildConfigManager::new(&path);
+        assert!(manager.set_translation_tuning(guild_id, Some(100), None).await.is_err());
+        assert!(manager.set_translation_tuning(guild_id, None, Some(10_000)).await.is_err());
+        assert_eq!(manager.get_guild_config(guild_id).await.translation_tuning, None);
+
+        let tuning = manager.set_translation_tuning(guild_id, Some(800), None).await.unwrap();
+        assert_eq!(tuning, UtteranceTuning { silence_ms: 800, min_speech_ms: 500 });
+        // Only the given value changes
+        let tuning = manager.set_translation_tuning(guild_id, None, Some(1200)).await.unwrap();
+        assert_eq!(tuning, UtteranceTuning { silence_ms: 800, min_speech_ms: 1200 });
+        manager.flush().await;
+
+        let reloaded = GuildConfigManager::new(&path);
+        assert_eq!(reloaded.get_guild_config(guild_id).await.translation_tuning, Some(tuning));
+
+        // A hand-edited file can't get around the limits
+        fs::write(&path, r#"{"1": {"translation_tuning": {"silence_ms": 0, "min_speech_ms": 999999}}}"#).unwrap();
+        let edited = GuildConfigManager::new(&path);
+        assert_eq!(
+            edited.get_guild_config(guild_id).await.translation_tuning,
+            Some(UtteranceTuning { silence_ms: 300, min_speech_ms: 5000 })
+        );
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_output_channel_falls_back_to_voice_chat() {
+        let manager = GuildConfigManager::new(&temp_config_path("output_channel"));
+        let guild_id = Id::new(1);
+        let voice_channel = Id::new(100);
+
+        // Unset: everything goes to the voice channel chat
+        let config = manager.get_guild_config(guild_id).await;
+        assert_eq!(config.output_channel(OutputKind::Minutes, voice_channel), voice_channel);
+        assert_eq!(config.output_channel(OutputKind::Translations, voice_channel), voice_channel);
+
+        // Each kind is routed on its own
+        manager.set_output_channel(guild_id, OutputKind::Minutes, Some(Id::new(200))).await;
+        let config = manager.get_guild_config(guild_id).await;
+        assert_eq!(config.output_channel(OutputKind::Minutes, voice_channel), Id::new(200));
+        assert_eq!(config.output_channel(OutputKind::Translations, voice_channel), voice_channel);
+
+        manager.set_output_channel(guild_id, OutputKind::Minutes, None).await;
+        let config = manager.get_guild_config(guild_id).await;
+        assert_eq!(config.output_channel(OutputKind::Minutes, voice_channel), voice_channel);
+    }
+
+    #[tokio::test]
+    async fn test_missing_guild_uses_defaults() {
+        let manager = GuildConfigManager::new(&temp_config_path("missing"));
+        assert_eq!(manager.get_guild_config(Id::new(1)).await, GuildConfig::default());
+
+        // Fields added later fall back to their defaults in older files
+        let old: GuildConfig = serde_json::from_str(r#"{"control_emoji": "🎙️"}"#).unwrap();
+        assert_eq!(old, GuildConfig { control_emoji: "🎙️".to_string(), ..Default::default() });
+    }
+
+    #[tokio::test]
+    async fn test_reload_picks_up_external_edits() {
+        let path = temp_config_path("reload");
+        let guild_id = Id::new(1);
+        let manager = GuildConfigManager::new(&path);
+        manager.set_control_emoji(guild_id, "🎙️").await;
+        // A reload before the change is saved would undo it
+        fs::write(&path, r#"{"1": {"control_emoji": "📼"}}"#).unwrap();
+        assert!(manager.reload().await.is_err());
+        assert_eq!(manager.get_guild_config(guild_id).await.control_emoji, "🎙️");
+        manager.flush().await;
+        assert!(!manager.reload().await.unwrap());
+
+        fs::write(&path, r#"{"1": {"control_emoji": "📼", "consent_prompt": true}}"#).unwrap();
+        assert!(manager.reload().await.unwrap());
+        let config = manager.get_guild_config(guild_id).await;
+        assert_eq!(config.control_emoji, "📼");
+        assert!(config.consent_prompt);
+
+        // A broken edit is rejected and the last good config stays active
+        fs::write(&path, r#"{"1": {"control_emoji": "#).unwrap();
+        assert!(manager.reload().await.is_err());
+        assert_eq!(manager.get_guild_config(guild_id).await.control_emoji, "📼");
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_required_role_check() {
+        let mut config = GuildConfig::default();
+        let (member, moderator) = (Id::new(10), Id::new(20));
+
+        // No role configured: anyone can record
+        assert!(has_required_role(&[], &config));
+        assert!(has_required_role(&[member], &config));
+
+        config.required_role_id = Some(moderator);
+        assert!(!has_required_role(&[], &config));
+        assert!(!has_required_role(&[member], &config));
+        assert!(has_required_role(&[member, moderator], &config));
+    }
+
+    #[test]
+    fn test_control_emoji_matching() {
+        let config = GuildConfig {
+            control_emoji: "🎙️".to_string(),
+            ..Default::default()
+        };
+
+        assert!(config.matches_control_emoji(&EmojiReactionType::Unicode { name: "🎙️".to_string() }));
+        assert!(!config.matches_control_emoji(&EmojiReactionType::Unicode { name: "🔴".to_string() }));
+        assert!(GuildConfig::default().matches_control_emoji(&EmojiReactionType::Unicode { name: "🔴".to_string() }));
+    }
+
+    #[test]
+    fn test_custom_control_emoji_matching() {
+        let config = GuildConfig {
+            control_emoji: "rec".to_string(),
+            control_emoji_id: Some(Id::new(42)),
+            ..Default::default()
+        };
+
+        // Matched by id even if the emoji was renamed
+        assert!(config.matches_control_emoji(&EmojiReactionType::Custom {
+            animated: false,
+            id: Id::new(42),
+            name: Some("renamed".to_string()),
+        }));
+        assert!(!config.matches_control_emoji(&EmojiReactionType::Custom {
+            animated: false,
+            id: Id::new(43),
+            name: Some("rec".to_string()),
+        }));
+        assert!(!config.matches_control_emoji(&EmojiReactionType::Unicode { name: "rec".to_string() }));
+        assert_eq!(config.control_emoji_display(), "<:rec:42>");
+        assert!(matches!(
+            config.control_reaction(),
+            RequestReactionType::Custom { id, name: Some("rec") } if id == Id::new(42)
+        ));
+    }
+
+    #[test]
+    fn test_redaction_is_opt_in() {
+        assert!(GuildConfig::default().redaction_options().is_none());
+
+        let config: GuildConfig = serde_json::from_str(r#"{"redact_transcripts":true,"redact_words":["darn"]}"#).unwrap();
+        let options = config.redaction_options().unwrap();
+        assert!(options.words.iter().any(|word| word == "shit"));
+        assert_eq!(options.words.last().map(String::as_str), Some("darn"));
+        assert!(options.mask_emails && options.mask_phones);
+    }
+
+    #[test]
+    fn test_parse_custom_emoji() {
+        assert_eq!(parse_custom_emoji("<:rec:42>"), Some(("rec".to_string(), Id::new(42), false)));
+        assert_eq!(parse_custom_emoji("<a:rec_anim:7>"), Some(("rec_anim".to_string(), Id::new(7), true)));
+        assert_eq!(parse_custom_emoji("<:rec:0>"), None);
+        assert_eq!(parse_custom_emoji("<:rec:abc>"), None);
+        assert_eq!(parse_custom_emoji("🔴"), None);
+    }
+
+    #[test]
+    fn test_unicode_emoji_validation() {
+        assert!(is_valid_unicode_emoji("🔴"));
+        assert!(is_valid_unicode_emoji("🎙️"));
+        assert!(!is_valid_unicode_emoji(""));
+        assert!(!is_valid_unicode_emoji("abc"));
+        assert!(!is_valid_unicode_emoji("<:record:123>"));
+    }
+
+    #[tokio::test]
+    async fn test_vocab_add_list_remove() {
+        let path = temp_config_path("vocab");
+        let guild_id = Id::new(1);
+        let manager = GuildConfigManager::new(&path);
+        let falcon = VocabTranslation { source_lang: "en".to_string(), target_lang: "ja".to_string(), text: "ファルコン".to_string() };
+
+        manager.add_vocab(guild_id, VocabEntry { term: "Kubernetes".to_string(), translation: None }).await.unwrap();
+        manager.add_vocab(guild_id, VocabEntry { term: "Falcon".to_string(), translation: None }).await.unwrap();
+        // Same term again replaces the entry
+        manager.add_vocab(guild_id, VocabEntry { term: "falcon".to_string(), translation: Some(falcon) }).await.unwrap();
+        manager.flush().await;
+
+        let config = GuildConfigManager::new(&path).get_guild_config(guild_id).await;
+        let terms: Vec<&str> = config.vocabulary.iter().map(|entry| entry.term.as_str()).collect();
+        assert_eq!(terms, vec!["Kubernetes", "falcon"]);
+        assert_eq!(config.glossary_entries("en", "ja"), vec![("falcon".to_string(), "ファルコン".to_string())]);
+        assert!(config.glossary_entries("ja", "en").is_empty());
+
+        assert!(manager.remove_vocab(guild_id, "KUBERNETES").await);
+        assert!(!manager.remove_vocab(guild_id, "Kubernetes").await);
+        assert_eq!(manager.get_guild_config(guild_id).await.vocabulary.len(), 1);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_vocabulary_prompt() {
+        assert_eq!(vocabulary_prompt(None, &[]), None);
+        assert_eq!(vocabulary_prompt(Some("  "), &[" "]), None);
+        assert_eq!(vocabulary_prompt(Some("Weekly sync."), &[]).as_deref(), Some("Weekly sync."));
+        assert_eq!(vocabulary_prompt(None, &["Kubernetes", "Falcon"]).as_deref(), Some("Kubernetes, Falcon."));
+        assert_eq!(
+            vocabulary_prompt(Some("Weekly sync."), &["Kubernetes", "Falcon"]).as_deref(),
+            Some("Weekly sync. Kubernetes, Falcon.")
+        );
+    }
+}
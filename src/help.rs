@@ -0,0 +1,309 @@
+use twilight_model::channel::message::embed::{Embed, EmbedField};
+
+/// One row of the `/help` table
+pub struct HelpEntry {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub usage: &'static str,
+}
+
+/// Static help table, one entry per registered slash command.
+/// Keep this in sync with `global_commands()` in main.rs (enforced by a test).
+pub const HELP_ENTRIES: &[HelpEntry] = &[
+    HelpEntry {
+        name: "record",
+        description: "Post a recording control message in this channel",
+        usage: "Join a voice channel, run `/record`, then press the control reaction (🔴 by default) to start. Remove it to stop and generate meeting minutes.",
+    },
+    HelpEntry {
+        name: "record_here",
+        description: "Join your voice channel and start recording immediately",
+        usage: "`/record_here [language:<language>]` — a control message is posted; press the control reaction and release it to stop. Choosing a language skips per-speaker detection.",
+    },
+    HelpEntry {
+        name: "record_schedule",
+        description: "Schedule a recording of your voice channel",
+        usage: "`/record_schedule start:<HH:MM | YYYY-MM-DD HH:MM | +30m> [duration:<minutes>]` — run it from the voice channel to record; skipped if the channel is empty at the start time",
+    },
+    HelpEntry {
+        name: "join",
+        description: "Join your voice channel without recording or translating",
+        usage: "`/join` — then start `/record_here` or `/translate_start` when you're ready",
+    },
+    HelpEntry {
+        name: "leave",
+        description: "Finish any recording or translation and leave the voice channel",
+        usage: "`/leave` — buffered speech is still translated, and an active recording's minutes are posted in this channel",
+    },
+    HelpEntry {
+        name: "record_abort",
+        description: "Stop recording and throw the recording away without transcribing it",
+        usage: "`/record_abort` — also stops the transcription of an earlier recording that's still being processed; nothing from either is posted",
+    },
+    HelpEntry {
+        name: "translate_start",
+        description: "Join your voice channel and start real-time voice translation",
+        usage: "Translations are posted to the voice channel chat. Only speakers with a `/translate_set` language pair are translated. `/translate_start mode:Listener` translates everyone's speech into the languages of the other participants instead of each speaker's own targets.",
+    },
+    HelpEntry {
+        name: "translate_stop",
+        description: "Stop real-time voice translation and leave the voice channel",
+        usage: "`/translate_stop`",
+    },
+    HelpEntry {
+        name: "translate_pause",
+        description: "Pause translation without leaving the voice channel",
+        usage: "`/translate_pause` — speech during the pause is not translated",
+    },
+    HelpEntry {
+        name: "translate_resume",
+        description: "Resume a paused translation session",
+        usage: "`/translate_resume`",
+    },
+    HelpEntry {
+        name: "translate_set",
+        description: "Set your speaking language and translation target",
+        usage: "`/translate_set source:<language> target:<language> [target2:<language>] [romaji:true] [auto_swap:true]` — `target2` adds a second translation, `romaji` adds a romaji line under Japanese speech, `auto_swap` translates back into your language when you speak your target language",
+    },
+    HelpEntry {
+        name: "translate_tune",
+        description: "Tune when real-time translation treats speech as finished (requires Manage Server)",
+        usage: "`/translate_tune [silence_ms:<300-5000>] [min_speech_ms:<100-5000>]` — lower the silence for fast talkers, raise it for deliberate ones; applies to the running session and is kept for this server. Without options it shows the current values",
+    },
+    HelpEntry {
+        name: "translate_history",
+        description: "Show the recent translations from the current translation session",
+        usage: "`/translate_history` — the last 50 utterances are kept with every language they were translated into until `/translate_stop`",
+    },
+    HelpEntry {
+        name: "translate_text",
+        description: "Translate a piece of text",
+        usage: "`/translate_text text:<text> target:<language> [source:<language>]` — the source language is detected automatically if omitted",
+    },
+    HelpEntry {
+        name: "record_emoji",
+        description: "Change the emoji used for the recording control reaction in this server",
+        usage: "`/record_emoji emoji:🎙️` or a custom server emoji (default: 🔴)",
+    },
+    HelpEntry {
+        name: "record_role",
+        description: "Require a role to start recordings in this server (requires Manage Server)",
+        usage: "`/record_role [role:<role>]` — leave `role` empty to let everyone record again",
+    },
+    HelpEntry {
+        name: "output_channel",
+        description: "Post minutes and/or translations in a text channel instead of the voice channel chat (requires Manage Server)",
+        usage: "`/output_channel kind:<Minutes|Translations|Both> [channel:<text channel>]` — leave `channel` empty to post in the voice channel chat again",
+    },
+    HelpEntry {
+        name: "record_consent",
+        description: "Announce recordings and let participants object before they start (requires Manage Server)",
+        usage: "`/record_consent enabled:<true|false> [seconds:<5-120>]` — anyone reacting with ❌ during the window cancels the recording",
+    },
+    HelpEntry {
+        name: "record_exclude",
+        description: "Leave a user out of recordings in this server (requires Manage Server)",
+        usage: "`/record_exclude user:<user>` — their audio is dropped before it's saved; lasts until `/record_include` or a restart",
+    },
+    HelpEntry {
+        name: "record_include",
+        description: "Record a user excluded with `/record_exclude` again (requires Manage Server)",
+        usage: "`/record_include user:<user>`",
+    },
+    HelpEntry {
+        name: "minutes_template",
+        description: "Use a custom prompt for this server's meeting minutes (requires Manage Server)",
+        usage: "`/minutes_template [template:<prompt>] [reset:<true|false>]` — the prompt must contain `{transcript}`; write `\\n` for a line break; omit both to see the current prompt",
+    },
+    HelpEntry {
+        name: "dryrun_summary",
+        description: "Preview meeting minutes for pasted text without recording",
+        usage: "`/dryrun_summary [text:<text>] [file:<text file>]` — runs the summarizer with this server's minutes prompt, handy for trying `/minutes_template`",
+    },
+    HelpEntry {
+        name: "selftest",
+        description: "Check that DeepL, z.ai and whisper are working (requires Manage Server)",
+        usage: "`/selftest` — translates a fixed phrase, summarizes a short transcript and transcribes a built-in sample, showing ✅/❌ and latency for each",
+    },
+    HelpEntry {
+        name: "transcribe_url",
+        description: "Transcribe and summarize an audio file from a link",
+        usage: "`/transcribe_url url:<https link> [language:<language>]` — WAV or Ogg Opus, up to 50 MB, once every two minutes per server; needs the required role, and links to internal addresses and redirects are refused",
+    },
+    HelpEntry {
+        name: "model",
+        description: "Choose the Whisper model used for this server's transcriptions (requires Manage Server)",
+        usage: "`/model [model:<default|base|fast>]` — `base` is more accurate, `fast` has lower latency; omit `model` to see the current choice",
+    },
+    HelpEntry {
+        name: "redaction",
+        description: "Mask profanity, emails and phone numbers in this server's transcripts",
+        usage: "`/redaction enabled:true [words:word1,word2]` — applied before transcripts are posted or summarized",
+    },
+    HelpEntry {
+        name: "recordings",
+        description: "List or delete this server's recordings on disk",
+        usage: "`/recordings list` shows files with sizes and ages; `/recordings clear` deletes this server's recordings (not while recording or transcribing)",
+    },
+    HelpEntry {
+        name: "vocab_add",
+        description: "Teach the bot a name or term used in this server",
+        usage: "`/vocab_add term:<term> [translation:<text> from:<lang> to:<lang>]` — terms improve transcription; translations are enforced through a DeepL glossary",
+    },
+    HelpEntry {
+        name: "vocab_list",
+        description: "List the names and terms registered for this server",
+        usage: "`/vocab_list`",
+    },
+    HelpEntry {
+        name: "vocab_remove",
+        description: "Remove a name or term from this server's vocabulary",
+        usage: "`/vocab_remove term:<term>`",
+    },
+    HelpEntry {
+        name: "feedback",
+        description: "Report a word or phrase that was transcribed wrong",
+        usage: "`/feedback wrong:<what was transcribed> correct:<what was said>` — corrections are logged for the operators to improve the vocabulary",
+    },
+    HelpEntry {
+        name: "whoami",
+        description: "Show which voice channel the bot thinks you're in and whether it's connected",
+        usage: "`/whoami` — for troubleshooting \"you must be in a voice channel\" errors; only you see the answer",
+    },
+    HelpEntry {
+        name: "settings_export",
+        description: "Download everyone's language settings as JSON, e.g. to move them to another bot instance (bot operators only)",
+        usage: "`/settings_export` — only users listed in `SETTINGS_ADMIN_IDS` can use it",
+    },
+    HelpEntry {
+        name: "settings_import",
+        description: "Merge language settings from a `/settings_export` file (bot operators only)",
+        usage: "`/settings_import file:<json file>` — replaces the settings of the users in the file and keeps everyone else's; invalid entries are skipped",
+    },
+    HelpEntry {
+        name: "transcript",
+        description: "Download the most recent meeting transcript (and minutes) as a file",
+        usage: "`/transcript` — the last few meetings are kept in memory until the bot restarts",
+    },
+    HelpEntry {
+        name: "export",
+        description: "Download the last meeting's transcript, minutes and metadata as a ZIP",
+        usage: "`/export` — audio is included when the bot keeps recordings (`RETAIN_RECORDINGS_MINS` or `KEEP_RECORDINGS`)",
+    },
+    HelpEntry {
+        name: "stats",
+        description: "Show uptime, active sessions and usage counters since the bot started",
+        usage: "`/stats`",
+    },
+    HelpEntry {
+        name: "help",
+        description: "Show this help message",
+        usage: "`/help`",
+    },
+];
+
+/// Languages selectable in `/translate_set`
+pub const SUPPORTED_LANGUAGES: &str = "🇯🇵 Japanese (`ja`) • 🇰🇷 Korean (`ko`) • 🇺🇸 English (`en`) • 🇬🇧 English (UK) (`en-gb`)";
+
+/// Discord rejects an embed with more fields than this
+const MAX_EMBED_FIELDS: usize = 25;
+/// Discord's limit on an embed's title, description and fields combined, which also
+/// applies to all the embeds in one message together
+const MAX_EMBED_CHARS: usize = 6000;
+
+const HELP_TITLE: &str = "diggy-gizzy Help";
+const HELP_CONTINUED_TITLE: &str = "diggy-gizzy Help (continued)";
+const HELP_DESCRIPTION: &str = "Records voice channels, transcribes them with Whisper, generates meeting minutes, and translates speech in real time.";
+
+/// Characters Discord counts toward an embed's total
+fn embed_chars(embed: &Embed) -> usize {
+    let text = |value: &Option<String>| value.as_deref().map_or(0, |v| v.chars().count());
+    text(&embed.title)
+        + text(&embed.description)
+        + embed.fields.iter().map(|f| f.name.chars().count() + f.value.chars().count()).sum::<usize>()
+}
+
+fn help_embed(title: &str, description: Option<&str>, fields: Vec<EmbedField>) -> Embed {
+    Embed {
+        author: None,
+        color: Some(0x3498db),
+        description: description.map(str::to_string),
+        fields,
+        footer: None,
+        image: None,
+        kind: "rich".to_string(),
+        provider: None,
+        thumbnail: None,
+        timestamp: None,
+        title: Some(title.to_string()),
+        url: None,
+        video: None,
+    }
+}
+
+/// Build the `/help` embeds from the static table, starting a new embed whenever
+/// the next command would break Discord's field or character limit. Each one is
+/// meant for its own message, since the character limit covers a whole message.
+pub fn build_help_embeds() -> Vec<Embed> {
+    let mut fields: Vec<EmbedField> = HELP_ENTRIES
+        .iter()
+        .map(|entry| EmbedField {
+            inline: false,
+            name: format!("/{}", entry.name),
+            value: format!("{}\n{}", entry.description, entry.usage),
+        })
+        .collect();
+
+    fields.push(EmbedField {
+        inline: false,
+        name: "🌐 Supported languages".to_string(),
+        value: SUPPORTED_LANGUAGES.to_string(),
+    });
+
+    let mut embeds = vec![help_embed(HELP_TITLE, Some(HELP_DESCRIPTION), Vec::new())];
+    for field in fields {
+        let field_chars = field.name.chars().count() + field.value.chars().count();
+        let current = embeds.last().expect("starts with one embed");
+        if !current.fields.is_empty()
+            && (current.fields.len() == MAX_EMBED_FIELDS || embed_chars(current) + field_chars > MAX_EMBED_CHARS)
+        {
+            embeds.push(help_embed(HELP_CONTINUED_TITLE, None, Vec::new()));
+        }
+        embeds.last_mut().expect("starts with one embed").fields.push(field);
+    }
+    embeds
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_registered_command_has_help_entry() {
+        for command in crate::global_commands() {
+            assert!(
+                HELP_ENTRIES.iter().any(|entry| entry.name == command.name),
+                "missing /help entry for /{}",
+                command.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_help_embeds_fit_discord_limits() {
+        let embeds = build_help_embeds();
+        for embed in &embeds {
+            assert!(embed.fields.len() <= MAX_EMBED_FIELDS, "{} fields", embed.fields.len());
+            assert!(embed_chars(embed) <= MAX_EMBED_CHARS, "{} characters", embed_chars(embed));
+            for field in &embed.fields {
+                assert!(field.name.chars().count() <= 256 && field.value.chars().count() <= 1024, "{} is too long", field.name);
+            }
+        }
+
+        // Every command once, in table order, then the languages
+        let fields: Vec<&EmbedField> = embeds.iter().flat_map(|embed| &embed.fields).collect();
+        assert_eq!(fields.len(), HELP_ENTRIES.len() + 1);
+        assert!(fields.iter().zip(HELP_ENTRIES).all(|(field, entry)| field.name == format!("/{}", entry.name)));
+        assert!(fields.last().unwrap().value.contains("Korean"));
+    }
+}
@@ -0,0 +1,125 @@
+use std::collections::HashMap;
+use twilight_model::application::interaction::Interaction;
+use twilight_model::channel::message::MessageFlags;
+use twilight_model::http::interaction::{InteractionResponse, InteractionResponseData, InteractionResponseType};
+use twilight_model::id::{marker::UserMarker, Id};
+
+/// Who invoked a command: Discord sends `user` in DMs and `member` in a server.
+/// None if neither is present, which callers report rather than ignore.
+pub fn resolve_user_id(interaction: &Interaction) -> Option<Id<UserMarker>> {
+    interaction
+        .user
+        .as_ref()
+        .map(|user| user.id)
+        .or_else(|| interaction.member.as_ref().and_then(|member| member.user.as_ref().map(|user| user.id)))
+}
+
+/// A plain message reply, shown only to the invoking user when `ephemeral`
+pub fn message_response(content: impl Into<String>, ephemeral: bool) -> InteractionResponse {
+    InteractionResponse {
+        kind: InteractionResponseType::ChannelMessageWithSource,
+        data: Some(InteractionResponseData {
+            content: Some(content.into()),
+            flags: ephemeral.then_some(MessageFlags::EPHEMERAL),
+            ..Default::default()
+        }),
+    }
+}
+
+/// Whether commands' confirmations (recording controls, translation start/stop,
+/// join/leave, settings saved) are ephemeral. Minutes and translations stay public.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResponsePolicy {
+    ephemeral_by_default: bool,
+    per_command: HashMap<String, bool>,
+}
+
+impl Default for ResponsePolicy {
+    fn default() -> Self {
+        Self { ephemeral_by_default: true, per_command: HashMap::new() }
+    }
+}
+
+impl ResponsePolicy {
+    /// Parse e.g. `ephemeral,record=public`: a bare value sets the default,
+    /// `command=value` overrides one command
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut policy = Self::default();
+        let visibility = |value: &str| match value.trim().to_lowercase().as_str() {
+            "ephemeral" => Ok(true),
+            "public" => Ok(false),
+            other => Err(format!("'{}' is not ephemeral or public", other)),
+        };
+        for part in spec.split(',').map(str::trim).filter(|part| !part.is_empty()) {
+            match part.split_once('=') {
+                Some((command, value)) => {
+                    policy.per_command.insert(command.trim().trim_start_matches('/').to_string(), visibility(value)?);
+                }
+                None => policy.ephemeral_by_default = visibility(part)?,
+            }
+        }
+        Ok(policy)
+    }
+
+    pub fn is_ephemeral(&self, command: &str) -> bool {
+        self.per_command.get(command).copied().unwrap_or(self.ephemeral_by_default)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn interaction(extra: serde_json::Value) -> Interaction {
+        let mut value = json!({
+            "application_id": "1",
+            "id": "2",
+            "type": 1,
+            "token": "token",
+            "version": 1,
+            "authorizing_integration_owners": {},
+        });
+        value.as_object_mut().unwrap().extend(extra.as_object().unwrap().clone());
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn user(id: &str) -> serde_json::Value {
+        json!({ "id": id, "username": "someone", "discriminator": "0", "avatar": null })
+    }
+
+    #[test]
+    fn test_resolve_user_id() {
+        // Direct message
+        let dm = interaction(json!({ "user": user("10") }));
+        assert_eq!(resolve_user_id(&dm), Some(Id::new(10)));
+
+        // In a server
+        let member = json!({
+            "deaf": false,
+            "mute": false,
+            "flags": 0,
+            "joined_at": null,
+            "nick": null,
+            "roles": [],
+            "user": user("20"),
+        });
+        let in_guild = interaction(json!({ "guild_id": "3", "member": member }));
+        assert_eq!(resolve_user_id(&in_guild), Some(Id::new(20)));
+
+        assert_eq!(resolve_user_id(&interaction(json!({}))), None);
+    }
+
+    #[test]
+    fn test_ephemeral_flag_follows_policy() {
+        let flags = |response: InteractionResponse| response.data.unwrap().flags;
+        assert_eq!(flags(message_response("✅ Saved", true)), Some(MessageFlags::EPHEMERAL));
+        assert_eq!(flags(message_response("✅ Saved", false)), None);
+
+        let policy = ResponsePolicy::parse("public, /record=ephemeral").unwrap();
+        assert!(policy.is_ephemeral("record"));
+        assert!(!policy.is_ephemeral("join"));
+        assert!(ResponsePolicy::default().is_ephemeral("join"));
+        assert!(ResponsePolicy::parse("join=hidden").is_err());
+    }
+}
@@ -9,6 +9,7 @@ use twilight_model::{
     application::interaction::application_command::CommandOptionValue,
     gateway::payload::incoming::ReactionAdd,
     gateway::payload::incoming::ReactionRemove,
+    gateway::payload::incoming::GuildCreate,
     http::interaction::{InteractionResponse, InteractionResponseType},
     id::Id,
 };
@@ -23,21 +24,91 @@ mod voice_translator;
 mod transcriber;
 mod summarizer;
 mod translator;
-mod commands;
 mod user_settings;
+mod help;
+mod guild_config;
+mod transcript_store;
+mod ogg_opus;
+mod mp3;
+mod translation_dispatcher;
+mod romaji;
+mod redaction;
+mod recording_service;
+mod voice_states;
+mod metrics;
+mod consent;
+mod presence;
+mod audio_export;
+mod session_export;
+mod gateway;
+mod stats;
+mod member_names;
+mod wav_info;
+mod persist;
+mod api_client;
+mod transcript_hooks;
+mod schedule;
+mod timezone;
+mod error;
+mod recording_files;
+mod voice_reconnect;
+mod remote_audio;
+mod selftest;
+mod interactions;
+mod feedback;
+mod discord_retry;
+mod minutes_webhook;
+mod preroll;
 
-use voice_recorder::{RecordingManager, VoiceReceiveHandler};
+use voice_recorder::{RecordingManager, RecordingMode};
+use translation_dispatcher::{TranslationDispatcher, SPEAKER_QUEUE_CAPACITY};
 use voice_translator::{TranslationManager, VoiceTranslateHandler};
-use transcriber::{Transcriber, transcribe_wav_file};
+use transcriber::{Transcriber, TranscriberRegistry};
 use summarizer::Summarizer;
 use translator::Translator;
-use commands::RecordingCommands;
+use recording_service::RecordingService;
 use user_settings::UserSettingsManager;
+use guild_config::{GuildConfigManager, has_required_role};
+use voice_states::VoiceStateTracker;
+use consent::{ConsentOutcome, ConsentTracker, CONSENT_OBJECTION_EMOJI};
+use transcript_store::LastTranscripts;
+use error::{AppError, TranslationFailure};
+use interactions::{message_response, resolve_user_id};
+use voice_reconnect::{ReconnectAction, ReconnectPolicy, VoiceConnectionEvent, VoiceConnectionHandler};
 
 #[derive(CommandModel, CreateCommand)]
 #[command(name = "record", desc = "Join voice channel and start recording control")]
 struct RecordCommand;
 
+/// Start recording your current voice channel right away
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "record_here", desc = "Join your voice channel and start recording immediately")]
+struct RecordHereCommand {
+    /// Language spoken in the meeting (detected per speaker if omitted)
+    language: Option<Language>,
+}
+
+/// Transcribe an audio file from a link
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "transcribe_url", desc = "Transcribe and summarize an audio file from an https link")]
+struct TranscribeUrlCommand {
+    /// https link to a WAV or Ogg Opus file
+    url: String,
+    /// Language spoken in the audio (detected if omitted)
+    language: Option<Language>,
+}
+
+/// Start recording later, e.g. for a recurring meeting
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "record_schedule", desc = "Schedule a recording of your voice channel")]
+struct RecordScheduleCommand {
+    /// Start time: HH:MM, YYYY-MM-DD HH:MM or an offset like +30m
+    start: String,
+    /// Stop automatically after this many minutes
+    #[command(min_value = 1, max_value = 480)]
+    duration: Option<i64>,
+}
+
 /// Language choices for translation
 #[derive(twilight_interactions::command::CommandOption, twilight_interactions::command::CreateOption)]
 enum Language {
@@ -47,6 +118,8 @@ enum Language {
     Korean,
     #[option(name = "🇺🇸 English", value = "en")]
     English,
+    #[option(name = "🇬🇧 English (UK)", value = "en-gb")]
+    BritishEnglish,
 }
 
 /// Set language for translation command
@@ -57,36 +130,473 @@ struct TranslateSetCommand {
     source: Language,
     /// Target language for translation
     target: Language,
+    /// Also translate into this language
+    target2: Option<Language>,
+    /// Show romaji under your Japanese speech
+    romaji: Option<bool>,
+    /// Translate the other way when you speak your target language
+    auto_swap: Option<bool>,
+}
+
+/// Translate pasted text
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "translate_text", desc = "Translate a piece of text")]
+struct TranslateTextCommand {
+    /// Text to translate
+    #[command(max_length = 6000)]
+    text: String,
+    /// Language to translate into
+    target: Language,
+    /// Language of the text (detected automatically if omitted)
+    source: Option<Language>,
+}
+
+/// Whose languages a translation session translates into
+#[derive(twilight_interactions::command::CommandOption, twilight_interactions::command::CreateOption)]
+enum TranslationMode {
+    #[option(name = "Speaker (each speaker's /translate_set targets)", value = "speaker")]
+    Speaker,
+    #[option(name = "Listener (every participant's own language)", value = "listener")]
+    Listener,
 }
 
 /// Start real-time voice translation
 #[derive(CommandModel, CreateCommand)]
 #[command(name = "translate_start", desc = "Start real-time voice translation")]
-struct TranslateStartCommand;
+struct TranslateStartCommand {
+    /// Translate into the speaker's targets (default) or into each listener's language
+    mode: Option<TranslationMode>,
+}
+
+/// Join the caller's voice channel without starting anything
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "join", desc = "Join your voice channel without recording or translating")]
+struct JoinCommand;
+
+/// Flush recording and translation buffers, then leave the voice channel
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "leave", desc = "Finish any recording or translation and leave the voice channel")]
+struct LeaveCommand;
+
+/// Drop the current recording without transcribing it
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "record_abort", desc = "Stop recording and throw the recording away without transcribing it")]
+struct RecordAbortCommand;
 
 /// Stop real-time voice translation
 #[derive(CommandModel, CreateCommand)]
 #[command(name = "translate_stop", desc = "Stop real-time voice translation")]
 struct TranslateStopCommand;
 
+/// Temporarily stop posting translations
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "translate_pause", desc = "Pause translation without leaving the voice channel")]
+struct TranslatePauseCommand;
+
+/// Resume posting translations after a pause
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "translate_resume", desc = "Resume a paused translation session")]
+struct TranslateResumeCommand;
+
+/// Set the recording control emoji for this server
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "record_emoji",
+    desc = "Set the emoji used to start/stop recording in this server",
+    default_permissions = "manage_guild_permission"
+)]
+struct RecordEmojiCommand {
+    /// Unicode or custom server emoji to use for the recording control reaction
+    emoji: String,
+}
+
+/// Restrict who can start recordings in this server
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "record_role",
+    desc = "Require a role to start recordings in this server",
+    default_permissions = "manage_guild_permission"
+)]
+struct RecordRoleCommand {
+    /// Role required to record (leave empty to allow everyone)
+    role: Option<Id<twilight_model::id::marker::RoleMarker>>,
+}
+
+/// What `/output_channel` routes
+#[derive(twilight_interactions::command::CommandOption, twilight_interactions::command::CreateOption)]
+enum OutputKindChoice {
+    #[option(name = "Minutes", value = "minutes")]
+    Minutes,
+    #[option(name = "Translations", value = "translations")]
+    Translations,
+    #[option(name = "Both", value = "both")]
+    Both,
+}
+
+/// Route minutes and translations to a text channel
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "output_channel",
+    desc = "Post minutes or translations in a text channel instead of the voice channel chat",
+    default_permissions = "manage_guild_permission"
+)]
+struct OutputChannelCommand {
+    /// What to post there
+    kind: OutputKindChoice,
+    /// Text channel to use (leave empty to post in the voice channel chat again)
+    #[command(channel_types = "guild_text guild_announcement")]
+    channel: Option<Id<twilight_model::id::marker::ChannelMarker>>,
+}
+
+/// Only server managers see `/record_role` by default
+fn manage_guild_permission() -> twilight_model::guild::Permissions {
+    twilight_model::guild::Permissions::MANAGE_GUILD
+}
+
+/// Ask participants for consent before recording starts
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "record_consent",
+    desc = "Announce recordings and let participants object before they start",
+    default_permissions = "manage_guild_permission"
+)]
+struct RecordConsentCommand {
+    /// Whether to announce recordings and wait for objections
+    enabled: bool,
+    /// Seconds participants have to react with ❌ (default 15)
+    #[command(min_value = 5, max_value = 120)]
+    seconds: Option<i64>,
+}
+
+/// Stop recording a user's audio in this server
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "record_exclude",
+    desc = "Leave a user out of recordings in this server",
+    default_permissions = "manage_guild_permission"
+)]
+struct RecordExcludeCommand {
+    /// User whose audio shouldn't be recorded
+    user: Id<twilight_model::id::marker::UserMarker>,
+}
+
+/// Record a previously excluded user again
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "record_include",
+    desc = "Record a user left out with /record_exclude again",
+    default_permissions = "manage_guild_permission"
+)]
+struct RecordIncludeCommand {
+    /// User to record again
+    user: Id<twilight_model::id::marker::UserMarker>,
+}
+
+/// Whisper model choices for `/model`
+#[derive(twilight_interactions::command::CommandOption, twilight_interactions::command::CreateOption)]
+enum TranscriptionModel {
+    #[option(name = "Default (base for recordings, fast for translation)", value = "default")]
+    Default,
+    #[option(name = "Base (more accurate)", value = "base")]
+    Base,
+    #[option(name = "Fast (lower latency)", value = "fast")]
+    Fast,
+}
+
+/// Choose the whisper model used for this server's transcriptions
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "model",
+    desc = "Choose the transcription model for this server",
+    default_permissions = "manage_guild_permission"
+)]
+struct ModelCommand {
+    /// Model to use (leave empty to show the current one)
+    model: Option<TranscriptionModel>,
+}
+
+/// Check that DeepL, z.ai and whisper all work
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "selftest",
+    desc = "Check that translation, summarization and transcription are working",
+    default_permissions = "manage_guild_permission"
+)]
+struct SelftestCommand;
+
+/// Summarize sample text without recording anything
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "dryrun_summary", desc = "Preview meeting minutes for pasted text")]
+struct DryrunSummaryCommand {
+    /// Text to summarize, e.g. a sample transcript
+    text: Option<String>,
+    /// Text file to summarize, for longer input
+    file: Option<twilight_model::channel::Attachment>,
+}
+
+/// Download every user's language settings
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "settings_export",
+    desc = "Download everyone's language settings as JSON (bot operators only)",
+    default_permissions = "manage_guild_permission"
+)]
+struct SettingsExportCommand;
+
+/// Merge language settings from an export
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "settings_import",
+    desc = "Merge language settings from a /settings_export file (bot operators only)",
+    default_permissions = "manage_guild_permission"
+)]
+struct SettingsImportCommand {
+    /// JSON file made by /settings_export
+    file: twilight_model::channel::Attachment,
+}
+
+/// Override the prompt used to write meeting minutes
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "minutes_template",
+    desc = "Use a custom prompt for this server's meeting minutes",
+    default_permissions = "manage_guild_permission"
+)]
+struct MinutesTemplateCommand {
+    /// Prompt containing {transcript}; write \n for a line break (leave empty to show the current one)
+    template: Option<String>,
+    /// Go back to the built-in prompt
+    reset: Option<bool>,
+}
+
+/// Adjust when real-time translation considers an utterance finished
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "translate_tune",
+    desc = "Set the pause and minimum speech length for real-time translation",
+    default_permissions = "manage_guild_permission"
+)]
+struct TranslateTuneCommand {
+    /// Pause in milliseconds that ends an utterance (default 1500)
+    #[command(min_value = 300, max_value = 5000)]
+    silence_ms: Option<i64>,
+    /// Shortest utterance in milliseconds worth translating (default 500)
+    #[command(min_value = 100, max_value = 5000)]
+    min_speech_ms: Option<i64>,
+}
+
+/// Show recent translations from the active session
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "translate_history", desc = "Show recent translations from the current translation session")]
+struct TranslateHistoryCommand;
+
+/// Configure transcript redaction for this server
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "redaction",
+    desc = "Mask profanity, emails and phone numbers in this server's transcripts",
+    default_permissions = "manage_guild_permission"
+)]
+struct RedactionCommand {
+    /// Whether transcripts should be redacted
+    enabled: bool,
+    /// Comma-separated words to mask besides common profanity (replaces the current list)
+    words: Option<String>,
+}
+
+/// Add a term to this server's vocabulary
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "vocab_add",
+    desc = "Teach the bot a name or term used in this server",
+    default_permissions = "manage_guild_permission"
+)]
+struct VocabAddCommand {
+    /// The term as it should be written
+    #[command(max_length = 100)]
+    term: String,
+    /// Fixed translation of the term
+    #[command(max_length = 100)]
+    translation: Option<String>,
+    /// Language of the term (needed with a translation)
+    from: Option<Language>,
+    /// Language of the translation (needed with a translation)
+    to: Option<Language>,
+}
+
+/// List this server's vocabulary
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "vocab_list",
+    desc = "List the names and terms registered for this server",
+    default_permissions = "manage_guild_permission"
+)]
+struct VocabListCommand;
+
+/// Remove a term from this server's vocabulary
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "vocab_remove",
+    desc = "Remove a name or term from this server's vocabulary",
+    default_permissions = "manage_guild_permission"
+)]
+struct VocabRemoveCommand {
+    /// The term to remove
+    term: String,
+}
+
+/// Report a transcription mistake
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "feedback", desc = "Report a word or phrase the bot transcribed wrong")]
+struct FeedbackCommand {
+    /// What the transcription said
+    #[command(max_length = 500)]
+    wrong: String,
+    /// What was actually said
+    #[command(max_length = 500)]
+    correct: String,
+}
+
+/// Show what the bot knows about your voice state
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "whoami", desc = "Show which voice channel the bot thinks you're in and whether it's connected")]
+struct WhoamiCommand;
+
+/// Inspect or clear this server's recordings on disk
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "recordings",
+    desc = "List or delete this server's recordings on disk",
+    default_permissions = "manage_guild_permission"
+)]
+enum RecordingsCommand {
+    #[command(name = "list")]
+    List(RecordingsListCommand),
+    #[command(name = "clear")]
+    Clear(RecordingsClearCommand),
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "list", desc = "List recording files with their sizes and ages")]
+struct RecordingsListCommand;
+
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "clear", desc = "Delete all recording files for this server")]
+struct RecordingsClearCommand;
+
+/// Show uptime and lifetime counters
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "stats", desc = "Show the bot's uptime and usage counters")]
+struct StatsCommand;
+
+/// Download the last meeting as a ZIP
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "export", desc = "Download the last meeting's transcript, minutes and audio as a ZIP")]
+struct ExportCommand;
+
+/// Re-fetch the last meeting's transcript
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "transcript", desc = "Download the most recent meeting transcript for this server")]
+struct TranscriptCommand;
+
+/// Show available commands
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "help", desc = "Show available commands and how to use them")]
+struct HelpCommand;
 
+/// Control message, its channel, the guild and the member who created it
+type ControlKey = (
+    Id<twilight_model::id::marker::MessageMarker>,
+    Id<twilight_model::id::marker::ChannelMarker>,
+    Id<twilight_model::id::marker::GuildMarker>,
+    Id<twilight_model::id::marker::UserMarker>,
+);
 
 struct BotState {
     http: Arc<HttpClient>,
+    bot_user_id: Id<twilight_model::id::marker::UserMarker>,
     application_id: Id<twilight_model::id::marker::ApplicationMarker>,
     http_client: ReqwestClient,
-    recording_commands: RecordingCommands,
+    /// Where `/export` uploads archives too large for Discord (`EXPORT_UPLOAD_URL`)
+    export_sink: Option<session_export::ExportSink>,
+    /// Per-server pause between `/transcribe_url` downloads
+    transcribe_url_limit: remote_audio::RateLimit,
+    /// Which commands acknowledge ephemerally
+    response_policy: interactions::ResponsePolicy,
+    recording: Arc<RecordingService>,
     translation_manager: Arc<TranslationManager>,
     translator: Arc<Translator>,
-    transcriber: Arc<Transcriber>,
+    transcribers: Arc<TranscriberRegistry>,
+    /// Real-time transcriptions less confident than this are redone with the meeting model
+    escalation_threshold: Option<f32>,
     user_settings: Arc<UserSettingsManager>,
-    user_voice_states: Arc<Mutex<HashMap<Id<twilight_model::id::marker::UserMarker>, Id<twilight_model::id::marker::ChannelMarker>>>>,
+    guild_config: Arc<GuildConfigManager>,
+    last_transcripts: Arc<LastTranscripts>,
+    /// Transcription corrections reported with `/feedback`
+    feedback_log: Arc<feedback::FeedbackLog>,
+    /// Users allowed to export and import everyone's settings (`SETTINGS_ADMIN_IDS`)
+    settings_admins: std::collections::HashSet<Id<twilight_model::id::marker::UserMarker>>,
+    voice_states: Arc<VoiceStateTracker>,
+    stats: Arc<stats::BotStats>,
+    member_names: Arc<member_names::MemberNameCache>,
+    schedule: Arc<schedule::RecordingSchedule>,
+    consent: Arc<ConsentTracker>,
     songbird: Arc<Songbird>,
-    voice_handlers: Arc<Mutex<HashMap<Id<twilight_model::id::marker::GuildMarker>, voice_recorder::VoiceReceiveHandler>>>,
     translate_handlers: Arc<Mutex<HashMap<Id<twilight_model::id::marker::GuildMarker>, VoiceTranslateHandler>>>,
+    /// Driver connect/disconnect events for the reconnect supervisor
+    voice_events: tokio::sync::mpsc::UnboundedSender<(Id<twilight_model::id::marker::GuildMarker>, VoiceConnectionEvent)>,
+    /// Text channel each guild's recording or translation was started from, for notices
+    /// that outlive the command, like losing the voice connection
+    session_text_channels: Arc<Mutex<HashMap<Id<twilight_model::id::marker::GuildMarker>, Id<twilight_model::id::marker::ChannelMarker>>>>,
     // Reaction control: (message_id, channel_id, guild_id, user_id) -> is_recording
-    reaction_controls: Arc<Mutex<HashMap<(Id<twilight_model::id::marker::MessageMarker>, Id<twilight_model::id::marker::ChannelMarker>, Id<twilight_model::id::marker::GuildMarker>, Id<twilight_model::id::marker::UserMarker>), bool>>>,
+    reaction_controls: Arc<Mutex<HashMap<ControlKey, bool>>>,
+}
+
+/// Discord webhook that minutes are posted through instead of the bot user, if configured.
+/// It's looked up once so it's only used for meetings in its own server and channel.
+async fn minutes_webhook_from_env(http: &HttpClient) -> Option<minutes_webhook::MinutesWebhook> {
+    let url = env::var("MINUTES_WEBHOOK_URL").ok().filter(|url| !url.trim().is_empty())?;
+    let Some(webhook) = minutes_webhook::MinutesWebhook::from_url(&url) else {
+        eprintln!("[WARN] MINUTES_WEBHOOK_URL is not a Discord webhook URL; posting minutes as the bot");
+        return None;
+    };
+    let webhook = match webhook.resolve(http).await {
+        Ok(webhook) => webhook,
+        Err(e) => {
+            eprintln!("[WARN] Couldn't look up MINUTES_WEBHOOK_URL, posting minutes as the bot: {}", e);
+            return None;
+        }
+    };
+    println!("[INFO] Posting meeting minutes bound for the webhook's channel through it");
+    Some(webhook.with_identity(env::var("MINUTES_WEBHOOK_USERNAME").ok(), env::var("MINUTES_WEBHOOK_AVATAR_URL").ok()))
+}
+
+/// Hooks enabled through the environment: a JSON webhook and an on-disk archive
+fn built_in_transcript_hooks(client: &ReqwestClient) -> Vec<Arc<dyn transcript_hooks::TranscriptHook>> {
+    let mut hooks: Vec<Arc<dyn transcript_hooks::TranscriptHook>> = Vec::new();
+    if let Ok(url) = env::var("TRANSCRIPT_WEBHOOK_URL") && !url.trim().is_empty() {
+        println!("[INFO] Posting finished transcripts to a webhook");
+        hooks.push(Arc::new(transcript_hooks::WebhookHook::new(client.clone(), url.trim().to_string())));
+    }
+    if let Ok(dir) = env::var("TRANSCRIPT_ARCHIVE_DIR") && !dir.trim().is_empty() {
+        println!("[INFO] Archiving finished transcripts to {}", dir.trim());
+        hooks.push(Arc::new(transcript_hooks::StorageHook::new(dir.trim())));
+    }
+    hooks
+}
+
+/// Songbird's setting for a decode rate; an unsupported `DECODE_SAMPLE_RATE` fails the build
+const fn songbird_sample_rate(rate: u32) -> SampleRate {
+    match rate {
+        8_000 => SampleRate::Hz8000,
+        12_000 => SampleRate::Hz12000,
+        16_000 => SampleRate::Hz16000,
+        24_000 => SampleRate::Hz24000,
+        48_000 => SampleRate::Hz48000,
+        _ => panic!("songbird can't decode at this sample rate"),
+    }
 }
+const DECODE_RATE: SampleRate = songbird_sample_rate(transcriber::DECODE_SAMPLE_RATE);
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
@@ -96,6 +606,7 @@ async fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
 
     tracing_subscriber::fmt::init();
     dotenvy::dotenv().ok();
+    timezone::init_from_env();
 
     let token = env::var("DISCORD_TOKEN")
         .map_err(|_| "DISCORD_TOKEN not set")?;
@@ -108,6 +619,9 @@ async fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
     let zai_api_key = env::var("ZAI_API_KEY")
         .unwrap_or_default();
 
+    let summary_language = env::var("SUMMARY_LANGUAGE")
+        .unwrap_or_else(|_| "ja".to_string());
+
     let deepl_api_key = env::var("DEEPL_API_KEY")
         .expect("DEEPL_API_KEY must be set");
 
@@ -117,9 +631,29 @@ async fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
     let whisper_model_fast_path = env::var("WHISPER_MODEL_FAST_PATH")
         .unwrap_or_else(|_| "./models/ggml-large-v3-turbo-q5_0.bin".to_string());
 
+    let transcription_timeout = env::var("TRANSCRIPTION_TIMEOUT_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .map(std::time::Duration::from_secs)
+        .unwrap_or(transcriber::DEFAULT_TRANSCRIPTION_TIMEOUT);
+
+    let recording_mode = RecordingMode::from_config(
+        &env::var("RECORDING_MODE").unwrap_or_default()
+    );
+
+    // Prometheus metrics are only served when an address is configured
+    if let Ok(metrics_addr) = env::var("METRICS_ADDR") {
+        tokio::spawn(async move {
+            if let Err(e) = metrics::serve(&metrics_addr).await {
+                eprintln!("[ERROR] Metrics server failed: {}", e);
+            }
+        });
+    }
+
     let http_client = ReqwestClient::new();
     let intents = Intents::GUILD_VOICE_STATES | Intents::GUILDS | Intents::GUILD_MEMBERS | Intents::GUILD_MESSAGE_REACTIONS | Intents::GUILD_MESSAGES;
     let mut shard = Shard::new(ShardId::ONE, token.clone(), intents);
+    println!("[INFO] {}", gateway::permissions_notice(application_id));
     let http = Arc::new(HttpClient::new(token));
     let application_id = Id::new(application_id);
 
@@ -133,39 +667,174 @@ async fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
     let twilight_map = TwilightMap::new(map);
     let songbird = Songbird::twilight(Arc::new(twilight_map), bot_user_id);
     
-    // Configure Songbird to decode received audio as mono 48kHz
+    // Configure Songbird to decode received audio as mono at DECODE_SAMPLE_RATE,
+    // or only decrypt it when recording Opus packets as-is
+    let decode_mode = match recording_mode {
+        RecordingMode::Pcm => DecodeMode::Decode,
+        RecordingMode::OpusPassthrough => {
+            println!("[INFO] Opus passthrough recording enabled; transcription and translation are disabled");
+            DecodeMode::Decrypt
+        }
+    };
     songbird.set_config(
         songbird::Config::default()
-            .decode_mode(DecodeMode::Decode)
+            .decode_mode(decode_mode)
             .decode_channels(Channels::Mono)
-            .decode_sample_rate(SampleRate::Hz48000)
+            .decode_sample_rate(DECODE_RATE)
             .use_softclip(true),
     );
 
-    let recording_manager = Arc::new(RecordingManager::new("./recordings".to_string()));
-    let transcriber = Arc::new(Transcriber::new(&whisper_model_path)?);
-    let transcriber_fast = Arc::new(Transcriber::new(&whisper_model_fast_path)?);
-    let summarizer = Arc::new(Summarizer::new(zai_api_key.clone()));
-    let translation_manager = Arc::new(TranslationManager::new());
-    let translator = Arc::new(Translator::new(deepl_api_key));
+    // Audio held per speaker until Discord says who is talking, so first syllables aren't lost (0 disables)
+    let audio_preroll = env::var("PREROLL_MS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .map(std::time::Duration::from_millis)
+        .unwrap_or(preroll::DEFAULT_PREROLL);
+
+    let record_mixdown = voice_recorder::Mixdown::from_config(&env::var("RECORD_MIXDOWN").unwrap_or_default());
+
+    // Keep WAVs after transcription so /export can include them (0 deletes them right away)
+    let retain_recordings = env::var("RETAIN_RECORDINGS_MINS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .map(|mins| std::time::Duration::from_secs(mins * 60))
+        .unwrap_or_default();
+
+    // Shorter or near-silent speaker recordings are dropped before transcription (0 keeps everything)
+    let min_recording_audio = env::var("MIN_RECORDING_AUDIO_SECS")
+        .ok()
+        .and_then(|v| v.parse::<f64>().ok())
+        .filter(|secs| secs.is_finite() && *secs >= 0.0)
+        .map(std::time::Duration::from_secs_f64)
+        .unwrap_or(transcriber::DEFAULT_MIN_RECORDING_AUDIO);
+
+    // Speakers' audio beyond this is written to their WAV as the meeting goes (0 keeps it all in memory)
+    let max_buffered_samples = env::var("MAX_SPEAKER_BUFFER_SECS")
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .map(|secs| secs * transcriber::DECODE_SAMPLE_RATE as usize)
+        .unwrap_or(voice_recorder::DEFAULT_MAX_BUFFERED_SAMPLES);
+
+    let recording_manager = Arc::new(
+        RecordingManager::new("./recordings".to_string())
+            .with_mode(recording_mode)
+            .with_mixdown(record_mixdown)
+            .with_min_audio(min_recording_audio)
+            .with_max_buffered_samples(max_buffered_samples)
+            .with_preroll(audio_preroll)
+            .with_formats(voice_recorder::OutputFormat::parse_list(
+                &env::var("RECORDING_FORMATS").unwrap_or_default()
+            ))
+    );
+    // Split recordings into utterances at pauses this long (0 or unset transcribes each file whole)
+    let silence_split = env::var("RECORDING_SILENCE_SPLIT_MS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .map(std::time::Duration::from_millis);
+    let response_policy = env::var("CONFIRMATION_VISIBILITY")
+        .map(|spec| interactions::ResponsePolicy::parse(&spec).unwrap_or_else(|e| {
+            eprintln!("[ERROR] CONFIRMATION_VISIBILITY: {}; confirmations will be ephemeral", e);
+            interactions::ResponsePolicy::default()
+        }))
+        .unwrap_or_default();
+    let no_speech = env::var("WHISPER_NO_SPEECH_THOLD")
+        .map(|spec| transcriber::NoSpeechThresholds::parse(&spec).unwrap_or_else(|e| {
+            eprintln!("[ERROR] WHISPER_NO_SPEECH_THOLD: {}; using the built-in thresholds", e);
+            transcriber::NoSpeechThresholds::default()
+        }))
+        .unwrap_or_default();
+
+    let transcriber = Arc::new(
+        Transcriber::new(&whisper_model_path)?
+            .with_timeout(transcription_timeout)
+            .with_silence_split(silence_split)
+            .with_no_speech_thresholds(no_speech.clone())
+    );
+    let transcriber_fast = Arc::new(
+        Transcriber::new_with_fallback(&[&whisper_model_fast_path, &whisper_model_path])?
+            .with_timeout(transcription_timeout)
+            .with_silence_split(silence_split)
+            .with_no_speech_thresholds(no_speech)
+    );
+    println!("[INFO] Whisper models: {} (meetings), {} (translation)", transcriber.model_name(), transcriber_fast.model_name());
+    let escalation_threshold = env::var("TRANSLATION_ESCALATION_CONFIDENCE")
+        .ok()
+        .and_then(|v| v.parse::<f32>().ok())
+        .filter(|threshold| *threshold > 0.0 && *threshold <= 1.0);
+    if let Some(threshold) = escalation_threshold {
+        println!("[INFO] Real-time transcriptions below {:.2} confidence are redone with {}", threshold, transcriber.model_name());
+    }
+    let transcribers = Arc::new(TranscriberRegistry::new(vec![
+        (transcriber::MODEL_BASE, transcriber),
+        (transcriber::MODEL_FAST, transcriber_fast),
+    ]));
+    let summarizer = Arc::new(
+        Summarizer::new(zai_api_key.clone())
+            .with_output_language(&summary_language)
+            .with_client_settings(api_client::ClientSettings::with_timeout(
+                api_client::timeout_from_env("SUMMARY_TIMEOUT_MS", api_client::DEFAULT_SUMMARY_TIMEOUT)
+            ))
+    );
+    // Real-time translation drops speech that's waited longer than this (0 keeps everything)
+    let translation_staleness = env::var("TRANSLATION_STALENESS_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .map(std::time::Duration::from_secs)
+        .unwrap_or(voice_translator::DEFAULT_STALENESS_WINDOW);
+    let translation_manager = Arc::new(
+        TranslationManager::new()
+            .with_staleness_window(translation_staleness)
+            .with_preroll(audio_preroll)
+    );
+    let translator = Arc::new(
+        Translator::new(deepl_api_key).with_client_settings(api_client::ClientSettings::with_timeout(
+            api_client::timeout_from_env("DEEPL_TIMEOUT_MS", api_client::DEFAULT_DEEPL_TIMEOUT)
+        ))
+    );
     let user_settings = Arc::new(UserSettingsManager::new("./user_settings.json"));
+    let guild_config = Arc::new(GuildConfigManager::new("./guild_config.json"));
+    user_settings.spawn_saver(persist::SAVE_DEBOUNCE);
+    guild_config.spawn_saver(persist::SAVE_DEBOUNCE);
+    if let Err(e) = guild_config.watch() {
+        eprintln!("[WARN] Guild config hot reload disabled: {}", e);
+    }
 
-    let recording_commands = RecordingCommands::new(
-        recording_manager.clone(),
-        transcriber.clone(),
+    let songbird = Arc::new(songbird);
+    let stats = Arc::new(stats::BotStats::new());
+    let member_names = Arc::new(member_names::MemberNameCache::new(http.clone()));
+    let last_transcripts = Arc::new(LastTranscripts::new(transcript_store::DEFAULT_TRANSCRIPT_HISTORY));
+    let recording = Arc::new(RecordingService::new(
+        recording_manager,
+        transcribers.clone(),
         summarizer,
-    );
+        songbird.clone(),
+        http.clone(),
+        guild_config.clone(),
+        last_transcripts.clone(),
+    ).with_retained(Arc::new(session_export::RetainedSessions::new(retain_recordings).with_file("./retained_recordings.json")))
+    .with_stats(stats.clone())
+    .with_member_names(member_names.clone())
+    .with_max_consecutive_errors(
+        env::var("MAX_CONSECUTIVE_TRANSCRIPTION_ERRORS")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .unwrap_or(transcriber::DEFAULT_MAX_CONSECUTIVE_ERRORS)
+    ).with_transcript_hooks(built_in_transcript_hooks(&http_client))
+    .with_keep_recordings(
+        env::var("KEEP_RECORDINGS")
+            .map(|v| matches!(v.trim().to_lowercase().as_str(), "1" | "true" | "yes"))
+            .unwrap_or(false)
+    ).with_minutes_as_file(
+        env::var("MINUTES_AS_FILE")
+            .map(|v| matches!(v.trim().to_lowercase().as_str(), "1" | "true" | "yes"))
+            .unwrap_or(false)
+    ).with_minutes_webhook(minutes_webhook_from_env(&http).await));
 
     // Register global commands using twilight-interactions
     println!("[INFO] Registering global commands...");
     let interaction_client = http.interaction(application_id);
     
-    let commands = vec![
-        RecordCommand::create_command().into(),
-        TranslateStartCommand::create_command().into(),
-        TranslateStopCommand::create_command().into(),
-        TranslateSetCommand::create_command().into(),
-    ];
+    let commands = global_commands();
     
     match interaction_client.set_global_commands(&commands).await {
         Ok(_) => println!("[INFO] Global commands registered successfully"),
@@ -175,29 +844,157 @@ async fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
     // Note: Guild commands are automatically removed when the bot leaves a guild
     // or can be manually removed by kicking and re-inviting the bot to a guild
 
+    let (voice_events, voice_event_rx) = tokio::sync::mpsc::unbounded_channel();
     let bot_state = Arc::new(BotState {
         http: http.clone(),
+        bot_user_id,
         application_id,
+        export_sink: env::var("EXPORT_UPLOAD_URL")
+            .ok()
+            .filter(|url| !url.trim().is_empty())
+            .map(|url| session_export::ExportSink::new(http_client.clone(), &url)),
+        transcribe_url_limit: remote_audio::RateLimit::new(remote_audio::TRANSCRIBE_URL_COOLDOWN),
         http_client,
-        recording_commands,
+        response_policy,
+        recording,
         translation_manager,
         translator,
-        transcriber: transcriber_fast,
+        transcribers,
+        escalation_threshold,
         user_settings,
-        user_voice_states: Arc::new(Mutex::new(HashMap::new())),
-        songbird: Arc::new(songbird),
-        voice_handlers: Arc::new(Mutex::new(HashMap::new())),
+        guild_config,
+        last_transcripts,
+        feedback_log: Arc::new(feedback::FeedbackLog::new(
+            env::var("FEEDBACK_LOG_PATH").unwrap_or_else(|_| feedback::DEFAULT_FEEDBACK_LOG.to_string())
+        )),
+        settings_admins: env::var("SETTINGS_ADMIN_IDS")
+            .unwrap_or_default()
+            .split(',')
+            .filter_map(|id| id.trim().parse::<u64>().ok().and_then(Id::new_checked))
+            .collect(),
+        voice_states: Arc::new(VoiceStateTracker::new()),
+        stats,
+        member_names,
+        schedule: Arc::new(schedule::RecordingSchedule::new("./recording_schedule.json")),
+        consent: Arc::new(ConsentTracker::new(bot_user_id)),
+        songbird,
         translate_handlers: Arc::new(Mutex::new(HashMap::new())),
+        voice_events,
+        session_text_channels: Arc::new(Mutex::new(HashMap::new())),
         reaction_controls: Arc::new(Mutex::new(HashMap::new())),
     });
 
+    // Show a recording indicator in the bot's status while sessions are active
+    tokio::spawn(presence::run_presence_updates(
+        shard.sender(),
+        bot_state.recording.recording_manager.subscribe_session_count(),
+    ));
+
+    // Periodically drop voice states for users who are no longer in the guild
+    tokio::spawn(sweep_voice_states(bot_state.clone()));
+
+    // Start recordings registered with /record_schedule
+    tokio::spawn(run_recording_schedule(bot_state.clone()));
+
+    // Rejoin voice channels whose connection dropped mid-session
+    tokio::spawn(run_voice_reconnect(bot_state.clone(), voice_event_rx));
+
+    // Runs even with retention off, so files retained before a restart are still deleted
+    let retained = bot_state.recording.retained.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(session_export::RETENTION_SWEEP_INTERVAL);
+        loop {
+            interval.tick().await;
+            let expired = retained.sweep_expired().await;
+            if expired > 0 {
+                println!("[INFO] Deleted {} expired retained recording(s)", expired);
+            }
+        }
+    });
+
+    // Settings are saved a moment after they change; write what's pending before exiting
+    let shutdown_state = bot_state.clone();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            println!("[INFO] Shutting down, saving recordings and settings...");
+            let saved = shutdown_state.recording.save_active_recordings().await;
+            if !saved.is_empty() {
+                println!("[INFO] Saved {} file(s) from recordings still in progress: {}", saved.len(), saved.join(", "));
+            }
+            shutdown_state.user_settings.flush().await;
+            shutdown_state.guild_config.flush().await;
+            std::process::exit(0);
+        }
+    });
+
     println!("Bot is starting...");
 
+    let mut fatal_errors = gateway::FatalErrorStreak::default();
+    let mut sessions_started = 0u32;
+
     while let Some(item) = shard.next_event(EventTypeFlags::all()).await {
-        let Ok(event) = item else {
-            tracing::warn!(source = ?item.unwrap_err(), "error receiving event");
-            continue;
+        let event = match item {
+            Ok(event) => event,
+            Err(e) => {
+                match gateway::classify_receive_error(e.kind()) {
+                    gateway::GatewayErrorKind::Recoverable => {
+                        tracing::warn!(source = ?e, "error receiving event");
+                    }
+                    gateway::GatewayErrorKind::Fatal => {
+                        let give_up = fatal_errors.record();
+                        eprintln!(
+                            "[ERROR] Gateway reconnect failed ({}/{}): {}",
+                            fatal_errors.count(),
+                            gateway::MAX_CONSECUTIVE_FATAL_ERRORS,
+                            e
+                        );
+                        if give_up {
+                            bot_state.user_settings.flush().await;
+                            bot_state.guild_config.flush().await;
+                            return Err(format!("Giving up after {} failed gateway reconnects", fatal_errors.count()).into());
+                        }
+                    }
+                }
+                continue;
+            }
         };
+        fatal_errors.reset();
+
+        match &event {
+            Event::GatewayClose(frame) => {
+                let code = frame.as_ref().map(|frame| frame.code);
+                match gateway::classify_close_code(code) {
+                    gateway::GatewayErrorKind::Fatal => {
+                        eprintln!("[ERROR] Gateway closed the connection with fatal code {:?}", code);
+                        // Intent and token problems won't fix themselves; stop with the fix instead
+                        if let Some(guidance) = code.and_then(|code| gateway::close_code_guidance(code, intents)) {
+                            eprintln!("[ERROR] {}", guidance);
+                            bot_state.user_settings.flush().await;
+                            bot_state.guild_config.flush().await;
+                            return Err(guidance.into());
+                        }
+                    }
+                    gateway::GatewayErrorKind::Recoverable => {
+                        println!("[WARN] Gateway connection closed ({:?}), reconnecting...", code);
+                    }
+                }
+            }
+            Event::GatewayReconnect => println!("[INFO] Gateway requested a reconnect"),
+            Event::GatewayInvalidateSession(resumable) => {
+                println!("[WARN] Gateway session invalidated (resumable: {})", resumable);
+            }
+            Event::Resumed => println!("[INFO] Gateway session resumed"),
+            Event::Ready(_) => {
+                sessions_started += 1;
+                // A new session (not a resume) may have missed voice state updates;
+                // GuildCreate will repopulate the tracker
+                if sessions_started > 1 {
+                    let cleared = bot_state.voice_states.clear().await;
+                    println!("[INFO] New gateway session, cleared {} tracked voice states", cleared);
+                }
+            }
+            _ => {}
+        }
 
         let state = Arc::clone(&bot_state);
         tokio::spawn(async move {
@@ -207,99 +1004,409 @@ async fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
         });
     }
 
-    Ok(())
+    // The shard only stops yielding events after a fatal close (bad token, disallowed intents, ...)
+    bot_state.user_settings.flush().await;
+    bot_state.guild_config.flush().await;
+    Err("Gateway connection closed and can't be resumed".into())
 }
 
-// Helper function to extract user_id from WAV filename
-// Format: {guild_id}_{user_id}_{timestamp}.wav
-fn extract_user_id_from_filename(file_path: &str) -> Option<Id<twilight_model::id::marker::UserMarker>> {
-    use std::path::Path;
-    
-    Path::new(file_path)
-        .file_stem()
-        .and_then(|stem| stem.to_str())
-        .and_then(|name| {
-            let parts: Vec<&str> = name.split('_').collect();
-            if parts.len() >= 2 {
-                parts[1].parse::<u64>().ok().map(Id::new)
-            } else {
-                None
-            }
-        })
+/// All global slash commands registered on startup
+fn global_commands() -> Vec<twilight_model::application::command::Command> {
+    vec![
+        RecordCommand::create_command().into(),
+        RecordHereCommand::create_command().into(),
+        TranscribeUrlCommand::create_command().into(),
+        RecordScheduleCommand::create_command().into(),
+        JoinCommand::create_command().into(),
+        LeaveCommand::create_command().into(),
+        RecordAbortCommand::create_command().into(),
+        TranslateStartCommand::create_command().into(),
+        TranslateStopCommand::create_command().into(),
+        TranslatePauseCommand::create_command().into(),
+        TranslateResumeCommand::create_command().into(),
+        TranslateSetCommand::create_command().into(),
+        TranslateHistoryCommand::create_command().into(),
+        TranslateTuneCommand::create_command().into(),
+        TranslateTextCommand::create_command().into(),
+        RecordEmojiCommand::create_command().into(),
+        RecordRoleCommand::create_command().into(),
+        OutputChannelCommand::create_command().into(),
+        RecordConsentCommand::create_command().into(),
+        RecordExcludeCommand::create_command().into(),
+        RecordIncludeCommand::create_command().into(),
+        ModelCommand::create_command().into(),
+        MinutesTemplateCommand::create_command().into(),
+        SelftestCommand::create_command().into(),
+        DryrunSummaryCommand::create_command().into(),
+        RedactionCommand::create_command().into(),
+        VocabAddCommand::create_command().into(),
+        VocabListCommand::create_command().into(),
+        VocabRemoveCommand::create_command().into(),
+        FeedbackCommand::create_command().into(),
+        WhoamiCommand::create_command().into(),
+        SettingsExportCommand::create_command().into(),
+        SettingsImportCommand::create_command().into(),
+        RecordingsCommand::create_command().into(),
+        TranscriptCommand::create_command().into(),
+        ExportCommand::create_command().into(),
+        StatsCommand::create_command().into(),
+        HelpCommand::create_command().into(),
+    ]
 }
 
-async fn handle_event(
-    event: Event,
-    state: Arc<BotState>,
-) -> Result<(), Box<dyn Error + Send + Sync>> {
-    match event {
-        Event::InteractionCreate(interaction_create) => {
-            let interaction = interaction_create.0;
+/// Re-verify voice state entries that haven't been updated in a while against the
+/// channel Discord reports. Users who left voice are pruned and missed moves are
+/// corrected; the bot's own entry is checked against songbird's connection.
+async fn sweep_voice_states(state: Arc<BotState>) {
+    use voice_states::{VOICE_STATE_STALE_AFTER, VOICE_STATE_SWEEP_INTERVAL};
 
-            if interaction.kind == InteractionType::ApplicationCommand {
-                handle_command(interaction, state).await?;
-            }
-        }
-        Event::VoiceStateUpdate(voice_state_update) => {
-            let voice_state = voice_state_update.0.clone();
-            let user_id = voice_state.user_id;
-            let guild_id = voice_state.guild_id;
-            
-            // Update songbird with voice state
-            state.songbird.process(&Event::VoiceStateUpdate(voice_state_update)).await;
-            
-            if let Some(_guild_id) = guild_id {
-                if let Some(channel_id) = voice_state.channel_id {
-                    let mut voice_states = state.user_voice_states.lock().await;
-                    voice_states.insert(user_id, channel_id);
-                } else {
-                    let mut voice_states = state.user_voice_states.lock().await;
-                    voice_states.remove(&user_id);
+    let mut interval = tokio::time::interval(VOICE_STATE_SWEEP_INTERVAL);
+    interval.tick().await;
+
+    loop {
+        interval.tick().await;
+
+        let mut pruned = 0;
+        for (guild_id, user_id) in state.voice_states.stale_entries(VOICE_STATE_STALE_AFTER).await {
+            let Some((_, cached_channel, _)) = state.voice_states.lookup(user_id).await else {
+                continue;
+            };
+            let current = if user_id == state.bot_user_id {
+                match state.songbird.get(guild_id) {
+                    Some(call) => call.lock().await.current_channel().map(|channel| Id::from(channel.0)),
+                    None => None,
+                }
+            } else {
+                match voice_states::fetch_voice_channel(&state.http_client, &state.http, guild_id, user_id).await {
+                    Ok(current) => current,
+                    Err(e) => {
+                        // Can't tell; check again on the next sweep
+                        eprintln!("[WARN] Failed to verify the voice state of user {}: {}", user_id, e);
+                        continue;
+                    }
+                }
+            };
+
+            match voice_states::sweep_action(cached_channel, current) {
+                voice_states::SweepAction::Keep => state.voice_states.touch(user_id).await,
+                voice_states::SweepAction::Move(channel_id) => {
+                    state.voice_states.update(guild_id, user_id, Some(channel_id)).await;
+                }
+                voice_states::SweepAction::Prune => {
+                    if state.voice_states.prune(user_id, VOICE_STATE_STALE_AFTER).await {
+                        pruned += 1;
+                    }
                 }
             }
         }
-        Event::VoiceServerUpdate(voice_server_update) => {
-            // Process voice server updates for songbird
-            state.songbird.process(&Event::VoiceServerUpdate(voice_server_update)).await;
-        }
-        Event::ReactionAdd(reaction_add) => {
-            handle_reaction_add(*reaction_add, state).await?;
+
+        if pruned > 0 {
+            println!("[INFO] Pruned {} stale voice states", pruned);
         }
-        Event::ReactionRemove(reaction_remove) => {
-            handle_reaction_remove(*reaction_remove, state).await?;
+    }
+}
+
+/// Register everything the guild's call feeds: the recording receive handler, the
+/// translation handler and the connection watcher. Existing handlers are cleared
+/// first, so calling this after every join never stacks duplicates.
+async fn attach_voice_handlers(state: &BotState, guild_id: Id<twilight_model::id::marker::GuildMarker>) {
+    let Some(call) = state.songbird.get(guild_id) else {
+        return;
+    };
+    let mut call = call.lock().await;
+    call.remove_all_global_events();
+    state.recording.reattach_receive_handler(guild_id, &mut call).await;
+    if let Some(handler) = state.translate_handlers.lock().await.get(&guild_id) {
+        for event in [CoreEvent::SpeakingStateUpdate, CoreEvent::VoiceTick] {
+            call.add_global_event(SongbirdEvent::Core(event), handler.clone());
         }
-        _ => {}
     }
+    let watcher = VoiceConnectionHandler::new(guild_id, state.voice_events.clone());
+    for event in [CoreEvent::DriverConnect, CoreEvent::DriverReconnect, CoreEvent::DriverDisconnect] {
+        call.add_global_event(SongbirdEvent::Core(event), watcher.clone());
+    }
+}
 
-    Ok(())
+/// Voice channel the guild's recording or translation session is in
+async fn session_voice_channel(
+    state: &BotState,
+    guild_id: Id<twilight_model::id::marker::GuildMarker>,
+) -> Option<Id<twilight_model::id::marker::ChannelMarker>> {
+    match state.recording.recording_manager.session_channel(guild_id).await {
+        Some(channel_id) => Some(channel_id),
+        None => state.translation_manager.session_channel(guild_id).await,
+    }
 }
 
-async fn handle_reaction_add(
-    reaction: ReactionAdd,
-    state: Arc<BotState>,
-) -> Result<(), Box<dyn Error + Send + Sync>> {
-    // Check if this is a 🔴 reaction on a control message
-    let emoji = &reaction.emoji;
-    let message_id = reaction.message_id;
-    let channel_id = reaction.channel_id;
-    let guild_id = match reaction.guild_id {
-        Some(id) => id,
-        None => {
-            eprintln!("[ERROR] Reaction add: No guild_id in reaction");
-            return Ok(());
-        }
+/// Whether the user is still in `channel_id`; they may have left while the bot
+/// was waiting to join
+async fn still_in_channel(
+    state: &BotState,
+    user_id: Id<twilight_model::id::marker::UserMarker>,
+    channel_id: Id<twilight_model::id::marker::ChannelMarker>,
+) -> bool {
+    state.voice_states.channel_of(user_id).await == Some(channel_id)
+}
+
+/// Whether anyone besides the bot is in `channel_id`
+async fn channel_occupied(
+    state: &BotState,
+    channel_id: Id<twilight_model::id::marker::ChannelMarker>,
+) -> bool {
+    let present = state.voice_states.users_in(channel_id).await;
+    present.iter().any(|user_id| *user_id != state.bot_user_id)
+}
+
+/// Leave again if nobody is heard within `NO_AUDIO_GRACE` of joining, so the bot
+/// doesn't sit alone in a channel everyone has left
+fn spawn_no_audio_watch(state: Arc<BotState>, guild_id: Id<twilight_model::id::marker::GuildMarker>, recording: bool) {
+    use voice_states::{join_grace, JoinGrace, NO_AUDIO_GRACE};
+
+    tokio::spawn(async move {
+        let joined = std::time::Instant::now();
+        let mut started_at = None;
+        loop {
+            let (session_start, received_audio) = if recording {
+                let manager = &state.recording.recording_manager;
+                (manager.session_start_time(guild_id).await, manager.received_audio(guild_id).await)
+            } else {
+                let manager = &state.translation_manager;
+                (manager.session_start_time(guild_id).await, manager.received_audio(guild_id).await)
+            };
+            // Stop watching once the session this join started has ended
+            let Some(session_start) = session_start else {
+                return;
+            };
+            if *started_at.get_or_insert(session_start) != session_start {
+                return;
+            }
+            match join_grace(joined.elapsed(), received_audio.unwrap_or(false), NO_AUDIO_GRACE) {
+                JoinGrace::Waiting(remaining) => tokio::time::sleep(remaining).await,
+                JoinGrace::Heard => return,
+                JoinGrace::Silent => break,
+            }
+        }
+        leave_silent_channel(&state, guild_id, recording).await;
+    });
+}
+
+async fn leave_silent_channel(state: &BotState, guild_id: Id<twilight_model::id::marker::GuildMarker>, recording: bool) {
+    let Some(channel_id) = session_voice_channel(state, guild_id).await else {
+        return;
+    };
+    println!(
+        "[INFO] Nobody spoke within {}s of joining voice channel {} in guild {}, leaving",
+        voice_states::NO_AUDIO_GRACE.as_secs(),
+        channel_id,
+        guild_id
+    );
+
+    let stopped = if recording { "Recording stopped." } else { "Translation stopped." };
+    let notice = format!(
+        "🔇 Nobody spoke within {} of joining, so I left the voice channel. {}",
+        stats::format_duration(voice_states::NO_AUDIO_GRACE),
+        stopped
+    );
+    if let Err(e) = state.http.create_message(channel_id).content(&notice).await {
+        eprintln!("[ERROR] Failed to post no-audio notice: {}", e);
+    }
+
+    if recording {
+        if let Err(e) = state.recording.stop_and_process(guild_id, channel_id).await {
+            eprintln!("[ERROR] Failed to stop silent recording in guild {}: {}", guild_id, e);
+        }
+    } else {
+        state.translation_manager.stop_translation(guild_id).await;
+        state.translate_handlers.lock().await.remove(&guild_id);
+        if let Err(e) = state.songbird.leave(guild_id).await {
+            eprintln!("[ERROR] Failed to leave voice channel: {}", e);
+        }
+    }
+}
+
+/// Rejoin voice connections that dropped mid-session, backing off between
+/// attempts. Once the attempts run out the session is finalized with a notice.
+async fn run_voice_reconnect(
+    state: Arc<BotState>,
+    mut events: tokio::sync::mpsc::UnboundedReceiver<(Id<twilight_model::id::marker::GuildMarker>, VoiceConnectionEvent)>,
+) {
+    let mut policies: HashMap<Id<twilight_model::id::marker::GuildMarker>, ReconnectPolicy> = HashMap::new();
+    while let Some((guild_id, event)) = events.recv().await {
+        match policies.entry(guild_id).or_default().on_event(event) {
+            ReconnectAction::Ignore => {}
+            ReconnectAction::Rejoin { attempt, delay } => {
+                println!(
+                    "[INFO] Rejoining voice in guild {} in {}s (attempt {}/{})",
+                    guild_id, delay.as_secs(), attempt, voice_reconnect::MAX_RECONNECT_ATTEMPTS
+                );
+                tokio::spawn(rejoin_voice(state.clone(), guild_id, delay));
+            }
+            ReconnectAction::GiveUp => {
+                policies.remove(&guild_id);
+                tokio::spawn(finalize_lost_voice(state.clone(), guild_id));
+            }
+        }
+    }
+}
+
+async fn rejoin_voice(
+    state: Arc<BotState>,
+    guild_id: Id<twilight_model::id::marker::GuildMarker>,
+    delay: std::time::Duration,
+) {
+    tokio::time::sleep(delay).await;
+
+    // Stopped while we waited; nothing left to reconnect
+    let Some(channel_id) = session_voice_channel(&state, guild_id).await else {
+        let _ = state.voice_events.send((guild_id, VoiceConnectionEvent::Left));
+        return;
+    };
+
+    let event = match state.songbird.join(guild_id, channel_id.into_nonzero()).await {
+        Ok(_) => {
+            println!("[INFO] Rejoined voice channel {} in guild {}", channel_id, guild_id);
+            attach_voice_handlers(&state, guild_id).await;
+            VoiceConnectionEvent::Connected
+        }
+        Err(e) => {
+            eprintln!("[WARN] Failed to rejoin voice channel {} in guild {}: {}", channel_id, guild_id, e);
+            VoiceConnectionEvent::RejoinFailed
+        }
+    };
+    let _ = state.voice_events.send((guild_id, event));
+}
+
+/// Reconnecting failed: tell the channel the session was started from, then stop
+/// translating and process the recording from what was captured before the drop
+async fn finalize_lost_voice(state: Arc<BotState>, guild_id: Id<twilight_model::id::marker::GuildMarker>) {
+    let Some(voice_channel_id) = session_voice_channel(&state, guild_id).await else {
+        return;
+    };
+    // The voice channel's own chat is the last resort
+    let channel_id = state.session_text_channels.lock().await.remove(&guild_id).unwrap_or(voice_channel_id);
+    let recording = state.recording.recording_manager.is_recording(guild_id).await;
+    let translating = state.translation_manager.is_translating(guild_id).await;
+    eprintln!("[ERROR] Giving up on the voice connection in guild {}", guild_id);
+
+    let mut lines = vec![format!(
+        "⚠️ **Lost the voice connection** and couldn't reconnect after {} attempts.",
+        voice_reconnect::MAX_RECONNECT_ATTEMPTS
+    )];
+    if translating {
+        state.translation_manager.stop_translation(guild_id).await;
+        state.translate_handlers.lock().await.remove(&guild_id);
+        lines.push("🌐 Translation stopped.".to_string());
+    }
+    if recording {
+        lines.push("🔴 Recording stopped; the transcript and minutes of what was captured will be posted here.".to_string());
+    }
+    if let Err(e) = state.http.create_message(channel_id).content(&lines.join("\n")).await {
+        eprintln!("[ERROR] Failed to post voice connection notice: {}", e);
+    }
+
+    if recording
+        && let Err(e) = state.recording.stop_and_process(guild_id, channel_id).await
+    {
+        eprintln!("[ERROR] Failed to finalize recording after losing voice in guild {}: {}", guild_id, e);
+    }
+    let _ = state.songbird.remove(guild_id).await;
+}
+
+async fn handle_event(
+    event: Event,
+    state: Arc<BotState>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    match event {
+        Event::InteractionCreate(interaction_create) => {
+            let interaction = interaction_create.0;
+
+            if interaction.kind == InteractionType::ApplicationCommand {
+                handle_command(interaction, state).await?;
+            }
+        }
+        Event::VoiceStateUpdate(voice_state_update) => {
+            let voice_state = voice_state_update.0.clone();
+            let user_id = voice_state.user_id;
+            let guild_id = voice_state.guild_id;
+            
+            // Update songbird with voice state
+            state.songbird.process(&Event::VoiceStateUpdate(voice_state_update)).await;
+            
+            if let Some(guild_id) = guild_id {
+                if let Some(channel_id) = voice_state.channel_id {
+                    // The bot itself was moved; songbird follows the connection,
+                    // but our sessions need to know the new channel
+                    if user_id == state.bot_user_id {
+                        state.recording.recording_manager.update_channel(guild_id, channel_id).await;
+                        state.translation_manager.update_channel(guild_id, channel_id).await;
+                    }
+                }
+                state.voice_states.update(guild_id, user_id, voice_state.channel_id).await;
+            }
+        }
+        Event::GuildCreate(guild_create) => {
+            // Voice states in the payload are authoritative; this repairs
+            // entries left behind by leave events missed while disconnected
+            if let GuildCreate::Available(guild) = &*guild_create {
+                let current = guild.voice_states.iter()
+                    .filter_map(|vs| vs.channel_id.map(|channel_id| (vs.user_id, channel_id)));
+                let pruned = state.voice_states.reconcile_guild(guild.id, current).await;
+                if pruned > 0 {
+                    println!("[DEBUG] Reconciled voice states for guild {}", guild.id);
+                }
+            }
+        }
+        Event::GuildDelete(guild_delete) => {
+            let removed = state.voice_states.remove_guild(guild_delete.id).await;
+            println!("[INFO] Left guild {}, cleared {} voice states", guild_delete.id, removed);
+        }
+        Event::VoiceServerUpdate(voice_server_update) => {
+            // Process voice server updates for songbird
+            state.songbird.process(&Event::VoiceServerUpdate(voice_server_update)).await;
+        }
+        Event::ReactionAdd(reaction_add) => {
+            handle_reaction_add(*reaction_add, state).await?;
+        }
+        Event::ReactionRemove(reaction_remove) => {
+            handle_reaction_remove(*reaction_remove, state).await?;
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+async fn handle_reaction_add(
+    reaction: ReactionAdd,
+    state: Arc<BotState>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    // Check if this is a control emoji reaction on a control message
+    let emoji = &reaction.emoji;
+    let message_id = reaction.message_id;
+    let channel_id = reaction.channel_id;
+    let guild_id = match reaction.guild_id {
+        Some(id) => id,
+        None => {
+            eprintln!("[ERROR] Reaction add: No guild_id in reaction");
+            return Ok(());
+        }
     };
     let user_id = reaction.user_id;
     
     println!("[DEBUG] Reaction add: emoji={:?}, user_id={}, message_id={}, channel_id={}, guild_id={}", 
              emoji, user_id, message_id, channel_id, guild_id);
     
-    // Only handle 🔴 emoji
-    // EmojiReactionType is an enum with Unicode and Custom variants
-    let is_target_emoji = matches!(emoji, twilight_model::channel::message::EmojiReactionType::Unicode { name } if name == "🔴");
+    // Objections to a pending recording announcement
+    if state.consent.record_reaction(message_id, user_id, emoji).await {
+        println!("[INFO] User {} objected to recording in guild {}", user_id, guild_id);
+        return Ok(());
+    }
+    
+    // Only handle the guild's configured control emoji
+    let guild_config = state.guild_config.get_guild_config(guild_id).await;
     
-    if !is_target_emoji {
-        println!("[DEBUG] Reaction add: Emoji is not 🔴, ignoring");
+    if !guild_config.matches_control_emoji(emoji) {
+        println!("[DEBUG] Reaction add: Emoji is not {}, ignoring", guild_config.control_emoji_display());
         return Ok(());
     }
     
@@ -307,71 +1414,53 @@ async fn handle_reaction_add(
     let key = (message_id, channel_id, guild_id, user_id);
     println!("[DEBUG] Reaction add: Looking up control key: {:?}", key);
     
-    let mut controls = state.reaction_controls.lock().await;
+    let controls = state.reaction_controls.lock().await;
     
     let control_entry = controls.get(&key);
     match control_entry {
         Some(is_recording) => {
             println!("[DEBUG] Reaction add: Found control entry, is_recording={}", is_recording);
             if !*is_recording {
+                // The required role may have been set after the control message was created
+                let roles = reaction.member.as_ref().map(|m| m.roles.as_slice()).unwrap_or(&[]);
+                if !has_required_role(roles, &guild_config) {
+                    println!("[INFO] User {} lacks the required role to record in guild {}", user_id, guild_id);
+                    let _ = state.http.create_message(channel_id)
+                        .content("❌ You don't have the role required to start recording in this server.")
+                        .await;
+                    return Ok(());
+                }
+
                 // Start recording
                 println!("[INFO] Starting recording via reaction for user {} in guild {}", user_id, guild_id);
                 
                 // Get the user's voice channel
-                let voice_states = state.user_voice_states.lock().await;
-                println!("[DEBUG] Reaction add: User voice states count: {}", voice_states.len());
+                println!("[DEBUG] Reaction add: User voice states count: {}", state.voice_states.len().await);
                 println!("[DEBUG] Reaction add: Looking for user {} in voice states", user_id);
                 
-                if let Some(channel_id) = voice_states.get(&user_id).copied() {
+                if let Some(channel_id) = state.voice_states.channel_of(user_id).await {
                     println!("[DEBUG] Reaction add: Found user in voice channel {}", channel_id);
-                    drop(voice_states);
                     
-                    // Join voice channel
-                    let channel_id_nz = match NonZeroU64::new(channel_id.get()) {
-                        Some(id) => {
-                            println!("[DEBUG] Reaction add: Created NonZeroU64: {}", id);
-                            id
-                        }
-                        None => {
-                            eprintln!("[ERROR] Failed to create NonZeroU64 from channel_id: {}", channel_id.get());
-                            return Ok(());
-                        }
-                    };
-                    
-                    println!("[DEBUG] Reaction add: Attempting to join voice channel {} in guild {}", channel_id_nz, guild_id);
-                    let call_result = state.songbird.join(guild_id, channel_id_nz).await;
+                    // Don't hold the control lock while participants get time to object
+                    drop(controls);
+                    if !await_recording_consent(&state, guild_id, channel_id).await? {
+                        return Ok(());
+                    }
+                    if !still_in_channel(&state, user_id, channel_id).await {
+                        println!("[INFO] User {} left voice channel {} before recording started", user_id, channel_id);
+                        let _ = state.http.create_message(channel_id)
+                            .content("❌ You left the voice channel before recording started, so I didn't join.")
+                            .await;
+                        return Ok(());
+                    }
+                    let mut controls = state.reaction_controls.lock().await;
                     
-                    match call_result {
-                        Ok(call) => {
-                            println!("[INFO] Successfully joined voice channel {}", channel_id);
-                            
-                            // Add voice receive handler
-                            let receive_handler = VoiceReceiveHandler::new(
-                                state.recording_commands.recording_manager.clone(),
-                                guild_id,
-                            );
-                            
-                            let mut call_lock = call.lock().await;
-                            call_lock.add_global_event(
-                                SongbirdEvent::Core(CoreEvent::SpeakingStateUpdate),
-                                receive_handler.clone(),
-                            );
-                            call_lock.add_global_event(
-                                SongbirdEvent::Core(CoreEvent::VoiceTick),
-                                receive_handler.clone(),
-                            );
-                            call_lock.add_global_event(
-                                SongbirdEvent::Core(CoreEvent::ClientDisconnect),
-                                receive_handler.clone(),
-                            );
-                            drop(call_lock);
-                            
-                            // Store the voice handler in state
-                            state.voice_handlers.lock().await.insert(guild_id, receive_handler);
-                            
-                            // Start recording session
-                            state.recording_commands.recording_manager.start_recording(guild_id, channel_id).await;
-                            
+                    println!("[DEBUG] Reaction add: Attempting to join voice channel {} in guild {}", channel_id, guild_id);
+                    match state.recording.start(guild_id, channel_id, user_id, None).await {
+                        Ok(()) => {
+                            attach_voice_handlers(&state, guild_id).await;
+                            state.session_text_channels.lock().await.insert(guild_id, key.1);
+                            spawn_no_audio_watch(state.clone(), guild_id, true);
                             // Update control state
                             controls.insert(key, true);
                             
@@ -385,7 +1474,7 @@ async fn handle_reaction_add(
                             }
                         }
                         Err(e) => {
-                            eprintln!("[ERROR] Failed to join voice channel: {:?}", e);
+                            eprintln!("[ERROR] Failed to join voice channel: {}", e);
                             // Notify user
                             let _ = state.http.create_message(channel_id)
                                 .content(&format!("❌ Failed to join voice channel: {}", e))
@@ -393,8 +1482,7 @@ async fn handle_reaction_add(
                         }
                     }
                 } else {
-                    eprintln!("[ERROR] User {} not found in voice states. Available users: {:?}", 
-                             user_id, voice_states.keys().collect::<Vec<_>>());
+                    eprintln!("[ERROR] User {} not found in voice states", user_id);
                     // Notify user
                     let _ = state.http.create_message(channel_id)
                         .content("❌ You must be in a voice channel to start recording!")
@@ -421,7 +1509,7 @@ async fn handle_reaction_remove(
     reaction: ReactionRemove,
     state: Arc<BotState>,
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
-    // Check if this is a 🔴 reaction on a control message
+    // Check if this is a control emoji reaction on a control message
     let emoji = &reaction.emoji;
     let message_id = reaction.message_id;
     let channel_id = reaction.channel_id;
@@ -431,11 +1519,10 @@ async fn handle_reaction_remove(
     println!("[DEBUG] Reaction remove: emoji={:?}, user_id={}, message_id={}, channel_id={}, guild_id={}", 
              emoji, user_id, message_id, channel_id, guild_id);
     
-    // Only handle 🔴 emoji
-    // EmojiReactionType is an enum with Unicode and Custom variants
-    let is_target_emoji = matches!(emoji, twilight_model::channel::message::EmojiReactionType::Unicode { name } if name == "🔴");
+    // Only handle the guild's configured control emoji
+    let guild_config = state.guild_config.get_guild_config(guild_id).await;
     
-    if !is_target_emoji {
+    if !guild_config.matches_control_emoji(emoji) {
         return Ok(());
     }
     
@@ -443,161 +1530,18 @@ async fn handle_reaction_remove(
     let key = (message_id, channel_id, guild_id, user_id);
     let mut controls = state.reaction_controls.lock().await;
     
-    if let Some(is_recording) = controls.get(&key) {
-        if *is_recording {
-            // Stop recording
-            println!("[INFO] Stopping recording via reaction for user {} in guild {}", user_id, guild_id);
-            
-            // Update control state back to not recording (don't remove, so it can be restarted)
-            controls.insert(key, false);
-            drop(controls);
-            
-            // Leave voice channel
-            let has_call = state.songbird.get(guild_id).is_some();
-            
-            if has_call {
-                // Flush audio buffers
-                if let Some(handler) = state.voice_handlers.lock().await.remove(&guild_id) {
-                    state.recording_commands.recording_manager.flush_audio_buffers(guild_id, &handler).await;
-                }
-                
-                if let Err(e) = state.songbird.leave(guild_id).await {
-                    eprintln!("[ERROR] Failed to leave voice channel: {}", e);
-                }
-            }
-            
-            // Get the voice channel ID to send messages to the voice channel chat
-            let voice_states = state.user_voice_states.lock().await;
-            let voice_channel_id = voice_states.get(&user_id).copied();
-            drop(voice_states);
-            
-            // Stop recording and process
-            let session = state.recording_commands.recording_manager.stop_recording(guild_id).await?;
-            
-            if let Some(session) = session {
-                let speaker_files = session.finalize("./recordings").await.unwrap_or_default();
-                
-                if !speaker_files.is_empty() {
-                    // Cache for user info to avoid duplicate API calls
-                    let mut user_cache: std::collections::HashMap<Id<twilight_model::id::marker::UserMarker>, String> = std::collections::HashMap::new();
-                    
-                    // Transcribe and summarize with speaker labels
-                    let mut full_transcript = String::new();
-                    let mut transcription_errors = Vec::new();
-                    
-                    for file_path in &speaker_files {
-                        println!("[INFO] Transcribing file: {}", file_path);
-                        
-                        // Extract user_id from filename (format: {guild_id}_{user_id}_{timestamp}.wav)
-                        let speaker_id = extract_user_id_from_filename(file_path);
-                        
-                        // Get or fetch speaker display name
-                        let speaker_name = if let Some(id) = speaker_id {
-                            if let Some(name) = user_cache.get(&id) {
-                                name.clone()
-                            } else {
-                                // Fetch guild member info
-                                let display_name = match state.http.guild_member(guild_id, id).await {
-                                    Ok(response) => {
-                                        if let Ok(member) = response.model().await {
-                                            // Use nickname if available, otherwise global username
-                                            member.nick.clone()
-                                                .map(|n| format!("{} ({})", n, member.user.name))
-                                                .unwrap_or_else(|| member.user.name.clone())
-                                        } else {
-                                            format!("User {}", id)
-                                        }
-                                    }
-                                    Err(_) => format!("User {}", id),
-                                };
-                                user_cache.insert(id, display_name.clone());
-                                display_name
-                            }
-                        } else {
-                            "Unknown Speaker".to_string()
-                        };
-                        
-                        match transcribe_wav_file(&state.recording_commands.transcriber, file_path).await {
-                            Ok(transcription) => {
-                                if !transcription.is_empty() {
-                                    // Add speaker label to each line of transcription
-                                    let labeled_text: String = transcription
-                                        .lines()
-                                        .map(|line| format!("**[{}]**: {}", speaker_name, line))
-                                        .collect::<Vec<_>>()
-                                        .join("\n");
-                                    full_transcript.push_str(&format!("{}\n\n", labeled_text));
-                                }
-                            }
-                            Err(e) => {
-                                eprintln!("[ERROR] Failed to transcribe file {}: {}", file_path, e);
-                                transcription_errors.push(format!("File {}: {}", file_path, e));
-                            }
-                        }
-                        
-                        // Delete the WAV file after transcription to save disk space
-                        if let Err(e) = tokio::fs::remove_file(file_path).await {
-                            eprintln!("[WARN] Failed to remove temporary file {}: {}", file_path, e);
-                        } else {
-                            println!("[INFO] Deleted temporary file: {}", file_path);
-                        }
-                    }
-                    
-                    // Send messages to the voice channel chat if available
-                    let target_channel_id = voice_channel_id.unwrap_or(channel_id);
-                    
-                    if full_transcript.is_empty() {
-                        let _ = state.http.create_message(target_channel_id)
-                            .content("⚠️ **No audio detected** or transcription failed. Meeting minutes cannot be generated.")
-                            .await;
-                    } else {
-                        println!("[INFO] Summarizing meeting with {} chars of transcript", full_transcript.len());
-                        match state.recording_commands.summarizer.summarize_meeting(&full_transcript).await {
-                            Ok(meeting_minutes) => {
-                                // Send full transcript first
-                                let transcript_msg = format!(
-                                    "📝 **Full Transcription**\n```\n{}\n```",
-                                    full_transcript.chars().take(1950).collect::<String>()
-                                );
-                                match state.http.create_message(target_channel_id)
-                                    .content(&transcript_msg)
-                                    .await {
-                                    Ok(_) => println!("[INFO] Sent full transcript to voice channel {}", target_channel_id),
-                                    Err(e) => eprintln!("[ERROR] Failed to send transcript: {}", e),
-                                }
-                                
-                                // Then send meeting minutes
-                                let result = format!(
-                                    "✅ **Meeting Minutes Generated**\n\n{}",
-                                    meeting_minutes
-                                );
-                                match state.http.create_message(target_channel_id)
-                                    .content(&result)
-                                    .await {
-                                    Ok(_) => println!("[INFO] Sent meeting minutes to voice channel {}", target_channel_id),
-                                    Err(e) => eprintln!("[ERROR] Failed to send meeting minutes: {}", e),
-                                }
-                            }
-                            Err(e) => {
-                                eprintln!("[ERROR] Failed to summarize meeting: {}", e);
-                                let result = format!(
-                                    "⚠️ **Transcription completed but summarization failed**\n\n**Raw Transcription:**\n```\n{}\n```\n\nError: {}",
-                                    full_transcript.chars().take(1900).collect::<String>(),
-                                    e
-                                );
-                                let _ = state.http.create_message(target_channel_id)
-                                    .content(&result)
-                                    .await;
-                            }
-                        }
-                    }
-                } else {
-                    let _ = state.http.create_message(channel_id)
-                        .content("❌ No audio data recorded")
-                        .await;
-                }
-            }
-        }
+    if let Some(true) = controls.get(&key) {
+        // Stop recording
+        println!("[INFO] Stopping recording via reaction for user {} in guild {}", user_id, guild_id);
+        
+        // Update control state back to not recording (don't remove, so it can be restarted)
+        controls.insert(key, false);
+        drop(controls);
+        
+        // Post results to the voice channel chat if the user is still in one
+        let voice_channel_id = state.voice_states.channel_of(user_id).await;
+        
+        state.recording.stop_and_process(guild_id, voice_channel_id.unwrap_or(channel_id)).await?;
     }
     
     Ok(())
@@ -611,42 +1555,40 @@ async fn handle_command(
     let token = interaction.token.clone();
     let guild_id = interaction.guild_id;
     let channel_id = interaction.channel_id;
-    let user_id = interaction
-        .user
-        .as_ref()
-        .map(|u| u.id)
-        .or_else(|| interaction.member.as_ref().and_then(|m| m.user.as_ref().map(|u| u.id)));
+    let user_id = resolve_user_id(&interaction);
     
     if let Some(InteractionData::ApplicationCommand(ref command_data)) = interaction.data {
         match command_data.name.as_str() {
             "record" => {
                 if let Some(guild_id) = guild_id {
                     if let (Some(user_id), Some(channel_id)) = (user_id, channel_id) {
-                        let _user_voice_states = state.user_voice_states.lock().await;
-                        // Send control message with 🔴 reaction
+                        let guild_config = state.guild_config.get_guild_config(guild_id).await;
+                        if !has_required_role(member_roles(&interaction), &guild_config) {
+                            send_missing_role_response(&state, interaction_id, token).await?;
+                            return Ok(());
+                        }
+                        let control_emoji = guild_config.control_emoji_display();
+                        
+                        // Send control message with the control emoji reaction
                         let control_message_response = state.http.create_message(channel_id)
-                            .content("🔴 **Recording Control**\n\nPress 🔴 to start recording\nPress 🔴 again to stop and generate meeting minutes")
+                            .content(&format!(
+                                "🔴 **Recording Control**\n\nPress {} to start recording\nPress {} again to stop and generate meeting minutes",
+                                control_emoji, control_emoji
+                            ))
                             .await?;
                         
                         // Get the message model to access the id
                         let control_message = control_message_response.model().await?;
                         
-                        // Add 🔴 reaction to the message using RequestReactionType
-                        use twilight_http::request::channel::reaction::RequestReactionType;
-                        state.http.create_reaction(channel_id, control_message.id, &RequestReactionType::Unicode { name: "🔴" }).await?;
+                        // Add the control reaction to the message
+                        state.http.create_reaction(channel_id, control_message.id, &guild_config.control_reaction()).await?;
                         
                         // Register this as a control message
                         let key = (control_message.id, channel_id, guild_id, user_id);
                         state.reaction_controls.lock().await.insert(key, false);
                         
                         // Send success response
-                        let response = InteractionResponse {
-                            kind: InteractionResponseType::ChannelMessageWithSource,
-                            data: Some(twilight_model::http::interaction::InteractionResponseData {
-                                content: Some("✅ **Recording control message created!**\n\nClick the 🔴 reaction above to start/stop recording.".to_string()),
-                                ..Default::default()
-                            }),
-                        };
+                        let response = message_response(format!("✅ **Recording control message created!**\n\nClick the {} reaction above to start/stop recording.", control_emoji), state.response_policy.is_ephemeral("record"));
 
                         if let Err(e) = state.http
                             .interaction(state.application_id)
@@ -655,6 +1597,14 @@ async fn handle_command(
                         {
                             eprintln!("[ERROR] Failed to send response: {}", e);
                         }
+                    } else {
+                        send_error_response(
+                            state.http.clone(),
+                            state.application_id,
+                            interaction_id,
+                            token,
+                            "Could not identify user"
+                        ).await?;
                     }
                 } else {
                     send_error_response(
@@ -672,9 +1622,102 @@ async fn handle_command(
             "translate_stop" => {
                 handle_translate_stop(interaction, state).await?;
             }
+            "translate_pause" => {
+                handle_translate_pause(interaction, state, true).await?;
+            }
+            "translate_resume" => {
+                handle_translate_pause(interaction, state, false).await?;
+            }
             "translate_set" => {
                 handle_translate_set(interaction, state).await?;
             }
+            "record_schedule" => {
+                handle_record_schedule(interaction, state).await?;
+            }
+            "record_here" => {
+                handle_record_here(interaction, state).await?;
+            }
+            "record_emoji" => {
+                handle_record_emoji(interaction, state).await?;
+            }
+            "record_role" => {
+                handle_record_role(interaction, state).await?;
+            }
+            "output_channel" => {
+                handle_output_channel(interaction, state).await?;
+            }
+            "model" => {
+                handle_model(interaction, state).await?;
+            }
+            "transcribe_url" => {
+                handle_transcribe_url(interaction, state).await?;
+            }
+            "selftest" => {
+                handle_selftest(interaction, state).await?;
+            }
+            "minutes_template" => {
+                handle_minutes_template(interaction, state).await?;
+            }
+            "dryrun_summary" => {
+                handle_dryrun_summary(interaction, state).await?;
+            }
+            "record_consent" => {
+                handle_record_consent(interaction, state).await?;
+            }
+            "record_exclude" => {
+                handle_record_exclude(interaction, state, true).await?;
+            }
+            "record_include" => {
+                handle_record_exclude(interaction, state, false).await?;
+            }
+            "translate_history" => {
+                handle_translate_history(interaction, state).await?;
+            }
+            "translate_tune" => {
+                handle_translate_tune(interaction, state).await?;
+            }
+            "translate_text" => {
+                handle_translate_text(interaction, state).await?;
+            }
+            "redaction" => {
+                handle_redaction(interaction, state).await?;
+            }
+            "vocab_add" | "vocab_list" | "vocab_remove" => {
+                handle_vocab(interaction, state).await?;
+            }
+            "feedback" => {
+                handle_feedback(interaction, state).await?;
+            }
+            "whoami" => {
+                handle_whoami(interaction, state).await?;
+            }
+            "settings_export" | "settings_import" => {
+                handle_settings_transfer(interaction, state).await?;
+            }
+            "recordings" => {
+                handle_recordings(interaction, state).await?;
+            }
+            "transcript" => {
+                handle_transcript(interaction, state).await?;
+            }
+            "export" => {
+                handle_export(interaction, state).await?;
+            }
+            "stats" => {
+                handle_stats(interaction, state).await?;
+            }
+            "join" => {
+                handle_join(interaction, state).await?;
+            }
+            "leave" => {
+                handle_leave(interaction, state).await?;
+            }
+            "record_abort" => {
+                handle_record_abort(interaction, state).await?;
+            }
+            "help" => {
+                handle_help(interaction, state).await?;
+            }
             _ => {}
         }
     }
@@ -691,7 +1734,18 @@ async fn handle_translate_start(
     let guild_id = interaction.guild_id;
 
     if let Some(guild_id) = guild_id {
-        if state.recording_commands.recording_manager.is_recording(guild_id).await {
+        if state.recording.recording_manager.mode() == RecordingMode::OpusPassthrough {
+            send_error_response(
+                state.http.clone(),
+                state.application_id,
+                interaction_id,
+                token,
+                "Translation is unavailable while the bot is in Opus passthrough recording mode"
+            ).await?;
+            return Ok(());
+        }
+
+        if state.recording.recording_manager.is_recording(guild_id).await {
             send_error_response(
                 state.http.clone(),
                 state.application_id,
@@ -713,16 +1767,19 @@ async fn handle_translate_start(
             return Ok(());
         }
 
-        let user_id = interaction
-            .user
-            .map(|u| u.id)
-            .or_else(|| interaction.member.as_ref().and_then(|m| m.user.as_ref().map(|u| u.id)));
+        let mut listener_mode = false;
+        if let Some(InteractionData::ApplicationCommand(command_data)) = &interaction.data {
+            for option in &command_data.options {
+                if let ("mode", CommandOptionValue::String(val)) = (option.name.as_str(), &option.value) {
+                    listener_mode = val == "listener";
+                }
+            }
+        }
+
+        let user_id = resolve_user_id(&interaction);
 
         if let Some(user_id) = user_id {
-            let voice_states = state.user_voice_states.lock().await;
-            
-            if let Some(voice_channel_id) = voice_states.get(&user_id).copied() {
-                drop(voice_states);
+            if let Some(voice_channel_id) = state.voice_states.channel_of(user_id).await {
 
                 let channel_id_nz = match NonZeroU64::new(voice_channel_id.get()) {
                     Some(id) => id,
@@ -741,60 +1798,66 @@ async fn handle_translate_start(
                 let call_result = state.songbird.join(guild_id, channel_id_nz).await;
 
                 match call_result {
-                    Ok(call) => {
+                    // Joining takes a moment; don't sit in a channel the user has already left
+                    Ok(_) if !still_in_channel(&state, user_id, voice_channel_id).await => {
+                        println!("[INFO] User {} left voice channel {} before translation started", user_id, voice_channel_id);
+                        let _ = state.songbird.leave(guild_id).await;
+                        send_error_response(
+                            state.http.clone(),
+                            state.application_id,
+                            interaction_id,
+                            token,
+                            "You left the voice channel before translation started, so I didn't stay"
+                        ).await?;
+                        return Ok(());
+                    }
+                    Ok(_) => {
                         let _session = state.translation_manager
                             .start_translation(guild_id, voice_channel_id, voice_translator::TranslationPair::new("ja", "en"))
                             .await;
+                        if let Some(tuning) = state.guild_config.get_guild_config(guild_id).await.translation_tuning {
+                            state.translation_manager.set_tuning(guild_id, tuning).await;
+                        }
 
                         let translate_handler = VoiceTranslateHandler::new(
                             state.translation_manager.clone(),
                             guild_id,
                         );
 
-                        let mut call_lock = call.lock().await;
-                        call_lock.add_global_event(
-                            SongbirdEvent::Core(CoreEvent::SpeakingStateUpdate),
-                            translate_handler.clone(),
-                        );
-                        call_lock.add_global_event(
-                            SongbirdEvent::Core(CoreEvent::VoiceTick),
-                            translate_handler.clone(),
-                        );
-                        drop(call_lock);
-
                         state.translate_handlers.lock().await.insert(guild_id, translate_handler);
+                        attach_voice_handlers(&state, guild_id).await;
+                        if let Some(channel) = &interaction.channel {
+                            state.session_text_channels.lock().await.insert(guild_id, channel.id);
+                        }
+                        spawn_no_audio_watch(state.clone(), guild_id, false);
 
-                        let http = state.http.clone();
-                        let application_id = state.application_id;
-                        let translation_manager = state.translation_manager.clone();
-                        let translator = state.translator.clone();
-                        let transcriber = state.transcriber.clone();
-                        let user_settings = state.user_settings.clone();
-                        let guild_id_for_task = guild_id;
-
-                        tokio::spawn(async move {
-                            process_translation_loop(
-                                http,
-                                application_id,
-                                translation_manager,
-                                translator,
-                                transcriber,
-                                user_settings,
-                                guild_id_for_task,
-                                voice_channel_id,
-                            ).await;
-                        });
-
-                        let response = InteractionResponse {
-                            kind: InteractionResponseType::ChannelMessageWithSource,
-                            data: Some(twilight_model::http::interaction::InteractionResponseData {
-                                content: Some("🌐 **Translation started!**\n\nUse `/translate_set <source> <target>` to configure your language pair.\n\n**Examples:**\n• `/translate_set ja ko` - Japanese to Korean\n• `/translate_set ko ja` - Korean to Japanese\n• `/translate_set en ja` - English to Japanese".to_string()),
-                                ..Default::default()
-                            }),
+                        let guild_config = state.guild_config.get_guild_config(guild_id).await;
+                        let transcriber = state.transcribers.resolve(guild_config.transcription_model.as_deref(), transcriber::MODEL_FAST);
+                        let escalation = state.escalation_threshold
+                            .zip(state.transcribers.get(transcriber::MODEL_BASE))
+                            .filter(|(_, accurate)| accurate.model_name() != transcriber.model_name())
+                            .map(|(threshold, accurate)| transcriber::Escalation { transcriber: accurate, threshold });
+                        let task = TranslationTask {
+                            guild_id,
+                            voice_channel_id,
+                            transcriber,
+                            escalation,
+                            initial_prompt: guild_config.initial_prompt(),
+                            listener_mode,
                         };
 
-                        state.http
-                            .interaction(state.application_id)
+                        tokio::spawn(process_translation_loop(state.clone(), task));
+
+                        let mode_note = if listener_mode {
+                            "\n\n👂 **Listener mode:** everyone's speech is translated into the languages of the other participants."
+                        } else {
+                            ""
+                        };
+
+                        let response = message_response(format!("🌐 **Translation started!**\n\nUse `/translate_set <source> <target>` to configure your language pair.\n\n**Examples:**\n• `/translate_set ja ko` - Japanese to Korean\n• `/translate_set ko ja` - Korean to Japanese\n• `/translate_set en ja` - English to Japanese{}", mode_note), state.response_policy.is_ephemeral("translate_start"));
+
+                        state.http
+                            .interaction(state.application_id)
                             .create_response(interaction_id, &token, &response)
                             .await?;
                     }
@@ -818,6 +1881,14 @@ async fn handle_translate_start(
                     "You must be in a voice channel"
                 ).await?;
             }
+        } else {
+            send_error_response(
+                state.http.clone(),
+                state.application_id,
+                interaction_id,
+                token,
+                "Could not identify user"
+            ).await?;
         }
     } else {
         send_error_response(
@@ -859,13 +1930,7 @@ async fn handle_translate_stop(
             eprintln!("[ERROR] Failed to leave voice channel: {}", e);
         }
 
-        let response = InteractionResponse {
-            kind: InteractionResponseType::ChannelMessageWithSource,
-            data: Some(twilight_model::http::interaction::InteractionResponseData {
-                content: Some("✅ **Translation stopped!**".to_string()),
-                ..Default::default()
-            }),
-        };
+        let response = message_response("✅ **Translation stopped!**".to_string(), state.response_policy.is_ephemeral("translate_stop"));
 
         state.http
             .interaction(state.application_id)
@@ -884,6 +1949,247 @@ async fn handle_translate_stop(
     Ok(())
 }
 
+/// `/record_abort`: leave without transcribing the recording, and stop transcribing
+/// an earlier one that's still in progress
+async fn handle_record_abort(
+    interaction: Interaction,
+    state: Arc<BotState>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let interaction_id = interaction.id;
+    let token = interaction.token.clone();
+
+    let Some(guild_id) = interaction.guild_id else {
+        send_error_response(
+            state.http.clone(),
+            state.application_id,
+            interaction_id,
+            token,
+            "This command can only be used in a server"
+        ).await?;
+        return Ok(());
+    };
+
+    let guild_config = state.guild_config.get_guild_config(guild_id).await;
+    if !has_required_role(member_roles(&interaction), &guild_config) {
+        send_missing_role_response(&state, interaction_id, token).await?;
+        return Ok(());
+    }
+
+    if !state.recording.abort(guild_id).await? {
+        send_error_response(
+            state.http.clone(),
+            state.application_id,
+            interaction_id,
+            token,
+            "Nothing is being recorded or transcribed in this server"
+        ).await?;
+        return Ok(());
+    }
+    // The control messages can start a fresh recording
+    for ((_, _, control_guild_id, _), recording) in state.reaction_controls.lock().await.iter_mut() {
+        if *control_guild_id == guild_id {
+            *recording = false;
+        }
+    }
+
+    let response = message_response(
+        "🗑️ **Recording discarded** — nothing from it will be transcribed or posted.".to_string(),
+        state.response_policy.is_ephemeral("record_abort"),
+    );
+    state.http
+        .interaction(state.application_id)
+        .create_response(interaction_id, &token, &response)
+        .await?;
+    Ok(())
+}
+
+/// `/join`: connect to the caller's voice channel; recording and translation start separately
+async fn handle_join(
+    interaction: Interaction,
+    state: Arc<BotState>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let interaction_id = interaction.id;
+    let token = interaction.token.clone();
+
+    let Some(guild_id) = interaction.guild_id else {
+        send_error_response(
+            state.http.clone(),
+            state.application_id,
+            interaction_id,
+            token,
+            "This command can only be used in a server"
+        ).await?;
+        return Ok(());
+    };
+
+    if state.recording.recording_manager.is_recording(guild_id).await
+        || state.translation_manager.is_translating(guild_id).await
+    {
+        send_error_response(
+            state.http.clone(),
+            state.application_id,
+            interaction_id,
+            token,
+            "Already connected for a recording or translation — use `/leave` first to move channels"
+        ).await?;
+        return Ok(());
+    }
+
+    let user_id = resolve_user_id(&interaction);
+    let voice_channel_id = match user_id {
+        Some(user_id) => state.voice_states.channel_of(user_id).await,
+        None => None,
+    };
+    let Some(voice_channel_id) = voice_channel_id else {
+        send_error_response(
+            state.http.clone(),
+            state.application_id,
+            interaction_id,
+            token,
+            "You need to be in a voice channel to use this command"
+        ).await?;
+        return Ok(());
+    };
+    let Some(channel_id_nz) = NonZeroU64::new(voice_channel_id.get()) else {
+        send_error_response(
+            state.http.clone(),
+            state.application_id,
+            interaction_id,
+            token,
+            "Invalid voice channel ID"
+        ).await?;
+        return Ok(());
+    };
+
+    let content = match state.songbird.join(guild_id, channel_id_nz).await {
+        Ok(_) => {
+            println!("[INFO] Joined voice channel {} in guild {} via /join", voice_channel_id, guild_id);
+            attach_voice_handlers(&state, guild_id).await;
+            format!("🔊 **Connected** to <#{}>. Use `/record_here` or `/translate_start` to begin, and `/leave` to disconnect.", voice_channel_id)
+        }
+        Err(e) => {
+            eprintln!("[ERROR] Failed to join voice channel: {:?}", e);
+            format!("❌ Failed to join voice channel: {}", e)
+        }
+    };
+
+    let response = message_response(content, state.response_policy.is_ephemeral("join"));
+    state.http
+        .interaction(state.application_id)
+        .create_response(interaction_id, &token, &response)
+        .await?;
+
+    Ok(())
+}
+
+/// `/leave`: flush whatever is buffered for recording and translation, then disconnect.
+/// A recording is processed as usual and its minutes are posted to this channel.
+async fn handle_leave(
+    interaction: Interaction,
+    state: Arc<BotState>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let interaction_id = interaction.id;
+    let token = interaction.token.clone();
+
+    let Some(guild_id) = interaction.guild_id else {
+        send_error_response(
+            state.http.clone(),
+            state.application_id,
+            interaction_id,
+            token,
+            "This command can only be used in a server"
+        ).await?;
+        return Ok(());
+    };
+
+    if state.songbird.get(guild_id).is_none() {
+        send_error_response(
+            state.http.clone(),
+            state.application_id,
+            interaction_id,
+            token,
+            "I'm not connected to a voice channel"
+        ).await?;
+        return Ok(());
+    }
+
+    let recording = state.recording.recording_manager.is_recording(guild_id).await;
+
+    // Stop feeding the translation session, then let its loop translate what's left
+    let translating = state.translation_manager.request_drain(guild_id).await;
+    if translating {
+        state.translate_handlers.lock().await.remove(&guild_id);
+    }
+
+    let mut lines = vec!["👋 **Disconnected** from the voice channel.".to_string()];
+    if translating {
+        lines.push("🌐 Translation stopped; speech still buffered is being translated.".to_string());
+    }
+    if recording {
+        lines.push("🔴 Recording stopped; the transcript and minutes will be posted here.".to_string());
+    }
+
+    let response = message_response(lines.join("\n"), state.response_policy.is_ephemeral("leave"));
+    state.http
+        .interaction(state.application_id)
+        .create_response(interaction_id, &token, &response)
+        .await?;
+
+    // The recording path flushes its receive buffers before leaving
+    if recording {
+        let text_channel_id = interaction.channel.as_ref().map(|channel| channel.id);
+        match text_channel_id {
+            Some(text_channel_id) => {
+                state.recording.stop_and_process(guild_id, text_channel_id).await?;
+            }
+            None => eprintln!("[ERROR] /leave in guild {} has no channel to post the recording to", guild_id),
+        }
+    }
+
+    if state.songbird.get(guild_id).is_some()
+        && let Err(e) = state.songbird.leave(guild_id).await
+    {
+        eprintln!("[ERROR] Failed to leave voice channel: {}", e);
+    }
+
+    Ok(())
+}
+
+/// `/translate_pause` and `/translate_resume`: toggle posting while staying connected
+async fn handle_translate_pause(
+    interaction: Interaction,
+    state: Arc<BotState>,
+    paused: bool,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let Some(guild_id) = interaction.guild_id else {
+        send_error_response(
+            state.http.clone(),
+            state.application_id,
+            interaction.id,
+            interaction.token.clone(),
+            "This command can only be used in a server"
+        ).await?;
+        return Ok(());
+    };
+
+    let content = if !state.translation_manager.set_paused(guild_id, paused).await {
+        "❌ No active translation session"
+    } else if paused {
+        "⏸️ **Translation paused.** Use `/translate_resume` to continue."
+    } else {
+        "▶️ **Translation resumed.**"
+    };
+
+    let response = message_response(content.to_string(), true);
+
+    state.http
+        .interaction(state.application_id)
+        .create_response(interaction.id, &interaction.token, &response)
+        .await?;
+
+    Ok(())
+}
+
 async fn handle_translate_set(
     interaction: Interaction,
     state: Arc<BotState>,
@@ -891,15 +2197,15 @@ async fn handle_translate_set(
     let interaction_id = interaction.id;
     let token = interaction.token.clone();
     
-    let user_id = interaction
-        .user
-        .map(|u| u.id)
-        .or_else(|| interaction.member.as_ref().and_then(|m| m.user.as_ref().map(|u| u.id)));
+    let user_id = resolve_user_id(&interaction);
 
     if let Some(user_id) = user_id {
         if let Some(InteractionData::ApplicationCommand(command_data)) = interaction.data {
             let mut source_lang = None;
             let mut target_lang = None;
+            let mut extra_target = None;
+            let mut romaji = false;
+            let mut auto_swap = false;
             
             for option in &command_data.options {
                 match option.name.as_str() {
@@ -913,6 +2219,21 @@ async fn handle_translate_set(
                             target_lang = Some(val.as_str());
                         }
                     }
+                    "target2" => {
+                        if let CommandOptionValue::String(val) = &option.value {
+                            extra_target = Some(val.as_str());
+                        }
+                    }
+                    "romaji" => {
+                        if let CommandOptionValue::Boolean(val) = &option.value {
+                            romaji = *val;
+                        }
+                    }
+                    "auto_swap" => {
+                        if let CommandOptionValue::Boolean(val) = &option.value {
+                            auto_swap = *val;
+                        }
+                    }
                     _ => {}
                 }
             }
@@ -931,49 +2252,49 @@ async fn handle_translate_set(
                 }
             };
             
-            let valid_langs = ["ja", "ko", "en"];
-            if !valid_langs.contains(&source) || !valid_langs.contains(&target) {
+            let chosen: Vec<&str> = [source, target].into_iter().chain(extra_target).collect();
+            if let Err(message) = user_settings::validate_languages(&chosen) {
                 send_error_response(
                     state.http.clone(),
                     state.application_id,
                     interaction_id,
                     token,
-                    "Invalid language codes. Use: ja, ko, or en"
+                    &message
                 ).await?;
                 return Ok(());
             }
 
-            state.user_settings.set_user_language(user_id, source, target).await;
+            let extra_targets: Vec<&str> = extra_target.into_iter().collect();
+            state.user_settings.set_user_language(user_id, source, target, &extra_targets, romaji, auto_swap).await;
+            let targets = state.user_settings.get_user_setting(user_id).await
+                .map(|setting| setting.targets())
+                .unwrap_or_else(|| vec![target.to_string()]);
 
-            let flag = |lang: &str| match lang {
-                "ja" => "🇯🇵",
-                "ko" => "🇰🇷",
-                "en" => "🇺🇸",
-                _ => "🌐",
-            };
+            let flag = user_settings::language_flag;
 
-            let lang_name = |lang: &str| -> String {
-                match lang {
-                    "ja" => "Japanese".to_string(),
-                    "ko" => "Korean".to_string(),
-                    "en" => "English".to_string(),
-                    _ => lang.to_string(),
-                }
+            let lang_name = |lang: &str| user_settings::language_name(lang).unwrap_or(lang).to_string();
+            // Settings follow the user everywhere; say so when they were set outside a server
+            let scope_note = if interaction.guild_id.is_none() {
+                "\n\n📬 This is your preference in every server."
+            } else {
+                ""
             };
 
-            let response = InteractionResponse {
-                kind: InteractionResponseType::ChannelMessageWithSource,
-                data: Some(twilight_model::http::interaction::InteractionResponseData {
-                    content: Some(format!(
-                        "✅ **Language setting saved!**\n\n{} **Speaking**: {}\n{} **Translation target**: {}",
-                        flag(source),
-                        lang_name(source),
-                        flag(target),
-                        lang_name(target)
-                    )),
-                    ..Default::default()
-                }),
-            };
+            let response = message_response(
+                format!(
+                    "✅ **Language setting saved!**\n\n{} **Speaking**: {}\n**Translation target**: {}{}{}{}",
+                    flag(source),
+                    lang_name(source),
+                    targets.iter()
+                        .map(|lang| format!("{} {}", flag(lang), lang_name(lang)))
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    if romaji && source == "ja" { "\n🔤 **Romaji**: shown" } else { "" },
+                    if auto_swap { "\n🔁 **Auto-swap**: on" } else { "" },
+                    scope_note
+                ),
+                state.response_policy.is_ephemeral("translate_set"),
+            );
 
             state.http
                 .interaction(state.application_id)
@@ -993,146 +2314,2418 @@ async fn handle_translate_set(
     Ok(())
 }
 
-async fn process_translation_loop(
-    http: Arc<HttpClient>,
-    _application_id: Id<twilight_model::id::marker::ApplicationMarker>,
-    translation_manager: Arc<TranslationManager>,
-    translator: Arc<Translator>,
-    transcriber: Arc<Transcriber>,
-    user_settings: Arc<UserSettingsManager>,
-    guild_id: Id<twilight_model::id::marker::GuildMarker>,
-    voice_channel_id: Id<twilight_model::id::marker::ChannelMarker>,
-) {
-    use twilight_model::channel::message::embed::Embed;
-    use twilight_model::channel::message::embed::EmbedField;
-    use transcriber::compute_rms;
-    use transcriber::convert_i16_to_f32;
-    use transcriber::downsample_48k_to_16k;
-    use transcriber::is_likely_hallucination;
-    use std::time::Instant;
-
-    loop {
-        if !translation_manager.is_translating(guild_id).await {
-            break;
-        }
-
-        let ready_buffers = translation_manager.get_ready_translations(guild_id).await;
+async fn handle_record_here(
+    interaction: Interaction,
+    state: Arc<BotState>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let interaction_id = interaction.id;
+    let token = interaction.token.clone();
 
-        for (user_id, samples) in ready_buffers {
-            let http = http.clone();
-            let translator = translator.clone();
-            let transcriber = transcriber.clone();
-            let user_settings = user_settings.clone();
-            let voice_channel_id = voice_channel_id;
-
-            tokio::spawn(async move {
-                let user_setting = match user_settings.get_user_setting(user_id).await {
-                    Some(setting) => setting,
-                    None => {
-                        println!("[INFO] Skipping user {} - no language settings", user_id);
-                        return;
-                    }
-                };
+    let Some(guild_id) = interaction.guild_id else {
+        send_error_response(
+            state.http.clone(),
+            state.application_id,
+            interaction_id,
+            token,
+            "This command can only be used in a server"
+        ).await?;
+        return Ok(());
+    };
 
-                if samples.len() < 24000 {
-                    return;
-                }
+    let user_id = resolve_user_id(&interaction);
+    let (Some(user_id), Some(text_channel_id)) = (user_id, interaction.channel.as_ref().map(|c| c.id)) else {
+        send_error_response(
+            state.http.clone(),
+            state.application_id,
+            interaction_id,
+            token,
+            "Could not determine user or channel"
+        ).await?;
+        return Ok(());
+    };
 
-                let total_start = Instant::now();
-                let convert_start = Instant::now();
-                let samples_f32 = convert_i16_to_f32(&samples);
-                let rms = compute_rms(&samples_f32);
-                if rms < 0.005 {
-                    println!("[INFO] Skipping low-volume audio (rms={:.5}) for user {}", rms, user_id);
-                    return;
-                }
-                let final_samples = downsample_48k_to_16k(&samples_f32);
-                let convert_time = convert_start.elapsed();
-                
-                let transcribe_start = Instant::now();
-                match transcriber.transcribe_with_language(&final_samples, Some(&user_setting.source_lang)) {
-                    Ok((transcription, _)) => {
-                        let transcribe_time = transcribe_start.elapsed();
-                        if !transcription.trim().is_empty() {
-                            let duration_ms = (final_samples.len() as u64 * 1000) / 16000;
-                            if is_likely_hallucination(&transcription, duration_ms, rms) {
-                                println!("[INFO] Dropping likely hallucination (duration_ms={}, rms={:.5}): {}", duration_ms, rms, transcription);
-                                return;
-                            }
+    let guild_config = state.guild_config.get_guild_config(guild_id).await;
+    if !has_required_role(member_roles(&interaction), &guild_config) {
+        send_missing_role_response(&state, interaction_id, token).await?;
+        return Ok(());
+    }
 
-                            let source_full = user_setting.get_source_full();
-                            let target_full = user_setting.get_target_full();
-                            
-                            let translate_start = Instant::now();
-                            match translator.translate(&transcription, &source_full, &target_full).await {
-                                Ok(translated) => {
-                                    let translate_time = translate_start.elapsed();
-                                    let total_time = total_start.elapsed();
-                                    println!("[PERF] Convert: {:?}, Transcribe: {:?}, Translate: {:?}, Total: {:?}", convert_time, transcribe_time, translate_time, total_time);
-                                    
-                                    let embed = Embed {
-                                        author: None,
-                                        color: Some(0x3498db),
-                                        description: None,
-                                        fields: vec![
-                                            EmbedField {
-                                                inline: false,
-                                                name: format!("🗣️ Original ({})", user_setting.source_lang.to_uppercase()),
-                                                value: transcription,
-                                            },
-                                            EmbedField {
-                                                inline: false,
-                                                name: format!("🌐 Translation ({})", user_setting.target_lang.to_uppercase()),
-                                                value: translated,
-                                            },
-                                        ],
-                                        footer: None,
-                                        image: None,
-                                        kind: "rich".to_string(),
-                                        provider: None,
-                                        thumbnail: None,
-                                        timestamp: None,
-                                        title: Some("Real-time Translation".to_string()),
-                                        url: None,
-                                        video: None,
-                                    };
-
-                                    let _ = http.create_message(voice_channel_id)
-                                        .embeds(&[embed])
-                                        .await;
-                                }
-                                Err(e) => {
-                                    eprintln!("[ERROR] Translation failed: {}", e);
-                                }
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        eprintln!("[ERROR] Transcription failed: {}", e);
-                    }
-                }
-            });
+    let mut language = None;
+    if let Some(InteractionData::ApplicationCommand(command_data)) = &interaction.data {
+        for option in &command_data.options {
+            if let ("language", CommandOptionValue::String(code)) = (option.name.as_str(), &option.value) {
+                language = Some(code.clone());
+            }
         }
-
-        tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
     }
-}
 
-async fn send_error_response(
-    http: Arc<HttpClient>,
-    application_id: Id<twilight_model::id::marker::ApplicationMarker>,
-    interaction_id: Id<twilight_model::id::marker::InteractionMarker>,
-    token: String,
-    message: &str,
-) -> Result<(), Box<dyn Error + Send + Sync>> {
-    let response = InteractionResponse {
-        kind: InteractionResponseType::ChannelMessageWithSource,
-        data: Some(twilight_model::http::interaction::InteractionResponseData {
-            content: Some(format!("❌ {}", message)),
+    let voice_channel_id = state.voice_states.channel_of(user_id).await;
+    let Some(voice_channel_id) = voice_channel_id else {
+        send_error_response(
+            state.http.clone(),
+            state.application_id,
+            interaction_id,
+            token,
+            "You must be in a voice channel to start recording!"
+        ).await?;
+        return Ok(());
+    };
+
+    if state.recording.recording_manager.is_recording(guild_id).await {
+        send_error_response(
+            state.http.clone(),
+            state.application_id,
+            interaction_id,
+            token,
+            "Recording is already in progress in this server"
+        ).await?;
+        return Ok(());
+    }
+
+    // The consent window outlasts the interaction deadline, so acknowledge first
+    if let Some(window) = guild_config.consent_window() {
+        let response = message_response(
+            format!(
+                "⏳ **Recording will start** in <#{}> in {} seconds unless someone objects",
+                voice_channel_id, window.as_secs()
+            ),
+            true,
+        );
+        state.http
+            .interaction(state.application_id)
+            .create_response(interaction_id, &token, &response)
+            .await?;
+
+        if !await_recording_consent(&state, guild_id, voice_channel_id).await? {
+            return Ok(());
+        }
+        if !still_in_channel(&state, user_id, voice_channel_id).await {
+            println!("[INFO] User {} left voice channel {} before recording started", user_id, voice_channel_id);
+            let _ = state.http.create_message(text_channel_id)
+                .content("❌ You left the voice channel before recording started, so I didn't join.")
+                .await;
+            return Ok(());
+        }
+        if let Err(e) = state.recording.start(guild_id, voice_channel_id, user_id, language.as_deref()).await {
+            eprintln!("[ERROR] Failed to join voice channel: {}", e);
+            let _ = state.http.create_message(text_channel_id)
+                .content(&format!("❌ Failed to join voice channel: {}", e))
+                .await;
+            return Ok(());
+        }
+        attach_voice_handlers(&state, guild_id).await;
+        state.session_text_channels.lock().await.insert(guild_id, text_channel_id);
+        spawn_no_audio_watch(state.clone(), guild_id, true);
+        post_record_here_control(&state, guild_id, user_id, text_channel_id, voice_channel_id).await?;
+        return Ok(());
+    }
+
+    if let Err(e) = state.recording.start(guild_id, voice_channel_id, user_id, language.as_deref()).await {
+        eprintln!("[ERROR] Failed to join voice channel: {}", e);
+        send_error_response(
+            state.http.clone(),
+            state.application_id,
+            interaction_id,
+            token,
+            &format!("Failed to join voice channel: {}", e)
+        ).await?;
+        return Ok(());
+    }
+    attach_voice_handlers(&state, guild_id).await;
+    state.session_text_channels.lock().await.insert(guild_id, text_channel_id);
+    spawn_no_audio_watch(state.clone(), guild_id, true);
+    post_record_here_control(&state, guild_id, user_id, text_channel_id, voice_channel_id).await?;
+
+    let response = message_response(format!("✅ **Recording started** in <#{}>", voice_channel_id), true);
+
+    state.http
+        .interaction(state.application_id)
+        .create_response(interaction_id, &token, &response)
+        .await?;
+
+    Ok(())
+}
+
+/// Post a control message for a recording started with `/record_here` or
+/// `/record_schedule`, so it can be stopped the usual way
+async fn post_record_here_control(
+    state: &BotState,
+    guild_id: Id<twilight_model::id::marker::GuildMarker>,
+    user_id: Id<twilight_model::id::marker::UserMarker>,
+    text_channel_id: Id<twilight_model::id::marker::ChannelMarker>,
+    voice_channel_id: Id<twilight_model::id::marker::ChannelMarker>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    println!("[INFO] Started recording for user {} in guild {}", user_id, guild_id);
+
+    let guild_config = state.guild_config.get_guild_config(guild_id).await;
+    let control_emoji = guild_config.control_emoji_display();
+    let control_message = state.http.create_message(text_channel_id)
+        .content(&format!(
+            "🔴 **Recording in progress** in <#{}>\n\nPress {} and release it again to stop and generate meeting minutes",
+            voice_channel_id, control_emoji
+        ))
+        .await?
+        .model()
+        .await?;
+    state.http.create_reaction(text_channel_id, control_message.id, &guild_config.control_reaction()).await?;
+
+    let key = (control_message.id, text_channel_id, guild_id, user_id);
+    state.reaction_controls.lock().await.insert(key, true);
+
+    Ok(())
+}
+
+async fn handle_record_schedule(
+    interaction: Interaction,
+    state: Arc<BotState>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let interaction_id = interaction.id;
+    let token = interaction.token.clone();
+
+    let Some(guild_id) = interaction.guild_id else {
+        send_error_response(
+            state.http.clone(),
+            state.application_id,
+            interaction_id,
+            token,
+            "This command can only be used in a server"
+        ).await?;
+        return Ok(());
+    };
+
+    let user_id = resolve_user_id(&interaction);
+    let (Some(user_id), Some(text_channel_id)) = (user_id, interaction.channel.as_ref().map(|c| c.id)) else {
+        send_error_response(
+            state.http.clone(),
+            state.application_id,
+            interaction_id,
+            token,
+            "Could not determine user or channel"
+        ).await?;
+        return Ok(());
+    };
+
+    let guild_config = state.guild_config.get_guild_config(guild_id).await;
+    if !has_required_role(member_roles(&interaction), &guild_config) {
+        send_missing_role_response(&state, interaction_id, token).await?;
+        return Ok(());
+    }
+
+    let Some(voice_channel_id) = state.voice_states.channel_of(user_id).await else {
+        send_error_response(
+            state.http.clone(),
+            state.application_id,
+            interaction_id,
+            token,
+            "Join the voice channel you want recorded, then schedule the recording"
+        ).await?;
+        return Ok(());
+    };
+
+    let mut start = String::new();
+    let mut duration_mins = None;
+    if let Some(InteractionData::ApplicationCommand(command_data)) = &interaction.data {
+        for option in &command_data.options {
+            match (option.name.as_str(), &option.value) {
+                ("start", CommandOptionValue::String(val)) => start = val.clone(),
+                ("duration", CommandOptionValue::Integer(val)) => {
+                    duration_mins = Some((*val).clamp(1, schedule::MAX_SCHEDULED_MINUTES) as u64)
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let start_time = match schedule::parse_start_time(&start, chrono::Local::now(), timezone::configured()) {
+        Ok(start_time) => start_time,
+        Err(e) => {
+            send_error_response(
+                state.http.clone(),
+                state.application_id,
+                interaction_id,
+                token,
+                &e
+            ).await?;
+            return Ok(());
+        }
+    };
+
+    let job = state.schedule
+        .add(guild_id, voice_channel_id, text_channel_id, user_id, start_time, duration_mins)
+        .await;
+    println!("[INFO] Scheduled recording #{} in guild {} for {}", job.id, guild_id, start_time);
+
+    let stop_note = match duration_mins {
+        Some(mins) => format!(" and stop after {} minutes", mins),
+        None => String::new(),
+    };
+    let response = message_response(
+        format!(
+            "🗓️ **Recording scheduled** — <#{}> will be recorded from <t:{}:F> (<t:{}:R>){}. Minutes are posted here.",
+            voice_channel_id, job.start_at, job.start_at, stop_note
+        ),
+        true,
+    );
+
+    state.http
+        .interaction(state.application_id)
+        .create_response(interaction_id, &token, &response)
+        .await?;
+
+    Ok(())
+}
+
+/// Start scheduled recordings once they're due, and stop them when their duration is up
+async fn run_recording_schedule(state: Arc<BotState>) {
+    let mut interval = tokio::time::interval(schedule::SCHEDULE_POLL_INTERVAL);
+    loop {
+        interval.tick().await;
+        let now = chrono::Local::now();
+        for job in state.schedule.take_due(now).await {
+            tokio::spawn(run_scheduled_recording(state.clone(), job));
+        }
+        for job in state.schedule.take_due_stops(now).await {
+            tokio::spawn(stop_scheduled_recording(state.clone(), job));
+        }
+    }
+}
+
+/// Stop the recording a scheduled job started, unless it already ended
+async fn stop_scheduled_recording(state: Arc<BotState>, job: schedule::ScheduledRecording) {
+    let Some((_, session_start)) = job.auto_stop else {
+        return;
+    };
+    // Only stop the session this job started, not one begun after a manual stop or restart
+    let current = state.recording.recording_manager.session_start_time(job.guild_id).await;
+    if current.map(|started| started.timestamp_millis()) != Some(session_start) {
+        return;
+    }
+    println!("[INFO] Scheduled recording #{} reached its duration", job.id);
+    if let Err(e) = state.recording.stop_and_process(job.guild_id, job.text_channel_id).await {
+        eprintln!("[ERROR] Failed to stop scheduled recording #{}: {}", job.id, e);
+    }
+}
+
+/// Join and record for one scheduled job, stopping after its duration
+async fn run_scheduled_recording(state: Arc<BotState>, job: schedule::ScheduledRecording) {
+    let notify = |content: String| {
+        let http = state.http.clone();
+        async move {
+            if let Err(e) = http.create_message(job.text_channel_id).content(&content).await {
+                eprintln!("[ERROR] Failed to post scheduled recording notice: {}", e);
+            }
+        }
+    };
+
+    if job.is_missed(chrono::Local::now(), schedule::MISSED_START_GRACE) {
+        println!("[INFO] Skipping scheduled recording #{}: start time passed while offline", job.id);
+        notify(format!(
+            "⏭️ **Scheduled recording skipped** — the bot was offline at <t:{}:F>",
+            job.start_at
+        )).await;
+        return;
+    }
+
+    if state.recording.recording_manager.is_recording(job.guild_id).await {
+        notify("⏭️ **Scheduled recording skipped** — a recording is already in progress".to_string()).await;
+        return;
+    }
+
+    // Nobody to record: don't sit in an empty channel
+    if !channel_occupied(&state, job.voice_channel_id).await {
+        println!("[INFO] Skipping scheduled recording #{}: channel {} is empty", job.id, job.voice_channel_id);
+        notify(format!(
+            "⏭️ **Scheduled recording skipped** — nobody was in <#{}> at the start time",
+            job.voice_channel_id
+        )).await;
+        return;
+    }
+
+    match await_recording_consent(&state, job.guild_id, job.voice_channel_id).await {
+        Ok(true) => {}
+        Ok(false) => return,
+        Err(e) => {
+            eprintln!("[ERROR] Consent prompt for scheduled recording #{} failed: {}", job.id, e);
+            return;
+        }
+    }
+    if !channel_occupied(&state, job.voice_channel_id).await {
+        println!("[INFO] Skipping scheduled recording #{}: channel {} emptied during the consent window", job.id, job.voice_channel_id);
+        notify(format!(
+            "⏭️ **Scheduled recording skipped** — everyone left <#{}> before recording started",
+            job.voice_channel_id
+        )).await;
+        return;
+    }
+
+    if let Err(e) = state.recording.start(job.guild_id, job.voice_channel_id, job.user_id, None).await {
+        eprintln!("[ERROR] Failed to start scheduled recording #{}: {}", job.id, e);
+        notify(format!("❌ Failed to start the scheduled recording: {}", e)).await;
+        return;
+    }
+    attach_voice_handlers(&state, job.guild_id).await;
+    state.session_text_channels.lock().await.insert(job.guild_id, job.text_channel_id);
+    spawn_no_audio_watch(state.clone(), job.guild_id, true);
+    if let Err(e) = post_record_here_control(&state, job.guild_id, job.user_id, job.text_channel_id, job.voice_channel_id).await {
+        eprintln!("[ERROR] Failed to post control message for scheduled recording #{}: {}", job.id, e);
+    }
+
+    let (Some(duration), Some(started_at)) = (
+        job.duration(),
+        state.recording.recording_manager.session_start_time(job.guild_id).await,
+    ) else {
+        return;
+    };
+    let stop_at = chrono::Local::now() + chrono::Duration::from_std(duration).unwrap_or_default();
+    state.schedule.add_auto_stop(job, stop_at, started_at).await;
+}
+
+async fn handle_record_emoji(
+    interaction: Interaction,
+    state: Arc<BotState>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let interaction_id = interaction.id;
+    let token = interaction.token.clone();
+
+    let Some(guild_id) = interaction.guild_id else {
+        send_error_response(
+            state.http.clone(),
+            state.application_id,
+            interaction_id,
+            token,
+            "This command can only be used in a server"
+        ).await?;
+        return Ok(());
+    };
+
+    let mut emoji = None;
+    if let Some(InteractionData::ApplicationCommand(command_data)) = &interaction.data {
+        for option in &command_data.options {
+            if let ("emoji", CommandOptionValue::String(val)) = (option.name.as_str(), &option.value) {
+                emoji = Some(val.trim().to_string());
+            }
+        }
+    }
+
+    let emoji = emoji.unwrap_or_default();
+    if let Some((name, emoji_id, animated)) = guild_config::parse_custom_emoji(&emoji) {
+        state.guild_config.set_custom_control_emoji(guild_id, &name, emoji_id, animated).await;
+    } else if guild_config::is_valid_unicode_emoji(&emoji) {
+        state.guild_config.set_control_emoji(guild_id, &emoji).await;
+    } else {
+        send_error_response(
+            state.http.clone(),
+            state.application_id,
+            interaction_id,
+            token,
+            "Please provide a single Unicode emoji (e.g. 🎙️) or a custom emoji from this server"
+        ).await?;
+        return Ok(());
+    }
+
+    let response = message_response(format!("✅ **Recording control emoji set to {}**\n\nNew `/record` control messages will use it.", emoji), state.response_policy.is_ephemeral("record_emoji"));
+
+    state.http
+        .interaction(state.application_id)
+        .create_response(interaction_id, &token, &response)
+        .await?;
+
+    Ok(())
+}
+
+async fn handle_feedback(
+    interaction: Interaction,
+    state: Arc<BotState>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let interaction_id = interaction.id;
+    let token = interaction.token.clone();
+
+    let Some(user_id) = interactions::resolve_user_id(&interaction) else {
+        send_error_response(
+            state.http.clone(),
+            state.application_id,
+            interaction_id,
+            token,
+            "Could not identify the user who ran this command"
+        ).await?;
+        return Ok(());
+    };
+
+    let mut wrong = String::new();
+    let mut correct = String::new();
+    if let Some(InteractionData::ApplicationCommand(command_data)) = &interaction.data {
+        for option in &command_data.options {
+            match (option.name.as_str(), &option.value) {
+                ("wrong", CommandOptionValue::String(val)) => wrong = val.clone(),
+                ("correct", CommandOptionValue::String(val)) => correct = val.clone(),
+                _ => {}
+            }
+        }
+    }
+
+    if wrong.trim().is_empty() || correct.trim().is_empty() {
+        send_error_response(
+            state.http.clone(),
+            state.application_id,
+            interaction_id,
+            token,
+            "Please give both the wrong text and the correction"
+        ).await?;
+        return Ok(());
+    }
+
+    let entry = feedback::FeedbackEntry::new(interaction.guild_id, user_id, &wrong, &correct);
+    if let Err(e) = state.feedback_log.append(&entry).await {
+        eprintln!("[ERROR] Failed to save feedback from user {}: {}", user_id, e);
+        send_error_response(
+            state.http.clone(),
+            state.application_id,
+            interaction_id,
+            token,
+            "Couldn't save your feedback, please try again later"
+        ).await?;
+        return Ok(());
+    }
+    println!("[INFO] Feedback from user {}: \"{}\" → \"{}\"", user_id, entry.wrong, entry.correct);
+
+    let response = message_response(
+        format!("🙏 **Thanks!** Noted that \"{}\" should be \"{}\".", entry.wrong, entry.correct),
+        true,
+    );
+
+    state.http
+        .interaction(state.application_id)
+        .create_response(interaction_id, &token, &response)
+        .await?;
+
+    Ok(())
+}
+
+/// `/whoami`: the voice state tracking that reaction and recording commands rely on
+async fn handle_whoami(
+    interaction: Interaction,
+    state: Arc<BotState>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let interaction_id = interaction.id;
+    let token = interaction.token.clone();
+
+    let Some(guild_id) = interaction.guild_id else {
+        send_error_response(
+            state.http.clone(),
+            state.application_id,
+            interaction_id,
+            token,
+            "This command can only be used in a server"
+        ).await?;
+        return Ok(());
+    };
+    let Some(user_id) = interactions::resolve_user_id(&interaction) else {
+        send_error_response(
+            state.http.clone(),
+            state.application_id,
+            interaction_id,
+            token,
+            "Could not identify the user who ran this command"
+        ).await?;
+        return Ok(());
+    };
+
+    let bot_channel = match state.songbird.get(guild_id) {
+        Some(call) => Some(call.lock().await.current_channel().map(|channel| Id::from(channel.0))),
+        None => None,
+    };
+    let summary = voice_states::voice_state_summary(
+        user_id,
+        guild_id,
+        state.voice_states.lookup(user_id).await,
+        bot_channel,
+        state.voice_states.len().await,
+    );
+
+    state.http
+        .interaction(state.application_id)
+        .create_response(interaction_id, &token, &message_response(summary, true))
+        .await?;
+
+    Ok(())
+}
+
+/// Longest settings file `/settings_import` downloads
+const MAX_SETTINGS_IMPORT_BYTES: u64 = 5 * 1024 * 1024;
+
+/// `/settings_export` and `/settings_import`. Language settings follow users across
+/// every server, so only the operators in `SETTINGS_ADMIN_IDS` may move them.
+async fn handle_settings_transfer(
+    interaction: Interaction,
+    state: Arc<BotState>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    use twilight_model::channel::message::MessageFlags;
+    use twilight_model::http::attachment::Attachment;
+
+    let interaction_id = interaction.id;
+    let token = interaction.token.clone();
+
+    let Some(InteractionData::ApplicationCommand(command_data)) = &interaction.data else {
+        return Ok(());
+    };
+    let is_operator = interactions::resolve_user_id(&interaction)
+        .is_some_and(|user_id| state.settings_admins.contains(&user_id));
+    if !is_operator {
+        send_error_response(
+            state.http.clone(),
+            state.application_id,
+            interaction_id,
+            token,
+            "Only the bot's operators can move everyone's settings (see `SETTINGS_ADMIN_IDS`)"
+        ).await?;
+        return Ok(());
+    }
+
+    if command_data.name == "settings_export" {
+        let settings = state.user_settings.list_all_settings().await;
+        let json = user_settings::export_settings(&settings)?;
+        let filename = format!("user_settings_{}.json", timezone::format(&chrono::Local::now(), "%Y%m%d_%H%M%S"));
+        let response = InteractionResponse {
+            kind: InteractionResponseType::ChannelMessageWithSource,
+            data: Some(twilight_model::http::interaction::InteractionResponseData {
+                content: Some(format!("📦 **Exported {} users' language settings**", settings.len())),
+                attachments: Some(vec![Attachment::from_bytes(filename, json.into_bytes(), 0)]),
+                flags: Some(MessageFlags::EPHEMERAL),
+                ..Default::default()
+            }),
+        };
+        state.http
+            .interaction(state.application_id)
+            .create_response(interaction_id, &token, &response)
+            .await?;
+        return Ok(());
+    }
+
+    let file = command_data.options.iter()
+        .find_map(|option| match (option.name.as_str(), &option.value) {
+            ("file", CommandOptionValue::Attachment(id)) => Some(*id),
+            _ => None,
+        })
+        .and_then(|id| command_data.resolved.as_ref()?.attachments.get(&id).cloned());
+    let Some(file) = file.filter(|file| file.size <= MAX_SETTINGS_IMPORT_BYTES) else {
+        send_error_response(
+            state.http.clone(),
+            state.application_id,
+            interaction_id,
+            token,
+            "Attach the JSON file from `/settings_export` (5 MB at most)"
+        ).await?;
+        return Ok(());
+    };
+
+    let response = InteractionResponse {
+        kind: InteractionResponseType::DeferredChannelMessageWithSource,
+        data: Some(twilight_model::http::interaction::InteractionResponseData {
+            flags: Some(MessageFlags::EPHEMERAL),
+            ..Default::default()
+        }),
+    };
+    state.http
+        .interaction(state.application_id)
+        .create_response(interaction_id, &token, &response)
+        .await?;
+
+    let interaction_client = state.http.interaction(state.application_id);
+    let downloaded = match state.http_client.get(&file.url).send().await {
+        Ok(response) => response.error_for_status().map_err(|e| e.to_string()),
+        Err(e) => Err(e.to_string()),
+    };
+    let body = match downloaded {
+        Ok(response) => response.text().await.map_err(|e| e.to_string()),
+        Err(e) => Err(e),
+    };
+    let import = match body.and_then(|body| user_settings::parse_settings_import(&body)) {
+        Ok(import) => import,
+        Err(e) => {
+            eprintln!("[ERROR] /settings_import failed for {}: {}", file.filename, e);
+            interaction_client.update_response(&token).content(Some(&format!("❌ {}", e))).await?;
+            return Ok(());
+        }
+    };
+
+    let skipped = import.skipped;
+    let applied = state.user_settings.set_many(import.valid).await;
+    println!("[INFO] Imported {} user settings ({} skipped) from {}", applied, skipped, file.filename);
+    let content = format!(
+        "✅ **Imported {} users' language settings**{}",
+        applied,
+        if skipped > 0 { format!("\n⚠️ Skipped {} invalid entries", skipped) } else { String::new() }
+    );
+    interaction_client.update_response(&token).content(Some(&content)).await?;
+
+    Ok(())
+}
+
+async fn handle_translate_history(
+    interaction: Interaction,
+    state: Arc<BotState>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    use twilight_model::channel::message::AllowedMentions;
+    use twilight_model::channel::message::MessageFlags;
+    use twilight_model::http::attachment::Attachment;
+
+    let interaction_id = interaction.id;
+    let token = interaction.token.clone();
+
+    let Some(guild_id) = interaction.guild_id else {
+        send_error_response(
+            state.http.clone(),
+            state.application_id,
+            interaction_id,
+            token,
+            "This command can only be used in a server"
+        ).await?;
+        return Ok(());
+    };
+
+    let Some(entries) = state.translation_manager.recent_history(guild_id).await else {
+        send_error_response(
+            state.http.clone(),
+            state.application_id,
+            interaction_id,
+            token,
+            "Translation is not active. Use `/translate_start` first."
+        ).await?;
+        return Ok(());
+    };
+
+    let history = voice_translator::format_translation_history(&entries);
+    let mut data = twilight_model::http::interaction::InteractionResponseData {
+        flags: Some(MessageFlags::EPHEMERAL),
+        // Speakers are shown as mentions without pinging them
+        allowed_mentions: Some(AllowedMentions::default()),
+        ..Default::default()
+    };
+
+    if entries.is_empty() {
+        data.content = Some("📜 No translations yet in this session.".to_string());
+    } else if history.chars().count() <= 1900 {
+        data.content = Some(format!("📜 **Recent translations** ({})\n\n{}", entries.len(), history));
+    } else {
+        // Too long for a message; send it as a file instead
+        let filename = format!("translation_history_{}.md", timezone::format(&chrono::Local::now(), "%Y%m%d_%H%M%S"));
+        data.content = Some(format!("📜 **Recent translations** ({})", entries.len()));
+        data.attachments = Some(vec![Attachment::from_bytes(filename, history.into_bytes(), 0)]);
+    }
+
+    let response = InteractionResponse {
+        kind: InteractionResponseType::ChannelMessageWithSource,
+        data: Some(data),
+    };
+
+    state.http
+        .interaction(state.application_id)
+        .create_response(interaction_id, &token, &response)
+        .await?;
+
+    Ok(())
+}
+
+async fn handle_translate_text(
+    interaction: Interaction,
+    state: Arc<BotState>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    use twilight_model::channel::message::AllowedMentions;
+    use twilight_model::http::attachment::Attachment;
+
+    let interaction_id = interaction.id;
+    let token = interaction.token.clone();
+
+    let mut text = String::new();
+    let mut source = None;
+    let mut target = None;
+    if let Some(InteractionData::ApplicationCommand(command_data)) = &interaction.data {
+        for option in &command_data.options {
+            match (option.name.as_str(), &option.value) {
+                ("text", CommandOptionValue::String(val)) => text = val.clone(),
+                ("source", CommandOptionValue::String(val)) => source = Some(val.clone()),
+                ("target", CommandOptionValue::String(val)) => target = Some(val.clone()),
+                _ => {}
+            }
+        }
+    }
+
+    let Some(target) = target else {
+        send_error_response(
+            state.http.clone(),
+            state.application_id,
+            interaction_id,
+            token,
+            "Please select a target language"
+        ).await?;
+        return Ok(());
+    };
+
+    // Long texts take several DeepL calls; acknowledge before the interaction expires
+    let response = InteractionResponse {
+        kind: InteractionResponseType::DeferredChannelMessageWithSource,
+        data: None,
+    };
+    state.http
+        .interaction(state.application_id)
+        .create_response(interaction_id, &token, &response)
+        .await?;
+
+    // The server's vocabulary applies when the source language is known
+    let glossary_id = match (interaction.guild_id, source.as_deref()) {
+        (Some(guild_id), Some(source)) => {
+            let entries = state.guild_config.get_guild_config(guild_id).await.glossary_entries(source, &target);
+            state.translator.glossary_id(guild_id.get(), source, &target, &entries).await
+        }
+        _ => None,
+    };
+
+    let mut translated = Vec::new();
+    let mut detected_source = None;
+    let mut error = None;
+    for chunk in translator::chunk_text(&text, translator::MAX_TRANSLATE_CHARS) {
+        match state.translator.translate(&chunk, source.as_deref(), &target, glossary_id.as_deref()).await {
+            Ok(result) => {
+                detected_source = detected_source.or(result.source_lang);
+                translated.push(result.text);
+            }
+            Err(e) => {
+                error = Some(e);
+                break;
+            }
+        }
+    }
+
+    if error.is_none() {
+        state.stats.record_translation();
+    }
+
+    let interaction_client = state.http.interaction(state.application_id);
+    let no_mentions = AllowedMentions::default();
+    match error {
+        Some(e) => {
+            eprintln!("[ERROR] /translate_text failed: {}", e);
+            let message = match e.translation_failure() {
+                Some(TranslationFailure::QuotaExceeded | TranslationFailure::Auth) => translation_outage_notice(&e).to_string(),
+                _ => format!("❌ Translation failed: {}", e),
+            };
+            interaction_client.update_response(&token).content(Some(&message)).await?;
+        }
+        None => {
+            let translated = translated.join("\n");
+            let header = match detected_source {
+                Some(source) => format!("🌐 **Translation ({} → {})**", source.to_uppercase(), target.to_uppercase()),
+                None => format!("🌐 **Translation ({})**", target.to_uppercase()),
+            };
+            if translated.chars().count() <= 1900 {
+                let message = format!("{}\n{}", header, translated);
+                interaction_client.update_response(&token)
+                    .content(Some(&message))
+                    .allowed_mentions(Some(&no_mentions))
+                    .await?;
+            } else {
+                let attachment = Attachment::from_bytes("translation.txt".to_string(), translated.into_bytes(), 0);
+                interaction_client.update_response(&token)
+                    .content(Some(&header))
+                    .attachments(&[attachment])
+                    .await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn handle_selftest(
+    interaction: Interaction,
+    state: Arc<BotState>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    use twilight_model::channel::message::MessageFlags;
+
+    let interaction_id = interaction.id;
+    let token = interaction.token.clone();
+
+    // Each check waits on an external service or a whisper run
+    let response = InteractionResponse {
+        kind: InteractionResponseType::DeferredChannelMessageWithSource,
+        data: Some(twilight_model::http::interaction::InteractionResponseData {
+            flags: Some(MessageFlags::EPHEMERAL),
+            ..Default::default()
+        }),
+    };
+    state.http
+        .interaction(state.application_id)
+        .create_response(interaction_id, &token, &response)
+        .await?;
+
+    let model = match interaction.guild_id {
+        Some(guild_id) => state.guild_config.get_guild_config(guild_id).await.transcription_model,
+        None => None,
+    };
+    let transcriber = state.transcribers.resolve(model.as_deref(), transcriber::MODEL_BASE);
+
+    let (deepl, zai, whisper) = tokio::join!(
+        selftest::run_check("DeepL", async {
+            state.translator.translate(selftest::SELFTEST_PHRASE, Some("en"), "ja", None).await.map(|result| result.text)
+        }),
+        selftest::run_check("z.ai", state.recording.summarizer.summarize_meeting(selftest::SELFTEST_TRANSCRIPT, None)),
+        selftest::run_check(
+            "Whisper",
+            async {
+                transcriber.transcribe_with_language_async(selftest::sample_audio(), Some("en".to_string()), None)
+                    .await
+                    .map(|_| format!("`{}` ran on a 1s sample", transcriber.model_name()))
+            }
+        ),
+    );
+    let results = [deepl, zai, whisper];
+    for result in &results {
+        if let Err(e) = &result.outcome {
+            eprintln!("[ERROR] Self-test: {} failed: {}", result.name, e);
+        }
+    }
+
+    let report = selftest::format_report(&results);
+    state.http
+        .interaction(state.application_id)
+        .update_response(&token)
+        .content(Some(&report))
+        .await?;
+
+    Ok(())
+}
+
+async fn handle_transcribe_url(
+    interaction: Interaction,
+    state: Arc<BotState>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let interaction_id = interaction.id;
+    let token = interaction.token.clone();
+
+    let Some(guild_id) = interaction.guild_id else {
+        send_error_response(
+            state.http.clone(),
+            state.application_id,
+            interaction_id,
+            token,
+            "This command can only be used in a server"
+        ).await?;
+        return Ok(());
+    };
+
+    let mut url = String::new();
+    let mut language = None;
+    if let Some(InteractionData::ApplicationCommand(command_data)) = &interaction.data {
+        for option in &command_data.options {
+            match (option.name.as_str(), &option.value) {
+                ("url", CommandOptionValue::String(val)) => url = val.clone(),
+                ("language", CommandOptionValue::String(val)) => language = Some(val.clone()),
+                _ => {}
+            }
+        }
+    }
+
+    let guild_config = state.guild_config.get_guild_config(guild_id).await;
+    if !has_required_role(member_roles(&interaction), &guild_config) {
+        send_missing_role_response(&state, interaction_id, token).await?;
+        return Ok(());
+    }
+
+    if let Err(e) = remote_audio::validate_url(&url) {
+        send_error_response(
+            state.http.clone(),
+            state.application_id,
+            interaction_id,
+            token,
+            &e
+        ).await?;
+        return Ok(());
+    }
+
+    if let Err(wait) = state.transcribe_url_limit.try_acquire(guild_id, std::time::Instant::now()) {
+        send_error_response(
+            state.http.clone(),
+            state.application_id,
+            interaction_id,
+            token,
+            &format!("This server transcribed a link recently; try again in {} seconds", wait.as_secs().max(1))
+        ).await?;
+        return Ok(());
+    }
+
+    // Downloading and transcribing take far longer than the interaction allows
+    let response = InteractionResponse {
+        kind: InteractionResponseType::DeferredChannelMessageWithSource,
+        data: None,
+    };
+    state.http
+        .interaction(state.application_id)
+        .create_response(interaction_id, &token, &response)
+        .await?;
+
+    let interaction_client = state.http.interaction(state.application_id);
+    let job_id = uuid::Uuid::new_v4();
+    let download_path = std::env::temp_dir().join(format!("transcribe_url_{}.download", job_id));
+    let container = match remote_audio::download(&url, &download_path).await {
+        Ok(container) => container,
+        Err(e) => {
+            let _ = tokio::fs::remove_file(&download_path).await;
+            eprintln!("[ERROR] /transcribe_url download failed: {}", e);
+            interaction_client.update_response(&token).content(Some(&format!("❌ {}", e))).await?;
+            return Ok(());
+        }
+    };
+    println!("[INFO] Downloaded {:?} audio for /transcribe_url in guild {}", container, guild_id);
+
+    let wav_path = std::env::temp_dir().join(format!("transcribe_url_{}.wav", job_id));
+    let written = tokio::task::spawn_blocking({
+        let (download_path, wav_path) = (download_path.clone(), wav_path.clone());
+        move || remote_audio::write_transcribable_wav(container, &download_path, &wav_path)
+    }).await?;
+    let _ = tokio::fs::remove_file(&download_path).await;
+    let outcome = match written {
+        Ok(()) => state.recording
+            .transcribe_file(guild_id, &wav_path.to_string_lossy(), "Audio", language.as_deref())
+            .await,
+        Err(e) => Err(e),
+    };
+    let _ = tokio::fs::remove_file(&wav_path).await;
+
+    let outcome = match outcome {
+        Ok(outcome) => outcome,
+        Err(e) => {
+            eprintln!("[ERROR] /transcribe_url failed: {}", e);
+            interaction_client.update_response(&token).content(Some(&format!("❌ {}", e))).await?;
+            return Ok(());
+        }
+    };
+
+    for (i, message) in outcome.messages().into_iter().enumerate() {
+        if i == 0 {
+            interaction_client.update_response(&token).content(Some(&message)).await?;
+        } else {
+            interaction_client.create_followup(&token).content(&message).await?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn handle_dryrun_summary(
+    interaction: Interaction,
+    state: Arc<BotState>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let interaction_id = interaction.id;
+    let token = interaction.token.clone();
+
+    let mut text = String::new();
+    let mut file = None;
+    if let Some(InteractionData::ApplicationCommand(command_data)) = &interaction.data {
+        for option in &command_data.options {
+            match (option.name.as_str(), &option.value) {
+                ("text", CommandOptionValue::String(val)) => text = val.clone(),
+                ("file", CommandOptionValue::Attachment(id)) => {
+                    file = command_data.resolved.as_ref().and_then(|resolved| resolved.attachments.get(id)).cloned();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    if let Some(file) = &file {
+        let is_text = file.content_type.as_deref().is_none_or(|kind| kind.starts_with("text/"));
+        // Each character takes up to four bytes in UTF-8
+        if !is_text || file.size > (summarizer::MAX_DRYRUN_CHARS * 4) as u64 {
+            send_error_response(
+                state.http.clone(),
+                state.application_id,
+                interaction_id,
+                token,
+                &format!("Attach a text file of at most {} characters", summarizer::MAX_DRYRUN_CHARS)
+            ).await?;
+            return Ok(());
+        }
+    }
+
+    // The summarizer can take well over the interaction's three seconds
+    let response = InteractionResponse {
+        kind: InteractionResponseType::DeferredChannelMessageWithSource,
+        data: None,
+    };
+    state.http
+        .interaction(state.application_id)
+        .create_response(interaction_id, &token, &response)
+        .await?;
+
+    let interaction_client = state.http.interaction(state.application_id);
+    if let Some(file) = file {
+        let downloaded = match state.http_client.get(&file.url).send().await {
+            Ok(response) => response.error_for_status().map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+        let body = match downloaded {
+            Ok(response) => response.text().await.map_err(|e| e.to_string()),
+            Err(e) => Err(e),
+        };
+        match body {
+            Ok(body) if text.is_empty() => text = body,
+            Ok(body) => text = format!("{}\n{}", text, body),
+            Err(e) => {
+                eprintln!("[ERROR] /dryrun_summary couldn't download {}: {}", file.filename, e);
+                interaction_client.update_response(&token).content(Some("❌ Couldn't download the attached file")).await?;
+                return Ok(());
+            }
+        }
+    }
+
+    // Preview with this server's custom prompt, so it can be tuned before a real meeting
+    let template = match interaction.guild_id {
+        Some(guild_id) => state.guild_config.get_guild_config(guild_id).await.minutes_template,
+        None => None,
+    };
+    let summarizer = &state.recording.summarizer;
+    let result = summarizer::dry_run_summary(&text, |text| async move {
+        summarizer.summarize_meeting(&text, template.as_deref()).await
+    }).await;
+
+    let messages = match result {
+        Ok(messages) => messages,
+        Err(e) => {
+            eprintln!("[ERROR] /dryrun_summary failed: {}", e);
+            interaction_client.update_response(&token).content(Some(&format!("❌ {}", e))).await?;
+            return Ok(());
+        }
+    };
+    for (i, message) in messages.into_iter().enumerate() {
+        if i == 0 {
+            interaction_client.update_response(&token).content(Some(&message)).await?;
+        } else {
+            interaction_client.create_followup(&token).content(&message).await?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn handle_redaction(
+    interaction: Interaction,
+    state: Arc<BotState>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let interaction_id = interaction.id;
+    let token = interaction.token.clone();
+
+    let Some(guild_id) = interaction.guild_id else {
+        send_error_response(
+            state.http.clone(),
+            state.application_id,
+            interaction_id,
+            token,
+            "This command can only be used in a server"
+        ).await?;
+        return Ok(());
+    };
+
+    let mut enabled = false;
+    let mut words = None;
+    if let Some(InteractionData::ApplicationCommand(command_data)) = &interaction.data {
+        for option in &command_data.options {
+            match (option.name.as_str(), &option.value) {
+                ("enabled", CommandOptionValue::Boolean(val)) => enabled = *val,
+                ("words", CommandOptionValue::String(val)) => {
+                    words = Some(
+                        val.split(',')
+                            .map(|w| w.trim().to_string())
+                            .filter(|w| !w.is_empty())
+                            .collect::<Vec<_>>(),
+                    );
+                }
+                _ => {}
+            }
+        }
+    }
+
+    state.guild_config.set_redaction(guild_id, enabled, words).await;
+    let config = state.guild_config.get_guild_config(guild_id).await;
+
+    let content = if enabled {
+        format!(
+            "✅ **Transcript redaction enabled**\n\nEmails, phone numbers and common profanity are masked{}.",
+            if config.redact_words.is_empty() {
+                String::new()
+            } else {
+                format!(", along with {} listed word(s)", config.redact_words.len())
+            }
+        )
+    } else {
+        "✅ **Transcript redaction disabled**".to_string()
+    };
+
+    let response = message_response(content, true);
+
+    state.http
+        .interaction(state.application_id)
+        .create_response(interaction_id, &token, &response)
+        .await?;
+
+    Ok(())
+}
+
+/// `/vocab_add`, `/vocab_list` and `/vocab_remove`
+async fn handle_vocab(
+    interaction: Interaction,
+    state: Arc<BotState>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    use twilight_model::channel::message::{AllowedMentions, MessageFlags};
+    use guild_config::{VocabEntry, VocabTranslation};
+
+    let interaction_id = interaction.id;
+    let token = interaction.token.clone();
+
+    let Some(guild_id) = interaction.guild_id else {
+        send_error_response(
+            state.http.clone(),
+            state.application_id,
+            interaction_id,
+            token,
+            "This command can only be used in a server"
+        ).await?;
+        return Ok(());
+    };
+
+    let mut name = "";
+    let mut term = String::new();
+    let mut translation = None;
+    let mut from = None;
+    let mut to = None;
+    if let Some(InteractionData::ApplicationCommand(command_data)) = &interaction.data {
+        name = command_data.name.as_str();
+        for option in &command_data.options {
+            match (option.name.as_str(), &option.value) {
+                ("term", CommandOptionValue::String(val)) => term = val.trim().to_string(),
+                ("translation", CommandOptionValue::String(val)) => translation = Some(val.trim().to_string()),
+                ("from", CommandOptionValue::String(val)) => from = Some(val.clone()),
+                ("to", CommandOptionValue::String(val)) => to = Some(val.clone()),
+                _ => {}
+            }
+        }
+    }
+
+    let content = match name {
+        "vocab_add" => {
+            let translation = match (translation.filter(|t| !t.is_empty()), from, to) {
+                (None, _, _) => None,
+                (Some(text), Some(source_lang), Some(target_lang)) if source_lang != target_lang => {
+                    Some(VocabTranslation { source_lang, target_lang, text })
+                }
+                _ => {
+                    send_error_response(
+                        state.http.clone(),
+                        state.application_id,
+                        interaction_id,
+                        token,
+                        "A translation needs two different languages in `from` and `to`"
+                    ).await?;
+                    return Ok(());
+                }
+            };
+            if term.is_empty() {
+                send_error_response(
+                    state.http.clone(),
+                    state.application_id,
+                    interaction_id,
+                    token,
+                    "Please give a term"
+                ).await?;
+                return Ok(());
+            }
+            let summary = match &translation {
+                Some(t) => format!("**{}** → **{}** ({} → {})", term, t.text, t.source_lang.to_uppercase(), t.target_lang.to_uppercase()),
+                None => format!("**{}**", term),
+            };
+            match state.guild_config.add_vocab(guild_id, VocabEntry { term, translation }).await {
+                Ok(()) => format!("✅ Added {} to this server's vocabulary", summary),
+                Err(e) => format!("❌ {}", e),
+            }
+        }
+        "vocab_remove" => {
+            if state.guild_config.remove_vocab(guild_id, &term).await {
+                format!("✅ Removed **{}** from this server's vocabulary", term)
+            } else {
+                format!("❌ **{}** isn't in this server's vocabulary", term)
+            }
+        }
+        _ => {
+            let vocabulary = state.guild_config.get_guild_config(guild_id).await.vocabulary;
+            if vocabulary.is_empty() {
+                "📖 No vocabulary yet. Add names and terms with `/vocab_add`.".to_string()
+            } else {
+                let lines: Vec<String> = vocabulary.iter().map(|entry| match &entry.translation {
+                    Some(t) => format!("• {} → {} ({} → {})", entry.term, t.text, t.source_lang.to_uppercase(), t.target_lang.to_uppercase()),
+                    None => format!("• {}", entry.term),
+                }).collect();
+                format!("📖 **Vocabulary** ({} terms)\n{}", vocabulary.len(), lines.join("\n"))
+            }
+        }
+    };
+
+    let response = InteractionResponse {
+        kind: InteractionResponseType::ChannelMessageWithSource,
+        data: Some(twilight_model::http::interaction::InteractionResponseData {
+            content: Some(content),
+            allowed_mentions: Some(AllowedMentions::default()),
+            flags: Some(MessageFlags::EPHEMERAL),
+            ..Default::default()
+        }),
+    };
+
+    state.http
+        .interaction(state.application_id)
+        .create_response(interaction_id, &token, &response)
+        .await?;
+
+    Ok(())
+}
+
+/// `/recordings list` and `/recordings clear`
+async fn handle_recordings(
+    interaction: Interaction,
+    state: Arc<BotState>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let interaction_id = interaction.id;
+    let token = interaction.token.clone();
+
+    let Some(guild_id) = interaction.guild_id else {
+        send_error_response(
+            state.http.clone(),
+            state.application_id,
+            interaction_id,
+            token,
+            "This command can only be used in a server"
+        ).await?;
+        return Ok(());
+    };
+
+    let mut clear = false;
+    if let Some(InteractionData::ApplicationCommand(command_data)) = &interaction.data {
+        for option in &command_data.options {
+            if let ("clear", CommandOptionValue::SubCommand(_)) = (option.name.as_str(), &option.value) {
+                clear = true;
+            }
+        }
+    }
+
+    let configured = state.guild_config.get_guild_config(guild_id).await.recording_dir;
+    let dir = match recording_files::recordings_dir(
+        configured.as_deref(),
+        state.recording.recording_manager.guild_output_dir(guild_id),
+    ) {
+        Ok(dir) => dir,
+        Err(e) => {
+            send_error_response(state.http.clone(), state.application_id, interaction_id, token, &e).await?;
+            return Ok(());
+        }
+    };
+
+    let content = if clear {
+        if state.recording.recording_manager.is_recording(guild_id).await {
+            send_error_response(
+                state.http.clone(),
+                state.application_id,
+                interaction_id,
+                token,
+                "A recording is in progress; stop it before clearing recordings"
+            ).await?;
+            return Ok(());
+        }
+        if state.recording.is_processing(guild_id).await {
+            send_error_response(
+                state.http.clone(),
+                state.application_id,
+                interaction_id,
+                token,
+                "The last recording is still being transcribed; try again once its minutes are posted"
+            ).await?;
+            return Ok(());
+        }
+        // Drop the meetings kept for /export first; their files are about to go
+        state.recording.retained.release_guild(guild_id).await;
+        let (count, bytes) = recording_files::clear_recordings(&dir, guild_id)?;
+        println!("[INFO] Cleared {} recording(s) ({} bytes) for guild {}", count, bytes, guild_id);
+        format!("🗑️ Deleted {} recording file(s), freeing {}", count, recording_files::format_size(bytes))
+    } else {
+        let files = recording_files::list_recordings(&dir)?;
+        recording_files::format_listing(&files, std::time::SystemTime::now())
+    };
+
+    let response = message_response(content, true);
+
+    state.http
+        .interaction(state.application_id)
+        .create_response(interaction_id, &token, &response)
+        .await?;
+
+    Ok(())
+}
+
+async fn handle_record_consent(
+    interaction: Interaction,
+    state: Arc<BotState>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let interaction_id = interaction.id;
+    let token = interaction.token.clone();
+
+    let Some(guild_id) = interaction.guild_id else {
+        send_error_response(
+            state.http.clone(),
+            state.application_id,
+            interaction_id,
+            token,
+            "This command can only be used in a server"
+        ).await?;
+        return Ok(());
+    };
+
+    let mut enabled = false;
+    let mut seconds = None;
+    if let Some(InteractionData::ApplicationCommand(command_data)) = &interaction.data {
+        for option in &command_data.options {
+            match (option.name.as_str(), &option.value) {
+                ("enabled", CommandOptionValue::Boolean(val)) => enabled = *val,
+                ("seconds", CommandOptionValue::Integer(val)) => seconds = Some((*val).clamp(5, 120) as u64),
+                _ => {}
+            }
+        }
+    }
+
+    state.guild_config.set_consent_prompt(guild_id, enabled, seconds).await;
+    let config = state.guild_config.get_guild_config(guild_id).await;
+
+    let content = match config.consent_window() {
+        Some(window) => format!(
+            "✅ **Consent prompt enabled**\n\nRecordings are announced in the voice channel chat and start after {} seconds unless someone reacts with {}.",
+            window.as_secs(), CONSENT_OBJECTION_EMOJI
+        ),
+        None => "✅ **Consent prompt disabled** — recordings start immediately".to_string(),
+    };
+
+    let response = message_response(content, true);
+
+    state.http
+        .interaction(state.application_id)
+        .create_response(interaction_id, &token, &response)
+        .await?;
+
+    Ok(())
+}
+
+async fn handle_record_role(
+    interaction: Interaction,
+    state: Arc<BotState>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let interaction_id = interaction.id;
+    let token = interaction.token.clone();
+
+    let Some(guild_id) = interaction.guild_id else {
+        send_error_response(
+            state.http.clone(),
+            state.application_id,
+            interaction_id,
+            token,
+            "This command can only be used in a server"
+        ).await?;
+        return Ok(());
+    };
+
+    let mut role_id = None;
+    if let Some(InteractionData::ApplicationCommand(command_data)) = &interaction.data {
+        for option in &command_data.options {
+            if let ("role", CommandOptionValue::Role(id)) = (option.name.as_str(), &option.value) {
+                role_id = Some(*id);
+            }
+        }
+    }
+
+    state.guild_config.set_required_role(guild_id, role_id).await;
+
+    let content = match role_id {
+        Some(id) => format!("✅ **Recording restricted** to members with <@&{}>", id),
+        None => "✅ **Recording restriction removed** — anyone can start recordings".to_string(),
+    };
+
+    let response = message_response(content, true);
+
+    state.http
+        .interaction(state.application_id)
+        .create_response(interaction_id, &token, &response)
+        .await?;
+
+    Ok(())
+}
+
+async fn handle_output_channel(
+    interaction: Interaction,
+    state: Arc<BotState>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    use twilight_model::channel::ChannelType;
+
+    let interaction_id = interaction.id;
+    let token = interaction.token.clone();
+
+    let Some(guild_id) = interaction.guild_id else {
+        send_error_response(
+            state.http.clone(),
+            state.application_id,
+            interaction_id,
+            token,
+            "This command can only be used in a server"
+        ).await?;
+        return Ok(());
+    };
+
+    let mut kinds = Vec::new();
+    let mut channel_id = None;
+    if let Some(InteractionData::ApplicationCommand(command_data)) = &interaction.data {
+        for option in &command_data.options {
+            match (option.name.as_str(), &option.value) {
+                ("kind", CommandOptionValue::String(val)) => {
+                    kinds = match val.as_str() {
+                        "minutes" => vec![guild_config::OutputKind::Minutes],
+                        "translations" => vec![guild_config::OutputKind::Translations],
+                        _ => vec![guild_config::OutputKind::Minutes, guild_config::OutputKind::Translations],
+                    };
+                }
+                ("channel", CommandOptionValue::Channel(id)) => channel_id = Some(*id),
+                _ => {}
+            }
+        }
+    }
+
+    // Discord only offers this guild's channels, but check rather than post somewhere unexpected
+    if let Some(channel_id) = channel_id {
+        let channel = match state.http.channel(channel_id).await {
+            Ok(response) => response.model().await.ok(),
+            Err(e) => {
+                eprintln!("[WARN] Couldn't fetch channel {}: {}", channel_id, e);
+                None
+            }
+        };
+        let problem = match channel {
+            None => Some("I can't see that channel; check my permissions there"),
+            Some(channel) if channel.guild_id != Some(guild_id) => Some("That channel isn't in this server"),
+            Some(channel) if !matches!(channel.kind, ChannelType::GuildText | ChannelType::GuildAnnouncement) => {
+                Some("Please pick a text channel")
+            }
+            Some(_) => None,
+        };
+        if let Some(problem) = problem {
+            send_error_response(
+                state.http.clone(),
+                state.application_id,
+                interaction_id,
+                token,
+                problem
+            ).await?;
+            return Ok(());
+        }
+    }
+
+    for kind in &kinds {
+        state.guild_config.set_output_channel(guild_id, *kind, channel_id).await;
+    }
+
+    let what = match kinds.as_slice() {
+        [guild_config::OutputKind::Minutes] => "Minutes",
+        [guild_config::OutputKind::Translations] => "Translations",
+        _ => "Minutes and translations",
+    };
+    let content = match channel_id {
+        Some(id) => format!("✅ **{} will be posted in <#{}>**", what, id),
+        None => format!("✅ **{} will be posted in the voice channel chat**", what),
+    };
+
+    let response = message_response(content, state.response_policy.is_ephemeral("output_channel"));
+
+    state.http
+        .interaction(state.application_id)
+        .create_response(interaction_id, &token, &response)
+        .await?;
+
+    Ok(())
+}
+
+/// `/record_exclude` and `/record_include`: drop or keep a user's audio before it's buffered
+async fn handle_record_exclude(
+    interaction: Interaction,
+    state: Arc<BotState>,
+    exclude: bool,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let interaction_id = interaction.id;
+    let token = interaction.token.clone();
+
+    let Some(guild_id) = interaction.guild_id else {
+        send_error_response(
+            state.http.clone(),
+            state.application_id,
+            interaction_id,
+            token,
+            "This command can only be used in a server"
+        ).await?;
+        return Ok(());
+    };
+
+    let mut user_id = None;
+    if let Some(InteractionData::ApplicationCommand(command_data)) = &interaction.data {
+        for option in &command_data.options {
+            if let ("user", CommandOptionValue::User(id)) = (option.name.as_str(), &option.value) {
+                user_id = Some(*id);
+            }
+        }
+    }
+    let Some(user_id) = user_id else {
+        send_error_response(
+            state.http.clone(),
+            state.application_id,
+            interaction_id,
+            token,
+            "Please choose a user"
+        ).await?;
+        return Ok(());
+    };
+
+    let manager = &state.recording.recording_manager;
+    let changed = manager.set_excluded(guild_id, user_id, exclude).await;
+    let content = match (exclude, changed) {
+        (true, true) => format!("🔇 <@{}> **won't be recorded** in this server; their audio is dropped before it's saved", user_id),
+        (true, false) => format!("<@{}> is already excluded from recordings", user_id),
+        (false, true) => format!("🔊 <@{}> **will be recorded** again", user_id),
+        (false, false) => format!("<@{}> isn't excluded from recordings", user_id),
+    };
+    let excluded = manager.excluded_speakers(guild_id).await;
+    let content = if excluded.is_empty() {
+        content
+    } else {
+        format!(
+            "{}\nExcluded: {}",
+            content,
+            excluded.iter().map(|id| format!("<@{}>", id)).collect::<Vec<_>>().join(", ")
+        )
+    };
+
+    let response = message_response(content, true);
+
+    state.http
+        .interaction(state.application_id)
+        .create_response(interaction_id, &token, &response)
+        .await?;
+
+    Ok(())
+}
+
+/// `/model`: pick the whisper model for the guild, or show the current one
+async fn handle_model(
+    interaction: Interaction,
+    state: Arc<BotState>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let interaction_id = interaction.id;
+    let token = interaction.token.clone();
+
+    let Some(guild_id) = interaction.guild_id else {
+        send_error_response(
+            state.http.clone(),
+            state.application_id,
+            interaction_id,
+            token,
+            "This command can only be used in a server"
+        ).await?;
+        return Ok(());
+    };
+
+    let mut choice = None;
+    if let Some(InteractionData::ApplicationCommand(command_data)) = &interaction.data {
+        for option in &command_data.options {
+            if let ("model", CommandOptionValue::String(val)) = (option.name.as_str(), &option.value) {
+                choice = Some(val.clone());
+            }
+        }
+    }
+
+    if let Some(choice) = choice {
+        let model = state.transcribers.names().into_iter()
+            .find(|name| *name == choice)
+            .map(str::to_string);
+        state.guild_config.set_transcription_model(guild_id, model).await;
+    }
+
+    let selected = state.guild_config.get_guild_config(guild_id).await.transcription_model;
+    let describe = |default: &str| {
+        let name = transcriber::select_model(&state.transcribers.names(), selected.as_deref(), default).to_string();
+        let file = state.transcribers.get(&name).map(|model| model.model_name().to_string()).unwrap_or_default();
+        format!("`{}` ({})", name, file)
+    };
+    let content = format!(
+        "🧠 **Transcription model**\nRecordings: {}\nTranslation: {}",
+        describe(transcriber::MODEL_BASE),
+        describe(transcriber::MODEL_FAST),
+    );
+
+    let response = message_response(content, true);
+
+    state.http
+        .interaction(state.application_id)
+        .create_response(interaction_id, &token, &response)
+        .await?;
+
+    Ok(())
+}
+
+async fn handle_minutes_template(
+    interaction: Interaction,
+    state: Arc<BotState>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let interaction_id = interaction.id;
+    let token = interaction.token.clone();
+
+    let Some(guild_id) = interaction.guild_id else {
+        send_error_response(
+            state.http.clone(),
+            state.application_id,
+            interaction_id,
+            token,
+            "This command can only be used in a server"
+        ).await?;
+        return Ok(());
+    };
+
+    let mut template = None;
+    let mut reset = false;
+    if let Some(InteractionData::ApplicationCommand(command_data)) = &interaction.data {
+        for option in &command_data.options {
+            match (option.name.as_str(), &option.value) {
+                ("template", CommandOptionValue::String(val)) => template = Some(val.replace("\\n", "\n")),
+                ("reset", CommandOptionValue::Boolean(val)) => reset = *val,
+                _ => {}
+            }
+        }
+    }
+
+    let content = if reset {
+        state.guild_config.set_minutes_template(guild_id, None).await?;
+        "📝 Meeting minutes will use the built-in prompt again".to_string()
+    } else if let Some(template) = template {
+        if let Err(e) = state.guild_config.set_minutes_template(guild_id, Some(template)).await {
+            send_error_response(
+                state.http.clone(),
+                state.application_id,
+                interaction_id,
+                token,
+                &e
+            ).await?;
+            return Ok(());
+        }
+        "📝 Meeting minutes will use your custom prompt".to_string()
+    } else {
+        match state.guild_config.get_guild_config(guild_id).await.minutes_template {
+            Some(template) => format!("📝 **Custom minutes prompt**\n```\n{}\n```", template),
+            None => "📝 Meeting minutes use the built-in prompt".to_string(),
+        }
+    };
+
+    let response = message_response(content, true);
+
+    state.http
+        .interaction(state.application_id)
+        .create_response(interaction_id, &token, &response)
+        .await?;
+
+    Ok(())
+}
+
+async fn handle_translate_tune(
+    interaction: Interaction,
+    state: Arc<BotState>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let interaction_id = interaction.id;
+    let token = interaction.token.clone();
+
+    let Some(guild_id) = interaction.guild_id else {
+        send_error_response(
+            state.http.clone(),
+            state.application_id,
+            interaction_id,
+            token,
+            "This command can only be used in a server"
+        ).await?;
+        return Ok(());
+    };
+
+    let mut silence_ms = None;
+    let mut min_speech_ms = None;
+    if let Some(InteractionData::ApplicationCommand(command_data)) = &interaction.data {
+        for option in &command_data.options {
+            match (option.name.as_str(), &option.value) {
+                ("silence_ms", CommandOptionValue::Integer(val)) => silence_ms = Some((*val).max(0) as u64),
+                ("min_speech_ms", CommandOptionValue::Integer(val)) => min_speech_ms = Some((*val).max(0) as u64),
+                _ => {}
+            }
+        }
+    }
+
+    let content = if silence_ms.is_none() && min_speech_ms.is_none() {
+        let tuning = state.guild_config.get_guild_config(guild_id).await.translation_tuning.unwrap_or_default();
+        format!(
+            "🎚️ **Real-time translation**: an utterance ends after {} ms of silence and needs at least {} ms of speech",
+            tuning.silence_ms, tuning.min_speech_ms
+        )
+    } else {
+        let tuning = match state.guild_config.set_translation_tuning(guild_id, silence_ms, min_speech_ms).await {
+            Ok(tuning) => tuning,
+            Err(e) => {
+                send_error_response(
+                    state.http.clone(),
+                    state.application_id,
+                    interaction_id,
+                    token,
+                    &e
+                ).await?;
+                return Ok(());
+            }
+        };
+        let applies = if state.translation_manager.set_tuning(guild_id, tuning).await {
+            "the current session"
+        } else {
+            "the next session"
+        };
+        format!(
+            "🎚️ **Real-time translation tuned** for {}: an utterance ends after {} ms of silence and needs at least {} ms of speech",
+            applies, tuning.silence_ms, tuning.min_speech_ms
+        )
+    };
+
+    let response = message_response(content, true);
+
+    state.http
+        .interaction(state.application_id)
+        .create_response(interaction_id, &token, &response)
+        .await?;
+
+    Ok(())
+}
+
+async fn handle_transcript(
+    interaction: Interaction,
+    state: Arc<BotState>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    use twilight_model::http::attachment::Attachment;
+
+    let interaction_id = interaction.id;
+    let token = interaction.token.clone();
+
+    let Some(guild_id) = interaction.guild_id else {
+        send_error_response(
+            state.http.clone(),
+            state.application_id,
+            interaction_id,
+            token,
+            "This command can only be used in a server"
+        ).await?;
+        return Ok(());
+    };
+
+    let guild_config = state.guild_config.get_guild_config(guild_id).await;
+    if !has_required_role(member_roles(&interaction), &guild_config) {
+        send_missing_role_response(&state, interaction_id, token).await?;
+        return Ok(());
+    }
+
+    let Some(stored) = state.last_transcripts.latest(guild_id).await else {
+        send_error_response(
+            state.http.clone(),
+            state.application_id,
+            interaction_id,
+            token,
+            "No meeting transcript is stored for this server yet"
+        ).await?;
+        return Ok(());
+    };
+
+    let attachment = Attachment::from_bytes(stored.filename(), stored.to_markdown().into_bytes(), 0);
+
+    let response = InteractionResponse {
+        kind: InteractionResponseType::ChannelMessageWithSource,
+        data: Some(twilight_model::http::interaction::InteractionResponseData {
+            content: Some(format!(
+                "📝 **Last meeting transcript** ({})",
+                timezone::format(&stored.created_at, "%Y-%m-%d %H:%M %Z")
+            )),
+            attachments: Some(vec![attachment]),
+            ..Default::default()
+        }),
+    };
+
+    state.http
+        .interaction(state.application_id)
+        .create_response(interaction_id, &token, &response)
+        .await?;
+
+    Ok(())
+}
+
+/// `/export`: ZIP the last meeting (with its audio, if retained) and upload it.
+/// Archives over the upload limit go to the storage sink and are linked; without one
+/// (or if that upload fails) they're sent without audio, followed by the WAVs split into parts.
+async fn handle_export(
+    interaction: Interaction,
+    state: Arc<BotState>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    use twilight_model::http::attachment::Attachment;
+
+    let interaction_id = interaction.id;
+    let token = interaction.token.clone();
+
+    let (Some(guild_id), Some(channel_id)) = (interaction.guild_id, interaction.channel.as_ref().map(|c| c.id)) else {
+        send_error_response(
+            state.http.clone(),
+            state.application_id,
+            interaction_id,
+            token,
+            "This command can only be used in a server"
+        ).await?;
+        return Ok(());
+    };
+
+    let guild_config = state.guild_config.get_guild_config(guild_id).await;
+    if !has_required_role(member_roles(&interaction), &guild_config) {
+        send_missing_role_response(&state, interaction_id, token).await?;
+        return Ok(());
+    }
+
+    let retained = state.recording.retained.get(guild_id).await;
+    let recorded_at = retained.as_ref().map(|session| session.recorded_at);
+    let (stored, audio_files) = match retained {
+        Some(session) => (session.transcript(), session.audio_files),
+        None => match state.last_transcripts.latest(guild_id).await {
+            Some(stored) => (stored, Vec::new()),
+            None => {
+                send_error_response(
+                    state.http.clone(),
+                    state.application_id,
+                    interaction_id,
+                    token,
+                    "No meeting is stored for this server yet"
+                ).await?;
+                return Ok(());
+            }
+        },
+    };
+
+    // Reading and zipping the audio can take a while
+    let response = InteractionResponse {
+        kind: InteractionResponseType::DeferredChannelMessageWithSource,
+        data: None,
+    };
+    state.http
+        .interaction(state.application_id)
+        .create_response(interaction_id, &token, &response)
+        .await?;
+
+    let mut audio = Vec::new();
+    for path in &audio_files {
+        match tokio::fs::read(path).await {
+            Ok(data) => {
+                let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+                audio.push((name, data));
+            }
+            Err(e) => eprintln!("[WARN] Skipping {} in export: {}", path.display(), e),
+        }
+    }
+
+    let limit = audio_export::guild_upload_limit(&state.http, guild_id).await;
+    let filename = session_export::export_filename(&stored);
+    let header = format!("📦 **Meeting export** ({})", timezone::format(&stored.created_at, "%Y-%m-%d %H:%M %Z"));
+
+    let archive = {
+        let stored = stored.clone();
+        tokio::task::spawn_blocking(move || session_export::build_export_zip(guild_id, &stored, &audio)).await??
+    };
+
+    let interaction_client = state.http.interaction(state.application_id);
+    if archive.len() as u64 <= limit {
+        let attachment = Attachment::from_bytes(filename, archive, 0);
+        interaction_client.update_response(&token)
+            .content(Some(&header))
+            .attachments(&[attachment])
+            .await?;
+    } else {
+        let size = archive.len();
+        let uploaded = match &state.export_sink {
+            Some(sink) => match sink.upload(guild_id, &filename, archive).await {
+                Ok(url) => Some(url),
+                Err(e) => {
+                    eprintln!("[ERROR] Failed to upload the export for guild {} to storage: {}", guild_id, e);
+                    None
+                }
+            },
+            None => None,
+        };
+
+        if let Some(url) = uploaded {
+            println!("[INFO] Export for guild {} is {} bytes, uploaded to {}", guild_id, size, url);
+            let content = format!("{}\n🔗 Too large for Discord, so it was uploaded here: {}", header, url);
+            interaction_client.update_response(&token)
+                .content(Some(&content))
+                .await?;
+        } else {
+            println!("[INFO] Export for guild {} is {} bytes, sending audio separately", guild_id, size);
+            let without_audio = {
+                let stored = stored.clone();
+                tokio::task::spawn_blocking(move || session_export::build_export_zip(guild_id, &stored, &[])).await??
+            };
+            let attachment = Attachment::from_bytes(filename, without_audio, 0);
+            let content = format!("{}\n🎧 Audio is too large for one file and follows in separate messages", header);
+            interaction_client.update_response(&token)
+                .content(Some(&content))
+                .attachments(&[attachment])
+                .await?;
+
+            for path in &audio_files {
+                if let Err(e) = audio_export::export_recording(&state.http, guild_id, channel_id, path).await {
+                    eprintln!("[ERROR] Failed to upload {}: {}", path.display(), e);
+                }
+            }
+        }
+    }
+
+    // Exported audio doesn't need to wait for the TTL
+    if let Some(recorded_at) = recorded_at
+        && !audio_files.is_empty()
+    {
+        state.recording.retained.release(guild_id, recorded_at).await;
+    }
+
+    Ok(())
+}
+
+async fn handle_stats(
+    interaction: Interaction,
+    state: Arc<BotState>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let embed = stats::build_stats_embed(
+        &state.stats,
+        state.recording.recording_manager.active_session_count(),
+        state.translation_manager.active_count().await,
+    );
+
+    let response = InteractionResponse {
+        kind: InteractionResponseType::ChannelMessageWithSource,
+        data: Some(twilight_model::http::interaction::InteractionResponseData {
+            embeds: Some(vec![embed]),
+            ..Default::default()
+        }),
+    };
+
+    state.http
+        .interaction(state.application_id)
+        .create_response(interaction.id, &interaction.token, &response)
+        .await?;
+
+    Ok(())
+}
+
+async fn handle_help(
+    interaction: Interaction,
+    state: Arc<BotState>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    use twilight_model::channel::message::MessageFlags;
+
+    // Discord's character limit covers every embed in a message, so each gets its own
+    let mut embeds = help::build_help_embeds().into_iter();
+    let response = InteractionResponse {
+        kind: InteractionResponseType::ChannelMessageWithSource,
+        data: Some(twilight_model::http::interaction::InteractionResponseData {
+            embeds: Some(embeds.next().into_iter().collect()),
+            flags: Some(MessageFlags::EPHEMERAL),
             ..Default::default()
         }),
     };
 
+    let interaction_client = state.http.interaction(state.application_id);
+    interaction_client
+        .create_response(interaction.id, &interaction.token, &response)
+        .await?;
+    for embed in embeds {
+        interaction_client
+            .create_followup(&interaction.token)
+            .embeds(&[embed])
+            .flags(MessageFlags::EPHEMERAL)
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Settings fixed for one translation session, shared by every utterance it handles
+#[derive(Clone)]
+struct TranslationTask {
+    guild_id: Id<twilight_model::id::marker::GuildMarker>,
+    voice_channel_id: Id<twilight_model::id::marker::ChannelMarker>,
+    transcriber: Arc<Transcriber>,
+    escalation: Option<transcriber::Escalation>,
+    initial_prompt: Option<String>,
+    listener_mode: bool,
+}
+
+async fn process_translation_loop(state: Arc<BotState>, task: TranslationTask) {
+    let guild_id = task.guild_id;
+    let translation_manager = state.translation_manager.clone();
+
+    // One worker per speaker so each speaker's translations post in spoken order
+    let dispatcher = TranslationDispatcher::new(SPEAKER_QUEUE_CAPACITY, move |user_id, samples: Vec<i16>| {
+        translate_utterance(state.clone(), task.clone(), user_id, samples)
+    })
+    .with_max_wait(translation_manager.staleness_window());
+
+    loop {
+        // Leaving the channel: translate whatever was still buffered, then stop
+        if let Some(pending) = translation_manager.take_drained(guild_id).await {
+            for (user_id, samples) in pending {
+                dispatcher.dispatch(user_id, samples).await;
+            }
+            break;
+        }
+
+        if !translation_manager.is_translating(guild_id).await {
+            break;
+        }
+
+        let ready_buffers = translation_manager.get_ready_translations(guild_id).await;
+
+        for (user_id, samples) in ready_buffers {
+            dispatcher.dispatch(user_id, samples).await;
+        }
+
+        // While the channel is quiet this sleeps until someone speaks
+        translation_manager.wait_for_work(guild_id).await;
+    }
+}
+
+/// Transcribe one utterance, translate it and post the result embed
+async fn translate_utterance(
+    state: Arc<BotState>,
+    task: TranslationTask,
+    user_id: Id<twilight_model::id::marker::UserMarker>,
+    samples: Vec<i16>,
+) {
+    use twilight_model::channel::message::embed::Embed;
+    use twilight_model::channel::message::embed::EmbedField;
+    use transcriber::compute_rms;
+    use transcriber::convert_i16_to_f32;
+    use transcriber::downsample_to_16k;
+    use transcriber::is_likely_hallucination;
+    use std::time::Instant;
+
+    let TranslationTask { guild_id, voice_channel_id, transcriber, escalation, initial_prompt, listener_mode } = task;
+
+    // Follow the session if the bot was moved to another channel
+    let voice_channel_id = state.translation_manager.session_channel(guild_id).await.unwrap_or(voice_channel_id);
+
+    let user_setting = match state.user_settings.get_user_setting(user_id).await {
+        Some(setting) => setting,
+        None => {
+            println!("[INFO] Skipping user {} - no language settings", user_id);
+            return;
+        }
+    };
+
+    // Half a second at minimum
+    if samples.len() < transcriber::DECODE_SAMPLE_RATE as usize / 2 {
+        return;
+    }
+
+    // Listener mode translates into the other participants' languages instead of the speaker's targets
+    let targets = if listener_mode {
+        let mut participants = Vec::new();
+        for listener in state.voice_states.users_in(voice_channel_id).await {
+            if let Some(setting) = state.user_settings.get_user_setting(listener).await {
+                participants.push((listener, setting));
+            }
+        }
+        let targets = user_settings::listener_targets(user_id, &user_setting.source_lang, &participants);
+        if targets.is_empty() {
+            println!("[INFO] Skipping user {} - no listeners need a translation", user_id);
+            return;
+        }
+        targets
+    } else {
+        user_setting.targets()
+    };
+
+    let total_start = Instant::now();
+    let convert_start = Instant::now();
+    let samples_f32 = convert_i16_to_f32(&samples);
+    let rms = compute_rms(&samples_f32);
+    if rms < transcriber::VAD_RMS_FLOOR {
+        println!("[INFO] Skipping low-volume audio (rms={:.5}) for user {}", rms, user_id);
+        return;
+    }
+    let final_samples = downsample_to_16k(&samples_f32, transcriber::DECODE_SAMPLE_RATE);
+    let convert_time = convert_start.elapsed();
+    
+    let duration_ms = (final_samples.len() as u64 * 1000) / 16000;
+    
+    let transcribe_start = Instant::now();
+    // Auto-swap needs whisper to detect the language instead of assuming the source
+    let language = (!user_setting.auto_swap).then(|| user_settings::speech_language(&user_setting.source_lang).to_string());
+    let retry_samples = escalation.as_ref().map(|_| final_samples.clone());
+    let mut result = transcriber.transcribe_scored_async(final_samples, language.clone(), initial_prompt.clone()).await;
+
+    // The fast model is often wrong when it's unsure; only then pay for the accurate one
+    let unsure = match (&escalation, &result) {
+        (Some(escalation), Ok((text, _, confidence))) if transcriber::should_escalate(text, *confidence, escalation.threshold) => Some(*confidence),
+        _ => None,
+    };
+    if let (Some(confidence), Some(escalation), Some(samples)) = (unsure, &escalation, retry_samples) {
+        println!(
+            "[INFO] Re-transcribing user {} with {} (fast model confidence {:.2})",
+            user_id, escalation.transcriber.model_name(), confidence
+        );
+        match escalation.transcriber.transcribe_scored_async(samples, language, initial_prompt).await {
+            Ok(accurate) => result = Ok(accurate),
+            Err(e) => eprintln!("[WARN] Accurate re-transcription failed, keeping the fast result: {}", e),
+        }
+    }
+
+    match result {
+        Ok((transcription, detected_lang, _)) => {
+            let transcribe_time = transcribe_start.elapsed();
+
+            // Speaking the target language flips the direction for this utterance
+            let (user_setting, targets) = match user_setting.swapped_for(&detected_lang) {
+                Some(swapped) => {
+                    println!(
+                        "[INFO] User {} spoke {}, translating {} → {}",
+                        user_id, detected_lang, swapped.source_lang, swapped.target_lang
+                    );
+                    // Listener mode keeps its targets, minus the language now being spoken
+                    let targets = if listener_mode {
+                        targets.into_iter().filter(|target| *target != swapped.source_lang).collect()
+                    } else {
+                        swapped.targets()
+                    };
+                    (swapped, targets)
+                }
+                None => (user_setting, targets),
+            };
+            if targets.is_empty() {
+                println!("[INFO] Skipping user {} - no listeners need a translation", user_id);
+                return;
+            }
+            state.stats.record_transcription(transcribe_time);
+            if !transcription.trim().is_empty() {
+                if is_likely_hallucination(&transcription, duration_ms, rms) {
+                    println!("[INFO] Dropping likely hallucination (duration_ms={}, rms={:.5}): {}", duration_ms, rms, transcription);
+                    return;
+                }
+
+                if !state.translation_manager.should_post_transcription(guild_id, user_id, &transcription).await {
+                    println!("[INFO] Skipping duplicate transcription for user {}: {}", user_id, transcription);
+                    return;
+                }
+
+                // Only pin the source when the text looks like the configured language;
+                // otherwise let DeepL detect it
+                let source_full = translator::confident_source(&user_setting.source_lang, &transcription)
+                    .map(|_| user_setting.get_source_full());
+                if source_full.is_none() {
+                    println!("[DEBUG] Text doesn't look like {}, letting DeepL detect the source", user_setting.source_lang);
+                }
+                
+                // One DeepL call per target language, run concurrently
+                let translate_start = Instant::now();
+                let guild_settings = state.guild_config.get_guild_config(guild_id).await;
+                let output_channel_id = guild_settings.output_channel(guild_config::OutputKind::Translations, voice_channel_id);
+                let results = futures::future::join_all(targets.iter().map(|target| {
+                    let target_full = user_setting.to_full_name(target);
+                    let translator = state.translator.clone();
+                    let transcription = transcription.clone();
+                    let source_full = source_full.clone();
+                    let glossary_entries = guild_settings.glossary_entries(&user_setting.source_lang, target);
+                    async move {
+                        let glossary_id = match &source_full {
+                            Some(source_full) => translator.glossary_id(guild_id.get(), source_full, &target_full, &glossary_entries).await,
+                            None => None,
+                        };
+                        translator.translate(&transcription, source_full.as_deref(), &target_full, glossary_id.as_deref()).await
+                    }
+                })).await;
+
+                let mut translations = Vec::new();
+                let mut detected_source = None;
+                let mut last_error = None;
+                for (target, result) in targets.into_iter().zip(results) {
+                    match result {
+                        Ok(translated) => {
+                            detected_source = detected_source.or(translated.source_lang);
+                            translations.push((target, translated.text));
+                        }
+                        Err(e) => {
+                            eprintln!("[ERROR] Translation to {} failed: {}", target, e);
+                            last_error = Some(e);
+                        }
+                    }
+                }
+
+                match last_error.filter(|_| translations.is_empty()) {
+                    None => {
+                        metrics::record_translation_stage("convert", convert_time);
+                        metrics::record_translation_stage("transcribe", transcribe_time);
+                        metrics::record_translation_stage("translate", translate_start.elapsed());
+                        metrics::record_translation_stage("total", total_start.elapsed());
+                        state.stats.record_translation();
+                        
+                        state.translation_manager.record_translation(guild_id, user_id, &transcription, &translations).await;
+
+                        let speaker = state.member_names.resolve(guild_id, user_id).await;
+                        let source_label = detected_source.unwrap_or_else(|| user_setting.source_lang.clone());
+                        let title = voice_translator::translation_title(&speaker, &source_label, &translations);
+
+                        let romaji_line = user_setting.wants_romaji()
+                            .then(|| romaji::to_romaji(&transcription))
+                            .filter(|romaji| *romaji != transcription);
+
+                        let mut fields = vec![
+                            EmbedField {
+                                inline: false,
+                                name: format!("🗣️ Original ({})", source_label.to_uppercase()),
+                                value: transcription,
+                            },
+                        ];
+                        if let Some(romaji) = romaji_line {
+                            fields.push(EmbedField {
+                                inline: false,
+                                name: "🔤 Romaji".to_string(),
+                                value: romaji,
+                            });
+                        }
+                        fields.extend(voice_translator::translation_fields(&translations));
+
+                        let embed = Embed {
+                            author: None,
+                            color: Some(0x3498db),
+                            description: None,
+                            fields,
+                            footer: None,
+                            image: None,
+                            kind: "rich".to_string(),
+                            provider: None,
+                            thumbnail: None,
+                            timestamp: None,
+                            title: Some(title),
+                            url: None,
+                            video: None,
+                        };
+
+                        let _ = state.http.create_message(output_channel_id)
+                            .embeds(&[embed])
+                            .await;
+                    }
+                    Some(e) => {
+                        eprintln!("[ERROR] Translation failed: {}", e);
+                        if state.translator.take_unavailable_notice() {
+                            let _ = state.http.create_message(output_channel_id)
+                                .content(translation_outage_notice(&e))
+                                .await;
+                        }
+                    }
+                }
+            }
+        }
+        Err(e) => {
+            eprintln!("[ERROR] Transcription failed: {}", e);
+        }
+    }
+}
+
+/// Channel notice for a DeepL outage; quota and key problems won't fix themselves
+fn translation_outage_notice(error: &AppError) -> &'static str {
+    match error.translation_failure() {
+        Some(TranslationFailure::QuotaExceeded) => "⚠️ **DeepL quota used up** — translation is paused until the quota resets or the plan is upgraded.",
+        Some(TranslationFailure::Auth) => "⚠️ **DeepL rejected the API key** — ask an admin to check `DEEPL_API_KEY`.",
+        _ => "⚠️ **Translation temporarily unavailable** — DeepL is not responding. Translation will resume automatically.",
+    }
+}
+
+/// If the guild wants consent prompts, announce the recording in the voice channel
+/// chat and wait for objections. Returns false if someone objected.
+async fn await_recording_consent(
+    state: &BotState,
+    guild_id: Id<twilight_model::id::marker::GuildMarker>,
+    voice_channel_id: Id<twilight_model::id::marker::ChannelMarker>,
+) -> Result<bool, Box<dyn Error + Send + Sync>> {
+    use twilight_http::request::channel::reaction::RequestReactionType;
+
+    let guild_config = state.guild_config.get_guild_config(guild_id).await;
+    let Some(window) = guild_config.consent_window() else {
+        return Ok(true);
+    };
+
+    let announcement = state.http.create_message(voice_channel_id)
+        .content(&format!(
+            "🎙️ **This voice channel will be recorded** in {} seconds for meeting minutes.\n\nReact with {} to object and cancel the recording.",
+            window.as_secs(), CONSENT_OBJECTION_EMOJI
+        ))
+        .await?
+        .model()
+        .await?;
+    let prompt = state.consent.open(announcement.id);
+    state.http.create_reaction(
+        voice_channel_id,
+        announcement.id,
+        &RequestReactionType::Unicode { name: CONSENT_OBJECTION_EMOJI },
+    ).await?;
+
+    match prompt.wait(window).await {
+        ConsentOutcome::Proceed => Ok(true),
+        ConsentOutcome::Objected(user_id) => {
+            println!("[INFO] Recording in guild {} cancelled after objection from {}", guild_id, user_id);
+            let _ = state.http.create_message(voice_channel_id)
+                .content(&format!("🚫 **Recording cancelled** — <@{}> objected.", user_id))
+                .allowed_mentions(Some(&twilight_model::channel::message::AllowedMentions::default()))
+                .await;
+            Ok(false)
+        }
+    }
+}
+
+/// Roles of the member who invoked an interaction (empty outside guilds)
+fn member_roles(interaction: &Interaction) -> &[Id<twilight_model::id::marker::RoleMarker>] {
+    interaction.member.as_ref().map(|m| m.roles.as_slice()).unwrap_or(&[])
+}
+
+/// Tell the invoker, privately, that they lack the guild's required recording role
+async fn send_missing_role_response(
+    state: &BotState,
+    interaction_id: Id<twilight_model::id::marker::InteractionMarker>,
+    token: String,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let response = message_response("❌ You don't have the role required to start recording in this server.".to_string(), true);
+
+    state.http
+        .interaction(state.application_id)
+        .create_response(interaction_id, &token, &response)
+        .await?;
+
+    Ok(())
+}
+
+async fn send_error_response(
+    http: Arc<HttpClient>,
+    application_id: Id<twilight_model::id::marker::ApplicationMarker>,
+    interaction_id: Id<twilight_model::id::marker::InteractionMarker>,
+    token: String,
+    message: &str,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let response = message_response(format!("❌ {}", message), false);
+
     if let Err(e) = http
         .interaction(application_id)
         .create_response(interaction_id, &token, &response)
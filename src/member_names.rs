@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use twilight_http::Client as HttpClient;
+use twilight_model::id::Id;
+use twilight_model::id::marker::{GuildMarker, UserMarker};
+
+/// Names kept before the least recently used are dropped
+pub const DEFAULT_NAME_CAPACITY: usize = 1000;
+/// How long a fetched name is trusted before nickname changes are picked up
+pub const DEFAULT_NAME_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// Display name for a member: guild nickname if set, otherwise username
+pub fn display_name(nick: Option<&str>, username: &str) -> String {
+    nick.filter(|nick| !nick.trim().is_empty())
+        .unwrap_or(username)
+        .to_string()
+}
+
+/// Label used when a member can't be fetched
+pub fn fallback_name(user_id: Id<UserMarker>) -> String {
+    format!("User {}", user_id)
+}
+
+/// Where the cache gets a member's display name from
+#[async_trait::async_trait]
+pub trait MemberFetcher: Send + Sync {
+    async fn fetch_name(&self, guild_id: Id<GuildMarker>, user_id: Id<UserMarker>) -> Result<String, String>;
+}
+
+#[async_trait::async_trait]
+impl MemberFetcher for HttpClient {
+    async fn fetch_name(&self, guild_id: Id<GuildMarker>, user_id: Id<UserMarker>) -> Result<String, String> {
+        let member = self.guild_member(guild_id, user_id).await.map_err(|e| e.to_string())?
+            .model().await.map_err(|e| e.to_string())?;
+        Ok(display_name(member.nick.as_deref(), &member.user.name))
+    }
+}
+
+struct CachedName {
+    name: String,
+    fetched_at: Instant,
+    /// Recency stamp for LRU eviction
+    last_used: u64,
+}
+
+/// Cached names by guild and member, with the clock that stamps `last_used`
+type NameEntries = (HashMap<(Id<GuildMarker>, Id<UserMarker>), CachedName>, u64);
+
+/// Speaker display names shared by the recording and translation pipelines
+pub struct MemberNameCache {
+    fetcher: Arc<dyn MemberFetcher>,
+    capacity: usize,
+    ttl: Duration,
+    names: Mutex<NameEntries>,
+}
+
+impl MemberNameCache {
+    pub fn new(http: Arc<HttpClient>) -> Self {
+        Self::with_fetcher(http, DEFAULT_NAME_CAPACITY, DEFAULT_NAME_TTL)
+    }
+
+    pub fn with_fetcher(fetcher: Arc<dyn MemberFetcher>, capacity: usize, ttl: Duration) -> Self {
+        Self {
+            fetcher,
+            capacity: capacity.max(1),
+            ttl,
+            names: Mutex::new((HashMap::new(), 0)),
+        }
+    }
+
+    /// Fetch a member's display name once per guild and refresh it after the TTL.
+    /// Failed fetches keep the stale name if there is one, otherwise fall back to
+    /// `User {id}`, and are retried next time.
+    pub async fn resolve(&self, guild_id: Id<GuildMarker>, user_id: Id<UserMarker>) -> String {
+        let key = (guild_id, user_id);
+        let stale = {
+            let mut guard = self.names.lock().await;
+            let (names, clock) = &mut *guard;
+            *clock += 1;
+            match names.get_mut(&key) {
+                Some(cached) => {
+                    cached.last_used = *clock;
+                    if cached.fetched_at.elapsed() < self.ttl {
+                        return cached.name.clone();
+                    }
+                    Some(cached.name.clone())
+                }
+                None => None,
+            }
+        };
+
+        match self.fetcher.fetch_name(guild_id, user_id).await {
+            Ok(name) => {
+                let mut guard = self.names.lock().await;
+                let (names, clock) = &mut *guard;
+                *clock += 1;
+                names.insert(key, CachedName { name: name.clone(), fetched_at: Instant::now(), last_used: *clock });
+                if names.len() > self.capacity
+                    && let Some(oldest) = names.iter().min_by_key(|(_, cached)| cached.last_used).map(|(key, _)| *key)
+                {
+                    names.remove(&oldest);
+                }
+                name
+            }
+            Err(e) => {
+                eprintln!("[WARN] Couldn't fetch member {} in guild {}: {}", user_id, guild_id, e);
+                stale.unwrap_or_else(|| fallback_name(user_id))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Returns `name-{n}` for the n-th fetch, or fails
+    struct MockFetcher {
+        calls: AtomicUsize,
+        fail: bool,
+    }
+
+    #[async_trait::async_trait]
+    impl MemberFetcher for MockFetcher {
+        async fn fetch_name(&self, _guild_id: Id<GuildMarker>, _user_id: Id<UserMarker>) -> Result<String, String> {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+            if self.fail { Err("HTTP 500".to_string()) } else { Ok(format!("name-{}", call)) }
+        }
+    }
+
+    fn mock(fail: bool) -> Arc<MockFetcher> {
+        Arc::new(MockFetcher { calls: AtomicUsize::new(0), fail })
+    }
+
+    #[test]
+    fn test_display_name() {
+        assert_eq!(display_name(Some("Alice"), "alice_01"), "Alice");
+        assert_eq!(display_name(None, "alice_01"), "alice_01");
+        assert_eq!(display_name(Some(" "), "alice_01"), "alice_01");
+        assert_eq!(fallback_name(Id::new(42)), "User 42");
+    }
+
+    #[tokio::test]
+    async fn test_names_are_fetched_once_and_refreshed_after_ttl() {
+        let guild = Id::new(1);
+
+        let fetcher = mock(false);
+        let cache = MemberNameCache::with_fetcher(fetcher.clone(), 2, Duration::from_secs(60));
+        assert_eq!(cache.resolve(guild, Id::new(10)).await, "name-1");
+        assert_eq!(cache.resolve(guild, Id::new(10)).await, "name-1");
+        assert_eq!(fetcher.calls.load(Ordering::SeqCst), 1);
+
+        // Over capacity the least recently used name goes: 10 was used after 20
+        cache.resolve(guild, Id::new(20)).await;
+        cache.resolve(guild, Id::new(10)).await;
+        cache.resolve(guild, Id::new(30)).await;
+        assert_eq!(cache.resolve(guild, Id::new(10)).await, "name-1");
+        assert_eq!(cache.resolve(guild, Id::new(20)).await, "name-4");
+
+        // Expired names are fetched again
+        let fetcher = mock(false);
+        let cache = MemberNameCache::with_fetcher(fetcher.clone(), 10, Duration::ZERO);
+        assert_eq!(cache.resolve(guild, Id::new(10)).await, "name-1");
+        assert_eq!(cache.resolve(guild, Id::new(10)).await, "name-2");
+
+        // Failures fall back without being cached
+        let fetcher = mock(true);
+        let cache = MemberNameCache::with_fetcher(fetcher.clone(), 10, Duration::from_secs(60));
+        assert_eq!(cache.resolve(guild, Id::new(10)).await, "User 10");
+        assert_eq!(cache.resolve(guild, Id::new(10)).await, "User 10");
+        assert_eq!(fetcher.calls.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,222 @@
+//! Prometheus metrics for transcription, translation and recording, served as
+//! plain text exposition format on `/metrics`.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+/// Histogram buckets in seconds, from fast DeepL calls to long meeting transcriptions
+const DURATION_BUCKETS: &[f64] = &[0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 300.0];
+
+/// Stages of the real-time translation pipeline, as timed in `translate_utterance`
+pub const TRANSLATION_STAGES: &[&str] = &["convert", "transcribe", "translate", "total"];
+
+/// PCM sample rate of recorded audio
+const RECORDING_SAMPLE_RATE: f64 = crate::transcriber::DECODE_SAMPLE_RATE as f64;
+
+pub struct Histogram {
+    buckets: Vec<AtomicU64>,
+    count: AtomicU64,
+    sum_micros: AtomicU64,
+}
+
+impl Histogram {
+    fn new() -> Self {
+        Self {
+            buckets: DURATION_BUCKETS.iter().map(|_| AtomicU64::new(0)).collect(),
+            count: AtomicU64::new(0),
+            sum_micros: AtomicU64::new(0),
+        }
+    }
+
+    pub fn observe(&self, duration: Duration) {
+        let seconds = duration.as_secs_f64();
+        for (bucket, &bound) in self.buckets.iter().zip(DURATION_BUCKETS) {
+            if seconds <= bound {
+                bucket.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.sum_micros.fetch_add(duration.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    fn render(&self, out: &mut String, name: &str, labels: &str) {
+        let sep = if labels.is_empty() { "" } else { "," };
+        for (bucket, bound) in self.buckets.iter().zip(DURATION_BUCKETS) {
+            let _ = writeln!(out, "{}_bucket{{{}{}le=\"{}\"}} {}", name, labels, sep, bound, bucket.load(Ordering::Relaxed));
+        }
+        let count = self.count.load(Ordering::Relaxed);
+        let sum = self.sum_micros.load(Ordering::Relaxed) as f64 / 1_000_000.0;
+        let braces = |labels: &str| if labels.is_empty() { String::new() } else { format!("{{{}}}", labels) };
+        let _ = writeln!(out, "{}_bucket{{{}{}le=\"+Inf\"}} {}", name, labels, sep, count);
+        let _ = writeln!(out, "{}_sum{} {}", name, braces(labels), sum);
+        let _ = writeln!(out, "{}_count{} {}", name, braces(labels), count);
+    }
+}
+
+pub struct Metrics {
+    pub transcriptions: AtomicU64,
+    pub transcription_failures: AtomicU64,
+    pub transcription_duration: Histogram,
+    pub translations: AtomicU64,
+    deepl_errors: Mutex<BTreeMap<String, u64>>,
+    pub active_recordings: AtomicI64,
+    buffered_audio_samples: AtomicI64,
+    translation_stages: Vec<Histogram>,
+}
+
+pub static METRICS: LazyLock<Metrics> = LazyLock::new(|| Metrics {
+    transcriptions: AtomicU64::new(0),
+    transcription_failures: AtomicU64::new(0),
+    transcription_duration: Histogram::new(),
+    translations: AtomicU64::new(0),
+    deepl_errors: Mutex::new(BTreeMap::new()),
+    active_recordings: AtomicI64::new(0),
+    buffered_audio_samples: AtomicI64::new(0),
+    translation_stages: TRANSLATION_STAGES.iter().map(|_| Histogram::new()).collect(),
+});
+
+/// Count a finished transcription and how long it took
+pub fn record_transcription(duration: Duration, succeeded: bool) {
+    if succeeded {
+        METRICS.transcriptions.fetch_add(1, Ordering::Relaxed);
+    } else {
+        METRICS.transcription_failures.fetch_add(1, Ordering::Relaxed);
+    }
+    METRICS.transcription_duration.observe(duration);
+}
+
+pub fn record_translation() {
+    METRICS.translations.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Count a failed DeepL call by HTTP status, or "network" if no response came back
+pub fn record_deepl_error(status: &str) {
+    let mut errors = METRICS.deepl_errors.lock().unwrap_or_else(|e| e.into_inner());
+    *errors.entry(status.to_string()).or_insert(0) += 1;
+}
+
+/// Time spent in one stage of the translation pipeline
+pub fn record_translation_stage(stage: &str, duration: Duration) {
+    if let Some(index) = TRANSLATION_STAGES.iter().position(|s| *s == stage) {
+        METRICS.translation_stages[index].observe(duration);
+    }
+}
+
+pub fn set_active_recordings(count: usize) {
+    METRICS.active_recordings.store(count as i64, Ordering::Relaxed);
+}
+
+/// Adjust the amount of recorded PCM held in memory (48kHz samples)
+pub fn add_buffered_samples(samples: i64) {
+    METRICS.buffered_audio_samples.fetch_add(samples, Ordering::Relaxed);
+}
+
+/// Render all metrics in the Prometheus text exposition format
+pub fn render() -> String {
+    let m = &*METRICS;
+    let mut out = String::new();
+
+    let _ = writeln!(out, "# HELP diggy_transcriptions_total Transcriptions completed");
+    let _ = writeln!(out, "# TYPE diggy_transcriptions_total counter");
+    let _ = writeln!(out, "diggy_transcriptions_total{{result=\"ok\"}} {}", m.transcriptions.load(Ordering::Relaxed));
+    let _ = writeln!(out, "diggy_transcriptions_total{{result=\"error\"}} {}", m.transcription_failures.load(Ordering::Relaxed));
+
+    let _ = writeln!(out, "# HELP diggy_transcription_duration_seconds Time spent per transcription");
+    let _ = writeln!(out, "# TYPE diggy_transcription_duration_seconds histogram");
+    m.transcription_duration.render(&mut out, "diggy_transcription_duration_seconds", "");
+
+    let _ = writeln!(out, "# HELP diggy_translations_total Successful DeepL translations");
+    let _ = writeln!(out, "# TYPE diggy_translations_total counter");
+    let _ = writeln!(out, "diggy_translations_total {}", m.translations.load(Ordering::Relaxed));
+
+    let _ = writeln!(out, "# HELP diggy_deepl_errors_total Failed DeepL requests by status");
+    let _ = writeln!(out, "# TYPE diggy_deepl_errors_total counter");
+    for (status, count) in m.deepl_errors.lock().unwrap_or_else(|e| e.into_inner()).iter() {
+        let _ = writeln!(out, "diggy_deepl_errors_total{{status=\"{}\"}} {}", status, count);
+    }
+
+    let _ = writeln!(out, "# HELP diggy_translation_stage_seconds Real-time translation pipeline timings");
+    let _ = writeln!(out, "# TYPE diggy_translation_stage_seconds histogram");
+    for (stage, histogram) in TRANSLATION_STAGES.iter().zip(&m.translation_stages) {
+        histogram.render(&mut out, "diggy_translation_stage_seconds", &format!("stage=\"{}\"", stage));
+    }
+
+    let _ = writeln!(out, "# HELP diggy_active_recordings Recording sessions in progress");
+    let _ = writeln!(out, "# TYPE diggy_active_recordings gauge");
+    let _ = writeln!(out, "diggy_active_recordings {}", m.active_recordings.load(Ordering::Relaxed));
+
+    let _ = writeln!(out, "# HELP diggy_buffered_audio_seconds Recorded audio held in memory");
+    let _ = writeln!(out, "# TYPE diggy_buffered_audio_seconds gauge");
+    let samples = m.buffered_audio_samples.load(Ordering::Relaxed).max(0);
+    let _ = writeln!(out, "diggy_buffered_audio_seconds {}", samples as f64 / RECORDING_SAMPLE_RATE);
+
+    out
+}
+
+/// Serve `/metrics` and `/health` on `addr` until the process exits
+pub async fn serve(addr: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let listener = TcpListener::bind(addr).await?;
+    println!("[INFO] Metrics server listening on {}", addr);
+
+    loop {
+        let (mut stream, _) = match listener.accept().await {
+            Ok(conn) => conn,
+            Err(e) => {
+                eprintln!("[ERROR] Metrics server accept failed: {}", e);
+                continue;
+            }
+        };
+
+        tokio::spawn(async move {
+            let mut request = [0u8; 1024];
+            let Ok(read) = stream.read(&mut request).await else {
+                return;
+            };
+            let request = String::from_utf8_lossy(&request[..read]);
+            let path = request.split_whitespace().nth(1).unwrap_or("/");
+
+            let (status, body) = match path {
+                "/metrics" => ("200 OK", render()),
+                "/health" => ("200 OK", "ok\n".to_string()),
+                _ => ("404 Not Found", "not found\n".to_string()),
+            };
+            let response = format!(
+                "HTTP/1.1 {}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            );
+            let _ = stream.write_all(response.as_bytes()).await;
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram_rendering() {
+        let histogram = Histogram::new();
+        histogram.observe(Duration::from_millis(300));
+        histogram.observe(Duration::from_secs(20));
+
+        let mut out = String::new();
+        histogram.render(&mut out, "test_seconds", "stage=\"total\"");
+        assert!(out.contains("test_seconds_bucket{stage=\"total\",le=\"0.25\"} 0"));
+        assert!(out.contains("test_seconds_bucket{stage=\"total\",le=\"0.5\"} 1"));
+        assert!(out.contains("test_seconds_bucket{stage=\"total\",le=\"+Inf\"} 2"));
+        assert!(out.contains("test_seconds_count{stage=\"total\"} 2"));
+    }
+
+    #[test]
+    fn test_deepl_errors_are_labeled_by_status() {
+        record_deepl_error("503");
+        assert!(render().contains("diggy_deepl_errors_total{status=\"503\"}"));
+    }
+}
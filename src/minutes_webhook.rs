@@ -0,0 +1,194 @@
+use twilight_http::Client as HttpClient;
+use twilight_model::channel::message::embed::Embed;
+use twilight_model::id::Id;
+use twilight_model::id::marker::{ChannelMarker, GuildMarker, WebhookMarker};
+
+use crate::discord_retry::send_resilient;
+use crate::translator::chunk_text;
+
+/// Discord's limit on an embed description
+const MAX_EMBED_DESCRIPTION: usize = 4096;
+/// Discord's limit on a message's content
+const MAX_MESSAGE_CHARS: usize = 2000;
+const MINUTES_EMBED_COLOR: u32 = 0x3498db;
+
+/// Webhook ID and token from `https://discord.com/api/webhooks/{id}/{token}`
+pub fn parse_webhook_url(url: &str) -> Option<(Id<WebhookMarker>, String)> {
+    let (_, rest) = url.trim().split_once("/webhooks/")?;
+    let mut parts = rest.split(['/', '?']);
+    let id = parts.next()?.parse::<u64>().ok().and_then(Id::new_checked)?;
+    let token = parts.next().filter(|token| !token.is_empty())?;
+    Some((id, token.to_string()))
+}
+
+/// One embed per piece of the minutes; pieces after the first are numbered
+pub fn minutes_embeds(minutes: &str) -> Vec<Embed> {
+    let chunks = chunk_text(minutes, MAX_EMBED_DESCRIPTION);
+    let total = chunks.len();
+    chunks
+        .into_iter()
+        .enumerate()
+        .map(|(i, chunk)| Embed {
+            author: None,
+            color: Some(MINUTES_EMBED_COLOR),
+            description: Some(chunk),
+            fields: Vec::new(),
+            footer: None,
+            image: None,
+            kind: "rich".to_string(),
+            provider: None,
+            thumbnail: None,
+            timestamp: None,
+            title: Some(if i == 0 {
+                "📝 Meeting Minutes".to_string()
+            } else {
+                format!("📝 Meeting Minutes ({}/{})", i + 1, total)
+            }),
+            url: None,
+            video: None,
+        })
+        .collect()
+}
+
+/// Bot messages carrying the minutes from the `sent`-th embed on, for when the
+/// webhook stopped partway. Each fits in one message.
+pub fn fallback_messages(minutes: &str, sent: usize) -> Vec<String> {
+    let header = if sent == 0 {
+        "✅ **Meeting Minutes Generated**\n\n"
+    } else {
+        "✅ **Meeting Minutes (continued)**\n\n"
+    };
+    let unsent: Vec<String> = minutes_embeds(minutes)
+        .into_iter()
+        .skip(sent)
+        .filter_map(|embed| embed.description)
+        .collect();
+    let mut messages = chunk_text(&unsent.join("\n"), MAX_MESSAGE_CHARS - header.chars().count());
+    if let Some(first) = messages.first_mut() {
+        first.insert_str(0, header);
+    }
+    messages
+}
+
+/// The webhook stopped after posting `sent` embeds
+#[derive(Debug)]
+pub struct PostFailure {
+    pub sent: usize,
+    pub error: twilight_http::Error,
+}
+
+/// Posts minutes through a Discord webhook so they appear under a custom name and avatar
+#[derive(Debug, Clone, PartialEq)]
+pub struct MinutesWebhook {
+    id: Id<WebhookMarker>,
+    token: String,
+    username: Option<String>,
+    avatar_url: Option<String>,
+    /// Where the webhook posts, filled in by [`Self::resolve`]
+    target: Option<(Option<Id<GuildMarker>>, Id<ChannelMarker>)>,
+}
+
+impl MinutesWebhook {
+    /// None if `url` isn't a Discord webhook URL
+    pub fn from_url(url: &str) -> Option<Self> {
+        let (id, token) = parse_webhook_url(url)?;
+        Some(Self { id, token, username: None, avatar_url: None, target: None })
+    }
+
+    /// Look up the server and channel the webhook posts in
+    pub async fn resolve(mut self, http: &HttpClient) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let webhook = http.webhook(self.id).token(&self.token).await?.model().await?;
+        self.target = Some((webhook.guild_id, webhook.channel_id));
+        Ok(self)
+    }
+
+    /// Whether minutes bound for `channel_id` in `guild_id` may go through this webhook.
+    /// Only the webhook's own channel qualifies, so one server's minutes never land in
+    /// another's and a guild's minutes channel setting is respected.
+    pub fn posts_to(&self, guild_id: Id<GuildMarker>, channel_id: Id<ChannelMarker>) -> bool {
+        self.target == Some((Some(guild_id), channel_id))
+    }
+
+    /// Name and avatar shown on the posts; the webhook's own are used when unset
+    pub fn with_identity(mut self, username: Option<String>, avatar_url: Option<String>) -> Self {
+        let non_empty = |value: Option<String>| value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+        self.username = non_empty(username);
+        self.avatar_url = non_empty(avatar_url);
+        self
+    }
+
+    /// Execute the webhook once per embed, retrying transient errors and
+    /// stopping at the first failure
+    pub async fn post_minutes(&self, http: &HttpClient, minutes: &str) -> Result<(), PostFailure> {
+        for (sent, embed) in minutes_embeds(minutes).into_iter().enumerate() {
+            let embeds = [embed];
+            send_resilient("Posting minutes through the webhook", || async {
+                let mut request = http.execute_webhook(self.id, &self.token).embeds(&embeds);
+                if let Some(username) = &self.username {
+                    request = request.username(username);
+                }
+                if let Some(avatar_url) = &self.avatar_url {
+                    request = request.avatar_url(avatar_url);
+                }
+                request.await
+            })
+            .await
+            .map_err(|error| PostFailure { sent, error })?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_webhook_payload() {
+        let webhook = MinutesWebhook::from_url("https://discord.com/api/webhooks/123/abc-DEF_9?wait=true")
+            .unwrap()
+            .with_identity(Some("Minutes".to_string()), Some(" ".to_string()));
+        assert_eq!(webhook.id, Id::new(123));
+        assert_eq!(webhook.token, "abc-DEF_9");
+        assert_eq!(webhook.username.as_deref(), Some("Minutes"));
+        assert_eq!(webhook.avatar_url, None);
+        // Unresolved webhooks aren't used anywhere
+        assert!(!webhook.posts_to(Id::new(1), Id::new(2)));
+        let resolved = MinutesWebhook { target: Some((Some(Id::new(1)), Id::new(2))), ..webhook };
+        assert!(resolved.posts_to(Id::new(1), Id::new(2)));
+        assert!(!resolved.posts_to(Id::new(1), Id::new(3)));
+        assert!(!resolved.posts_to(Id::new(9), Id::new(2)));
+        assert!(MinutesWebhook::from_url("https://discord.com/api/webhooks/123").is_none());
+        assert!(MinutesWebhook::from_url("https://example.com/hooks/minutes").is_none());
+
+        let short = minutes_embeds("## Decisions\n- Ship on Friday");
+        assert_eq!(short.len(), 1);
+        assert_eq!(short[0].title.as_deref(), Some("📝 Meeting Minutes"));
+        assert_eq!(short[0].description.as_deref(), Some("## Decisions\n- Ship on Friday"));
+
+        let long = minutes_embeds(&"We agreed to ship. ".repeat(400));
+        assert_eq!(long.len(), 2);
+        assert!(long.iter().all(|embed| embed.description.as_ref().unwrap().chars().count() <= MAX_EMBED_DESCRIPTION));
+        assert_eq!(long[1].title.as_deref(), Some("📝 Meeting Minutes (2/2)"));
+    }
+
+    #[test]
+    fn test_fallback_covers_only_unsent_embeds() {
+        let minutes = "We agreed to ship. ".repeat(400);
+        let embeds = minutes_embeds(&minutes);
+
+        let all = fallback_messages(&minutes, 0);
+        assert!(all.len() >= 4);
+        assert!(all[0].starts_with("✅ **Meeting Minutes Generated**"));
+        assert!(all.iter().all(|message| message.chars().count() <= MAX_MESSAGE_CHARS));
+
+        // After the first embed went out, only the second one's text is resent
+        let rest = fallback_messages(&minutes, 1);
+        assert!(rest[0].starts_with("✅ **Meeting Minutes (continued)**"));
+        assert!(rest.iter().all(|message| message.chars().count() <= MAX_MESSAGE_CHARS));
+        let resent: usize = rest.iter().map(|message| message.matches("ship").count()).sum();
+        assert_eq!(resent, embeds[1].description.as_ref().unwrap().matches("ship").count());
+
+        assert!(fallback_messages(&minutes, embeds.len()).is_empty());
+    }
+}
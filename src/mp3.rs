@@ -0,0 +1,49 @@
+use std::io::Write;
+use mp3lame_encoder::{Bitrate, Builder, FlushNoGap, MonoPcm, Quality};
+use crate::error::AppError;
+
+/// Plenty for speech, and keeps an hour-long speaker under 30 MB
+const MP3_BITRATE: Bitrate = Bitrate::Kbps64;
+/// Samples handed to LAME at a time: one second
+const ENCODE_CHUNK_SAMPLES: usize = crate::transcriber::DECODE_SAMPLE_RATE as usize;
+
+/// Encode decoded mono PCM as a constant-bitrate MP3. Like
+/// [`crate::ogg_opus::encode_ogg_opus`], samples are pulled a chunk at a time and
+/// the first error reading them stops the encode.
+pub fn encode_mp3<W: Write>(
+    mut writer: W,
+    samples: impl IntoIterator<Item = Result<i16, AppError>>,
+) -> Result<(), AppError> {
+    let mut builder = Builder::new().ok_or_else(|| AppError::Encoding("failed to create the LAME encoder".to_string()))?;
+    builder.set_num_channels(1).map_err(lame_error)?;
+    builder.set_sample_rate(crate::transcriber::DECODE_SAMPLE_RATE).map_err(lame_error)?;
+    builder.set_brate(MP3_BITRATE).map_err(lame_error)?;
+    builder.set_quality(Quality::Good).map_err(lame_error)?;
+    let mut encoder = builder.build().map_err(lame_error)?;
+
+    let mut samples = samples.into_iter();
+    let mut chunk = Vec::with_capacity(ENCODE_CHUNK_SAMPLES);
+    let mut output = Vec::new();
+    loop {
+        chunk.clear();
+        for sample in samples.by_ref().take(ENCODE_CHUNK_SAMPLES) {
+            chunk.push(sample?);
+        }
+        if chunk.is_empty() {
+            break;
+        }
+        output.clear();
+        encoder.encode_to_vec(MonoPcm(&chunk), &mut output).map_err(lame_error)?;
+        writer.write_all(&output)?;
+    }
+
+    output.clear();
+    encoder.flush_to_vec::<FlushNoGap>(&mut output).map_err(lame_error)?;
+    writer.write_all(&output)?;
+    Ok(())
+}
+
+/// LAME's errors only implement `Debug`
+fn lame_error(e: impl std::fmt::Debug) -> AppError {
+    AppError::Encoding(format!("{:?}", e))
+}
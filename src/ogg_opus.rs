@@ -0,0 +1,170 @@
+use std::io::Write;
+use audiopus::coder::Encoder;
+use audiopus::{Application, Channels, SampleRate};
+use ogg::writing::{PacketWriteEndInfo, PacketWriter};
+use crate::error::AppError;
+
+/// Samples per Discord voice frame (20ms at 48kHz)
+pub const OPUS_FRAME_SAMPLES: u64 = 960;
+
+/// Audio packets per Ogg page (~1 second of audio)
+const PACKETS_PER_PAGE: usize = 50;
+
+/// Discord sends stereo Opus at 48kHz
+const DISCORD_CHANNELS: u8 = 2;
+const OPUS_SAMPLE_RATE: u32 = 48000;
+
+/// Largest packet libopus recommends allocating for
+const MAX_PACKET_BYTES: usize = 4000;
+
+/// `OpusHead` identification header (RFC 7845 §5.1)
+fn opus_head(channels: u8) -> Vec<u8> {
+    let mut head = Vec::with_capacity(19);
+    head.extend_from_slice(b"OpusHead");
+    head.push(1); // version
+    head.push(channels);
+    head.extend_from_slice(&0u16.to_le_bytes()); // pre-skip
+    head.extend_from_slice(&OPUS_SAMPLE_RATE.to_le_bytes());
+    head.extend_from_slice(&0i16.to_le_bytes()); // output gain
+    head.push(0); // channel mapping family
+    head
+}
+
+/// `OpusTags` comment header with no user comments (RFC 7845 §5.2)
+fn opus_tags() -> Vec<u8> {
+    let vendor = concat!("diggy-gizzy ", env!("CARGO_PKG_VERSION"));
+    let mut tags = Vec::with_capacity(16 + vendor.len());
+    tags.extend_from_slice(b"OpusTags");
+    tags.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+    tags.extend_from_slice(vendor.as_bytes());
+    tags.extend_from_slice(&0u32.to_le_bytes());
+    tags
+}
+
+/// Write received Opus packets as a single Ogg Opus stream without re-encoding.
+/// Every packet is assumed to be one 20ms frame, as sent by Discord clients.
+pub fn write_ogg_opus<W: Write>(
+    writer: W,
+    serial: u32,
+    packets: &[Vec<u8>],
+) -> std::io::Result<()> {
+    write_ogg_stream(writer, serial, DISCORD_CHANNELS, packets.iter().map(Vec::as_slice))
+}
+
+/// Encode 48kHz mono PCM as Ogg Opus, one 20ms frame per packet (the last one zero-padded).
+/// Samples are pulled as the stream is written, so a recording read from disk is never
+/// held in memory whole; the first error reading them stops the encode.
+pub fn encode_ogg_opus<W: Write>(
+    writer: W,
+    serial: u32,
+    samples: impl IntoIterator<Item = Result<i16, AppError>>,
+) -> Result<(), AppError> {
+    let encoder = Encoder::new(SampleRate::Hz48000, Channels::Mono, Application::Voip)?;
+    let mut output = [0u8; MAX_PACKET_BYTES];
+    let mut frame = [0i16; OPUS_FRAME_SAMPLES as usize];
+    let mut samples = samples.into_iter();
+    let mut failure: Option<AppError> = None;
+
+    let packets = std::iter::from_fn(|| {
+        let mut len = 0;
+        while len < frame.len() {
+            match samples.next() {
+                Some(Ok(sample)) => {
+                    frame[len] = sample;
+                    len += 1;
+                }
+                Some(Err(e)) => {
+                    failure = Some(e);
+                    return None;
+                }
+                None if len == 0 => return None,
+                None => break,
+            }
+        }
+        frame[len..].fill(0);
+        match encoder.encode(&frame, &mut output) {
+            Ok(written) => Some(output[..written].to_vec()),
+            Err(e) => {
+                failure = Some(e.into());
+                None
+            }
+        }
+    });
+    write_ogg_stream(writer, serial, 1, packets)?;
+
+    failure.map_or(Ok(()), Err)
+}
+
+fn write_ogg_stream<W: Write, P: Into<Box<[u8]>>>(
+    writer: W,
+    serial: u32,
+    channels: u8,
+    packets: impl IntoIterator<Item = P>,
+) -> std::io::Result<()> {
+    let mut writer = PacketWriter::new(writer);
+    let mut packets = packets.into_iter().peekable();
+
+    // Header packets must each end their own page
+    writer.write_packet(opus_head(channels).into_boxed_slice(), serial, PacketWriteEndInfo::EndPage, 0)?;
+    let tags_end = if packets.peek().is_none() {
+        PacketWriteEndInfo::EndStream
+    } else {
+        PacketWriteEndInfo::EndPage
+    };
+    writer.write_packet(opus_tags().into_boxed_slice(), serial, tags_end, 0)?;
+
+    let mut written = 0u64;
+    while let Some(packet) = packets.next() {
+        written += 1;
+        let end = if packets.peek().is_none() {
+            PacketWriteEndInfo::EndStream
+        } else if written.is_multiple_of(PACKETS_PER_PAGE as u64) {
+            PacketWriteEndInfo::EndPage
+        } else {
+            PacketWriteEndInfo::NormalPacket
+        };
+        writer.write_packet(packet.into(), serial, end, written * OPUS_FRAME_SAMPLES)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ogg::reading::PacketReader;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_ogg_page_framing() {
+        let packets: Vec<Vec<u8>> = (0..3u8).map(|i| vec![0xfc, i, i, i]).collect();
+        let mut buffer = Vec::new();
+        write_ogg_opus(&mut buffer, 7, &packets).unwrap();
+
+        assert_eq!(&buffer[..4], b"OggS");
+
+        let mut reader = PacketReader::new(Cursor::new(buffer));
+
+        let head = reader.read_packet().unwrap().unwrap();
+        assert!(head.first_in_stream());
+        assert!(head.last_in_page());
+        assert_eq!(&head.data[..8], b"OpusHead");
+        assert_eq!(head.data.len(), 19);
+        assert_eq!(head.stream_serial(), 7);
+
+        let tags = reader.read_packet().unwrap().unwrap();
+        assert!(tags.last_in_page());
+        assert_eq!(&tags.data[..8], b"OpusTags");
+
+        for (i, expected) in packets.iter().enumerate() {
+            let packet = reader.read_packet().unwrap().unwrap();
+            assert_eq!(&packet.data, expected);
+            if i + 1 == packets.len() {
+                assert!(packet.last_in_stream());
+                assert_eq!(packet.absgp_page(), 3 * OPUS_FRAME_SAMPLES);
+            }
+        }
+
+        assert!(reader.read_packet().unwrap().is_none());
+    }
+}
@@ -0,0 +1,167 @@
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use serde::de::DeserializeOwned;
+use tokio::sync::{Mutex, Notify};
+
+/// How long the background saver waits after a change before writing,
+/// so a burst of changes becomes one write
+pub const SAVE_DEBOUNCE: Duration = Duration::from_secs(2);
+
+/// Sibling path with `suffix` appended to the file name, e.g. `settings.json.bak`
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+/// Replace `path` with `contents` so a crash leaves either the old or the new file,
+/// never a partial one: write a temp file in the same directory, sync it, then rename.
+/// Each write gets its own temp file, so concurrent writers can't interleave in one.
+pub async fn write_atomic(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let tmp_path = with_suffix(path, &format!(".{}.tmp", uuid::Uuid::new_v4()));
+    let result = async {
+        let mut file = tokio::fs::File::create(&tmp_path).await?;
+        tokio::io::AsyncWriteExt::write_all(&mut file, contents).await?;
+        file.sync_all().await?;
+        drop(file);
+        tokio::fs::rename(&tmp_path, path).await
+    }.await;
+
+    if result.is_err() {
+        let _ = tokio::fs::remove_file(&tmp_path).await;
+    }
+    result
+}
+
+/// Load a JSON file, or the default when it doesn't exist. A file that can't be read
+/// or parsed is moved aside to `.bak` and reported, so the next save can't destroy it.
+pub fn load_json_or_default<T: DeserializeOwned + Default>(path: &Path) -> T {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return T::default(),
+        Err(e) => {
+            eprintln!("[ERROR] Failed to read {}: {}", path.display(), e);
+            preserve_as_backup(path);
+            return T::default();
+        }
+    };
+
+    match serde_json::from_str(&content) {
+        Ok(value) => value,
+        Err(e) => {
+            eprintln!("[ERROR] {} is not valid JSON ({}); starting with empty settings", path.display(), e);
+            preserve_as_backup(path);
+            T::default()
+        }
+    }
+}
+
+fn preserve_as_backup(path: &Path) {
+    let backup = with_suffix(path, ".bak");
+    match std::fs::rename(path, &backup) {
+        Ok(()) => eprintln!("[ERROR] Moved the unreadable file to {}", backup.display()),
+        Err(e) => eprintln!("[ERROR] Failed to back up {} to {}: {}", path.display(), backup.display(), e),
+    }
+}
+
+/// Unsaved-change tracking for a settings file. Changes bump a revision and wake
+/// the background saver; a save whose snapshot is already on disk is skipped.
+#[derive(Default)]
+pub struct DebouncedSave {
+    /// Bumped on every change
+    revision: AtomicU64,
+    /// Revision last written to disk
+    saved_revision: AtomicU64,
+    /// Held while writing so saves don't overlap
+    writing: Mutex<()>,
+    dirty: Notify,
+    /// Files actually written
+    writes: AtomicU64,
+}
+
+impl DebouncedSave {
+    pub fn mark_dirty(&self) {
+        self.revision.fetch_add(1, Ordering::Release);
+        self.dirty.notify_one();
+    }
+
+    /// Write the JSON `snapshot` produces unless nothing changed since the last write.
+    /// Returns whether the file was written.
+    pub async fn save<F, Fut>(&self, path: &Path, snapshot: F) -> std::io::Result<bool>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = serde_json::Result<String>>,
+    {
+        let _writing = self.writing.lock().await;
+        // Read before the snapshot: a change racing in is written now or on the next save, never lost
+        let revision = self.revision.load(Ordering::Acquire);
+        if revision <= self.saved_revision.load(Ordering::Acquire) {
+            return Ok(false);
+        }
+        let json = snapshot().await.map_err(std::io::Error::other)?;
+        write_atomic(path, json.as_bytes()).await?;
+        self.saved_revision.store(revision, Ordering::Release);
+        self.writes.fetch_add(1, Ordering::Relaxed);
+        Ok(true)
+    }
+
+    /// Call `save` after each change, waiting `interval` first; changes that
+    /// arrive meanwhile are covered by the same save
+    pub async fn run<F, Fut>(&self, interval: Duration, mut save: F)
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        loop {
+            self.dirty.notified().await;
+            tokio::time::sleep(interval).await;
+            save().await;
+        }
+    }
+
+    /// Doesn't wait on a save in progress, so it's safe to call while holding the
+    /// lock a save's snapshot reads through
+    pub fn has_unsaved_changes(&self) -> bool {
+        self.revision.load(Ordering::Acquire) > self.saved_revision.load(Ordering::Acquire)
+    }
+
+    #[cfg(test)]
+    pub fn writes(&self) -> u64 {
+        self.writes.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[tokio::test]
+    async fn test_corrupt_file_is_kept_as_backup() {
+        let dir = std::env::temp_dir().join(format!("persist_test_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("settings.json");
+        std::fs::write(&path, r#"{"1": {"source_lang": "ja""#).unwrap();
+
+        let loaded: HashMap<String, String> = load_json_or_default(&path);
+        assert!(loaded.is_empty());
+        let backup = dir.join("settings.json.bak");
+        assert_eq!(std::fs::read_to_string(&backup).unwrap(), r#"{"1": {"source_lang": "ja""#);
+
+        // Saving afterwards writes a fresh file and leaves the backup alone
+        write_atomic(&path, b"{}").await.unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "{}");
+        assert!(backup.exists());
+        let mut left: Vec<String> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        left.sort();
+        assert_eq!(left, ["settings.json", "settings.json.bak"]);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,67 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+/// Audio kept per SSRC while its speaker is unknown, unless `PREROLL_MS` is set
+pub const DEFAULT_PREROLL: Duration = Duration::from_millis(500);
+/// Songbird hands over one frame per speaking SSRC every 20 ms
+const FRAME_DURATION: Duration = Duration::from_millis(20);
+
+/// The last few frames from SSRCs whose user mapping hasn't arrived yet.
+/// Discord often sends the first packets of an utterance before the
+/// SpeakingStateUpdate that says who is talking; without this they're lost.
+pub struct Preroll<T> {
+    max_frames: usize,
+    frames: HashMap<u32, VecDeque<T>>,
+}
+
+impl<T> Preroll<T> {
+    /// A zero window disables buffering
+    pub fn new(window: Duration) -> Self {
+        Self {
+            max_frames: (window.as_millis() / FRAME_DURATION.as_millis()) as usize,
+            frames: HashMap::new(),
+        }
+    }
+
+    /// Keep a frame from an unmapped SSRC, dropping its oldest beyond the window.
+    /// Returns true for the first frame buffered for the SSRC.
+    pub fn push(&mut self, ssrc: u32, frame: T) -> bool {
+        if self.max_frames == 0 {
+            return false;
+        }
+        let frames = self.frames.entry(ssrc).or_default();
+        if frames.len() == self.max_frames {
+            frames.pop_front();
+        }
+        frames.push_back(frame);
+        frames.len() == 1
+    }
+
+    /// Everything buffered for the SSRC, oldest first
+    pub fn take(&mut self, ssrc: u32) -> Vec<T> {
+        self.frames.remove(&ssrc).map(Vec::from).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preroll_keeps_the_latest_frames() {
+        let mut preroll = Preroll::new(Duration::from_millis(60));
+        assert!(preroll.push(7, 1));
+        for frame in 2..=5 {
+            assert!(!preroll.push(7, frame));
+        }
+        preroll.push(8, 10);
+
+        assert_eq!(preroll.take(7), vec![3, 4, 5]);
+        assert!(preroll.take(7).is_empty());
+        assert_eq!(preroll.take(8), vec![10]);
+
+        let mut disabled = Preroll::new(Duration::ZERO);
+        assert!(!disabled.push(7, 1));
+        assert!(disabled.take(7).is_empty());
+    }
+}
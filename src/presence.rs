@@ -0,0 +1,97 @@
+use std::time::Duration;
+use tokio::sync::watch;
+use twilight_gateway::MessageSender;
+use twilight_model::gateway::OpCode;
+use twilight_model::gateway::payload::outgoing::UpdatePresence;
+use twilight_model::gateway::payload::outgoing::update_presence::UpdatePresencePayload;
+use twilight_model::gateway::presence::{Activity, ActivityType, Status};
+
+/// Wait for the session count to settle before touching the presence,
+/// so quick start/stop sequences don't hit the gateway rate limit
+pub const PRESENCE_DEBOUNCE: Duration = Duration::from_secs(5);
+
+/// Custom status shown while recordings are active, or None when idle
+pub fn recording_presence(active_sessions: usize) -> Option<String> {
+    match active_sessions {
+        0 => None,
+        1 => Some("🔴 Recording in 1 channel".to_string()),
+        n => Some(format!("🔴 Recording in {} channels", n)),
+    }
+}
+
+fn presence_update(active_sessions: usize) -> UpdatePresence {
+    let activities = recording_presence(active_sessions)
+        .map(|text| Activity {
+            application_id: None,
+            assets: None,
+            buttons: Vec::new(),
+            created_at: None,
+            details: None,
+            emoji: None,
+            flags: None,
+            id: None,
+            instance: None,
+            kind: ActivityType::Custom,
+            name: "Custom Status".to_string(),
+            party: None,
+            secrets: None,
+            state: Some(text),
+            timestamps: None,
+            url: None,
+        })
+        .into_iter()
+        .collect();
+
+    // Built directly because `UpdatePresence::new` rejects an empty activity list,
+    // which is exactly what clears the status
+    UpdatePresence {
+        d: UpdatePresencePayload {
+            activities,
+            afk: false,
+            since: None,
+            status: Status::Online,
+        },
+        op: OpCode::PresenceUpdate,
+    }
+}
+
+/// Keep the bot's presence in sync with the number of active recordings
+pub async fn run_presence_updates(sender: MessageSender, mut session_count: watch::Receiver<usize>) {
+    let mut shown = 0;
+
+    while session_count.changed().await.is_ok() {
+        // Debounce: restart the wait whenever the count changes again
+        loop {
+            match tokio::time::timeout(PRESENCE_DEBOUNCE, session_count.changed()).await {
+                Ok(Ok(())) => continue,
+                Ok(Err(_)) => return,
+                Err(_) => break,
+            }
+        }
+
+        let active = *session_count.borrow_and_update();
+        if active == shown {
+            continue;
+        }
+        if let Err(e) = sender.command(&presence_update(active)) {
+            eprintln!("[ERROR] Failed to update presence: {}", e);
+            continue;
+        }
+        shown = active;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recording_presence_text() {
+        assert_eq!(recording_presence(0), None);
+        assert_eq!(recording_presence(1).as_deref(), Some("🔴 Recording in 1 channel"));
+        assert_eq!(recording_presence(3).as_deref(), Some("🔴 Recording in 3 channels"));
+
+        assert!(presence_update(0).d.activities.is_empty());
+        assert_eq!(presence_update(2).d.activities[0].state.as_deref(), Some("🔴 Recording in 2 channels"));
+    }
+}
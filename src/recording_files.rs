@@ -0,0 +1,164 @@
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use twilight_model::id::Id;
+use twilight_model::id::marker::GuildMarker;
+
+use crate::stats::format_duration;
+use crate::voice_recorder::parse_recording_filename;
+
+/// Most files listed by `/recordings list`; the totals still cover everything
+pub const MAX_LISTED_RECORDINGS: usize = 20;
+
+/// A file in a guild's recording directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordingFile {
+    pub name: String,
+    pub size: u64,
+    pub modified: SystemTime,
+}
+
+/// The directory `/recordings` may read and clear: the guild's configured
+/// `recording_dir`, or the default. A configured path that climbs out with `..` is refused.
+pub fn recordings_dir(configured: Option<&str>, default: String) -> Result<PathBuf, String> {
+    let Some(configured) = configured else {
+        return Ok(PathBuf::from(default));
+    };
+    let path = PathBuf::from(configured);
+    if path.components().any(|component| component == Component::ParentDir) {
+        return Err(format!("The configured recording directory `{}` contains `..`", configured));
+    }
+    Ok(path)
+}
+
+/// Regular files directly inside `dir`, newest first. Subdirectories and
+/// symlinks are skipped so nothing outside the directory is reported or touched.
+/// A missing directory has no recordings.
+pub fn list_recordings(dir: &Path) -> io::Result<Vec<RecordingFile>> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+
+    let mut files = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        let metadata = entry.path().symlink_metadata()?;
+        if !metadata.file_type().is_file() {
+            continue;
+        }
+        files.push(RecordingFile {
+            name: entry.file_name().to_string_lossy().into_owned(),
+            size: metadata.len(),
+            modified: metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+        });
+    }
+    files.sort_by(|a, b| b.modified.cmp(&a.modified).then_with(|| a.name.cmp(&b.name)));
+    Ok(files)
+}
+
+pub fn total_size(files: &[RecordingFile]) -> u64 {
+    files.iter().map(|file| file.size).sum()
+}
+
+/// Whether `name` is one of the guild's speaker recordings or mixdowns
+pub fn is_guild_recording(name: &str, guild_id: Id<GuildMarker>) -> bool {
+    parse_recording_filename(name).is_some_and(|parsed| parsed.guild_id == guild_id)
+        || name.starts_with(&format!("{}_mixed_", guild_id))
+}
+
+/// Delete the guild's recordings among the files `list_recordings` reports,
+/// leaving anything else in the directory alone; returns how many and how many bytes
+pub fn clear_recordings(dir: &Path, guild_id: Id<GuildMarker>) -> io::Result<(usize, u64)> {
+    let mut removed = (0, 0);
+    for file in list_recordings(dir)?.into_iter().filter(|file| is_guild_recording(&file.name, guild_id)) {
+        let path = dir.join(&file.name);
+        match std::fs::remove_file(&path) {
+            Ok(()) => {
+                removed.0 += 1;
+                removed.1 += file.size;
+            }
+            Err(e) => eprintln!("[WARN] Failed to remove recording {}: {}", path.display(), e),
+        }
+    }
+    Ok(removed)
+}
+
+/// Size like `1.5 MB`
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+/// `/recordings list` message body
+pub fn format_listing(files: &[RecordingFile], now: SystemTime) -> String {
+    if files.is_empty() {
+        return "📂 No recordings on disk for this server".to_string();
+    }
+
+    let mut lines = vec![format!(
+        "📂 **Recordings on disk**: {} files, {}",
+        files.len(),
+        format_size(total_size(files))
+    )];
+    for file in files.iter().take(MAX_LISTED_RECORDINGS) {
+        let age = now.duration_since(file.modified).unwrap_or(Duration::ZERO);
+        lines.push(format!("`{}` — {}, {} ago", file.name, format_size(file.size), format_duration(age)));
+    }
+    if files.len() > MAX_LISTED_RECORDINGS {
+        lines.push(format!("…and {} more", files.len() - MAX_LISTED_RECORDINGS));
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_listing_sums_sizes_and_skips_subdirectories() {
+        let dir = std::env::temp_dir().join(format!("recording_files_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("nested")).unwrap();
+        std::fs::write(dir.join("7_42_20250101_120000_0.wav"), vec![0u8; 1000]).unwrap();
+        std::fs::write(dir.join("7_mixed_20250101_120000_0.wav"), vec![0u8; 2048]).unwrap();
+        std::fs::write(dir.join("nested").join("7_43_20250101_120000_0.wav"), vec![0u8; 500]).unwrap();
+
+        let files = list_recordings(&dir).unwrap();
+        let mut names: Vec<&str> = files.iter().map(|file| file.name.as_str()).collect();
+        names.sort();
+        assert_eq!(names, vec!["7_42_20250101_120000_0.wav", "7_mixed_20250101_120000_0.wav"]);
+        assert_eq!(total_size(&files), 3048);
+
+        let listing = format_listing(&files, SystemTime::now());
+        assert!(listing.starts_with("📂 **Recordings on disk**: 2 files, 3.0 KB"));
+        assert!(listing.contains("`7_mixed_20250101_120000_0.wav` — 2.0 KB, 0s ago"));
+
+        // Another guild's recordings and unrelated files sharing the directory are kept
+        std::fs::write(dir.join("8_42_20250101_120000_0.wav"), vec![0u8; 10]).unwrap();
+        std::fs::write(dir.join("notes.txt"), vec![0u8; 10]).unwrap();
+        assert_eq!(clear_recordings(&dir, Id::new(7)).unwrap(), (2, 3048));
+        let mut left: Vec<String> = list_recordings(&dir).unwrap().into_iter().map(|file| file.name).collect();
+        left.sort();
+        assert_eq!(left, vec!["8_42_20250101_120000_0.wav", "notes.txt"]);
+        // Only the directory itself is cleared
+        assert!(dir.join("nested").join("7_43_20250101_120000_0.wav").exists());
+
+        assert!(list_recordings(&dir.join("missing")).unwrap().is_empty());
+        assert_eq!(recordings_dir(None, "./recordings/1".to_string()).unwrap(), PathBuf::from("./recordings/1"));
+        assert_eq!(recordings_dir(Some("/srv/rec"), String::new()).unwrap(), PathBuf::from("/srv/rec"));
+        assert!(recordings_dir(Some("/srv/rec/../../etc"), String::new()).is_err());
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(3 * 1024 * 1024 / 2), "1.5 MB");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
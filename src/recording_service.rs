@@ -0,0 +1,1117 @@
+use std::collections::{HashMap, HashSet};
+use std::num::NonZeroU64;
+use std::path::PathBuf;
+use std::sync::Arc;
+use chrono::Local;
+use songbird::{Call, Songbird};
+use songbird::events::{Event as SongbirdEvent, CoreEvent};
+use tokio::sync::{watch, Mutex};
+use tokio_util::sync::CancellationToken;
+use twilight_http::Client as HttpClient;
+use twilight_model::http::attachment::Attachment;
+use twilight_model::id::Id;
+use twilight_model::id::marker::{ChannelMarker, GuildMarker, MessageMarker, UserMarker};
+
+use crate::discord_retry::{send_message_resilient, send_resilient};
+use crate::error::AppError;
+use crate::guild_config::{GuildConfigManager, OutputKind};
+use crate::member_names::MemberNameCache;
+use crate::minutes_webhook::{self, MinutesWebhook};
+use crate::redaction;
+use crate::summarizer::{self, ActionItem, Summarizer};
+use crate::transcriber::{self, FileTranscript, TranscriberRegistry, transcribe_wav_file, transcribe_files_concurrently, TranscriptionTasks, DEFAULT_MAX_CONSECUTIVE_ERRORS, MAX_CONCURRENT_TRANSCRIPTIONS};
+use crate::session_export::RetainedSessions;
+use crate::stats::BotStats;
+use crate::transcript_hooks::{self, TranscriptContext, TranscriptHook};
+use crate::transcript_store::{LastTranscripts, StoredTranscript};
+use crate::timezone;
+use crate::user_settings;
+use crate::voice_recorder::{file_to_timeline, parse_recording_filename, OutputFormat, RecordingManager, RecordingMode, RecordingSession, VoiceReceiveHandler};
+
+/// What came out of a finished recording
+#[derive(Debug, Clone, PartialEq)]
+pub enum MeetingOutcome {
+    /// Nobody spoke while recording
+    NoAudio,
+    /// Opus passthrough mode: files were saved without transcription
+    OpusArchived(Vec<String>),
+    /// Audio was recorded but nothing could be transcribed
+    NothingTranscribed,
+    Minutes { transcript: String, minutes: String, action_items: Vec<ActionItem> },
+    SummaryFailed { transcript: String, error: String },
+    /// Too many speakers failed in a row, so the rest were not attempted
+    TranscriptionFailing { failures: usize, error: String },
+    /// A newer recording started or the recording was aborted while this one was
+    /// processing; its results are dropped without a word
+    Cancelled,
+}
+
+impl MeetingOutcome {
+    /// Messages to post in the meeting's text channel, in order
+    pub fn messages(&self) -> Vec<String> {
+        match self {
+            MeetingOutcome::NoAudio => vec!["❌ No audio data recorded".to_string()],
+            MeetingOutcome::OpusArchived(files) => vec![format!(
+                "💾 **Recording saved** ({} Opus files, transcription skipped)\n{}",
+                files.len(),
+                files.iter().map(|f| format!("`{}`", f)).collect::<Vec<_>>().join("\n")
+            )],
+            MeetingOutcome::NothingTranscribed => vec![
+                "⚠️ **No audio detected** or transcription failed. Meeting minutes cannot be generated.".to_string(),
+            ],
+            MeetingOutcome::Minutes { transcript, minutes, .. } => vec![
+                format!(
+                    "📝 **Full Transcription**\n```\n{}\n```",
+                    transcript.chars().take(1950).collect::<String>()
+                ),
+                format!("✅ **Meeting Minutes Generated**\n\n{}", minutes),
+            ],
+            MeetingOutcome::SummaryFailed { transcript, error } => vec![format!(
+                "⚠️ **Transcription completed but summarization failed**\n\n**Raw Transcription:**\n```\n{}\n```\n\nError: {}",
+                transcript.chars().take(1900).collect::<String>(),
+                error
+            )],
+            MeetingOutcome::TranscriptionFailing { failures, error } => vec![format!(
+                "🛑 **Transcription subsystem is failing** — {} speakers in a row could not be transcribed, so the rest were skipped and no minutes were generated.\n\nLast error: {}",
+                failures, error
+            )],
+            MeetingOutcome::Cancelled => Vec::new(),
+        }
+    }
+
+    /// The transcript and minutes (if summarization worked), when anything was transcribed
+    pub fn transcript_and_minutes(&self) -> Option<(&str, Option<&str>)> {
+        match self {
+            MeetingOutcome::Minutes { transcript, minutes, .. } => Some((transcript, Some(minutes))),
+            MeetingOutcome::SummaryFailed { transcript, .. } => Some((transcript, None)),
+            _ => None,
+        }
+    }
+
+    /// Extracted action items, empty unless minutes were generated
+    pub fn action_items(&self) -> &[ActionItem] {
+        match self {
+            MeetingOutcome::Minutes { action_items, .. } => action_items,
+            _ => &[],
+        }
+    }
+}
+
+/// Run `task` unless `cancel` fires first; None means it was cancelled
+pub async fn unless_cancelled<T>(cancel: &CancellationToken, task: impl Future<Output = T>) -> Option<T> {
+    tokio::select! {
+        _ = cancel.cancelled() => None,
+        result = task => Some(result),
+    }
+}
+
+/// `Alice (detected: English)`, shown above a speaker whose language was auto-detected
+pub fn detected_language_header(speaker_name: &str, detected: &str) -> String {
+    let language = user_settings::language_name(detected)
+        .map(str::to_string)
+        .unwrap_or_else(|| detected.to_uppercase());
+    format!("__{} (detected: {})__", speaker_name, language)
+}
+
+/// Join each speaker's labeled lines into the full meeting transcript
+pub fn assemble_transcript(speakers: &[(String, FileTranscript)]) -> String {
+    let mut full_transcript = String::new();
+    for (speaker_name, transcript) in speakers {
+        // One speaker-labeled line per segment, low-confidence ones flagged
+        let labeled_text = transcriber::format_labeled_transcript(speaker_name, &transcript.segments);
+        if labeled_text.is_empty() {
+            continue;
+        }
+        if let Some(detected) = &transcript.detected_language {
+            full_transcript.push_str(&format!("{}\n", detected_language_header(speaker_name, detected)));
+        }
+        full_transcript.push_str(&format!("{}\n\n", labeled_text));
+    }
+    full_transcript
+}
+
+/// The minutes, action items and speaker-labeled transcript as one Markdown document
+pub fn combined_markdown(
+    started_at: &chrono::DateTime<Local>,
+    transcript: &str,
+    minutes: Option<&str>,
+    action_items: &[ActionItem],
+) -> String {
+    let mut sections = vec![format!("# Meeting Minutes ({})", timezone::format(started_at, "%Y-%m-%d %H:%M %Z"))];
+    sections.push(format!(
+        "## Summary\n\n{}",
+        minutes.map(str::trim).unwrap_or("_Minutes could not be generated for this meeting._")
+    ));
+    if !action_items.is_empty() {
+        sections.push(format!("## Action Items\n\n{}", summarizer::format_action_items(action_items)));
+    }
+    // One paragraph per line so Markdown viewers don't run the speakers together
+    let lines: Vec<&str> = transcript.lines().map(str::trim).filter(|line| !line.is_empty()).collect();
+    sections.push(format!("## Transcript\n\n{}", lines.join("\n\n")));
+    format!("{}\n", sections.join("\n\n"))
+}
+
+/// Marker put before speech that overlaps another speaker
+pub const OVERLAP_MARKER: &str = "(overlap)";
+/// Shorter overlaps are whisper's loose segment boundaries, not crosstalk
+const MIN_OVERLAP_CENTIS: i64 = 50;
+
+/// Segments (speaker index, segment index) that overlap another speaker's on the
+/// session timeline. `timelines` holds each speaker's segment spans in centiseconds.
+pub fn find_overlaps(timelines: &[Vec<(i64, i64)>]) -> HashSet<(usize, usize)> {
+    let mut spans: Vec<(i64, i64, usize, usize)> = timelines
+        .iter()
+        .enumerate()
+        .flat_map(|(speaker, spans)| {
+            spans.iter().enumerate().map(move |(segment, &(start, end))| (start, end, speaker, segment))
+        })
+        .collect();
+    spans.sort();
+
+    let mut overlapping = HashSet::new();
+    for (i, &(_, end, speaker, segment)) in spans.iter().enumerate() {
+        for &(other_start, other_end, other_speaker, other_segment) in &spans[i + 1..] {
+            if other_start >= end {
+                break;
+            }
+            if other_speaker != speaker && end.min(other_end) - other_start >= MIN_OVERLAP_CENTIS {
+                overlapping.insert((speaker, segment));
+                overlapping.insert((other_speaker, other_segment));
+            }
+        }
+    }
+    overlapping
+}
+
+/// Prefix overlapping segments with `OVERLAP_MARKER`; `timelines` is in `speakers` order
+pub fn mark_overlaps(speakers: &mut [(String, FileTranscript)], timelines: &[Vec<(i64, i64)>]) -> usize {
+    let overlapping = find_overlaps(timelines);
+    for &(speaker, segment) in &overlapping {
+        if let Some(segment) = speakers.get_mut(speaker).and_then(|(_, transcript)| transcript.segments.get_mut(segment)) {
+            segment.text = format!("{} {}", OVERLAP_MARKER, segment.text.trim());
+        }
+    }
+    overlapping.len()
+}
+
+/// What happens to a meeting's WAVs once they've been transcribed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordingDisposition {
+    /// Deleted to save disk space
+    Delete,
+    /// Kept until `/export` or `RETAIN_RECORDINGS_MINS` runs out
+    RetainForExport,
+    /// Never deleted (`KEEP_RECORDINGS`); the paths are posted with the minutes
+    Keep,
+}
+
+/// Delete transcribed recordings unless they're being kept; returns the paths left on disk
+pub async fn release_recordings(files: &[String], disposition: RecordingDisposition) -> Vec<String> {
+    if disposition != RecordingDisposition::Delete {
+        return files.to_vec();
+    }
+    for file_path in files {
+        match tokio::fs::remove_file(file_path).await {
+            Ok(()) => println!("[INFO] Deleted temporary file: {}", file_path),
+            Err(e) => eprintln!("[WARN] Failed to remove temporary file {}: {}", file_path, e),
+        }
+    }
+    Vec::new()
+}
+
+/// Message listing recordings kept on disk
+pub fn kept_recordings_message(files: &[String]) -> String {
+    format!(
+        "💾 **Recordings kept** ({} files)\n{}",
+        files.len(),
+        files.iter().map(|f| format!("`{}`", f)).collect::<Vec<_>>().join("\n")
+    )
+}
+
+/// Progress line shown while a meeting's speakers are transcribed
+pub fn transcription_progress_text(done: usize, total: usize) -> String {
+    if done >= total {
+        format!("⏳ Transcribed {}/{}, writing minutes…", total, total)
+    } else {
+        format!("⏳ Transcribing {}/{}…", done + 1, total)
+    }
+}
+
+/// Keep the progress message in step with `done` until the sender is dropped.
+/// A failed edit is logged and the next update tries again.
+async fn show_transcription_progress(
+    http: Arc<HttpClient>,
+    channel_id: Id<ChannelMarker>,
+    message_id: Id<MessageMarker>,
+    total: usize,
+    mut done: watch::Receiver<usize>,
+) {
+    loop {
+        let text = transcription_progress_text(*done.borrow_and_update(), total);
+        if let Err(e) = http.update_message(channel_id, message_id).content(Some(&text)).await {
+            eprintln!("[WARN] Failed to update transcription progress: {}", e);
+        }
+        if done.changed().await.is_err() {
+            return;
+        }
+    }
+}
+
+/// Guilds with a recording still being transcribed: when it started, and the token
+/// that stops its transcription
+type ProcessingRecordings = HashMap<Id<GuildMarker>, (chrono::DateTime<Local>, CancellationToken)>;
+
+fn register_receive_handler(call: &mut Call, handler: &VoiceReceiveHandler) {
+    for event in [CoreEvent::SpeakingStateUpdate, CoreEvent::VoiceTick, CoreEvent::ClientDisconnect] {
+        call.add_global_event(SongbirdEvent::Core(event), handler.clone());
+    }
+}
+
+/// Starts and stops recordings and turns finished ones into transcripts and minutes
+pub struct RecordingService {
+    pub recording_manager: Arc<RecordingManager>,
+    pub transcribers: Arc<TranscriberRegistry>,
+    pub summarizer: Arc<Summarizer>,
+    songbird: Arc<Songbird>,
+    http: Arc<HttpClient>,
+    guild_config: Arc<GuildConfigManager>,
+    last_transcripts: Arc<LastTranscripts>,
+    /// Meetings whose WAVs are kept for `/export`
+    pub retained: Arc<RetainedSessions>,
+    stats: Arc<BotStats>,
+    member_names: Arc<MemberNameCache>,
+    /// Failed speakers in a row before a meeting's transcription is abandoned (0 never gives up)
+    max_consecutive_errors: usize,
+    /// Run in the background on every finished transcript
+    transcript_hooks: Arc<Vec<Arc<dyn TranscriptHook>>>,
+    /// Keep WAVs after transcription unless a guild overrides it
+    keep_recordings: bool,
+    /// Post minutes as one Markdown file unless a guild overrides it
+    minutes_as_file: bool,
+    /// Posts the minutes under a custom name and avatar instead of the bot user
+    minutes_webhook: Option<MinutesWebhook>,
+    voice_handlers: Mutex<HashMap<Id<GuildMarker>, VoiceReceiveHandler>>,
+    /// Recordings still being transcribed, by when they started
+    processing: Mutex<ProcessingRecordings>,
+}
+
+impl RecordingService {
+    pub fn new(
+        recording_manager: Arc<RecordingManager>,
+        transcribers: Arc<TranscriberRegistry>,
+        summarizer: Arc<Summarizer>,
+        songbird: Arc<Songbird>,
+        http: Arc<HttpClient>,
+        guild_config: Arc<GuildConfigManager>,
+        last_transcripts: Arc<LastTranscripts>,
+    ) -> Self {
+        let member_names = Arc::new(MemberNameCache::new(http.clone()));
+        Self {
+            recording_manager,
+            transcribers,
+            summarizer,
+            songbird,
+            http,
+            guild_config,
+            last_transcripts,
+            retained: Arc::new(RetainedSessions::new(std::time::Duration::ZERO)),
+            stats: Arc::new(BotStats::new()),
+            member_names,
+            max_consecutive_errors: DEFAULT_MAX_CONSECUTIVE_ERRORS,
+            transcript_hooks: Arc::new(Vec::new()),
+            keep_recordings: false,
+            minutes_as_file: false,
+            minutes_webhook: None,
+            voice_handlers: Mutex::new(HashMap::new()),
+            processing: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Keep finished meetings' WAVs for `/export`; by default nothing is retained
+    pub fn with_retained(mut self, retained: Arc<RetainedSessions>) -> Self {
+        self.retained = retained;
+        self
+    }
+
+    /// Count transcriptions and summaries in the bot-wide stats
+    pub fn with_stats(mut self, stats: Arc<BotStats>) -> Self {
+        self.stats = stats;
+        self
+    }
+
+    /// Share the name cache the rest of the bot resolves speakers with
+    pub fn with_member_names(mut self, member_names: Arc<MemberNameCache>) -> Self {
+        self.member_names = member_names;
+        self
+    }
+
+    pub fn with_max_consecutive_errors(mut self, limit: usize) -> Self {
+        self.max_consecutive_errors = limit;
+        self
+    }
+
+    pub fn with_transcript_hooks(mut self, hooks: Vec<Arc<dyn TranscriptHook>>) -> Self {
+        self.transcript_hooks = Arc::new(hooks);
+        self
+    }
+
+    pub fn with_keep_recordings(mut self, keep: bool) -> Self {
+        self.keep_recordings = keep;
+        self
+    }
+
+    pub fn with_minutes_as_file(mut self, as_file: bool) -> Self {
+        self.minutes_as_file = as_file;
+        self
+    }
+
+    pub fn with_minutes_webhook(mut self, webhook: Option<MinutesWebhook>) -> Self {
+        self.minutes_webhook = webhook;
+        self
+    }
+
+    /// Join the voice channel, attach the receive handler and start a recording session.
+    /// `language` forces the transcription language; None auto-detects it.
+    pub async fn start(
+        &self,
+        guild_id: Id<GuildMarker>,
+        channel_id: Id<ChannelMarker>,
+        initiator: Id<UserMarker>,
+        language: Option<&str>,
+    ) -> Result<(), AppError> {
+        let channel_id_nz = NonZeroU64::new(channel_id.get())
+            .ok_or_else(|| AppError::Voice("Invalid voice channel".to_string()))?;
+        self.cancel_processing(guild_id).await;
+        let call = self.songbird.join(guild_id, channel_id_nz).await?;
+        println!("[INFO] Successfully joined voice channel {} (started by user {})", channel_id, initiator);
+
+        // Start recording session and add voice receive handler
+        let recording_dir = self.guild_config.get_guild_config(guild_id).await.recording_dir;
+        let receive_handler = self.recording_manager
+            .start_with_handler(guild_id, channel_id, recording_dir.as_deref(), language)
+            .await;
+
+        register_receive_handler(&mut *call.lock().await, &receive_handler);
+        self.voice_handlers.lock().await.insert(guild_id, receive_handler);
+
+        Ok(())
+    }
+
+    /// Stop transcribing the guild's previous recording so its results aren't posted.
+    /// Returns false if nothing was being processed.
+    pub async fn cancel_processing(&self, guild_id: Id<GuildMarker>) -> bool {
+        match self.processing.lock().await.remove(&guild_id) {
+            Some((_, cancel)) => {
+                println!("[INFO] Cancelling transcription of the previous recording in guild {}", guild_id);
+                cancel.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Leave the voice channel and throw the guild's recording away without transcribing
+    /// it, along with any earlier recording still being transcribed.
+    /// Returns false if there was nothing to abort.
+    pub async fn abort(&self, guild_id: Id<GuildMarker>) -> Result<bool, AppError> {
+        let cancelled = self.cancel_processing(guild_id).await;
+        if !self.recording_manager.is_recording(guild_id).await {
+            return Ok(cancelled);
+        }
+
+        self.voice_handlers.lock().await.remove(&guild_id);
+        if self.songbird.get(guild_id).is_some()
+            && let Err(e) = self.songbird.leave(guild_id).await
+        {
+            eprintln!("[ERROR] Failed to leave voice channel: {}", e);
+        }
+        let Some(session) = self.recording_manager.stop_recording(guild_id).await? else {
+            return Ok(cancelled);
+        };
+        session.discard().await;
+        println!("[INFO] Discarded the recording in guild {}", guild_id);
+        Ok(true)
+    }
+
+    /// Stop every active recording and write its files without transcribing, so
+    /// shutting down doesn't lose what was captured. Returns the files written.
+    pub async fn save_active_recordings(&self) -> Vec<String> {
+        let mut saved = Vec::new();
+        for guild_id in self.recording_manager.recording_guilds().await {
+            if let Some(handler) = self.voice_handlers.lock().await.remove(&guild_id) {
+                self.recording_manager.flush_audio_buffers(guild_id, &handler).await;
+            }
+            let session = match self.recording_manager.stop_recording(guild_id).await {
+                Ok(Some(session)) => session,
+                Ok(None) => continue,
+                Err(e) => {
+                    eprintln!("[ERROR] Failed to stop the recording in guild {}: {}", guild_id, e);
+                    continue;
+                }
+            };
+            match session.finalize(self.recording_manager.formats(), &HashMap::new()).await {
+                Ok(outputs) => saved.extend(outputs.into_values().flatten()),
+                Err(e) => eprintln!("[ERROR] Failed to save the recording in guild {}: {}", guild_id, e),
+            }
+        }
+        saved
+    }
+
+    /// Whether a finished recording of the guild is still being transcribed
+    pub async fn is_processing(&self, guild_id: Id<GuildMarker>) -> bool {
+        self.processing.lock().await.contains_key(&guild_id)
+    }
+
+    /// Register the guild's receive handler on `call` again, e.g. after a rejoin.
+    /// Returns false if the guild isn't recording.
+    pub async fn reattach_receive_handler(&self, guild_id: Id<GuildMarker>, call: &mut Call) -> bool {
+        match self.voice_handlers.lock().await.get(&guild_id) {
+            Some(handler) => {
+                register_receive_handler(call, handler);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Leave the voice channel, finish the recording and post the transcript and
+    /// minutes to the guild's minutes channel, or `text_channel_id` if it has none.
+    /// Returns None if the guild wasn't recording.
+    pub async fn stop_and_process(
+        &self,
+        guild_id: Id<GuildMarker>,
+        text_channel_id: Id<ChannelMarker>,
+    ) -> Result<Option<MeetingOutcome>, AppError> {
+        if self.songbird.get(guild_id).is_some() {
+            // Flush audio buffers
+            if let Some(handler) = self.voice_handlers.lock().await.remove(&guild_id) {
+                self.recording_manager.flush_audio_buffers(guild_id, &handler).await;
+            }
+
+            if let Err(e) = self.songbird.leave(guild_id).await {
+                eprintln!("[ERROR] Failed to leave voice channel: {}", e);
+            }
+        }
+
+        let Some(session) = self.recording_manager.stop_recording(guild_id).await? else {
+            return Ok(None);
+        };
+        let cancel = CancellationToken::new();
+        self.processing.lock().await.insert(guild_id, (session.start_time, cancel.clone()));
+        let text_channel_id = self.guild_config.get_guild_config(guild_id).await
+            .output_channel(OutputKind::Minutes, text_channel_id);
+
+        // Names go into each WAV's metadata and label the transcript
+        let speaker_ids: Vec<_> = session.speaker_buffers.read().await.keys().copied().collect();
+        let mut speaker_names = HashMap::new();
+        for user_id in speaker_ids {
+            speaker_names.insert(user_id, self.member_names.resolve(guild_id, user_id).await);
+        }
+
+        // Transcription can take minutes; show that something is happening
+        let progress_message = match self.http.create_message(text_channel_id).content("⏳ Processing recording…").await {
+            Ok(response) => response.model().await.ok().map(|message| message.id),
+            Err(e) => {
+                eprintln!("[WARN] Failed to post transcription progress: {}", e);
+                None
+            }
+        };
+
+        let (outcome, kept_files) = self.process_session(&session, &speaker_names, text_channel_id, progress_message, &cancel).await;
+        {
+            let mut processing = self.processing.lock().await;
+            if processing.get(&guild_id).is_some_and(|(started, _)| *started == session.start_time) {
+                processing.remove(&guild_id);
+            }
+        }
+        if outcome == MeetingOutcome::Cancelled {
+            // Nothing of an abandoned recording is posted, not even the progress note
+            if let Some(message_id) = progress_message
+                && let Err(e) = self.http.delete_message(text_channel_id, message_id).await
+            {
+                eprintln!("[WARN] Failed to remove transcription progress message: {}", e);
+            }
+            return Ok(Some(outcome));
+        }
+        self.stats.record_recording();
+
+        if let Some((transcript, minutes)) = outcome.transcript_and_minutes()
+            && !self.transcript_hooks.is_empty()
+        {
+            let mut participants: Vec<String> = speaker_names.into_values().collect();
+            participants.sort();
+            let ctx = TranscriptContext {
+                guild_id,
+                channel_id: session.channel_id,
+                started_at: session.start_time.to_rfc3339(),
+                participants,
+                transcript: transcript.to_string(),
+                minutes: minutes.map(str::to_string),
+            };
+            let hooks = self.transcript_hooks.clone();
+            tokio::spawn(async move { transcript_hooks::run_hooks(&hooks, &ctx).await });
+        }
+
+        let as_file = self.guild_config.get_guild_config(guild_id).await.posts_minutes_as_file(self.minutes_as_file);
+        match outcome.transcript_and_minutes() {
+            Some((transcript, minutes)) if as_file => {
+                let markdown = combined_markdown(&session.start_time, transcript, minutes, outcome.action_items());
+                if !self.post_combined_file(text_channel_id, progress_message, &session.start_time, markdown).await {
+                    // The upload failed; post the results as regular messages instead
+                    self.post_messages(text_channel_id, progress_message, outcome.messages()).await;
+                }
+            }
+            _ => {
+                let mut messages = outcome.messages();
+                // With a webhook the minutes go through it after the transcript, in place of the bot message
+                let webhook_minutes = match (&self.minutes_webhook, &outcome) {
+                    (Some(webhook), MeetingOutcome::Minutes { minutes, .. }) if webhook.posts_to(guild_id, text_channel_id) => {
+                        messages.pop();
+                        Some((webhook, minutes))
+                    }
+                    _ => None,
+                };
+                self.post_messages(text_channel_id, progress_message, messages).await;
+
+                if let Some((webhook, minutes)) = webhook_minutes {
+                    match webhook.post_minutes(&self.http, minutes).await {
+                        Ok(()) => println!("[INFO] Sent meeting minutes through the webhook for channel {}", text_channel_id),
+                        Err(failure) => {
+                            eprintln!(
+                                "[ERROR] Failed to post minutes through the webhook after {} embeds, sending the rest as the bot: {}",
+                                failure.sent, failure.error
+                            );
+                            for message in minutes_webhook::fallback_messages(minutes, failure.sent) {
+                                if let Err(e) = send_message_resilient(&self.http, text_channel_id, &message).await {
+                                    eprintln!("[ERROR] Failed to send meeting results: {}", e);
+                                }
+                            }
+                        }
+                    }
+                }
+
+                let action_items = outcome.action_items();
+                if !action_items.is_empty() {
+                    self.post_action_items(text_channel_id, action_items).await;
+                }
+            }
+        }
+
+        if !kept_files.is_empty()
+            && let Err(e) = send_message_resilient(&self.http, text_channel_id, &kept_recordings_message(&kept_files)).await
+        {
+            eprintln!("[ERROR] Failed to send kept recording paths: {}", e);
+        }
+
+        Ok(Some(outcome))
+    }
+
+    async fn process_session(
+        &self,
+        session: &RecordingSession,
+        speaker_names: &HashMap<Id<UserMarker>, String>,
+        text_channel_id: Id<ChannelMarker>,
+        progress_message: Option<Id<MessageMarker>>,
+        cancel: &CancellationToken,
+    ) -> (MeetingOutcome, Vec<String>) {
+        let guild_id = session.guild_id;
+        let mut outputs = session
+            .finalize(self.recording_manager.formats(), speaker_names)
+            .await
+            .unwrap_or_default();
+
+        // Opus recordings are archived as-is; there is no PCM to transcribe
+        if session.mode == RecordingMode::OpusPassthrough {
+            let files = outputs.remove(&OutputFormat::Opus).unwrap_or_default();
+            if files.is_empty() {
+                return (MeetingOutcome::NoAudio, Vec::new());
+            }
+            return (MeetingOutcome::OpusArchived(files), Vec::new());
+        }
+
+        // Extra formats are archive copies; transcription reads the WAVs
+        let mut speaker_files = outputs.remove(&OutputFormat::Wav).unwrap_or_default();
+        if speaker_files.is_empty() {
+            return (MeetingOutcome::NoAudio, Vec::new());
+        }
+
+        let guild_config = self.guild_config.get_guild_config(guild_id).await;
+        let initial_prompt = guild_config.initial_prompt();
+        let transcriber = self.transcribers.resolve(guild_config.transcription_model.as_deref(), transcriber::MODEL_BASE);
+
+        // Transcribe speakers concurrently; results come back in file (speaker) order
+        speaker_files.sort();
+        let disposition = if guild_config.keeps_recordings(self.keep_recordings) {
+            RecordingDisposition::Keep
+        } else if self.retained.is_enabled() {
+            RecordingDisposition::RetainForExport
+        } else {
+            RecordingDisposition::Delete
+        };
+        let (progress_tx, progress_rx) = watch::channel(0);
+        let progress_task = progress_message.map(|message_id| {
+            tokio::spawn(show_transcription_progress(self.http.clone(), text_channel_id, message_id, speaker_files.len(), progress_rx))
+        });
+        let language = session.transcription_language();
+        // Cancelled with the recording, or once its results are in to stop any stragglers
+        let transcription = cancel.child_token();
+        let tasks = TranscriptionTasks::default();
+        let results = unless_cancelled(cancel, transcribe_files_concurrently(&speaker_files, MAX_CONCURRENT_TRANSCRIPTIONS, self.max_consecutive_errors, |file_path| {
+            let transcriber = transcriber.clone();
+            let language = language.clone();
+            let initial_prompt = initial_prompt.clone();
+            let stats = self.stats.clone();
+            let transcription = transcription.clone();
+            let tasks = tasks.clone();
+            async move {
+                println!("[INFO] Transcribing file: {}", file_path);
+                let started = std::time::Instant::now();
+                // No language chosen: whisper detects it per speaker and the transcript shows it
+                let result = transcribe_wav_file(transcriber, &file_path, language, initial_prompt, transcription, &tasks).await;
+                stats.record_transcription(started.elapsed());
+                result
+            }
+        }, |done| {
+            progress_tx.send_replace(done);
+        })).await;
+
+        // Let the last progress edit land before the results replace it
+        drop(progress_tx);
+        if let Some(task) = progress_task {
+            let _ = task.await;
+        }
+
+        // Transcription is done with the WAVs either way, but threads may still be reading
+        // them: the files left after an abort or cancel, or a call that timed out. Stop
+        // those and delete the files once they've returned.
+        transcription.cancel();
+        if disposition == RecordingDisposition::Delete {
+            let files = speaker_files.clone();
+            tokio::spawn(async move {
+                tasks.finished().await;
+                release_recordings(&files, disposition).await;
+            });
+        }
+        let reported_files = if disposition == RecordingDisposition::Keep { speaker_files.clone() } else { Vec::new() };
+
+        let Some(results) = results else {
+            println!("[INFO] Dropped the transcription of guild {}'s previous recording", guild_id);
+            return (MeetingOutcome::Cancelled, reported_files);
+        };
+        let results = match results {
+            Ok(results) => results,
+            Err(aborted) => {
+                eprintln!("[ERROR] Abandoning transcription for guild {}: {}", guild_id, aborted);
+                let outcome = MeetingOutcome::TranscriptionFailing { failures: aborted.failures, error: aborted.last_error };
+                return (outcome, reported_files);
+            }
+        };
+
+        let mut speakers = Vec::new();
+        let mut timelines = Vec::new();
+
+        for (file_path, result) in results {
+            match result {
+                Ok(transcript) => {
+                    let user_id = parse_recording_filename(&file_path).map(|name| name.user_id);
+                    let speaker_name = match user_id {
+                        Some(id) => self.member_names.resolve(guild_id, id).await,
+                        None => "Unknown Speaker".to_string(),
+                    };
+                    // Where this speaker's segments fall on the meeting's timeline
+                    let chunks = match user_id {
+                        Some(id) => session.timeline_chunks(id).await,
+                        None => Vec::new(),
+                    };
+                    timelines.push(transcript.segments.iter()
+                        .map(|segment| (file_to_timeline(&chunks, segment.start), file_to_timeline(&chunks, segment.end)))
+                        .collect());
+                    speakers.push((speaker_name, transcript));
+                }
+                Err(e) => {
+                    eprintln!("[ERROR] Failed to transcribe file {}: {}", file_path, e);
+                }
+            }
+        }
+
+        let overlaps = mark_overlaps(&mut speakers, &timelines);
+        if overlaps > 0 {
+            println!("[INFO] Marked {} overlapping segment(s) in guild {}'s transcript", overlaps, guild_id);
+        }
+        let mut full_transcript = assemble_transcript(&speakers);
+
+        // Mask profanity and PII before anything is posted or summarized
+        if let Some(options) = guild_config.redaction_options() {
+            full_transcript = redaction::redact_transcript(&full_transcript, &options);
+        }
+
+        let Some(outcome) = unless_cancelled(cancel, self.summarize(guild_id, full_transcript)).await else {
+            return (MeetingOutcome::Cancelled, reported_files);
+        };
+
+        let (transcript, minutes) = outcome.transcript_and_minutes().unwrap_or_default();
+        let stored = StoredTranscript {
+            created_at: Local::now(),
+            transcript: transcript.to_string(),
+            minutes: minutes.map(str::to_string),
+        };
+        let audio_files = speaker_files.iter().map(PathBuf::from).collect();
+        match disposition {
+            RecordingDisposition::Keep => self.retained.retain_kept(guild_id, stored, audio_files).await,
+            RecordingDisposition::RetainForExport => self.retained.retain(guild_id, stored, audio_files).await,
+            RecordingDisposition::Delete => {}
+        }
+
+        (outcome, reported_files)
+    }
+
+    /// Transcribe a standalone audio file (e.g. from `/transcribe_url`) as a
+    /// single speaker and summarize it like a meeting
+    pub async fn transcribe_file(
+        &self,
+        guild_id: Id<GuildMarker>,
+        wav_path: &str,
+        speaker_name: &str,
+        language: Option<&str>,
+    ) -> Result<MeetingOutcome, AppError> {
+        let guild_config = self.guild_config.get_guild_config(guild_id).await;
+        let transcriber = self.transcribers.resolve(guild_config.transcription_model.as_deref(), transcriber::MODEL_BASE);
+        let language = language.map(|code| user_settings::speech_language(code).to_string());
+
+        println!("[INFO] Transcribing file: {}", wav_path);
+        let started = std::time::Instant::now();
+        let transcript = transcribe_wav_file(transcriber, wav_path, language, guild_config.initial_prompt(), CancellationToken::new(), &TranscriptionTasks::default()).await?;
+        self.stats.record_transcription(started.elapsed());
+
+        let mut full_transcript = assemble_transcript(&[(speaker_name.to_string(), transcript)]);
+        if let Some(options) = guild_config.redaction_options() {
+            full_transcript = redaction::redact_transcript(&full_transcript, &options);
+        }
+        Ok(self.summarize(guild_id, full_transcript).await)
+    }
+
+    /// Turn a finished transcript into minutes (and action items)
+    async fn summarize(&self, guild_id: Id<GuildMarker>, full_transcript: String) -> MeetingOutcome {
+        if full_transcript.is_empty() {
+            return MeetingOutcome::NothingTranscribed;
+        }
+
+        println!("[INFO] Summarizing meeting with {} chars of transcript", full_transcript.len());
+        let minutes_template = self.guild_config.get_guild_config(guild_id).await.minutes_template;
+        match self.summarizer.summarize_meeting(&full_transcript, minutes_template.as_deref()).await {
+            Ok(minutes) => {
+                self.last_transcripts.push(guild_id, full_transcript.clone(), Some(minutes.clone())).await;
+                let action_items = match self.summarizer.extract_action_items(&full_transcript).await {
+                    Ok(items) => items,
+                    Err(e) => {
+                        eprintln!("[ERROR] Failed to extract action items: {}", e);
+                        Vec::new()
+                    }
+                };
+                MeetingOutcome::Minutes { transcript: full_transcript, minutes, action_items }
+            }
+            Err(e) => {
+                eprintln!("[ERROR] Failed to summarize meeting: {}", e);
+                self.last_transcripts.push(guild_id, full_transcript.clone(), None).await;
+                MeetingOutcome::SummaryFailed { transcript: full_transcript, error: e.to_string() }
+            }
+        }
+    }
+
+    /// Post the meeting results, the first one replacing the progress message
+    async fn post_messages(&self, text_channel_id: Id<ChannelMarker>, progress_message: Option<Id<MessageMarker>>, messages: Vec<String>) {
+        for (i, message) in messages.into_iter().enumerate() {
+            if i == 0
+                && let Some(message_id) = progress_message
+                && self.http.update_message(text_channel_id, message_id).content(Some(&message)).await.is_ok()
+            {
+                println!("[INFO] Sent meeting results to channel {}", text_channel_id);
+                continue;
+            }
+            match send_message_resilient(&self.http, text_channel_id, &message).await {
+                Ok(_) => println!("[INFO] Sent meeting results to channel {}", text_channel_id),
+                Err(e) => eprintln!("[ERROR] Failed to send meeting results: {}", e),
+            }
+        }
+    }
+
+    /// Upload the combined Markdown with a short pointer, in place of the progress
+    /// message. Returns false if the upload failed; the progress message is kept then.
+    async fn post_combined_file(
+        &self,
+        text_channel_id: Id<ChannelMarker>,
+        progress_message: Option<Id<MessageMarker>>,
+        started_at: &chrono::DateTime<Local>,
+        markdown: String,
+    ) -> bool {
+        let filename = format!("minutes_{}.md", timezone::format(started_at, "%Y%m%d_%H%M%S"));
+        let attachments = [Attachment::from_bytes(filename, markdown.into_bytes(), 0)];
+
+        let sent = send_resilient("Sending meeting results file", || async {
+            self.http.create_message(text_channel_id)
+                .content("✅ **Meeting minutes ready** — the minutes and full transcript are in the attached file")
+                .attachments(&attachments)
+                .await
+        })
+        .await;
+        if let Err(e) = sent {
+            eprintln!("[ERROR] Failed to send meeting results file: {}", e);
+            return false;
+        }
+        println!("[INFO] Sent meeting results file to channel {}", text_channel_id);
+        if let Some(message_id) = progress_message
+            && let Err(e) = self.http.delete_message(text_channel_id, message_id).await
+        {
+            eprintln!("[WARN] Failed to remove transcription progress message: {}", e);
+        }
+        true
+    }
+
+    /// Post action items as bullets with the JSON attached for the project tracker
+    async fn post_action_items(&self, text_channel_id: Id<ChannelMarker>, items: &[ActionItem]) {
+        let json = match serde_json::to_vec_pretty(items) {
+            Ok(json) => json,
+            Err(e) => {
+                eprintln!("[ERROR] Failed to serialize action items: {}", e);
+                return;
+            }
+        };
+        let content = format!("📌 **Action Items**\n{}", summarizer::format_action_items(items))
+            .chars()
+            .take(2000)
+            .collect::<String>();
+        let attachment = Attachment::from_bytes("action_items.json".to_string(), json, 0);
+
+        if let Err(e) = self.http.create_message(text_channel_id)
+            .content(&content)
+            .attachments(&[attachment])
+            .await
+        {
+            eprintln!("[ERROR] Failed to send action items: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcriber::TranscriptSegment;
+
+    fn segment(text: &str) -> TranscriptSegment {
+        TranscriptSegment { start: 0, end: 100, text: text.to_string(), confidence: 0.9 }
+    }
+
+    fn given(segments: Vec<TranscriptSegment>) -> FileTranscript {
+        FileTranscript { segments, detected_language: None }
+    }
+
+    /// A service recording into `dir` that never reaches Discord or a whisper model
+    fn offline_service(dir: &std::path::Path) -> RecordingService {
+        let http = Arc::new(HttpClient::new(String::new()));
+        let songbird = Songbird::twilight(Arc::new(songbird::shards::TwilightMap::new(HashMap::new())), Id::<UserMarker>::new(1));
+        RecordingService::new(
+            Arc::new(RecordingManager::new(dir.to_string_lossy().into_owned()).with_max_buffered_samples(10)),
+            Arc::new(TranscriberRegistry::new(Vec::new())),
+            Arc::new(Summarizer::new(String::new())),
+            Arc::new(songbird),
+            http.clone(),
+            Arc::new(GuildConfigManager::new(&dir.join("guild_config.json").to_string_lossy())),
+            Arc::new(LastTranscripts::new(1)),
+        )
+    }
+
+    #[test]
+    fn test_assemble_transcript_skips_silent_speakers() {
+        let speakers = vec![
+            ("Alice".to_string(), given(vec![segment("hello"), segment("second line")])),
+            ("Bob".to_string(), given(Vec::new())),
+            ("Carol".to_string(), given(vec![segment("hi")])),
+        ];
+        assert_eq!(
+            assemble_transcript(&speakers),
+            "**[Alice]**: hello\n**[Alice]**: second line\n\n**[Carol]**: hi\n\n"
+        );
+    }
+
+    #[test]
+    fn test_combined_markdown_sections() {
+        use chrono::TimeZone;
+
+        let started_at = Local.with_ymd_and_hms(2024, 5, 1, 10, 0, 0).unwrap();
+        let speakers = vec![
+            ("Alice".to_string(), given(vec![segment("ship on Friday")])),
+            ("Bob".to_string(), given(vec![segment("I'll write the changelog")])),
+        ];
+        let transcript = assemble_transcript(&speakers);
+        let items = vec![ActionItem { assignee: "Bob".to_string(), task: "Write the changelog".to_string(), due: None }];
+
+        let markdown = combined_markdown(&started_at, &transcript, Some("Release is on Friday.\n"), &items);
+        assert!(markdown.starts_with("# Meeting Minutes (2024-05-01 10:00"));
+        let summary = markdown.find("## Summary\n\nRelease is on Friday.").unwrap();
+        let action_items = markdown.find("## Action Items\n\n- **Bob**: Write the changelog").unwrap();
+        let transcript_at = markdown.find("## Transcript\n\n**[Alice]**: ship on Friday\n\n**[Bob]**: I'll write the changelog\n").unwrap();
+        assert!(summary < action_items && action_items < transcript_at);
+
+        // Summarization failed and nothing to do: no action item section
+        let markdown = combined_markdown(&started_at, &transcript, None, &[]);
+        assert!(markdown.contains("## Summary\n\n_Minutes could not be generated for this meeting._"));
+        assert!(!markdown.contains("## Action Items"));
+    }
+
+    #[test]
+    fn test_overlapping_speech_is_marked() {
+        let mut speakers = vec![
+            ("Alice".to_string(), given(vec![segment("first point"), segment("so as I was saying")])),
+            ("Bob".to_string(), given(vec![segment("sorry, quick question")])),
+        ];
+        // Bob talks over Alice's second segment; a 0.2s brush with the first doesn't count
+        let timelines = vec![vec![(0, 320), (400, 900)], vec![(300, 600)]];
+
+        assert_eq!(mark_overlaps(&mut speakers, &timelines), 2);
+        let transcript = assemble_transcript(&speakers);
+        assert!(transcript.contains("**[Alice]**: first point\n"));
+        assert!(transcript.contains("**[Alice]**: (overlap) so as I was saying"));
+        assert!(transcript.contains("**[Bob]**: (overlap) sorry, quick question"));
+
+        // A speaker can't overlap themselves
+        assert!(find_overlaps(&[vec![(0, 500), (100, 600)]]).is_empty());
+        // File positions map onto the timeline through the chunks' arrival times
+        assert_eq!(file_to_timeline(&[(0, 0), (48_000, 480_000)], 150), 1050);
+    }
+
+    #[test]
+    fn test_detected_language_labels_speaker() {
+        let detected = |code: &str, text: &str| FileTranscript {
+            segments: vec![segment(text)],
+            detected_language: Some(code.to_string()),
+        };
+        let speakers = vec![
+            ("Alice".to_string(), detected("en", "hello")),
+            ("Bob".to_string(), detected("de", "hallo")),
+        ];
+        assert_eq!(
+            assemble_transcript(&speakers),
+            "__Alice (detected: English)__\n**[Alice]**: hello\n\n__Bob (detected: DE)__\n**[Bob]**: hallo\n\n"
+        );
+    }
+
+    #[test]
+    fn test_outcome_messages() {
+        let minutes = MeetingOutcome::Minutes {
+            transcript: "**[Alice]**: hello".to_string(),
+            minutes: "📋 Summary".to_string(),
+            action_items: Vec::new(),
+        };
+        let messages = minutes.messages();
+        assert_eq!(messages.len(), 2);
+        assert!(messages[0].starts_with("📝 **Full Transcription**"));
+        assert!(messages[1].contains("📋 Summary"));
+
+        let failed = MeetingOutcome::SummaryFailed {
+            transcript: "x".repeat(3000),
+            error: "timeout".to_string(),
+        };
+        let messages = failed.messages();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].matches('x').count(), 1900);
+        assert!(messages[0].ends_with("Error: timeout"));
+
+        assert_eq!(MeetingOutcome::NoAudio.messages(), vec!["❌ No audio data recorded".to_string()]);
+        assert!(MeetingOutcome::OpusArchived(vec!["a.ogg".to_string()]).messages()[0].contains("`a.ogg`"));
+    }
+
+    #[tokio::test]
+    async fn test_release_recordings_keeps_or_deletes() {
+        let dir = std::env::temp_dir().join(format!("release_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let files: Vec<String> = ["1.wav", "2.wav"]
+            .iter()
+            .map(|name| {
+                let path = dir.join(name);
+                std::fs::write(&path, b"RIFF").unwrap();
+                path.to_string_lossy().into_owned()
+            })
+            .collect();
+
+        assert_eq!(release_recordings(&files, RecordingDisposition::Keep).await, files);
+        assert_eq!(release_recordings(&files, RecordingDisposition::RetainForExport).await, files);
+        assert!(files.iter().all(|f| std::path::Path::new(f).exists()));
+        assert!(kept_recordings_message(&files).starts_with("💾 **Recordings kept** (2 files)"));
+
+        assert!(release_recordings(&files, RecordingDisposition::Delete).await.is_empty());
+        assert!(files.iter().all(|f| !std::path::Path::new(f).exists()));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_processing_never_posts() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        let cancel = CancellationToken::new();
+        let posted = Arc::new(AtomicBool::new(false));
+        let processing = {
+            let posted = posted.clone();
+            async move {
+                tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+                posted.store(true, Ordering::SeqCst);
+                MeetingOutcome::NothingTranscribed
+            }
+        };
+        let canceller = cancel.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            canceller.cancel();
+        });
+
+        assert!(unless_cancelled(&cancel, processing).await.is_none());
+        tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+        assert!(!posted.load(Ordering::SeqCst));
+        assert!(MeetingOutcome::Cancelled.transcript_and_minutes().is_none());
+
+        assert_eq!(unless_cancelled(&CancellationToken::new(), async { 7 }).await, Some(7));
+    }
+
+    #[tokio::test]
+    async fn test_abort_discards_the_recording_and_cancels_processing() {
+        let dir = std::env::temp_dir().join(format!("abort_{}", uuid::Uuid::new_v4()));
+        let service = offline_service(&dir);
+        let guild_id = Id::new(1);
+
+        // The previous recording is still being transcribed while a new one runs
+        let previous = CancellationToken::new();
+        service.processing.lock().await.insert(guild_id, (Local::now(), previous.clone()));
+        service.recording_manager.start_recording(guild_id, Id::new(10), None, None).await;
+        service.recording_manager.add_audio_to_session(guild_id, Id::new(42), &[1; 60]).await;
+
+        assert!(service.abort(guild_id).await.unwrap());
+        assert!(previous.is_cancelled());
+        assert!(!service.is_processing(guild_id).await);
+        assert!(!service.recording_manager.is_recording(guild_id).await);
+        // The audio spilled to disk went with it
+        let wavs = walk_files(&dir).into_iter().filter(|path| path.extension().is_some_and(|ext| ext == "wav")).count();
+        assert_eq!(wavs, 0);
+
+        assert!(!service.abort(guild_id).await.unwrap());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    fn walk_files(dir: &std::path::Path) -> Vec<PathBuf> {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return Vec::new();
+        };
+        entries
+            .flatten()
+            .flat_map(|entry| match entry.path() {
+                path if path.is_dir() => walk_files(&path),
+                path => vec![path],
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_transcription_progress_text() {
+        assert_eq!(transcription_progress_text(0, 5), "⏳ Transcribing 1/5…");
+        assert_eq!(transcription_progress_text(3, 5), "⏳ Transcribing 4/5…");
+        assert_eq!(transcription_progress_text(5, 5), "⏳ Transcribed 5/5, writing minutes…");
+    }
+}
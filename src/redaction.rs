@@ -0,0 +1,151 @@
+use regex::Regex;
+use std::sync::LazyLock;
+
+static EMAIL_PATTERN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}").unwrap()
+});
+
+/// Digit runs with optional separators; only those with enough digits are phone numbers
+static PHONE_PATTERN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\+?\d[\d\s().-]{7,}\d").unwrap()
+});
+
+/// Dates (2024-10-16, 2024/10/16) and clock times (10:30, 10:30:00), which run
+/// together into phone-number-sized digit runs when they appear side by side
+static DATE_TIME_PATTERN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b(?:19|20)\d{2}[-/.](?:0?[1-9]|1[0-2])[-/.](?:0?[1-9]|[12]\d|3[01])\b|\b\d{1,2}:\d{2}(?::\d{2})?\b").unwrap()
+});
+
+/// Masked in every redacted transcript, on top of the guild's own words
+pub const DEFAULT_PROFANITY: &[&str] = &[
+    "fuck", "fucking", "fucked", "fucker", "motherfucker", "shit", "shitty", "bullshit",
+    "bitch", "bastard", "asshole", "dickhead", "cunt", "wanker", "slut", "whore",
+    "くそ", "クソ", "ちくしょう", "畜生", "くたばれ", "死ね",
+];
+
+#[derive(Debug, Clone, Default)]
+pub struct RedactionOptions {
+    /// Words masked with asterisks (case-insensitive)
+    pub words: Vec<String>,
+    pub mask_emails: bool,
+    pub mask_phones: bool,
+}
+
+fn word_pattern(word: &str) -> Option<Regex> {
+    let word = word.trim();
+    if word.is_empty() {
+        return None;
+    }
+    // Word boundaries only make sense for space-separated scripts
+    let pattern = if word.chars().all(|c| c.is_ascii_alphanumeric()) {
+        format!(r"(?i)\b{}\b", regex::escape(word))
+    } else {
+        format!("(?i){}", regex::escape(word))
+    };
+    Regex::new(&pattern).ok()
+}
+
+/// Mask listed words, email addresses and phone numbers in `text`
+pub fn redact(text: &str, options: &RedactionOptions) -> String {
+    let mut result = text.to_string();
+
+    if options.mask_emails {
+        result = EMAIL_PATTERN.replace_all(&result, "[email]").into_owned();
+    }
+    if options.mask_phones {
+        result = mask_phones(&result);
+    }
+    for pattern in options.words.iter().filter_map(|w| word_pattern(w)) {
+        result = pattern
+            .replace_all(&result, |caps: &regex::Captures| "*".repeat(caps[0].chars().count()))
+            .into_owned();
+    }
+
+    result
+}
+
+/// Mask phone numbers, looking only between dates and times so they're never taken for one
+fn mask_phones(text: &str) -> String {
+    let mask = |part: &str| {
+        PHONE_PATTERN
+            .replace_all(part, |caps: &regex::Captures| {
+                let digits = caps[0].chars().filter(|c| c.is_ascii_digit()).count();
+                if digits >= 9 { "[phone]".to_string() } else { caps[0].to_string() }
+            })
+            .into_owned()
+    };
+    let mut result = String::with_capacity(text.len());
+    let mut last = 0;
+    for date_time in DATE_TIME_PATTERN.find_iter(text) {
+        result.push_str(&mask(&text[last..date_time.start()]));
+        result.push_str(date_time.as_str());
+        last = date_time.end();
+    }
+    result.push_str(&mask(&text[last..]));
+    result
+}
+
+/// Redact a speaker-labeled transcript, leaving the `**[name]**:` prefixes intact
+pub fn redact_transcript(transcript: &str, options: &RedactionOptions) -> String {
+    transcript
+        .split('\n')
+        .map(|line| match line.strip_prefix("**[").and_then(|rest| rest.find("]**: ")) {
+            Some(end) => {
+                let split = "**[".len() + end + "]**: ".len();
+                format!("{}{}", &line[..split], redact(&line[split..], options))
+            }
+            None => redact(line, options),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options() -> RedactionOptions {
+        RedactionOptions {
+            words: vec!["darn".to_string(), "くそ".to_string()],
+            mask_emails: true,
+            mask_phones: true,
+        }
+    }
+
+    #[test]
+    fn test_word_masking() {
+        let options = options();
+        assert_eq!(redact("Darn it, darned printer", &options), "**** it, darned printer");
+        assert_eq!(redact("くそっ", &options), "**っ");
+    }
+
+    #[test]
+    fn test_email_and_phone_redaction() {
+        let options = options();
+        assert_eq!(redact("mail me at jane.doe@example.com", &options), "mail me at [email]");
+        assert_eq!(redact("call 090-1234-5678 now", &options), "call [phone] now");
+        assert_eq!(redact("call +1 (555) 123-4567", &options), "call [phone]");
+        assert_eq!(redact("toll-free 0120-12-3456", &options), "toll-free [phone]");
+        // Times, dates and short numbers are left alone
+        assert_eq!(redact("meet at 10:30 on 2024-05-01", &options), "meet at 10:30 on 2024-05-01");
+        assert_eq!(redact("started 2024-10-16 10:30:00", &options), "started 2024-10-16 10:30:00");
+        assert_eq!(redact("2024/10/16 9:05 to 11:45", &options), "2024/10/16 9:05 to 11:45");
+        assert_eq!(redact("from 10:30:00 - 11:00:00", &options), "from 10:30:00 - 11:00:00");
+        assert_eq!(redact("room 1234", &options), "room 1234");
+        // A number right after a date is still caught
+        assert_eq!(redact("2024-10-16 090-1234-5678", &options), "2024-10-16 [phone]");
+    }
+
+    #[test]
+    fn test_speaker_labels_are_preserved() {
+        let options = RedactionOptions {
+            words: vec!["darn".to_string()],
+            ..Default::default()
+        };
+        let transcript = "**[darn_gamer]**: darn it\n\n**[Alice]**: fine";
+        assert_eq!(
+            redact_transcript(transcript, &options),
+            "**[darn_gamer]**: **** it\n\n**[Alice]**: fine"
+        );
+    }
+}
@@ -0,0 +1,170 @@
+//! Kana to Hepburn romaji transliteration.
+//! Only kana are converted; kanji, Latin text and punctuation pass through unchanged.
+
+/// Map katakana to the equivalent hiragana so one table covers both
+fn to_hiragana(c: char) -> char {
+    match c {
+        'ァ'..='ヶ' => char::from_u32(c as u32 - 0x60).unwrap_or(c),
+        _ => c,
+    }
+}
+
+/// Romaji for a single full-size hiragana
+fn syllable(c: char) -> Option<&'static str> {
+    let romaji = match c {
+        'あ' => "a", 'い' => "i", 'う' => "u", 'え' => "e", 'お' => "o",
+        'か' => "ka", 'き' => "ki", 'く' => "ku", 'け' => "ke", 'こ' => "ko",
+        'が' => "ga", 'ぎ' => "gi", 'ぐ' => "gu", 'げ' => "ge", 'ご' => "go",
+        'さ' => "sa", 'し' => "shi", 'す' => "su", 'せ' => "se", 'そ' => "so",
+        'ざ' => "za", 'じ' => "ji", 'ず' => "zu", 'ぜ' => "ze", 'ぞ' => "zo",
+        'た' => "ta", 'ち' => "chi", 'つ' => "tsu", 'て' => "te", 'と' => "to",
+        'だ' => "da", 'ぢ' => "ji", 'づ' => "zu", 'で' => "de", 'ど' => "do",
+        'な' => "na", 'に' => "ni", 'ぬ' => "nu", 'ね' => "ne", 'の' => "no",
+        'は' => "ha", 'ひ' => "hi", 'ふ' => "fu", 'へ' => "he", 'ほ' => "ho",
+        'ば' => "ba", 'び' => "bi", 'ぶ' => "bu", 'べ' => "be", 'ぼ' => "bo",
+        'ぱ' => "pa", 'ぴ' => "pi", 'ぷ' => "pu", 'ぺ' => "pe", 'ぽ' => "po",
+        'ま' => "ma", 'み' => "mi", 'む' => "mu", 'め' => "me", 'も' => "mo",
+        'や' => "ya", 'ゆ' => "yu", 'よ' => "yo",
+        'ら' => "ra", 'り' => "ri", 'る' => "ru", 'れ' => "re", 'ろ' => "ro",
+        'わ' => "wa", 'ゐ' => "wi", 'ゑ' => "we", 'を' => "o",
+        'ゔ' => "vu",
+        // Small kana on their own
+        'ぁ' => "a", 'ぃ' => "i", 'ぅ' => "u", 'ぇ' => "e", 'ぉ' => "o",
+        'ゃ' => "ya", 'ゅ' => "yu", 'ょ' => "yo", 'ゎ' => "wa",
+        _ => return None,
+    };
+    Some(romaji)
+}
+
+/// Vowel added by a small kana that combines with the preceding one (きゃ, ファ, ティ...)
+fn combining_vowel(c: char) -> Option<(&'static str, bool)> {
+    match c {
+        'ゃ' => Some(("a", true)),
+        'ゅ' => Some(("u", true)),
+        'ょ' => Some(("o", true)),
+        'ぁ' => Some(("a", false)),
+        'ぃ' => Some(("i", false)),
+        'ぅ' => Some(("u", false)),
+        'ぇ' => Some(("e", false)),
+        'ぉ' => Some(("o", false)),
+        _ => None,
+    }
+}
+
+/// Combine a syllable with a following small kana
+fn combine(base: &str, vowel: &str, palatal: bool) -> String {
+    let stem = &base[..base.len() - 1];
+    if palatal {
+        // し/ち/じ already carry the palatal sound: しゃ → sha, not shya
+        if matches!(stem, "sh" | "ch" | "j") {
+            format!("{}{}", stem, vowel)
+        } else {
+            format!("{}y{}", stem, vowel)
+        }
+    } else if base == "i" {
+        // イェ → ye
+        format!("y{}", vowel)
+    } else if stem.is_empty() {
+        // ウィ → wi
+        format!("w{}", vowel)
+    } else {
+        format!("{}{}", stem, vowel)
+    }
+}
+
+/// Read one syllable (with any combining small kana) starting at `i`
+fn read_syllable(chars: &[char], i: usize) -> Option<(String, usize)> {
+    let base = syllable(chars[i])?;
+    if let Some((vowel, palatal)) = chars.get(i + 1).and_then(|&c| combining_vowel(c))
+        && (base.len() > 1 || !palatal)
+    {
+        return Some((combine(base, vowel, palatal), 2));
+    }
+    Some((base.to_string(), 1))
+}
+
+/// Transliterate the kana in `text` to Hepburn romaji
+pub fn to_romaji(text: &str) -> String {
+    let chars: Vec<char> = text.chars().map(to_hiragana).collect();
+    let original: Vec<char> = text.chars().collect();
+    let mut output = String::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            // Small tsu doubles the next consonant (っち → tchi)
+            'っ' => {
+                if let Some((next, _)) = chars.get(i + 1).and_then(|_| read_syllable(&chars, i + 1)) {
+                    if next.starts_with("ch") {
+                        output.push('t');
+                    } else if let Some(first) = next.chars().next().filter(|c| !"aiueo".contains(*c)) {
+                        output.push(first);
+                    }
+                }
+                i += 1;
+            }
+            // Long vowel mark repeats the previous vowel
+            'ー' => {
+                match output.chars().last().filter(|c| "aiueo".contains(*c)) {
+                    Some(vowel) => output.push(vowel),
+                    None => output.push(original[i]),
+                }
+                i += 1;
+            }
+            'ん' => {
+                let next = chars.get(i + 1).and_then(|_| read_syllable(&chars, i + 1));
+                output.push('n');
+                if next.is_some_and(|(s, _)| s.starts_with(|c: char| "aiueoy".contains(c))) {
+                    output.push('\'');
+                }
+                i += 1;
+            }
+            _ => match read_syllable(&chars, i) {
+                Some((romaji, consumed)) => {
+                    output.push_str(&romaji);
+                    i += consumed;
+                }
+                None => {
+                    output.push(original[i]);
+                    i += 1;
+                }
+            },
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_basic_hiragana_and_katakana() {
+        assert_eq!(to_romaji("こんにちは"), "konnichiha");
+        assert_eq!(to_romaji("ありがとう"), "arigatou");
+        assert_eq!(to_romaji("カタカナ"), "katakana");
+        assert_eq!(to_romaji("すし"), "sushi");
+    }
+
+    #[test]
+    fn test_combined_kana() {
+        assert_eq!(to_romaji("きょう"), "kyou");
+        assert_eq!(to_romaji("しゃしん"), "shashin");
+        assert_eq!(to_romaji("ちょっと"), "chotto");
+        assert_eq!(to_romaji("まっちゃ"), "matcha");
+        assert_eq!(to_romaji("コーヒー"), "koohii");
+        assert_eq!(to_romaji("パーティー"), "paatii");
+        assert_eq!(to_romaji("ファイル"), "fairu");
+        assert_eq!(to_romaji("イェス"), "yesu");
+        assert_eq!(to_romaji("ウェブ"), "webu");
+        assert_eq!(to_romaji("きんえん"), "kin'en");
+    }
+
+    #[test]
+    fn test_non_kana_passes_through() {
+        assert_eq!(to_romaji("今日はOKです。"), "今日haOKdesu。");
+        assert_eq!(to_romaji("hello"), "hello");
+    }
+}
@@ -0,0 +1,275 @@
+use chrono::{DateTime, Duration as ChronoDuration, Local, NaiveDateTime, NaiveTime, TimeDelta};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use twilight_model::id::{marker::{ChannelMarker, GuildMarker, UserMarker}, Id};
+
+use crate::persist;
+use crate::timezone::BotTimezone;
+
+/// How often the scheduler checks for due recordings
+pub const SCHEDULE_POLL_INTERVAL: Duration = Duration::from_secs(20);
+/// A start missed by more than this (e.g. the bot was down) is skipped rather than run late
+pub const MISSED_START_GRACE: Duration = Duration::from_secs(10 * 60);
+/// Longest `/record_schedule` duration
+pub const MAX_SCHEDULED_MINUTES: i64 = 8 * 60;
+
+/// A recording registered with `/record_schedule`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScheduledRecording {
+    pub id: u64,
+    pub guild_id: Id<GuildMarker>,
+    pub voice_channel_id: Id<ChannelMarker>,
+    /// Where the control message and minutes are posted
+    pub text_channel_id: Id<ChannelMarker>,
+    pub user_id: Id<UserMarker>,
+    /// Unix timestamp (seconds) of the start
+    pub start_at: i64,
+    /// Stop automatically after this many minutes; None records until stopped
+    pub duration_mins: Option<u64>,
+    /// Set once the job started a recording with a duration: when to stop it and
+    /// the session's start (Unix ms), so a later manual recording isn't stopped
+    #[serde(default)]
+    pub auto_stop: Option<(i64, i64)>,
+}
+
+impl ScheduledRecording {
+    pub fn duration(&self) -> Option<Duration> {
+        self.duration_mins.map(|mins| Duration::from_secs(mins * 60))
+    }
+
+    /// Whether the start was missed by more than `grace`
+    pub fn is_missed(&self, now: DateTime<Local>, grace: Duration) -> bool {
+        now.timestamp() - self.start_at > grace.as_secs() as i64
+    }
+}
+
+/// Parse a start time relative to `now`: `HH:MM` (today, or tomorrow once it has
+/// passed), `YYYY-MM-DD HH:MM`, or an offset like `+30m` / `+2h`. Clock times are
+/// read in `zone`, the one `BOT_TIMEZONE` shows times in.
+pub fn parse_start_time(input: &str, now: DateTime<Local>, zone: BotTimezone) -> Result<DateTime<Local>, String> {
+    let input = input.trim();
+
+    if let Some(offset) = input.strip_prefix('+') {
+        let (amount, to_delta): (&str, fn(i64) -> Option<TimeDelta>) = if let Some(amount) = offset.strip_suffix('m') {
+            (amount, TimeDelta::try_minutes)
+        } else if let Some(amount) = offset.strip_suffix('h') {
+            (amount, TimeDelta::try_hours)
+        } else {
+            return Err(format!("Offset `{}` must end in `m` or `h`", input));
+        };
+        let amount: i64 = amount.parse().map_err(|_| format!("Invalid offset `{}`", input))?;
+        if amount <= 0 {
+            return Err("The offset must be in the future".to_string());
+        }
+        return to_delta(amount)
+            .and_then(|offset| now.checked_add_signed(offset))
+            .ok_or_else(|| format!("Offset `{}` is too far in the future", input));
+    }
+
+    if let Ok(time) = NaiveTime::parse_from_str(input, "%H:%M") {
+        let date = zone.date_of(&now);
+        let today = zoned_time(date.and_time(time), zone)?;
+        return Ok(if today > now { today } else { zoned_time((date + ChronoDuration::days(1)).and_time(time), zone)? });
+    }
+
+    let wall_clock = NaiveDateTime::parse_from_str(input, "%Y-%m-%d %H:%M")
+        .map_err(|_| format!("Couldn't read `{}`; use `HH:MM`, `YYYY-MM-DD HH:MM` or `+30m`", input))?;
+    let at = zoned_time(wall_clock, zone)?;
+    if at <= now {
+        return Err(format!("{} is in the past", wall_clock.format("%Y-%m-%d %H:%M")));
+    }
+    Ok(at)
+}
+
+fn zoned_time(at: NaiveDateTime, zone: BotTimezone) -> Result<DateTime<Local>, String> {
+    zone.resolve(at).ok_or_else(|| format!("{} doesn't exist in the bot's timezone", at))
+}
+
+/// Split jobs into those due at `now` and those still pending
+pub fn split_due(jobs: Vec<ScheduledRecording>, now: DateTime<Local>) -> (Vec<ScheduledRecording>, Vec<ScheduledRecording>) {
+    jobs.into_iter().partition(|job| job.start_at <= now.timestamp())
+}
+
+/// Pending scheduled recordings, persisted so a restart doesn't lose them
+pub struct RecordingSchedule {
+    jobs: RwLock<Vec<ScheduledRecording>>,
+    file_path: String,
+}
+
+impl RecordingSchedule {
+    pub fn new(file_path: &str) -> Self {
+        let jobs: Vec<ScheduledRecording> = persist::load_json_or_default(Path::new(file_path));
+        if !jobs.is_empty() {
+            println!("[INFO] Loaded {} scheduled recording(s)", jobs.len());
+        }
+        Self {
+            jobs: RwLock::new(jobs),
+            file_path: file_path.to_string(),
+        }
+    }
+
+    /// Register a recording and return it with its id
+    pub async fn add(
+        &self,
+        guild_id: Id<GuildMarker>,
+        voice_channel_id: Id<ChannelMarker>,
+        text_channel_id: Id<ChannelMarker>,
+        user_id: Id<UserMarker>,
+        start: DateTime<Local>,
+        duration_mins: Option<u64>,
+    ) -> ScheduledRecording {
+        let job = {
+            let mut jobs = self.jobs.write().await;
+            let id = jobs.iter().map(|job| job.id).max().unwrap_or(0) + 1;
+            let job = ScheduledRecording {
+                id,
+                guild_id,
+                voice_channel_id,
+                text_channel_id,
+                user_id,
+                start_at: start.timestamp(),
+                duration_mins,
+                auto_stop: None,
+            };
+            jobs.push(job.clone());
+            job
+        };
+        self.save_to_file().await;
+        job
+    }
+
+    /// Remove and return the jobs whose start time has come
+    pub async fn take_due(&self, now: DateTime<Local>) -> Vec<ScheduledRecording> {
+        let due = {
+            let mut jobs = self.jobs.write().await;
+            let (running, waiting): (Vec<_>, Vec<_>) = std::mem::take(&mut *jobs)
+                .into_iter()
+                .partition(|job| job.auto_stop.is_some());
+            let (due, pending) = split_due(waiting, now);
+            *jobs = running.into_iter().chain(pending).collect();
+            due
+        };
+        if !due.is_empty() {
+            self.save_to_file().await;
+        }
+        due
+    }
+
+    /// Keep a started job until its duration is up, so the stop survives a restart
+    pub async fn add_auto_stop(&self, mut job: ScheduledRecording, stop_at: DateTime<Local>, session_start: DateTime<Local>) {
+        job.auto_stop = Some((stop_at.timestamp(), session_start.timestamp_millis()));
+        self.jobs.write().await.push(job);
+        self.save_to_file().await;
+    }
+
+    /// Remove and return the started jobs whose recording should stop now
+    pub async fn take_due_stops(&self, now: DateTime<Local>) -> Vec<ScheduledRecording> {
+        let due: Vec<ScheduledRecording> = {
+            let mut jobs = self.jobs.write().await;
+            let (due, keep) = std::mem::take(&mut *jobs)
+                .into_iter()
+                .partition(|job| job.auto_stop.is_some_and(|(stop_at, _)| stop_at <= now.timestamp()));
+            *jobs = keep;
+            due
+        };
+        if !due.is_empty() {
+            self.save_to_file().await;
+        }
+        due
+    }
+
+    async fn save_to_file(&self) {
+        let jobs = self.jobs.read().await;
+        if let Ok(json) = serde_json::to_string_pretty(&*jobs)
+            && let Err(e) = persist::write_atomic(Path::new(&self.file_path), json.as_bytes()).await
+        {
+            eprintln!("[ERROR] Failed to save recording schedule to {}: {}", self.file_path, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> DateTime<Local> {
+        zoned_time(NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap(), BotTimezone::Local).unwrap()
+    }
+
+    #[test]
+    fn test_parse_start_time() {
+        let now = at("2024-05-01 10:00");
+        assert_eq!(parse_start_time("14:30", now, BotTimezone::Local).unwrap(), at("2024-05-01 14:30"));
+        // Already passed today: tomorrow
+        assert_eq!(parse_start_time("09:00", now, BotTimezone::Local).unwrap(), at("2024-05-02 09:00"));
+        assert_eq!(parse_start_time("2024-05-03 08:15", now, BotTimezone::Local).unwrap(), at("2024-05-03 08:15"));
+        assert_eq!(parse_start_time("+30m", now, BotTimezone::Local).unwrap(), at("2024-05-01 10:30"));
+        assert_eq!(parse_start_time(" +2h ", now, BotTimezone::Local).unwrap(), at("2024-05-01 12:00"));
+
+        assert!(parse_start_time("2024-04-30 08:00", now, BotTimezone::Local).is_err());
+        assert!(parse_start_time("+0m", now, BotTimezone::Local).is_err());
+        assert!(parse_start_time("+5d", now, BotTimezone::Local).is_err());
+        assert!(parse_start_time("tomorrow", now, BotTimezone::Local).is_err());
+        // Multibyte units and huge offsets are errors, not panics
+        assert!(parse_start_time("+5分", now, BotTimezone::Local).is_err());
+        assert!(parse_start_time("+分", now, BotTimezone::Local).is_err());
+        assert!(parse_start_time("+99999999999999h", now, BotTimezone::Local).is_err());
+        assert!(parse_start_time(&format!("+{}m", i64::MAX), now, BotTimezone::Local).is_err());
+    }
+
+    #[test]
+    fn test_start_time_is_read_in_the_bot_timezone() {
+        use chrono::{TimeZone, Utc};
+
+        // 09:00 in Tokyo, 20:00 the day before in New York, whatever the host's zone is
+        let now = Utc.with_ymd_and_hms(2024, 5, 1, 0, 0, 0).unwrap().with_timezone(&Local);
+        let tokyo = BotTimezone::parse("Asia/Tokyo").unwrap();
+        let new_york = BotTimezone::parse("America/New_York").unwrap();
+        let utc = |h, m| Utc.with_ymd_and_hms(2024, 5, 1, h, m, 0).unwrap();
+
+        assert_eq!(parse_start_time("14:00", now, tokyo).unwrap(), utc(5, 0));
+        assert_eq!(parse_start_time("2024-05-01 10:30", now, tokyo).unwrap(), utc(1, 30));
+        assert!(parse_start_time("2024-05-01 08:00", now, tokyo).is_err());
+        // 14:00 has already passed on New York's date, so it's the next day there
+        assert_eq!(parse_start_time("14:00", now, new_york).unwrap(), utc(18, 0));
+        // Offsets don't depend on the zone
+        assert_eq!(parse_start_time("+30m", now, tokyo).unwrap(), utc(0, 30));
+    }
+
+    #[tokio::test]
+    async fn test_due_jobs_are_taken_once() {
+        let path = std::env::temp_dir().join(format!("schedule_{}.json", uuid::Uuid::new_v4()));
+        let path = path.to_str().unwrap().to_string();
+        let schedule = RecordingSchedule::new(&path);
+        let now = at("2024-05-01 10:00");
+        let (guild, voice, text, user) = (Id::new(1), Id::new(2), Id::new(3), Id::new(4));
+
+        let early = schedule.add(guild, voice, text, user, at("2024-05-01 09:59"), Some(30)).await;
+        let later = schedule.add(guild, voice, text, user, at("2024-05-01 11:00"), None).await;
+        assert_ne!(early.id, later.id);
+
+        assert_eq!(schedule.take_due(now).await, vec![early.clone()]);
+        assert!(schedule.take_due(now).await.is_empty());
+
+        // Pending jobs survive a restart
+        let reloaded = RecordingSchedule::new(&path);
+        assert_eq!(reloaded.take_due(at("2024-05-01 11:00")).await, vec![later]);
+
+        assert!(!early.is_missed(now, MISSED_START_GRACE));
+        assert!(early.is_missed(at("2024-05-01 10:30"), MISSED_START_GRACE));
+        assert_eq!(early.duration(), Some(Duration::from_secs(30 * 60)));
+
+        // A running job's stop is persisted and isn't taken as a new start
+        let session_start = at("2024-05-01 10:00");
+        schedule.add_auto_stop(early.clone(), at("2024-05-01 10:30"), session_start).await;
+        let reloaded = RecordingSchedule::new(&path);
+        assert!(reloaded.take_due(at("2024-05-01 10:31")).await.is_empty());
+        assert!(reloaded.take_due_stops(at("2024-05-01 10:29")).await.is_empty());
+        let stops = reloaded.take_due_stops(at("2024-05-01 10:30")).await;
+        assert_eq!(stops.len(), 1);
+        assert_eq!(stops[0].auto_stop, Some((at("2024-05-01 10:30").timestamp(), session_start.timestamp_millis())));
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,101 @@
+use std::fmt::Display;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+/// Phrase sent to DeepL
+pub const SELFTEST_PHRASE: &str = "Good morning, the meeting is about to start.";
+/// Transcript sent to the summarizer
+pub const SELFTEST_TRANSCRIPT: &str = "**[Alice]**: Let's ship the release on Friday.\n**[Bob]**: I'll update the changelog before then.";
+
+/// Longest detail shown per check
+const MAX_DETAIL_CHARS: usize = 120;
+
+/// Outcome of one integration check
+#[derive(Debug, Clone, PartialEq)]
+pub struct CheckResult {
+    pub name: &'static str,
+    pub latency: Duration,
+    /// What came back on success, the error otherwise
+    pub outcome: Result<String, String>,
+}
+
+/// Run one check and time it
+pub async fn run_check<T, E, Fut>(name: &'static str, check: Fut) -> CheckResult
+where
+    T: Display,
+    E: Display,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let started = Instant::now();
+    let outcome = check.await.map(|value| value.to_string()).map_err(|e| e.to_string());
+    CheckResult { name, latency: started.elapsed(), outcome }
+}
+
+/// One second of a quiet 440 Hz tone at 16 kHz; enough to run whisper end to end
+pub fn sample_audio() -> Vec<f32> {
+    (0..16_000)
+        .map(|i| (i as f32 * 440.0 * std::f32::consts::TAU / 16_000.0).sin() * 0.1)
+        .collect()
+}
+
+fn format_latency(latency: Duration) -> String {
+    if latency < Duration::from_secs(1) {
+        format!("{} ms", latency.as_millis())
+    } else {
+        format!("{:.1} s", latency.as_secs_f32())
+    }
+}
+
+fn one_line(text: &str) -> String {
+    let line = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if line.chars().count() > MAX_DETAIL_CHARS {
+        format!("{}…", line.chars().take(MAX_DETAIL_CHARS).collect::<String>())
+    } else {
+        line
+    }
+}
+
+/// `/selftest` message: a summary line, then ✅/❌ with latency per check
+pub fn format_report(results: &[CheckResult]) -> String {
+    let passed = results.iter().filter(|result| result.outcome.is_ok()).count();
+    let mut lines = vec![format!("🩺 **Self-test**: {}/{} checks passed", passed, results.len())];
+    for result in results {
+        let (mark, detail) = match &result.outcome {
+            Ok(detail) if detail.trim().is_empty() => ("✅", "(no output)".to_string()),
+            Ok(detail) => ("✅", one_line(detail)),
+            Err(e) => ("❌", one_line(e)),
+        };
+        lines.push(format!("{} **{}** ({}) — {}", mark, result.name, format_latency(result.latency), detail));
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_report_mixes_passes_and_failures() {
+        let deepl = run_check("DeepL", async { Ok::<_, String>("おはようございます") }).await;
+        let zai = run_check("z.ai", async { Err::<String, _>("Summarization failed: HTTP 401\nunauthorized") }).await;
+        assert!(deepl.outcome.is_ok());
+        assert_eq!(zai.outcome, Err("Summarization failed: HTTP 401\nunauthorized".to_string()));
+
+        let whisper = CheckResult { name: "Whisper", latency: Duration::from_millis(2340), outcome: Ok(String::new()) };
+        let report = format_report(&[
+            CheckResult { latency: Duration::from_millis(312), ..deepl },
+            CheckResult { latency: Duration::from_millis(45), ..zai },
+            whisper,
+        ]);
+
+        let lines: Vec<&str> = report.lines().collect();
+        assert_eq!(lines[0], "🩺 **Self-test**: 2/3 checks passed");
+        assert_eq!(lines[1], "✅ **DeepL** (312 ms) — おはようございます");
+        assert_eq!(lines[2], "❌ **z.ai** (45 ms) — Summarization failed: HTTP 401 unauthorized");
+        assert_eq!(lines[3], "✅ **Whisper** (2.3 s) — (no output)");
+
+        let long = CheckResult { name: "x", latency: Duration::ZERO, outcome: Err("e".repeat(500)) };
+        assert!(format_report(&[long]).ends_with(&format!("{}…", "e".repeat(MAX_DETAIL_CHARS))));
+        assert_eq!(sample_audio().len(), 16_000);
+    }
+}
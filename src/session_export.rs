@@ -0,0 +1,413 @@
+use std::error::Error;
+use std::io::{Cursor, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use chrono::{Local, TimeZone};
+use reqwest::Client as ReqwestClient;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use twilight_model::id::Id;
+use twilight_model::id::marker::GuildMarker;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::persist;
+use crate::timezone;
+use crate::transcript_store::StoredTranscript;
+
+/// How often expired recordings are deleted
+pub const RETENTION_SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// A finished meeting whose WAVs are kept on disk until `/export` or the TTL
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RetainedSession {
+    pub guild_id: Id<GuildMarker>,
+    /// When the meeting was recorded (Unix ms); tells a guild's meetings apart
+    pub recorded_at: i64,
+    transcript: String,
+    minutes: Option<String>,
+    pub audio_files: Vec<PathBuf>,
+    /// Unix timestamp (ms) after which the files are deleted; kept files never expire
+    expires_at: i64,
+    /// False for recordings kept permanently (`KEEP_RECORDINGS`); only the entry is dropped
+    delete_files: bool,
+}
+
+impl RetainedSession {
+    pub fn transcript(&self) -> StoredTranscript {
+        StoredTranscript {
+            created_at: Local.timestamp_millis_opt(self.recorded_at).single().unwrap_or_else(Local::now),
+            transcript: self.transcript.clone(),
+            minutes: self.minutes.clone(),
+        }
+    }
+
+    async fn discard(&self) {
+        if self.delete_files {
+            remove_files(&self.audio_files).await;
+        }
+    }
+}
+
+/// `metadata.json` sidecar describing an export
+#[derive(Debug, Serialize)]
+struct ExportMetadata {
+    guild_id: String,
+    recorded_at: String,
+    exported_at: String,
+    audio_files: Vec<String>,
+    has_minutes: bool,
+}
+
+/// Finished meetings kept for export when retention is enabled. Each meeting's
+/// files stay until it's exported or its TTL passes, even after the guild records
+/// again; `/export` offers the newest.
+pub struct RetainedSessions {
+    ttl: Duration,
+    sessions: Mutex<Vec<RetainedSession>>,
+    /// Where the list is saved, so files retained before a restart are still deleted
+    file_path: Option<PathBuf>,
+}
+
+impl RetainedSessions {
+    /// A zero `ttl` disables retention: WAVs are deleted right after transcription
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            sessions: Mutex::new(Vec::new()),
+            file_path: None,
+        }
+    }
+
+    /// Save the retained meetings to `file_path` and pick up the ones saved before a restart
+    pub fn with_file(mut self, file_path: &str) -> Self {
+        let sessions: Vec<RetainedSession> = persist::load_json_or_default(Path::new(file_path));
+        if !sessions.is_empty() {
+            println!("[INFO] Loaded {} retained meeting(s)", sessions.len());
+        }
+        self.sessions = Mutex::new(sessions);
+        self.file_path = Some(PathBuf::from(file_path));
+        self
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.ttl.is_zero()
+    }
+
+    /// Keep a meeting's files until it's exported or the TTL passes
+    pub async fn retain(&self, guild_id: Id<GuildMarker>, transcript: StoredTranscript, audio_files: Vec<PathBuf>) {
+        self.insert(guild_id, transcript, audio_files, true).await;
+    }
+
+    /// Offer permanently kept files to `/export` without ever deleting them
+    pub async fn retain_kept(&self, guild_id: Id<GuildMarker>, transcript: StoredTranscript, audio_files: Vec<PathBuf>) {
+        self.insert(guild_id, transcript, audio_files, false).await;
+    }
+
+    async fn insert(&self, guild_id: Id<GuildMarker>, transcript: StoredTranscript, audio_files: Vec<PathBuf>, delete_files: bool) {
+        let expires_at = match delete_files {
+            true => Local::now().timestamp_millis().saturating_add(self.ttl.as_millis() as i64),
+            false => i64::MAX,
+        };
+        let session = RetainedSession {
+            guild_id,
+            recorded_at: transcript.created_at.timestamp_millis(),
+            transcript: transcript.transcript,
+            minutes: transcript.minutes,
+            audio_files,
+            expires_at,
+            delete_files,
+        };
+        {
+            let mut sessions = self.sessions.lock().await;
+            // Kept files outlive their entry anyway; only the newest is worth offering
+            sessions.retain(|retained| retained.guild_id != guild_id || retained.delete_files);
+            sessions.push(session);
+        }
+        self.save_to_file().await;
+    }
+
+    /// The guild's newest retained meeting
+    pub async fn get(&self, guild_id: Id<GuildMarker>) -> Option<RetainedSession> {
+        self.sessions.lock().await
+            .iter()
+            .filter(|session| session.guild_id == guild_id)
+            .max_by_key(|session| session.recorded_at)
+            .cloned()
+    }
+
+    /// Drop one of a guild's retained meetings once it has been exported
+    pub async fn release(&self, guild_id: Id<GuildMarker>, recorded_at: i64) {
+        self.remove_where(|session| session.guild_id == guild_id && session.recorded_at == recorded_at).await;
+    }
+
+    /// Drop all of a guild's retained meetings, e.g. before its recordings are cleared
+    pub async fn release_guild(&self, guild_id: Id<GuildMarker>) {
+        self.remove_where(|session| session.guild_id == guild_id).await;
+    }
+
+    /// Delete meetings past their TTL; returns how many were removed
+    pub async fn sweep_expired(&self) -> usize {
+        let now = Local::now().timestamp_millis();
+        self.remove_where(|session| session.expires_at <= now).await
+    }
+
+    async fn remove_where(&self, matches: impl Fn(&RetainedSession) -> bool) -> usize {
+        let removed: Vec<RetainedSession> = {
+            let mut sessions = self.sessions.lock().await;
+            let (removed, kept) = std::mem::take(&mut *sessions).into_iter().partition(|session| matches(session));
+            *sessions = kept;
+            removed
+        };
+        if removed.is_empty() {
+            return 0;
+        }
+
+        self.save_to_file().await;
+        for session in &removed {
+            session.discard().await;
+        }
+        removed.len()
+    }
+
+    async fn save_to_file(&self) {
+        let Some(file_path) = &self.file_path else {
+            return;
+        };
+        let json = serde_json::to_string_pretty(&*self.sessions.lock().await);
+        if let Ok(json) = json
+            && let Err(e) = persist::write_atomic(file_path, json.as_bytes()).await
+        {
+            eprintln!("[ERROR] Failed to save retained meetings to {}: {}", file_path.display(), e);
+        }
+    }
+}
+
+/// Where exports too large for a Discord upload go instead (`EXPORT_UPLOAD_URL`)
+pub struct ExportSink {
+    client: ReqwestClient,
+    base_url: String,
+}
+
+impl ExportSink {
+    pub fn new(client: ReqwestClient, base_url: &str) -> Self {
+        Self { client, base_url: base_url.trim().trim_end_matches('/').to_string() }
+    }
+
+    /// Where a guild's archive is stored
+    pub fn url_for(&self, guild_id: Id<GuildMarker>, filename: &str) -> String {
+        format!("{}/{}/{}", self.base_url, guild_id, filename)
+    }
+
+    /// PUT the archive to [`Self::url_for`] and return that URL
+    pub async fn upload(&self, guild_id: Id<GuildMarker>, filename: &str, archive: Vec<u8>) -> Result<String, Box<dyn Error + Send + Sync>> {
+        let url = self.url_for(guild_id, filename);
+        let response = self.client
+            .put(&url)
+            .header(reqwest::header::CONTENT_TYPE, "application/zip")
+            .body(archive)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(format!("storage returned {}", response.status()).into());
+        }
+        Ok(url)
+    }
+}
+
+async fn remove_files(files: &[PathBuf]) {
+    for file in files {
+        match tokio::fs::remove_file(file).await {
+            Ok(()) => println!("[INFO] Deleted retained recording: {}", file.display()),
+            Err(e) => eprintln!("[WARN] Failed to remove retained recording {}: {}", file.display(), e),
+        }
+    }
+}
+
+/// File name for a meeting's export archive
+pub fn export_filename(transcript: &StoredTranscript) -> String {
+    format!("meeting_{}.zip", timezone::format(&transcript.created_at, "%Y%m%d_%H%M%S"))
+}
+
+/// Package a meeting into a ZIP: `transcript.md`, `minutes.md` (if generated),
+/// `metadata.json` and the WAVs under `audio/`
+pub fn build_export_zip(
+    guild_id: Id<GuildMarker>,
+    transcript: &StoredTranscript,
+    audio: &[(String, Vec<u8>)],
+) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+    let text = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    // PCM barely compresses; storing it keeps exports fast
+    let binary = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+
+    zip.start_file("transcript.md", text)?;
+    zip.write_all(transcript.transcript.as_bytes())?;
+
+    if let Some(minutes) = &transcript.minutes {
+        zip.start_file("minutes.md", text)?;
+        zip.write_all(minutes.as_bytes())?;
+    }
+
+    let metadata = ExportMetadata {
+        guild_id: guild_id.to_string(),
+        recorded_at: transcript.created_at.to_rfc3339(),
+        exported_at: Local::now().to_rfc3339(),
+        audio_files: audio.iter().map(|(name, _)| name.clone()).collect(),
+        has_minutes: transcript.minutes.is_some(),
+    };
+    zip.start_file("metadata.json", text)?;
+    zip.write_all(&serde_json::to_vec_pretty(&metadata)?)?;
+
+    for (name, data) in audio {
+        zip.start_file(format!("audio/{}", name), binary)?;
+        zip.write_all(data)?;
+    }
+
+    Ok(zip.finish()?.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    fn stored(minutes: Option<&str>) -> StoredTranscript {
+        StoredTranscript {
+            created_at: Local::now(),
+            transcript: "**[Alice]**: hello".to_string(),
+            minutes: minutes.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_export_zip_entries() {
+        let audio = vec![
+            ("1_2_20250101_120000_0.wav".to_string(), vec![1u8; 64]),
+            ("1_3_20250101_120000_0.wav".to_string(), vec![2u8; 64]),
+        ];
+        let bytes = build_export_zip(Id::new(1), &stored(Some("📋 Summary")), &audio).unwrap();
+
+        let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).unwrap();
+        let mut names: Vec<String> = archive.file_names().map(str::to_string).collect();
+        names.sort();
+        assert_eq!(names, vec![
+            "audio/1_2_20250101_120000_0.wav",
+            "audio/1_3_20250101_120000_0.wav",
+            "metadata.json",
+            "minutes.md",
+            "transcript.md",
+        ]);
+
+        let mut minutes = String::new();
+        archive.by_name("minutes.md").unwrap().read_to_string(&mut minutes).unwrap();
+        assert_eq!(minutes, "📋 Summary");
+
+        let mut metadata = String::new();
+        archive.by_name("metadata.json").unwrap().read_to_string(&mut metadata).unwrap();
+        let metadata: serde_json::Value = serde_json::from_str(&metadata).unwrap();
+        assert_eq!(metadata["guild_id"], "1");
+        assert_eq!(metadata["audio_files"].as_array().unwrap().len(), 2);
+        assert_eq!(metadata["has_minutes"], true);
+
+        let mut wav = Vec::new();
+        archive.by_name("audio/1_3_20250101_120000_0.wav").unwrap().read_to_end(&mut wav).unwrap();
+        assert_eq!(wav, vec![2u8; 64]);
+    }
+
+    #[test]
+    fn test_export_zip_without_minutes_or_audio() {
+        let bytes = build_export_zip(Id::new(1), &stored(None), &[]).unwrap();
+        let archive = zip::ZipArchive::new(Cursor::new(bytes)).unwrap();
+        let mut names: Vec<&str> = archive.file_names().collect();
+        names.sort();
+        assert_eq!(names, vec!["metadata.json", "transcript.md"]);
+    }
+
+    fn temp_wav(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("retained_{}_{}.wav", name, uuid::Uuid::new_v4()));
+        std::fs::write(&path, b"RIFF").unwrap();
+        path
+    }
+
+    #[tokio::test]
+    async fn test_expired_sessions_are_deleted() {
+        let path = temp_wav("expired");
+        let kept = temp_wav("kept");
+
+        let retained = RetainedSessions::new(Duration::from_millis(1));
+        assert!(retained.is_enabled());
+        retained.retain(Id::new(1), stored(None), vec![path.clone()]).await;
+        retained.retain_kept(Id::new(2), stored(None), vec![kept.clone()]).await;
+        assert!(retained.get(Id::new(1)).await.is_some());
+
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        assert_eq!(retained.sweep_expired().await, 1);
+        assert!(retained.get(Id::new(1)).await.is_none());
+        assert!(!path.exists());
+        // Kept recordings stay on offer
+        assert!(retained.get(Id::new(2)).await.is_some());
+        assert!(kept.exists());
+
+        assert!(!RetainedSessions::new(Duration::ZERO).is_enabled());
+        let _ = std::fs::remove_file(&kept);
+    }
+
+    #[tokio::test]
+    async fn test_release_only_drops_the_exported_meeting() {
+        let (older, newer) = (temp_wav("older"), temp_wav("newer"));
+        let retained = RetainedSessions::new(Duration::from_secs(600));
+        let first = stored(None);
+        let mut second = stored(Some("minutes"));
+        second.created_at = first.created_at + chrono::TimeDelta::seconds(90);
+        retained.retain(Id::new(1), first, vec![older.clone()]).await;
+        retained.retain(Id::new(1), second, vec![newer.clone()]).await;
+
+        // The earlier meeting's files stay while it might still be being exported
+        assert!(older.exists());
+        let latest = retained.get(Id::new(1)).await.unwrap();
+        assert_eq!(latest.audio_files, vec![newer.clone()]);
+        assert_eq!(latest.transcript().minutes.as_deref(), Some("minutes"));
+
+        // Finishing an export of the older meeting leaves the newer one alone
+        let older_export = latest.recorded_at - 90_000;
+        retained.release(Id::new(1), older_export).await;
+        assert!(!older.exists());
+        assert!(newer.exists());
+        assert_eq!(retained.get(Id::new(1)).await, Some(latest));
+
+        retained.release_guild(Id::new(1)).await;
+        assert!(retained.get(Id::new(1)).await.is_none());
+        assert!(!newer.exists());
+    }
+
+    #[tokio::test]
+    async fn test_retained_meetings_survive_a_restart() {
+        let file = std::env::temp_dir().join(format!("retained_{}.json", uuid::Uuid::new_v4()));
+        let file = file.to_string_lossy().into_owned();
+        let wav = temp_wav("restart");
+
+        let retained = RetainedSessions::new(Duration::from_millis(1)).with_file(&file);
+        retained.retain(Id::new(1), stored(Some("minutes")), vec![wav.clone()]).await;
+        let saved = retained.get(Id::new(1)).await;
+        drop(retained);
+
+        let reloaded = RetainedSessions::new(Duration::from_millis(1)).with_file(&file);
+        assert_eq!(reloaded.get(Id::new(1)).await, saved);
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        assert_eq!(reloaded.sweep_expired().await, 1);
+        assert!(!wav.exists());
+
+        let _ = std::fs::remove_file(&file);
+    }
+
+    #[test]
+    fn test_export_sink_url() {
+        let sink = ExportSink::new(ReqwestClient::new(), " https://files.example.com/exports/ ");
+        assert_eq!(
+            sink.url_for(Id::new(7), "meeting_20250101_120000.zip"),
+            "https://files.example.com/exports/7/meeting_20250101_120000.zip"
+        );
+    }
+}
@@ -0,0 +1,129 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use twilight_model::channel::message::embed::{Embed, EmbedField};
+
+/// Lifetime counters shown by `/stats`, reset when the bot restarts
+pub struct BotStats {
+    started_at: Instant,
+    recordings_processed: AtomicU64,
+    translations: AtomicU64,
+    transcription_millis: AtomicU64,
+}
+
+impl BotStats {
+    pub fn new() -> Self {
+        Self {
+            started_at: Instant::now(),
+            recordings_processed: AtomicU64::new(0),
+            translations: AtomicU64::new(0),
+            transcription_millis: AtomicU64::new(0),
+        }
+    }
+
+    pub fn record_recording(&self) {
+        self.recordings_processed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_translation(&self) {
+        self.translations.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Add time spent in whisper
+    pub fn record_transcription(&self, elapsed: Duration) {
+        self.transcription_millis.fetch_add(elapsed.as_millis() as u64, Ordering::Relaxed);
+    }
+
+    pub fn uptime(&self) -> Duration {
+        self.started_at.elapsed()
+    }
+
+    pub fn recordings_processed(&self) -> u64 {
+        self.recordings_processed.load(Ordering::Relaxed)
+    }
+
+    pub fn translations(&self) -> u64 {
+        self.translations.load(Ordering::Relaxed)
+    }
+
+    pub fn transcription_time(&self) -> Duration {
+        Duration::from_millis(self.transcription_millis.load(Ordering::Relaxed))
+    }
+}
+
+/// Compact duration like `3d 4h 5m`, or seconds under a minute
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let (days, hours, minutes) = (secs / 86_400, secs / 3_600 % 24, secs / 60 % 60);
+
+    match (days, hours, minutes) {
+        (0, 0, 0) => format!("{}s", secs),
+        (0, 0, m) => format!("{}m", m),
+        (0, h, m) => format!("{}h {}m", h, m),
+        (d, h, m) => format!("{}d {}h {}m", d, h, m),
+    }
+}
+
+/// `/stats` embed
+pub fn build_stats_embed(stats: &BotStats, active_recordings: usize, active_translations: usize) -> Embed {
+    let field = |name: &str, value: String| EmbedField {
+        inline: true,
+        name: name.to_string(),
+        value,
+    };
+
+    Embed {
+        author: None,
+        color: Some(0x2ecc71),
+        description: None,
+        fields: vec![
+            field("⏱️ Uptime", format_duration(stats.uptime())),
+            field("🔴 Active recordings", active_recordings.to_string()),
+            field("🌐 Active translations", active_translations.to_string()),
+            field("📝 Recordings processed", stats.recordings_processed().to_string()),
+            field("💬 Translations", stats.translations().to_string()),
+            field("🧠 Transcription time", format_duration(stats.transcription_time())),
+        ],
+        footer: None,
+        image: None,
+        kind: "rich".to_string(),
+        provider: None,
+        thumbnail: None,
+        timestamp: None,
+        title: Some("diggy-gizzy Stats".to_string()),
+        url: None,
+        video: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counters() {
+        let stats = BotStats::new();
+        stats.record_recording();
+        stats.record_translation();
+        stats.record_translation();
+        stats.record_transcription(Duration::from_millis(1500));
+        stats.record_transcription(Duration::from_millis(2500));
+
+        assert_eq!(stats.recordings_processed(), 1);
+        assert_eq!(stats.translations(), 2);
+        assert_eq!(stats.transcription_time(), Duration::from_secs(4));
+
+        let embed = build_stats_embed(&stats, 1, 0);
+        assert_eq!(embed.fields[1].value, "1");
+        assert_eq!(embed.fields[4].value, "2");
+        assert_eq!(embed.fields[5].value, "4s");
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_secs(42)), "42s");
+        assert_eq!(format_duration(Duration::from_secs(5 * 60 + 9)), "5m");
+        assert_eq!(format_duration(Duration::from_secs(2 * 3600 + 7 * 60)), "2h 7m");
+        assert_eq!(format_duration(Duration::from_secs(3 * 86_400 + 4 * 3600 + 5 * 60)), "3d 4h 5m");
+        assert_eq!(format_duration(Duration::from_secs(86_400)), "1d 0h 0m");
+    }
+}
@@ -1,4 +1,6 @@
 use reqwest::Client;
+use crate::api_client::{ClientSettings, DEFAULT_SUMMARY_TIMEOUT};
+use crate::error::AppError;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize)]
@@ -30,50 +32,135 @@ struct ZaiResponse {
     choices: Vec<ZaiChoice>,
 }
 
+/// A follow-up task pulled out of a meeting transcript
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ActionItem {
+    pub assignee: String,
+    pub task: String,
+    #[serde(default)]
+    pub due: Option<String>,
+}
+
+const ACTION_ITEMS_SCHEMA: &str = "Extract the action items from the meeting transcript.\n\
+    Respond with a JSON array only, matching this schema:\n\
+    [{\"assignee\": string, \"task\": string, \"due\": string | null}]\n\
+    Use \"Unassigned\" when nobody took the task, and null when no deadline was mentioned. \
+    Return [] if there are no action items.";
+
+const ACTION_ITEMS_STRICT: &str = "Your previous answer could not be parsed. \
+    Output ONLY the raw JSON array: no code fences, no explanations, no trailing text.";
+
+/// Prompt template for meeting minutes in a single output language
+struct MinutesTemplate {
+    system: &'static str,
+    instructions: &'static str,
+}
+
+const JAPANESE_MINUTES: MinutesTemplate = MinutesTemplate {
+    system: "あなたはプロの会議議事録作成者です。与えられた文字起こしテキストから、構造化された議事録を作成してください。日本語で回答してください。",
+    instructions: "以下の会議の文字起こしテキストから、議事録を作成してください。\n\n\
+        以下の形式で出力してください:\n\
+        📋 **会議概要**\n\
+        [簡潔な会議の要約（3-5行）]\n\n\
+        👥 **参加者**\n\
+        [発言者一覧]\n\n\
+        💬 **主な議論内容**\n\
+        - [議題1]: [要点]\n\
+        - [議題2]: [要点]\n\n\
+        ✅ **決定事項**\n\
+        - [決定1]\n\
+        - [決定2]\n\n\
+        📌 **アクションアイテム**\n\
+        - [担当]: [タスク内容]\n\n\
+        ---\n\
+        文字起こしテキスト:\n",
+};
+
+const KOREAN_MINUTES: MinutesTemplate = MinutesTemplate {
+    system: "당신은 전문 회의록 작성자입니다. 주어진 회의 전사 텍스트로부터 구조화된 회의록을 작성해 주세요. 한국어로 답변해 주세요.",
+    instructions: "다음 회의 전사 텍스트로부터 회의록을 작성해 주세요.\n\n\
+        다음 형식으로 출력해 주세요:\n\
+        📋 **개요**\n\
+        [간결한 회의 요약 (3-5줄)]\n\n\
+        👥 **참석자**\n\
+        [발언자 목록]\n\n\
+        💬 **논의 내용**\n\
+        - [안건1]: [요점]\n\
+        - [안건2]: [요점]\n\n\
+        ✅ **결정 사항**\n\
+        - [결정1]\n\
+        - [결정2]\n\n\
+        📌 **액션 아이템**\n\
+        - [담당자]: [작업 내용]\n\n\
+        ---\n\
+        전사 텍스트:\n",
+};
+
+/// Marks where a guild's custom minutes prompt gets the transcript
+pub const TRANSCRIPT_PLACEHOLDER: &str = "{transcript}";
+/// Longest custom minutes prompt accepted
+pub const MAX_PROMPT_TEMPLATE_CHARS: usize = 4000;
+
+/// Check a guild's custom minutes prompt before it's stored
+pub fn validate_prompt_template(template: &str) -> Result<(), String> {
+    if !template.contains(TRANSCRIPT_PLACEHOLDER) {
+        return Err(format!("The template must contain `{}` where the transcript goes", TRANSCRIPT_PLACEHOLDER));
+    }
+    if template.chars().count() > MAX_PROMPT_TEMPLATE_CHARS {
+        return Err(format!("The template is longer than {} characters", MAX_PROMPT_TEMPLATE_CHARS));
+    }
+    Ok(())
+}
+
+/// Resolve the minutes template for an output language code (Japanese by default)
+fn minutes_template(language: &str) -> &'static MinutesTemplate {
+    match language.trim().to_lowercase().as_str() {
+        "ko" | "korean" => &KOREAN_MINUTES,
+        _ => &JAPANESE_MINUTES,
+    }
+}
+
 pub struct Summarizer {
     api_key: String,
     client: Client,
+    output_language: String,
 }
 
 impl Summarizer {
     pub fn new(api_key: String) -> Self {
         Self {
             api_key,
-            client: Client::new(),
+            client: ClientSettings::with_timeout(DEFAULT_SUMMARY_TIMEOUT).build().unwrap(),
+            output_language: "ja".to_string(),
         }
     }
 
-    pub async fn summarize_meeting(
-        &self,
-        transcript: &str,
-    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-        let prompt = format!(
-            "以下の会議の文字起こしテキストから、議事録を作成してください。\n\n\
-            以下の形式で出力してください:\n\
-            📋 **会議概要**\n\
-            [簡潔な会議の要約（3-5行）]\n\n\
-            👥 **参加者**\n\
-            [発言者一覧]\n\n\
-            💬 **主な議論内容**\n\
-            - [議題1]: [要点]\n\
-            - [議題2]: [要点]\n\n\
-            ✅ **決定事項**\n\
-            - [決定1]\n\
-            - [決定2]\n\n\
-            📌 **アクションアイテム**\n\
-            - [担当]: [タスク内容]\n\n\
-            ---\n\
-            文字起こしテキスト:\n\
-            {}",
-            transcript
-        );
+    /// Rebuild the HTTP client with a different timeout or pool tuning
+    pub fn with_client_settings(mut self, settings: ClientSettings) -> Self {
+        self.client = settings.build().unwrap();
+        self
+    }
 
-        let request = ZaiRequest {
+    /// Set the language meeting minutes are written in (`ja` or `ko`)
+    pub fn with_output_language(mut self, language: &str) -> Self {
+        self.output_language = language.to_string();
+        self
+    }
+
+    /// `custom_template` replaces the built-in instructions; its `{transcript}` is filled in
+    fn build_minutes_request(&self, transcript: &str, custom_template: Option<&str>) -> ZaiRequest {
+        let template = minutes_template(&self.output_language);
+        let prompt = match custom_template {
+            Some(custom) => custom.replace(TRANSCRIPT_PLACEHOLDER, transcript),
+            None => format!("{}{}", template.instructions, transcript),
+        };
+
+        ZaiRequest {
             model: "glm-4.7-flash".to_string(),
             messages: vec![
                 ZaiChatMessage {
                     role: "system".to_string(),
-                    content: "あなたはプロの会議議事録作成者です。与えられた文字起こしテキストから、構造化された議事録を作成してください。日本語で回答してください。".to_string(),
+                    content: template.system.to_string(),
                 },
                 ZaiChatMessage {
                     role: "user".to_string(),
@@ -82,36 +169,26 @@ impl Summarizer {
             ],
             temperature: 0.7,
             max_tokens: 4096,
-        };
-
-        let response = self
-            .client
-            .post("https://api.z.ai/api/paas/v4/chat/completions")
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .header("Content-Type", "application/json")
-            .json(&request)
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let text = response.text().await.unwrap_or_default();
-            return Err(format!("z.ai API error: {} - {}", status, text).into());
         }
+    }
 
-        let zai_response: ZaiResponse = response.json().await?;
-        
-        if let Some(choice) = zai_response.choices.first() {
-            Ok(choice.message.content.clone())
-        } else {
-            Err("No response from z.ai API".into())
-        }
+    /// Minutes for a transcript, using the guild's custom prompt when it has one.
+    /// If the model answers with nothing, a local extractive summary is returned
+    /// instead so the meeting isn't lost.
+    pub async fn summarize_meeting(
+        &self,
+        transcript: &str,
+        custom_template: Option<&str>,
+    ) -> Result<String, AppError> {
+        let request = self.build_minutes_request(transcript, custom_template);
+        let response = self.request_chat(&request).await?;
+        Ok(minutes_or_fallback(response, transcript))
     }
 
     pub async fn summarize_short(
         &self,
         transcript: &str,
-    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<String, AppError> {
         let prompt = format!(
             "以下のテキストを簡潔に要約してください（200文字以内）:\n\n{}",
             transcript
@@ -133,27 +210,349 @@ impl Summarizer {
             max_tokens: 512,
         };
 
+        self.send_chat(&request).await
+    }
+
+    fn build_action_items_request(&self, transcript: &str, strict: bool) -> ZaiRequest {
+        let mut instructions = format!(
+            "{}\nWrite the task text in the transcript's main language.\n",
+            ACTION_ITEMS_SCHEMA
+        );
+        if strict {
+            instructions.push_str(ACTION_ITEMS_STRICT);
+        }
+
+        ZaiRequest {
+            model: "glm-4.7-flash".to_string(),
+            messages: vec![
+                ZaiChatMessage {
+                    role: "system".to_string(),
+                    content: instructions,
+                },
+                ZaiChatMessage {
+                    role: "user".to_string(),
+                    content: transcript.to_string(),
+                },
+            ],
+            temperature: 0.2,
+            max_tokens: 2048,
+        }
+    }
+
+    /// Ask the model for the meeting's action items as JSON.
+    /// Malformed output is retried once with a stricter instruction.
+    pub async fn extract_action_items(
+        &self,
+        transcript: &str,
+    ) -> Result<Vec<ActionItem>, AppError> {
+        let response = self.send_chat(&self.build_action_items_request(transcript, false)).await?;
+        if let Some(items) = parse_action_items(&response) {
+            return Ok(items);
+        }
+
+        println!("[WARN] Action item response wasn't valid JSON, retrying with a stricter prompt");
+        let response = self.send_chat(&self.build_action_items_request(transcript, true)).await?;
+        parse_action_items(&response)
+            .ok_or_else(|| AppError::Summarization(format!(
+                "Model returned malformed action items: {}",
+                response.chars().take(200).collect::<String>()
+            )))
+    }
+
+    async fn send_chat(
+        &self,
+        request: &ZaiRequest,
+    ) -> Result<String, AppError> {
+        let response = self.request_chat(request).await?;
+        first_choice(response).ok_or_else(|| AppError::Summarization("No response from z.ai API".to_string()))
+    }
+
+    async fn request_chat(
+        &self,
+        request: &ZaiRequest,
+    ) -> Result<ZaiResponse, AppError> {
         let response = self
             .client
             .post("https://api.z.ai/api/paas/v4/chat/completions")
             .header("Authorization", format!("Bearer {}", self.api_key))
             .header("Content-Type", "application/json")
-            .json(&request)
+            .json(request)
             .send()
-            .await?;
+            .await
+            .map_err(|e| AppError::Summarization(format!("z.ai request failed: {}", e)))?;
 
         if !response.status().is_success() {
             let status = response.status();
             let text = response.text().await.unwrap_or_default();
-            return Err(format!("z.ai API error: {} - {}", status, text).into());
+            return Err(AppError::Summarization(format!("z.ai API error: {} - {}", status, text)));
         }
 
-        let zai_response: ZaiResponse = response.json().await?;
-        
-        if let Some(choice) = zai_response.choices.first() {
-            Ok(choice.message.content.clone())
-        } else {
-            Err("No response from z.ai API".into())
+        response
+            .json()
+            .await
+            .map_err(|e| AppError::Summarization(format!("Unreadable z.ai response: {}", e)))
+    }
+}
+
+/// The first choice's text, or None if the model returned nothing usable
+fn first_choice(response: ZaiResponse) -> Option<String> {
+    response.choices
+        .into_iter()
+        .next()
+        .map(|choice| choice.message.content)
+        .filter(|content| !content.trim().is_empty())
+}
+
+fn minutes_or_fallback(response: ZaiResponse, transcript: &str) -> String {
+    match first_choice(response) {
+        Some(minutes) => minutes,
+        None => {
+            println!("[WARN] z.ai returned empty minutes, falling back to a local extractive summary");
+            format!(
+                "📋 **Key points** (extracted automatically; the summarizer returned nothing)\n{}",
+                extractive_summary(transcript, EXTRACTIVE_SUMMARY_LINES)
+            )
+        }
+    }
+}
+
+/// Lines kept by the local extractive summary
+pub const EXTRACTIVE_SUMMARY_LINES: usize = 8;
+
+/// Words used for scoring: lowercase words of 3+ letters, or character
+/// pairs for scripts written without spaces
+fn summary_terms(text: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+    for word in text.split(|c: char| !c.is_alphanumeric()) {
+        let chars: Vec<char> = word.chars().collect();
+        if chars.iter().any(|c| (*c as u32) >= 0x3000) {
+            terms.extend(chars.windows(2).map(|pair| pair.iter().collect::<String>()));
+        } else if chars.len() >= 3 {
+            terms.push(word.to_lowercase());
+        }
+    }
+    terms
+}
+
+/// Summarize without a model: keep the `max_lines` transcript lines whose words
+/// come up most across the meeting, in their original order
+pub fn extractive_summary(transcript: &str, max_lines: usize) -> String {
+    use std::collections::HashMap;
+
+    let lines: Vec<&str> = transcript
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with("__"))
+        .collect();
+
+    let mut frequency: HashMap<String, usize> = HashMap::new();
+    for line in &lines {
+        for term in summary_terms(line) {
+            *frequency.entry(term).or_default() += 1;
         }
     }
+
+    // Average term frequency, so long rambling lines don't win by length alone
+    let mut scored: Vec<(usize, f32)> = lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| {
+            let terms = summary_terms(line);
+            let total: usize = terms.iter().map(|term| frequency[term]).sum();
+            (i, total as f32 / terms.len().max(1) as f32)
+        })
+        .collect();
+    scored.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+
+    let mut keep: Vec<usize> = scored.into_iter().take(max_lines).map(|(i, _)| i).collect();
+    keep.sort_unstable();
+    keep.into_iter()
+        .map(|i| format!("- {}", lines[i]))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Parse the model's action item JSON. Accepts a bare array or an
+/// `{"action_items": [...]}` object, optionally wrapped in a code fence or prose.
+pub fn parse_action_items(response: &str) -> Option<Vec<ActionItem>> {
+    #[derive(Deserialize)]
+    struct Wrapped {
+        action_items: Vec<ActionItem>,
+    }
+
+    let trimmed = response.trim();
+    if let Ok(items) = serde_json::from_str::<Vec<ActionItem>>(trimmed) {
+        return Some(items);
+    }
+    if let Ok(wrapped) = serde_json::from_str::<Wrapped>(trimmed) {
+        return Some(wrapped.action_items);
+    }
+
+    // Fall back to the outermost array, ignoring fences and surrounding text
+    let start = trimmed.find('[')?;
+    let end = trimmed.rfind(']')?;
+    serde_json::from_str(trimmed.get(start..=end)?).ok()
+}
+
+/// Action items as Markdown bullets for posting to Discord
+pub fn format_action_items(items: &[ActionItem]) -> String {
+    items
+        .iter()
+        .map(|item| match &item.due {
+            Some(due) => format!("- **{}**: {} (due: {})", item.assignee, item.task, due),
+            None => format!("- **{}**: {}", item.assignee, item.task),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Longest sample text `/dryrun_summary` accepts
+pub const MAX_DRYRUN_CHARS: usize = 50_000;
+/// Minutes are split into messages of at most this many characters
+const DRYRUN_MESSAGE_CHARS: usize = 1900;
+
+/// `/dryrun_summary`: minutes for pasted text, split into Discord messages.
+/// `summarize` is the summarizer call, normally `summarize_meeting` with the guild's template.
+pub async fn dry_run_summary<F, Fut>(text: &str, summarize: F) -> Result<Vec<String>, AppError>
+where
+    F: FnOnce(String) -> Fut,
+    Fut: std::future::Future<Output = Result<String, AppError>>,
+{
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(AppError::Summarization("Paste some text or attach a text file".to_string()));
+    }
+    if text.chars().count() > MAX_DRYRUN_CHARS {
+        return Err(AppError::Summarization(format!("The text is longer than {} characters", MAX_DRYRUN_CHARS)));
+    }
+
+    let minutes = summarize(text.to_string()).await?;
+    let mut messages = crate::translator::chunk_text(&minutes, DRYRUN_MESSAGE_CHARS);
+    match messages.first_mut() {
+        Some(first) => first.insert_str(0, "🧪 **Dry-run minutes** (nothing was recorded)\n\n"),
+        None => messages.push("🧪 **Dry-run minutes**: the summarizer returned nothing".to_string()),
+    }
+    Ok(messages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_korean_minutes_request() {
+        let summarizer = Summarizer::new("test".to_string()).with_output_language("ko");
+        let body = serde_json::to_string(&summarizer.build_minutes_request("안녕하세요", None)).unwrap();
+
+        for header in ["개요", "참석자", "논의 내용", "결정 사항", "액션 아이템"] {
+            assert!(body.contains(header), "missing section header {}", header);
+        }
+        assert!(body.contains("한국어로 답변해 주세요"));
+        assert!(body.contains("안녕하세요"));
+    }
+
+    #[test]
+    fn test_parse_action_items() {
+        let response = r#"[
+            {"assignee": "Alice", "task": "Send the slides", "due": "Friday"},
+            {"assignee": "Bob", "task": "Book a room", "due": null},
+            {"assignee": "Unassigned", "task": "Update the wiki"}
+        ]"#;
+        let items = parse_action_items(response).unwrap();
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[0], ActionItem {
+            assignee: "Alice".to_string(),
+            task: "Send the slides".to_string(),
+            due: Some("Friday".to_string()),
+        });
+        assert_eq!(items[1].due, None);
+        assert_eq!(items[2].due, None);
+
+        // Wrapped objects and fenced output are accepted too
+        let wrapped = r#"{"action_items": [{"assignee": "Carol", "task": "Review PR"}]}"#;
+        assert_eq!(parse_action_items(wrapped).unwrap()[0].assignee, "Carol");
+        let fenced = "Here you go:\n```json\n[{\"assignee\": \"Dan\", \"task\": \"Ship it\"}]\n```";
+        assert_eq!(parse_action_items(fenced).unwrap()[0].task, "Ship it");
+
+        assert_eq!(parse_action_items("[]"), Some(Vec::new()));
+        assert_eq!(parse_action_items("No action items were found."), None);
+        assert_eq!(parse_action_items(r#"[{"who": "Alice"}]"#), None);
+
+        assert_eq!(
+            format_action_items(&items[..2]),
+            "- **Alice**: Send the slides (due: Friday)\n- **Bob**: Book a room"
+        );
+    }
+
+    #[test]
+    fn test_japanese_minutes_is_default() {
+        let summarizer = Summarizer::new("test".to_string());
+        let body = serde_json::to_string(&summarizer.build_minutes_request("テスト", None)).unwrap();
+        assert!(body.contains("会議概要"));
+        assert!(!body.contains("개요"));
+    }
+
+    #[test]
+    fn test_custom_minutes_template() {
+        let summarizer = Summarizer::new("test".to_string());
+        let template = "Decisions first, then risks.\n\n{transcript}\n\nEnd with open questions.";
+        assert!(validate_prompt_template(template).is_ok());
+
+        let request = summarizer.build_minutes_request("[Alice] ship Friday", Some(template));
+        assert_eq!(
+            request.messages[1].content,
+            "Decisions first, then risks.\n\n[Alice] ship Friday\n\nEnd with open questions."
+        );
+        assert!(!request.messages[1].content.contains("会議概要"));
+
+        assert!(validate_prompt_template("Summarize the meeting").unwrap_err().contains("{transcript}"));
+        let too_long = format!("{}{}", TRANSCRIPT_PLACEHOLDER, "x".repeat(MAX_PROMPT_TEMPLATE_CHARS));
+        assert!(validate_prompt_template(&too_long).is_err());
+    }
+
+    #[test]
+    fn test_empty_model_output_falls_back_to_extractive_summary() {
+        let transcript = "**[Alice]**: The release is blocked on the database migration\n\
+            **[Bob]**: ok\n\
+            **[Alice]**: We ship the release after the migration finishes\n\
+            **[Bob]**: The migration needs a database backup first";
+
+        let empty: ZaiResponse = serde_json::from_str(r#"{"choices": []}"#).unwrap();
+        let minutes = minutes_or_fallback(empty, transcript);
+        assert!(minutes.starts_with("📋 **Key points**"));
+        assert!(minutes.contains("- **[Alice]**: The release is blocked on the database migration"));
+
+        let blank: ZaiResponse = serde_json::from_str(r#"{"choices": [{"message": {"content": "  "}}]}"#).unwrap();
+        assert!(minutes_or_fallback(blank, transcript).starts_with("📋 **Key points**"));
+
+        let answered: ZaiResponse = serde_json::from_str(r#"{"choices": [{"message": {"content": "Minutes"}}]}"#).unwrap();
+        assert_eq!(minutes_or_fallback(answered, transcript), "Minutes");
+
+        // The filler line scores lowest and is the first to go
+        let summary = extractive_summary(transcript, 3);
+        assert_eq!(summary.lines().count(), 3);
+        assert!(!summary.contains("**[Bob]**: ok"));
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_passes_text_to_summarizer() {
+        let mut received = None;
+        let messages = dry_run_summary("  **[Alice]**: ship on Friday  ", |text| {
+            received = Some(text);
+            async { Ok(format!("{}\n{}", "a".repeat(1500), "b".repeat(1500))) }
+        }).await.unwrap();
+
+        assert_eq!(received.as_deref(), Some("**[Alice]**: ship on Friday"));
+        assert_eq!(messages.len(), 2);
+        assert!(messages[0].starts_with("🧪 **Dry-run minutes**"));
+        assert_eq!(messages[1], "b".repeat(1500));
+
+        let called = std::cell::Cell::new(false);
+        let empty = dry_run_summary(" ", |_| {
+            called.set(true);
+            async { Ok(String::new()) }
+        }).await;
+        assert!(empty.is_err());
+        assert!(!called.get());
+    }
 }
@@ -0,0 +1,96 @@
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone};
+use chrono_tz::Tz;
+use std::sync::OnceLock;
+
+/// Zone used for timestamps shown to users and in file names
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BotTimezone {
+    /// The server's local zone
+    #[default]
+    Local,
+    Named(Tz),
+}
+
+impl BotTimezone {
+    /// Parse an IANA name such as `Asia/Tokyo`; empty means the server's zone
+    pub fn parse(name: &str) -> Result<Self, String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Ok(BotTimezone::Local);
+        }
+        name.parse::<Tz>()
+            .map(BotTimezone::Named)
+            .map_err(|_| format!("Unknown timezone `{}`", name))
+    }
+
+    /// Format an instant in this zone
+    pub fn format<T: TimeZone>(&self, instant: &DateTime<T>, fmt: &str) -> String {
+        match self {
+            BotTimezone::Local => instant.with_timezone(&Local).format(fmt).to_string(),
+            BotTimezone::Named(tz) => instant.with_timezone(tz).format(fmt).to_string(),
+        }
+    }
+
+    /// The calendar date `instant` falls on in this zone
+    pub fn date_of<T: TimeZone>(&self, instant: &DateTime<T>) -> NaiveDate {
+        match self {
+            BotTimezone::Local => instant.with_timezone(&Local).date_naive(),
+            BotTimezone::Named(tz) => instant.with_timezone(tz).date_naive(),
+        }
+    }
+
+    /// The instant a wall-clock time in this zone names. A time repeated by a DST change
+    /// is its earlier occurrence; one skipped by it is None.
+    pub fn resolve(&self, at: NaiveDateTime) -> Option<DateTime<Local>> {
+        match self {
+            BotTimezone::Local => Local.from_local_datetime(&at).earliest(),
+            BotTimezone::Named(tz) => tz.from_local_datetime(&at).earliest().map(|at| at.with_timezone(&Local)),
+        }
+    }
+}
+
+static BOT_TIMEZONE: OnceLock<BotTimezone> = OnceLock::new();
+
+/// Read `BOT_TIMEZONE` once at startup; an invalid name is reported and ignored
+pub fn init_from_env() {
+    let configured = match std::env::var("BOT_TIMEZONE") {
+        Ok(name) => BotTimezone::parse(&name).unwrap_or_else(|e| {
+            eprintln!("[ERROR] {}; using the server's local time", e);
+            BotTimezone::Local
+        }),
+        Err(_) => BotTimezone::Local,
+    };
+    if let BotTimezone::Named(tz) = configured {
+        println!("[INFO] Showing timestamps in {}", tz);
+    }
+    let _ = BOT_TIMEZONE.set(configured);
+}
+
+/// The zone read by [`init_from_env`]
+pub fn configured() -> BotTimezone {
+    BOT_TIMEZONE.get().copied().unwrap_or_default()
+}
+
+/// Format an instant in the configured zone
+pub fn format<T: TimeZone>(instant: &DateTime<T>, fmt: &str) -> String {
+    configured().format(instant, fmt)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_format_in_configured_zone() {
+        let instant = Utc.with_ymd_and_hms(2024, 5, 1, 23, 30, 0).unwrap();
+
+        let tokyo = BotTimezone::parse("Asia/Tokyo").unwrap();
+        let new_york = BotTimezone::parse(" America/New_York ").unwrap();
+        assert_eq!(tokyo.format(&instant, "%Y%m%d_%H%M%S"), "20240502_083000");
+        assert_eq!(new_york.format(&instant, "%Y-%m-%d %H:%M %Z"), "2024-05-01 19:30 EDT");
+
+        assert_eq!(BotTimezone::parse("").unwrap(), BotTimezone::Local);
+        assert!(BotTimezone::parse("Mars/Olympus").is_err());
+    }
+}
@@ -1,5 +1,83 @@
 use whisper_rs::{WhisperContext, WhisperContextParameters, FullParams, SamplingStrategy};
+use futures::stream::{self, StreamExt};
+use std::collections::HashMap;
+use std::future::Future;
 use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+use crate::error::AppError;
+use crate::metrics;
+
+/// Recordings transcribed at the same time after a meeting
+pub const MAX_CONCURRENT_TRANSCRIPTIONS: usize = 3;
+
+/// Segments whose mean token probability falls below this are flagged for review
+pub const LOW_CONFIDENCE_THRESHOLD: f32 = 0.5;
+
+/// Failed files in a row before a meeting's transcription is abandoned
+pub const DEFAULT_MAX_CONSECUTIVE_ERRORS: usize = 3;
+
+/// How long a single transcription may run before it is abandoned
+pub const DEFAULT_TRANSCRIPTION_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Audio quieter than this RMS is treated as silence
+pub const VAD_RMS_FLOOR: f32 = 0.005;
+
+/// Speaker recordings shorter than this aren't worth transcribing
+pub const DEFAULT_MIN_RECORDING_AUDIO: Duration = Duration::from_secs(1);
+
+/// 16 kHz audio longer than this (one minute) is transcribed in overlapping chunks
+pub const LONG_AUDIO_THRESHOLD: usize = 16_000 * 60;
+/// Whisper's native window
+const CHUNK_SAMPLES: usize = 16_000 * 30;
+/// Shared between neighbouring chunks so words at a boundary aren't cut in half
+const CHUNK_OVERLAP_SAMPLES: usize = 16_000 * 2;
+/// Rate songbird decodes received voice at; recordings and translation buffers use it too
+pub const DECODE_SAMPLE_RATE: u32 = 48_000;
+/// Rate whisper expects its input at
+pub const WHISPER_SAMPLE_RATE: u32 = 16_000;
+/// Whisper timestamps are in centiseconds
+const SAMPLES_PER_CENTISECOND: usize = 160;
+/// RMS window (100ms at 16 kHz) used to find silences in a recording
+const SILENCE_WINDOW_SAMPLES: usize = 1600;
+
+/// Why a speaker's recording was left out of transcription
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkipReason {
+    TooShort,
+    TooQuiet,
+}
+
+impl std::fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SkipReason::TooShort => write!(f, "too short"),
+            SkipReason::TooQuiet => write!(f, "too quiet"),
+        }
+    }
+}
+
+/// Whether decoded mono audio should be skipped instead of transcribed.
+/// A zero `min_duration` disables the check.
+pub fn recording_skip_reason(samples: &[i16], min_duration: Duration) -> Option<SkipReason> {
+    skip_reason_for(samples.len(), || compute_rms(&convert_i16_to_f32(samples)), min_duration)
+}
+
+/// `recording_skip_reason` for audio that isn't all in memory: its length in
+/// samples and a way to get its RMS
+pub fn skip_reason_for(len: usize, rms: impl FnOnce() -> f32, min_duration: Duration) -> Option<SkipReason> {
+    if min_duration.is_zero() {
+        return None;
+    }
+    if (len as f64 / DECODE_SAMPLE_RATE as f64) < min_duration.as_secs_f64() {
+        return Some(SkipReason::TooShort);
+    }
+    if rms() < VAD_RMS_FLOOR {
+        return Some(SkipReason::TooQuiet);
+    }
+    None
+}
 
 const LANGUAGE_CODES: &[&str] = &[
     "en", "zh", "de", "es", "ru", "ko", "fr", "ja", "pt", "tr", "pl", "ca", "nl", "ar", "sv",
@@ -11,18 +89,174 @@ const LANGUAGE_CODES: &[&str] = &[
     "mg", "as", "tt", "haw", "ln", "ha", "ba", "jw", "su",
 ];
 
+/// Whisper only looks at the last ~224 prompt tokens; longer glossaries are cut here
+const MAX_INITIAL_PROMPT_CHARS: usize = 800;
+
+/// `no_speech_thold` for languages without their own value
+pub const DEFAULT_NO_SPEECH_THOLD: f32 = 0.6;
+
+/// Per-language `no_speech_thold`: segments whisper thinks are more likely
+/// silence than this are dropped, so higher keeps more quiet speech
+#[derive(Debug, Clone, PartialEq)]
+pub struct NoSpeechThresholds {
+    default: f32,
+    per_language: HashMap<String, f32>,
+}
+
+impl Default for NoSpeechThresholds {
+    fn default() -> Self {
+        Self { default: DEFAULT_NO_SPEECH_THOLD, per_language: HashMap::new() }
+    }
+}
+
+impl NoSpeechThresholds {
+    /// Parse overrides like `0.6,ja=0.8,en=0.5`: a bare number replaces the
+    /// default and `lang=value` entries replace or add a language's value
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut thresholds = Self::default();
+        for entry in spec.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+            let (language, value) = match entry.split_once('=') {
+                Some((language, value)) => (Some(language.trim().to_lowercase()), value.trim()),
+                None => (None, entry),
+            };
+            let thold: f32 = value.parse()
+                .ok()
+                .filter(|thold| (0.0..=1.0).contains(thold))
+                .ok_or_else(|| format!("Invalid no-speech threshold `{}`; use a number from 0 to 1", entry))?;
+            match language {
+                Some(language) => { thresholds.per_language.insert(language, thold); }
+                None => thresholds.default = thold,
+            }
+        }
+        Ok(thresholds)
+    }
+
+    /// Threshold for a chosen or detected language; the default when it's unknown
+    pub fn for_language(&self, language: Option<&str>) -> f32 {
+        language
+            .and_then(|language| self.per_language.get(language))
+            .copied()
+            .unwrap_or(self.default)
+    }
+}
+
+/// The decode settings that vary per call, so they can be checked without loading a model
+trait DecodeParams<'a> {
+    fn language(&mut self, language: &'a str);
+    fn initial_prompt(&mut self, prompt: &str);
+    fn no_speech_thold(&mut self, thold: f32);
+}
+
+impl<'a, 'b> DecodeParams<'a> for FullParams<'a, 'b> {
+    fn language(&mut self, language: &'a str) {
+        self.set_language(Some(language));
+    }
+
+    fn initial_prompt(&mut self, prompt: &str) {
+        self.set_initial_prompt(prompt);
+    }
+
+    fn no_speech_thold(&mut self, thold: f32) {
+        self.set_no_speech_thold(thold);
+    }
+}
+
+/// Apply the language, its no-speech threshold and the optional initial prompt
+/// (glossary of expected terms)
+fn apply_decode_context<'a>(
+    params: &mut impl DecodeParams<'a>,
+    language: Option<&'a str>,
+    initial_prompt: Option<&str>,
+    no_speech: &NoSpeechThresholds,
+) {
+    if let Some(lang) = language {
+        params.language(lang);
+    }
+    params.no_speech_thold(no_speech.for_language(language));
+    // whisper-rs panics on interior NULs
+    let prompt: String = initial_prompt
+        .unwrap_or_default()
+        .trim()
+        .chars()
+        .filter(|c| *c != '\0')
+        .take(MAX_INITIAL_PROMPT_CHARS)
+        .collect();
+    if !prompt.is_empty() {
+        params.initial_prompt(&prompt);
+    }
+}
+
+/// One whisper segment with its timing (in centiseconds) and confidence
+#[derive(Debug, Clone, PartialEq)]
+pub struct TranscriptSegment {
+    pub start: i64,
+    pub end: i64,
+    pub text: String,
+    /// Mean token probability, 0.0-1.0
+    pub confidence: f32,
+}
+
+impl TranscriptSegment {
+    pub fn is_low_confidence(&self) -> bool {
+        self.confidence < LOW_CONFIDENCE_THRESHOLD
+    }
+}
+
+/// Speaker-labeled transcript lines, one per segment.
+/// Low-confidence segments are prefixed with ⚠️ so reviewers know to double-check them.
+pub fn format_labeled_transcript(speaker_name: &str, segments: &[TranscriptSegment]) -> String {
+    segments
+        .iter()
+        .filter(|segment| !segment.text.trim().is_empty())
+        .map(|segment| {
+            let marker = if segment.is_low_confidence() { "⚠️ " } else { "" };
+            format!("**[{}]**: {}{}", speaker_name, marker, segment.text.trim())
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 fn get_lang_str_from_id(lang_id: i32) -> &'static str {
     LANGUAGE_CODES.get(lang_id as usize).copied().unwrap_or("en")
 }
 
+/// Try each path in order and return the first one `load` accepts, logging the failures.
+/// The error of the last attempt is returned if none load.
+fn load_first<T>(
+    paths: &[&str],
+    mut load: impl FnMut(&str) -> Result<T, AppError>,
+) -> Result<T, AppError> {
+    let mut last_error = None;
+    for (index, path) in paths.iter().enumerate() {
+        match load(path) {
+            Ok(loaded) => {
+                if index > 0 {
+                    println!("[WARN] Running in degraded mode with fallback Whisper model {}", path);
+                }
+                return Ok(loaded);
+            }
+            Err(e) => {
+                eprintln!("[WARN] Failed to load Whisper model {}: {}", path, e);
+                last_error = Some(e);
+            }
+        }
+    }
+    Err(last_error.unwrap_or_else(|| AppError::ModelMissing("(no paths given)".to_string())))
+}
+
 pub struct Transcriber {
     ctx: WhisperContext,
+    model_path: String,
+    timeout: Duration,
+    /// Split recordings at silences at least this long; None transcribes each file whole
+    silence_split: Option<Duration>,
+    no_speech: NoSpeechThresholds,
 }
 
 impl Transcriber {
-    pub fn new(model_path: &str) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+    pub fn new(model_path: &str) -> Result<Self, AppError> {
         if !Path::new(model_path).exists() {
-            return Err(format!("Whisper model not found at: {}", model_path).into());
+            return Err(AppError::ModelMissing(model_path.to_string()));
         }
 
         let ctx = WhisperContext::new_with_params(
@@ -30,52 +264,105 @@ impl Transcriber {
             WhisperContextParameters::default(),
         )?;
 
-        Ok(Self { ctx })
+        Ok(Self {
+            ctx,
+            model_path: model_path.to_string(),
+            timeout: DEFAULT_TRANSCRIPTION_TIMEOUT,
+            silence_split: None,
+            no_speech: NoSpeechThresholds::default(),
+        })
+    }
+
+    /// Load the first model in `paths` that loads, so a missing preferred model
+    /// (e.g. an unquantized download) doesn't stop the bot from starting
+    pub fn new_with_fallback(paths: &[&str]) -> Result<Self, AppError> {
+        load_first(paths, Self::new)
+    }
+
+    /// File name of the loaded model, e.g. `ggml-base.bin`
+    pub fn model_name(&self) -> &str {
+        Path::new(&self.model_path)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or(&self.model_path)
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn timeout(&self) -> Duration {
+        self.timeout
     }
 
-    pub fn transcribe(&self, audio_data: &[f32], language: Option<&str>) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-        let (text, _) = self.transcribe_with_language(audio_data, language)?;
-        Ok(text)
+    /// Transcribe each utterance of a recording separately, splitting at silences of `min_gap`
+    pub fn with_silence_split(mut self, min_gap: Option<Duration>) -> Self {
+        self.silence_split = min_gap.filter(|gap| !gap.is_zero());
+        self
+    }
+
+    pub fn with_no_speech_thresholds(mut self, no_speech: NoSpeechThresholds) -> Self {
+        self.no_speech = no_speech;
+        self
+    }
+
+    /// `transcribe_with_language` on a blocking thread, abandoned after the configured timeout
+    pub async fn transcribe_with_language_async(
+        self: &Arc<Self>,
+        audio_data: Vec<f32>,
+        language: Option<String>,
+        initial_prompt: Option<String>,
+    ) -> Result<(String, String), AppError> {
+        let transcriber = self.clone();
+        let task = tokio::task::spawn_blocking(move || {
+            transcriber.transcribe_with_language(&audio_data, language.as_deref(), initial_prompt.as_deref())
+        });
+        with_timeout(self.timeout, async { task.await? }).await
+    }
+
+    /// `transcribe_scored` on a blocking thread, abandoned after the configured timeout
+    pub async fn transcribe_scored_async(
+        self: &Arc<Self>,
+        audio_data: Vec<f32>,
+        language: Option<String>,
+        initial_prompt: Option<String>,
+    ) -> Result<(String, String, f32), AppError> {
+        let transcriber = self.clone();
+        let task = tokio::task::spawn_blocking(move || {
+            transcriber.transcribe_scored(&audio_data, language.as_deref(), initial_prompt.as_deref())
+        });
+        with_timeout(self.timeout, async { task.await? }).await
     }
 
     /// Transcribe audio and return (text, detected_language_code)
-    /// If language is None, auto-detects the language
-    pub fn transcribe_with_language(&self, audio_data: &[f32], language: Option<&str>) -> Result<(String, String), Box<dyn std::error::Error + Send + Sync>> {
+    /// If language is None, auto-detects the language. `initial_prompt` primes the
+    /// transcription pass with expected names and terms.
+    pub fn transcribe_with_language(
+        &self,
+        audio_data: &[f32],
+        language: Option<&str>,
+        initial_prompt: Option<&str>,
+    ) -> Result<(String, String), AppError> {
+        self.transcribe_scored(audio_data, language, initial_prompt)
+            .map(|(text, language, _)| (text, language))
+    }
+
+    /// `transcribe_with_language` plus the mean probability of the transcribed tokens
+    pub fn transcribe_scored(
+        &self,
+        audio_data: &[f32],
+        language: Option<&str>,
+        initial_prompt: Option<&str>,
+    ) -> Result<(String, String, f32), AppError> {
         if audio_data.is_empty() {
-            return Ok((String::new(), "en".to_string()));
+            return Ok((String::new(), "en".to_string(), 1.0));
         }
 
         // First pass: auto-detect language
-        let detected_lang = if let Some(lang) = language {
-            lang.to_string()
-        } else {
-            let mut state = self.ctx.create_state()?;
-            let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
-            
-            // First pass without language hint to detect language
-            params.set_translate(false);
-            params.set_print_special(false);
-            params.set_print_progress(false);
-            params.set_print_realtime(false);
-            params.set_print_timestamps(false);
-            params.set_no_context(true);
-            params.set_suppress_blank(true);
-            params.set_suppress_nst(true);
-            params.set_temperature(0.0);
-            params.set_no_speech_thold(0.6);
-            
-            state.full(params, audio_data)?;
-            
-            match state.lang_detect(0, 4) {
-                Ok((lang_id, _probs)) => {
-                    get_lang_str_from_id(lang_id).to_string()
-                }
-                Err(_) => {
-                    // Fallback to local detection based on text content
-                    let text = self.extract_text(&state)?;
-                    Self::detect_language_local(&text)
-                }
-            }
+        let detected_lang = match language {
+            Some(lang) => lang.to_string(),
+            None => self.detect_language(audio_data)?,
         };
 
         // Second pass: transcribe with detected language
@@ -83,7 +370,7 @@ impl Transcriber {
         let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
         
         // Set the detected language for transcription
-        params.set_language(Some(&detected_lang));
+        apply_decode_context(&mut params, Some(&detected_lang), initial_prompt, &self.no_speech);
         params.set_translate(false);
         params.set_print_special(false);
         params.set_print_progress(false);
@@ -93,15 +380,45 @@ impl Transcriber {
         params.set_suppress_blank(true);
         params.set_suppress_nst(true);
         params.set_temperature(0.0);
-        params.set_no_speech_thold(0.6);
 
         state.full(params, audio_data)?;
         let transcription = self.extract_text(&state)?;
-        
-        Ok((transcription, detected_lang))
+        let confidence = self.overall_confidence(&state)?;
+
+        Ok((transcription, detected_lang, confidence))
     }
 
-    fn extract_text(&self, state: &whisper_rs::WhisperState) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    /// Whisper's guess at the spoken language, e.g. `en`
+    pub fn detect_language(&self, audio_data: &[f32]) -> Result<String, AppError> {
+        let mut state = self.ctx.create_state()?;
+        let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
+
+        // A pass without a language hint to detect the language
+        params.set_translate(false);
+        params.set_print_special(false);
+        params.set_print_progress(false);
+        params.set_print_realtime(false);
+        params.set_print_timestamps(false);
+        params.set_no_context(true);
+        params.set_suppress_blank(true);
+        params.set_suppress_nst(true);
+        params.set_temperature(0.0);
+        // The language isn't known yet
+        params.set_no_speech_thold(self.no_speech.for_language(None));
+
+        state.full(params, audio_data)?;
+
+        match state.lang_detect(0, 4) {
+            Ok((lang_id, _probs)) => Ok(get_lang_str_from_id(lang_id).to_string()),
+            Err(_) => {
+                // Fallback to local detection based on text content
+                let text = self.extract_text(&state)?;
+                Ok(Self::detect_language_local(&text))
+            }
+        }
+    }
+
+    fn extract_text(&self, state: &whisper_rs::WhisperState) -> Result<String, AppError> {
         let num_segments = state.full_n_segments()?;
         let mut transcription = String::new();
 
@@ -142,7 +459,12 @@ impl Transcriber {
         }
     }
 
-    pub fn transcribe_with_timestamps(&self, audio_data: &[f32], language: Option<&str>) -> Result<Vec<(i64, i64, String)>, Box<dyn std::error::Error + Send + Sync>> {
+    pub fn transcribe_with_timestamps(
+        &self,
+        audio_data: &[f32],
+        language: Option<&str>,
+        initial_prompt: Option<&str>,
+    ) -> Result<Vec<TranscriptSegment>, AppError> {
         if audio_data.is_empty() {
             return Ok(Vec::new());
         }
@@ -151,15 +473,17 @@ impl Transcriber {
 
         let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
         
-        if let Some(lang) = language {
-            params.set_language(Some(lang));
-        }
+        apply_decode_context(&mut params, language, initial_prompt, &self.no_speech);
         
         params.set_translate(false);
         params.set_print_special(false);
         params.set_print_progress(false);
         params.set_print_realtime(false);
-        params.set_print_timestamps(true);
+        params.set_print_timestamps(false);
+        params.set_no_context(true);
+        params.set_suppress_blank(true);
+        params.set_suppress_nst(true);
+        params.set_temperature(0.0);
 
         state.full(params, audio_data)?;
 
@@ -172,12 +496,123 @@ impl Transcriber {
             let end = state.full_get_segment_t1(i)?;
             
             if !text.trim().is_empty() {
-                segments.push((start, end, text));
+                segments.push(TranscriptSegment {
+                    start,
+                    end,
+                    text,
+                    confidence: self.segment_confidence(&state, i)?,
+                });
             }
         }
 
         Ok(segments)
     }
+
+    /// `transcribe_with_timestamps` for audio longer than whisper's 30-second window:
+    /// overlapping chunks are transcribed one by one and stitched back together,
+    /// stopping between chunks once `cancel` fires
+    pub fn transcribe_long(
+        &self,
+        audio_data: &[f32],
+        language: Option<&str>,
+        initial_prompt: Option<&str>,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<TranscriptSegment>, AppError> {
+        transcribe_chunked(audio_data, CHUNK_SAMPLES, CHUNK_OVERLAP_SAMPLES, |chunk| {
+            check_cancelled(cancel)?;
+            self.transcribe_with_timestamps(chunk, language, initial_prompt)
+        })
+    }
+
+    /// Mean probability of every text token in the transcription
+    fn overall_confidence(&self, state: &whisper_rs::WhisperState) -> Result<f32, AppError> {
+        let mut tokens = Vec::new();
+        for segment in 0..state.full_n_segments()? {
+            tokens.extend(self.segment_tokens(state, segment)?);
+        }
+        Ok(mean_text_probability(&tokens, self.ctx.token_eot()))
+    }
+
+    /// Mean probability of a segment's text tokens
+    fn segment_confidence(&self, state: &whisper_rs::WhisperState, segment: i32) -> Result<f32, AppError> {
+        let tokens = self.segment_tokens(state, segment)?;
+        Ok(mean_text_probability(&tokens, self.ctx.token_eot()))
+    }
+
+    /// `(id, probability)` of each token in a segment
+    fn segment_tokens(&self, state: &whisper_rs::WhisperState, segment: i32) -> Result<Vec<(i32, f32)>, AppError> {
+        let mut tokens = Vec::new();
+        for token in 0..state.full_n_tokens(segment)? {
+            tokens.push((state.full_get_token_id(segment, token)?, state.full_get_token_prob(segment, token)?));
+        }
+        Ok(tokens)
+    }
+}
+
+/// Mean probability of the text tokens among `tokens`. Whisper numbers its special
+/// tokens (start/end of transcript, language, timestamps) from `eot` up; they're
+/// nearly always certain and would hide an unsure transcription. 1.0 without text.
+fn mean_text_probability(tokens: &[(i32, f32)], eot: i32) -> f32 {
+    let text: Vec<f32> = tokens.iter().filter(|(id, _)| *id < eot).map(|(_, prob)| *prob).collect();
+    if text.is_empty() {
+        return 1.0;
+    }
+    text.iter().sum::<f32>() / text.len() as f32
+}
+
+/// Accurate model loaded from `WHISPER_MODEL_PATH`, used for recordings by default
+pub const MODEL_BASE: &str = "base";
+
+/// Quick model loaded from `WHISPER_MODEL_FAST_PATH`, used for live translation by default
+pub const MODEL_FAST: &str = "fast";
+
+/// Real-time speech the fast model is unsure about is transcribed again with this model
+#[derive(Clone)]
+pub struct Escalation {
+    pub transcriber: Arc<Transcriber>,
+    /// Mean token probability below which the fast result isn't trusted
+    pub threshold: f32,
+}
+
+/// Whether a fast-model result should be redone by the accurate model. Empty text
+/// is silence, which the accurate model wouldn't improve on.
+pub fn should_escalate(text: &str, confidence: f32, threshold: f32) -> bool {
+    !text.trim().is_empty() && confidence < threshold
+}
+
+/// Name of the model to use: the guild's selection if it's loaded, otherwise `default`
+pub fn select_model<'a>(available: &[&'a str], selected: Option<&str>, default: &'a str) -> &'a str {
+    selected
+        .and_then(|name| available.iter().find(|loaded| **loaded == name))
+        .copied()
+        .unwrap_or(default)
+}
+
+/// The loaded whisper models, by name
+pub struct TranscriberRegistry {
+    models: Vec<(&'static str, Arc<Transcriber>)>,
+}
+
+impl TranscriberRegistry {
+    pub fn new(models: Vec<(&'static str, Arc<Transcriber>)>) -> Self {
+        Self { models }
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.models.iter().map(|(name, _)| *name).collect()
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<Transcriber>> {
+        self.models.iter().find(|(loaded, _)| *loaded == name).map(|(_, model)| model.clone())
+    }
+
+    /// Model for a transcription: the guild's selection, or `default` for this use
+    pub fn resolve(&self, selected: Option<&str>, default: &str) -> Arc<Transcriber> {
+        let name = select_model(&self.names(), selected, default);
+        self.get(name)
+            .or_else(|| self.models.first().map(|(_, model)| model.clone()))
+            .expect("at least one whisper model is loaded")
+    }
 }
 
 pub fn convert_i16_to_f32(samples: &[i16]) -> Vec<f32> {
@@ -219,32 +654,771 @@ pub fn is_likely_hallucination(text: &str, duration_ms: u64, rms: f32) -> bool {
     known_phrases.iter().any(|phrase| normalized.contains(phrase))
 }
 
-pub fn downsample_48k_to_16k(samples: &[f32]) -> Vec<f32> {
-    samples.iter()
-        .step_by(3)
-        .copied()
+/// Resample `input_rate` audio to whisper's 16 kHz. Whole-number ratios keep every
+/// n-th sample; other rates interpolate linearly between neighbours.
+pub fn downsample_to_16k(samples: &[f32], input_rate: u32) -> Vec<f32> {
+    let input_rate = input_rate as usize;
+    let output_rate = WHISPER_SAMPLE_RATE as usize;
+    if input_rate.is_multiple_of(output_rate) {
+        return samples.iter().step_by(input_rate / output_rate).copied().collect();
+    }
+
+    let len = (samples.len() * output_rate).div_ceil(input_rate);
+    (0..len)
+        .map(|i| {
+            let position = i as f64 * input_rate as f64 / output_rate as f64;
+            let index = position as usize;
+            let fraction = (position - index as f64) as f32;
+            let current = samples[index.min(samples.len() - 1)];
+            let next = samples[(index + 1).min(samples.len() - 1)];
+            current + (next - current) * fraction
+        })
         .collect()
 }
 
+/// `[start, end)` sample ranges of `chunk`-long windows covering `len` samples,
+/// each sharing `overlap` samples with the previous one
+pub fn chunk_windows(len: usize, chunk: usize, overlap: usize) -> Vec<(usize, usize)> {
+    let step = chunk.saturating_sub(overlap).max(1);
+    let mut windows = Vec::new();
+    let mut start = 0;
+    loop {
+        let end = (start + chunk).min(len);
+        windows.push((start, end));
+        if end >= len {
+            return windows;
+        }
+        start += step;
+    }
+}
+
+/// Fewest repeated words trimmed at a boundary; one shared word is usually coincidence
+const MIN_STITCH_OVERLAP_WORDS: usize = 2;
+/// Fewest repeated characters trimmed in text without spaces (Japanese)
+const MIN_STITCH_OVERLAP_CHARS: usize = 3;
+/// Longest overlap looked for, in words or characters
+const MAX_STITCH_OVERLAP: usize = 30;
+
+/// Length of the longest run at the end of `previous` that `next` starts with
+fn overlap_len<T: PartialEq>(previous: &[T], next: &[T], min: usize) -> usize {
+    let max = MAX_STITCH_OVERLAP.min(previous.len()).min(next.len());
+    (min..=max)
+        .rev()
+        .find(|&n| previous[previous.len() - n..] == next[..n])
+        .unwrap_or(0)
+}
+
+/// `next` without the words (or, for text without spaces, characters) that repeat
+/// the end of `previous`. Words are compared ignoring case and punctuation.
+fn trim_overlap(previous: &str, next: &str) -> String {
+    let next = next.trim();
+    if next.contains(char::is_whitespace) || previous.trim().contains(char::is_whitespace) {
+        let normalize = |word: &str| word.trim_matches(|c: char| !c.is_alphanumeric()).to_lowercase();
+        let previous_words: Vec<String> = previous.split_whitespace().map(normalize).collect();
+        let next_words: Vec<&str> = next.split_whitespace().collect();
+        let normalized: Vec<String> = next_words.iter().map(|word| normalize(word)).collect();
+        let n = overlap_len(&previous_words, &normalized, MIN_STITCH_OVERLAP_WORDS);
+        next_words[n..].join(" ")
+    } else {
+        let previous_chars: Vec<char> = previous.trim().chars().collect();
+        let next_chars: Vec<char> = next.chars().collect();
+        let n = overlap_len(&previous_chars, &next_chars, MIN_STITCH_OVERLAP_CHARS);
+        next_chars[n..].iter().collect()
+    }
+}
+
+/// Join text transcribed from adjacent chunks with blank lines, dropping the words
+/// each chunk repeats from the end of the one before and chunks that only repeat it
+pub fn stitch_segments(segments: Vec<String>) -> String {
+    let mut stitched: Vec<String> = Vec::new();
+    for segment in segments {
+        let text = match stitched.last() {
+            Some(previous) => trim_overlap(previous, &segment),
+            None => segment.trim().to_string(),
+        };
+        if !text.is_empty() && stitched.last() != Some(&text) {
+            stitched.push(text);
+        }
+    }
+    stitched.join("\n\n")
+}
+
+/// Transcribe `audio` window by window and stitch the segments into one timeline.
+/// Each overlap is split at its midpoint: the earlier chunk keeps segments starting
+/// before it and the later chunk those starting after, so nothing is repeated.
+/// Words repeated across the cut are trimmed from the later chunk's first segment.
+pub fn transcribe_chunked<F>(
+    audio: &[f32],
+    chunk: usize,
+    overlap: usize,
+    mut transcribe_chunk: F,
+) -> Result<Vec<TranscriptSegment>, AppError>
+where
+    F: FnMut(&[f32]) -> Result<Vec<TranscriptSegment>, AppError>,
+{
+    let windows = chunk_windows(audio.len(), chunk, overlap);
+    let mut stitched: Vec<TranscriptSegment> = Vec::new();
+
+    for (i, &(start, end)) in windows.iter().enumerate() {
+        let offset = (start / SAMPLES_PER_CENTISECOND) as i64;
+        let keep_from = match i {
+            0 => i64::MIN,
+            _ => ((start + windows[i - 1].1) / 2 / SAMPLES_PER_CENTISECOND) as i64,
+        };
+        let keep_until = match windows.get(i + 1) {
+            Some(&(next_start, _)) => ((next_start + end) / 2 / SAMPLES_PER_CENTISECOND) as i64,
+            None => i64::MAX,
+        };
+
+        let mut at_boundary = i > 0;
+        for mut segment in transcribe_chunk(&audio[start..end])? {
+            segment.start += offset;
+            segment.end += offset;
+            if segment.start < keep_from || segment.start >= keep_until {
+                continue;
+            }
+            // A sentence straddling the cut can still come back from both sides
+            if stitched.last().is_some_and(|last| last.text.trim() == segment.text.trim()) {
+                continue;
+            }
+            if std::mem::take(&mut at_boundary) && let Some(last) = stitched.last() {
+                // Whatever follows the previous segment once the pair is stitched is new
+                let joined = stitch_segments(vec![last.text.clone(), segment.text.clone()]);
+                let new_text = joined.strip_prefix(last.text.trim()).unwrap_or(&joined).trim_start();
+                if new_text.is_empty() {
+                    continue;
+                }
+                if new_text != segment.text.trim() {
+                    segment.text = format!(" {}", new_text);
+                }
+            }
+            stitched.push(segment);
+        }
+    }
+
+    Ok(stitched)
+}
+
+/// `[start, end)` ranges of 16 kHz `samples` that contain speech, split wherever
+/// the RMS stays below `VAD_RMS_FLOOR` for at least `min_gap`
+pub fn split_at_silence(samples: &[f32], min_gap: Duration) -> Vec<(usize, usize)> {
+    let gap_windows = ((min_gap.as_secs_f32() * 16_000.0) as usize).div_ceil(SILENCE_WINDOW_SAMPLES).max(1);
+    let mut utterances = Vec::new();
+    let mut start: Option<usize> = None;
+    let mut speech_end = 0;
+    let mut silent_windows = 0;
+
+    for (i, window) in samples.chunks(SILENCE_WINDOW_SAMPLES).enumerate() {
+        let offset = i * SILENCE_WINDOW_SAMPLES;
+        if compute_rms(window) >= VAD_RMS_FLOOR {
+            start.get_or_insert(offset);
+            speech_end = offset + window.len();
+            silent_windows = 0;
+            continue;
+        }
+        silent_windows += 1;
+        if silent_windows == gap_windows
+            && let Some(utterance_start) = start.take()
+        {
+            utterances.push((utterance_start, speech_end));
+        }
+    }
+    if let Some(utterance_start) = start {
+        utterances.push((utterance_start, speech_end));
+    }
+    utterances
+}
+
+/// Transcribe 16 kHz samples, chunking them if they run past whisper's window
+fn transcribe_samples(
+    transcriber: &Transcriber,
+    samples: &[f32],
+    language: Option<&str>,
+    initial_prompt: Option<&str>,
+    cancel: &CancellationToken,
+) -> Result<Vec<TranscriptSegment>, AppError> {
+    if samples.len() > LONG_AUDIO_THRESHOLD {
+        transcriber.transcribe_long(samples, language, initial_prompt, cancel)
+    } else {
+        transcriber.transcribe_with_timestamps(samples, language, initial_prompt)
+    }
+}
+
+/// Segments of one transcribed file
+#[derive(Debug, Clone, Default)]
+pub struct FileTranscript {
+    pub segments: Vec<TranscriptSegment>,
+    /// Language whisper detected; None when the language was given
+    pub detected_language: Option<String>,
+}
+
+/// Blocking transcriptions started for one recording. A timeout or cancellation stops
+/// waiting for a transcription but not its whisper thread, so whatever deletes the
+/// audio waits here first.
+#[derive(Clone, Default)]
+pub struct TranscriptionTasks(Arc<tokio::sync::RwLock<()>>);
+
+impl TranscriptionTasks {
+    /// Held by a transcription's blocking thread until it returns
+    async fn running(&self) -> tokio::sync::OwnedRwLockReadGuard<()> {
+        self.0.clone().read_owned().await
+    }
+
+    /// Wait until every transcription started so far has returned
+    pub async fn finished(&self) {
+        let _ = self.0.write().await;
+    }
+}
+
+/// Transcribe a WAV file on a blocking thread so whisper doesn't stall the runtime.
+/// With no `language`, it is detected first. Once `cancel` fires the thread stops
+/// at the next chunk or utterance instead of transcribing the rest.
 pub async fn transcribe_wav_file(
+    transcriber: Arc<Transcriber>,
+    wav_path: &str,
+    language: Option<String>,
+    initial_prompt: Option<String>,
+    cancel: CancellationToken,
+    tasks: &TranscriptionTasks,
+) -> Result<FileTranscript, AppError> {
+    let timeout = transcriber.timeout();
+    let wav_path = wav_path.to_string();
+    let running = tasks.running().await;
+    let task = tokio::task::spawn_blocking(move || {
+        let _running = running;
+        transcribe_wav_file_blocking(&transcriber, &wav_path, language.as_deref(), initial_prompt.as_deref(), &cancel)
+    });
+    with_timeout(timeout, async { task.await? }).await
+}
+
+/// Give up on a transcription after `limit`. The blocking whisper call can't be
+/// interrupted, so it keeps running in the background and its result is dropped.
+pub async fn with_timeout<T, Fut>(
+    limit: Duration,
+    task: Fut,
+) -> Result<T, AppError>
+where
+    Fut: Future<Output = Result<T, AppError>>,
+{
+    let start = std::time::Instant::now();
+    let result = match tokio::time::timeout(limit, task).await {
+        Ok(result) => result,
+        Err(_) => Err(AppError::TranscriptionTimeout(limit)),
+    };
+    metrics::record_transcription(start.elapsed(), result.is_ok());
+    result
+}
+
+/// Err once `cancel` has fired, for the checkpoints of a blocking transcription
+fn check_cancelled(cancel: &CancellationToken) -> Result<(), AppError> {
+    if cancel.is_cancelled() {
+        return Err(AppError::TranscriptionCancelled);
+    }
+    Ok(())
+}
+
+fn transcribe_wav_file_blocking(
     transcriber: &Transcriber,
     wav_path: &str,
-) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    language: Option<&str>,
+    initial_prompt: Option<&str>,
+    cancel: &CancellationToken,
+) -> Result<FileTranscript, AppError> {
     use hound::WavReader;
     
+    check_cancelled(cancel)?;
     let mut reader = WavReader::open(wav_path)?;
     let spec = reader.spec();
     
     let samples: Vec<i16> = reader.samples::<i16>().filter_map(Result::ok).collect();
     let samples_f32 = convert_i16_to_f32(&samples);
     
-    let final_samples = if spec.sample_rate == 48000 {
-        downsample_48k_to_16k(&samples_f32)
-    } else if spec.sample_rate == 16000 {
-        samples_f32
-    } else {
-        return Err(format!("Unsupported sample rate: {}", spec.sample_rate).into());
+    if spec.sample_rate < WHISPER_SAMPLE_RATE {
+        return Err(AppError::Transcription(format!("Unsupported sample rate: {}", spec.sample_rate)));
+    }
+    let final_samples = downsample_to_16k(&samples_f32, spec.sample_rate);
+
+    // Whisper only listens to the first window when detecting the language anyway
+    let detected_language = match language {
+        Some(_) => None,
+        None => Some(transcriber.detect_language(&final_samples[..final_samples.len().min(CHUNK_SAMPLES)])?),
+    };
+    let language = language.or(detected_language.as_deref());
+    if final_samples.len() > LONG_AUDIO_THRESHOLD {
+        println!(
+            "[INFO] Transcribing {:.0}s of audio from {} in chunks",
+            final_samples.len() as f32 / 16_000.0,
+            wav_path
+        );
+    }
+
+    let Some(min_gap) = transcriber.silence_split else {
+        let segments = transcribe_samples(transcriber, &final_samples, language, initial_prompt, cancel)?;
+        return Ok(FileTranscript { segments, detected_language });
     };
 
-    transcriber.transcribe(&final_samples, Some("ja"))
+    // Each utterance on its own, with timestamps shifted back onto the recording
+    let utterances = split_at_silence(&final_samples, min_gap);
+    println!("[INFO] Split {} into {} utterance(s)", wav_path, utterances.len());
+    let mut segments = Vec::new();
+    for (start, end) in utterances {
+        check_cancelled(cancel)?;
+        let offset = (start / SAMPLES_PER_CENTISECOND) as i64;
+        for mut segment in transcribe_samples(transcriber, &final_samples[start..end], language, initial_prompt, cancel)? {
+            segment.start += offset;
+            segment.end += offset;
+            segments.push(segment);
+        }
+    }
+    Ok(FileTranscript { segments, detected_language })
+}
+
+/// Transcription gave up because too many files failed in a row
+#[derive(Debug, Clone, PartialEq)]
+pub struct TranscriptionAborted {
+    pub failures: usize,
+    pub last_error: String,
+}
+
+impl std::fmt::Display for TranscriptionAborted {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} transcriptions failed in a row (last error: {})", self.failures, self.last_error)
+    }
+}
+
+impl std::error::Error for TranscriptionAborted {}
+
+/// Counts failures in a row; a success resets it. A zero limit never trips.
+#[derive(Debug)]
+pub struct FailureStreak {
+    limit: usize,
+    consecutive: usize,
+}
+
+impl FailureStreak {
+    pub fn new(limit: usize) -> Self {
+        Self { limit, consecutive: 0 }
+    }
+
+    /// Record a failure; returns true once the limit is reached
+    pub fn record_failure(&mut self) -> bool {
+        self.consecutive += 1;
+        self.limit > 0 && self.consecutive >= self.limit
+    }
+
+    pub fn record_success(&mut self) {
+        self.consecutive = 0;
+    }
+
+    pub fn count(&self) -> usize {
+        self.consecutive
+    }
+}
+
+/// Run `transcribe` over `files` with at most `concurrency` in flight.
+/// Results come back in the order of `files`, regardless of which finished first.
+/// After `max_consecutive_errors` failures in a row (in completion order) the rest
+/// are abandoned; 0 never gives up. `on_progress` gets the number of finished files.
+pub async fn transcribe_files_concurrently<T, E, F, Fut, P>(
+    files: &[String],
+    concurrency: usize,
+    max_consecutive_errors: usize,
+    transcribe: F,
+    mut on_progress: P,
+) -> Result<Vec<(String, Result<T, E>)>, TranscriptionAborted>
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    E: std::fmt::Display,
+    P: FnMut(usize),
+{
+    let mut pending = stream::iter(files.iter().cloned().enumerate())
+        .map(|(index, file)| {
+            let task = transcribe(file.clone());
+            async move { (index, file, task.await) }
+        })
+        .buffer_unordered(concurrency.max(1));
+
+    let mut streak = FailureStreak::new(max_consecutive_errors);
+    let mut results = Vec::with_capacity(files.len());
+    while let Some((index, file, result)) = pending.next().await {
+        match &result {
+            Ok(_) => streak.record_success(),
+            Err(e) => {
+                if streak.record_failure() {
+                    return Err(TranscriptionAborted {
+                        failures: streak.count(),
+                        last_error: e.to_string(),
+                    });
+                }
+            }
+        }
+        results.push((index, file, result));
+        on_progress(results.len());
+    }
+
+    results.sort_by_key(|(index, _, _)| *index);
+    Ok(results.into_iter().map(|(_, file, result)| (file, result)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn segment(text: &str, confidence: f32) -> TranscriptSegment {
+        TranscriptSegment { start: 0, end: 100, text: text.to_string(), confidence }
+    }
+
+    #[derive(Default)]
+    struct RecordedParams {
+        language: Option<String>,
+        initial_prompt: Option<String>,
+        no_speech_thold: Option<f32>,
+    }
+
+    impl<'a> DecodeParams<'a> for RecordedParams {
+        fn language(&mut self, language: &'a str) {
+            self.language = Some(language.to_string());
+        }
+
+        fn initial_prompt(&mut self, prompt: &str) {
+            self.initial_prompt = Some(prompt.to_string());
+        }
+
+        fn no_speech_thold(&mut self, thold: f32) {
+            self.no_speech_thold = Some(thold);
+        }
+    }
+
+    #[test]
+    fn test_initial_prompt_is_applied() {
+        let mut params = RecordedParams::default();
+        apply_decode_context(&mut params, Some("ja"), Some("  Kubernetes, gRPC, ディギー  "), &NoSpeechThresholds::default());
+        assert_eq!(params.language.as_deref(), Some("ja"));
+        assert_eq!(params.initial_prompt.as_deref(), Some("Kubernetes, gRPC, ディギー"));
+
+        // No prompt (or a blank one) leaves the params alone
+        let mut params = RecordedParams::default();
+        apply_decode_context(&mut params, None, Some("   "), &NoSpeechThresholds::default());
+        assert_eq!(params.language, None);
+        assert_eq!(params.initial_prompt, None);
+
+        let mut params = RecordedParams::default();
+        apply_decode_context(&mut params, None, Some(&format!("a\0b{}", "x".repeat(2000))), &NoSpeechThresholds::default());
+        let prompt = params.initial_prompt.unwrap();
+        assert!(prompt.starts_with("abx"));
+        assert_eq!(prompt.chars().count(), MAX_INITIAL_PROMPT_CHARS);
+    }
+
+    #[test]
+    fn test_no_speech_threshold_follows_language() {
+        let thresholds = NoSpeechThresholds::parse("0.55, ko=0.7, en=0.45").unwrap();
+        assert_eq!(thresholds.for_language(Some("ko")), 0.7);
+        assert_eq!(thresholds.for_language(Some("en")), 0.45);
+        // Languages without their own value use the replaced default
+        assert_eq!(thresholds.for_language(Some("ja")), 0.55);
+        assert_eq!(thresholds.for_language(Some("fr")), 0.55);
+        assert_eq!(NoSpeechThresholds::default().for_language(Some("ja")), DEFAULT_NO_SPEECH_THOLD);
+        assert_eq!(thresholds.for_language(None), 0.55);
+
+        let mut params = RecordedParams::default();
+        apply_decode_context(&mut params, Some("ko"), None, &thresholds);
+        assert_eq!(params.no_speech_thold, Some(0.7));
+        let mut params = RecordedParams::default();
+        apply_decode_context(&mut params, None, None, &thresholds);
+        assert_eq!(params.no_speech_thold, Some(0.55));
+
+        assert_eq!(NoSpeechThresholds::parse("").unwrap(), NoSpeechThresholds::default());
+        assert!(NoSpeechThresholds::parse("ja=1.5").is_err());
+        assert!(NoSpeechThresholds::parse("ja=quiet").is_err());
+    }
+
+    #[test]
+    fn test_per_guild_model_selection() {
+        let available = [MODEL_BASE, MODEL_FAST];
+
+        // No selection: each use keeps its default
+        assert_eq!(select_model(&available, None, MODEL_BASE), MODEL_BASE);
+        assert_eq!(select_model(&available, None, MODEL_FAST), MODEL_FAST);
+
+        // A guild selection applies to every use
+        assert_eq!(select_model(&available, Some(MODEL_FAST), MODEL_BASE), MODEL_FAST);
+        assert_eq!(select_model(&available, Some(MODEL_BASE), MODEL_FAST), MODEL_BASE);
+
+        // A stale selection for a model that isn't loaded falls back to the default
+        assert_eq!(select_model(&[MODEL_BASE], Some(MODEL_FAST), MODEL_BASE), MODEL_BASE);
+        assert_eq!(select_model(&available, Some("large"), MODEL_FAST), MODEL_FAST);
+    }
+
+    #[test]
+    fn test_model_fallback_selection() {
+        let valid = std::env::temp_dir().join(format!("fallback_model_{}.bin", std::process::id()));
+        std::fs::write(&valid, b"model").unwrap();
+        let valid = valid.to_str().unwrap();
+        let missing = "./models/does-not-exist.bin";
+
+        // Stand-in loader: a model "loads" if its file exists
+        let load = |path: &str| -> Result<String, AppError> {
+            if Path::new(path).exists() { Ok(path.to_string()) } else { Err(AppError::ModelMissing(path.to_string())) }
+        };
+
+        assert_eq!(load_first(&[missing, valid], load).unwrap(), valid);
+        assert_eq!(load_first(&[valid, missing], load).unwrap(), valid);
+        assert!(load_first(&[missing], load).is_err());
+        assert!(load_first(&[], load).is_err());
+        std::fs::remove_file(valid).unwrap();
+
+        assert!(Transcriber::new_with_fallback(&[missing, "./models/also-missing.bin"]).is_err());
+    }
+
+    #[test]
+    fn test_recording_skip_decision() {
+        let min = Duration::from_secs(1);
+        let loud_long = vec![4000i16; 96_000];
+        let loud_short = vec![4000i16; 12_000];
+        let quiet_long = vec![20i16; 96_000];
+
+        assert_eq!(recording_skip_reason(&loud_long, min), None);
+        assert_eq!(recording_skip_reason(&loud_short, min), Some(SkipReason::TooShort));
+        assert_eq!(recording_skip_reason(&quiet_long, min), Some(SkipReason::TooQuiet));
+        // Disabled guard keeps everything
+        assert_eq!(recording_skip_reason(&quiet_long[..10], Duration::ZERO), None);
+    }
+
+    #[test]
+    fn test_downsample_to_16k_from_any_rate() {
+        let from_48k = downsample_to_16k(&vec![0.5; 48_000], 48_000);
+        assert_eq!(from_48k.len(), 16_000);
+        assert!(from_48k.iter().all(|&s| s == 0.5));
+
+        // 24 kHz isn't a whole multiple of 16 kHz, so it's interpolated
+        let ramp: Vec<f32> = (0..24_000).map(|i| i as f32).collect();
+        let from_24k = downsample_to_16k(&ramp, 24_000);
+        assert_eq!(from_24k.len(), 16_000);
+        assert_eq!(&from_24k[..3], &[0.0, 1.5, 3.0]);
+
+        assert_eq!(downsample_to_16k(&ramp, 16_000), ramp);
+    }
+
+    #[test]
+    fn test_escalation_decision() {
+        assert!(should_escalate("多分こんにちは", 0.35, 0.5));
+        assert!(!should_escalate("こんにちは", 0.92, 0.5));
+        // Exactly at the threshold is trusted
+        assert!(!should_escalate("hello", 0.5, 0.5));
+        assert!(!should_escalate("  ", 0.1, 0.5));
+    }
+
+    #[test]
+    fn test_confidence_ignores_special_tokens() {
+        let eot = 50257;
+        // [SOT] [ja] [transcribe] [0.00] "たぶん" "こう" [1.20] [EOT]
+        let tokens = [
+            (eot + 1, 0.99),
+            (eot + 2, 0.98),
+            (eot + 102, 0.99),
+            (eot + 107, 0.97),
+            (1000, 0.4),
+            (2000, 0.2),
+            (eot + 167, 0.96),
+            (eot, 0.99),
+        ];
+        assert!((mean_text_probability(&tokens, eot) - 0.3).abs() < 1e-6);
+        assert_eq!(mean_text_probability(&tokens[..4], eot), 1.0);
+        assert_eq!(mean_text_probability(&[], eot), 1.0);
+    }
+
+    #[test]
+    fn test_low_confidence_segments_are_marked() {
+        let segments = vec![
+            segment(" 今日の議題です", 0.92),
+            segment(" たぶんこう言った", 0.31),
+            segment("  ", 0.1),
+        ];
+
+        assert_eq!(
+            format_labeled_transcript("Alice", &segments),
+            "**[Alice]**: 今日の議題です\n**[Alice]**: ⚠️ たぶんこう言った"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_slow_transcription_times_out() {
+        let slow = tokio::task::spawn_blocking(|| {
+            std::thread::sleep(Duration::from_millis(200));
+            Ok::<_, AppError>("late".to_string())
+        });
+        let result = with_timeout(Duration::from_millis(20), async { slow.await? }).await;
+        assert!(result.unwrap_err().to_string().contains("timed out"));
+
+        let fast = with_timeout(Duration::from_secs(1), async { Ok("done".to_string()) }).await;
+        assert_eq!(fast.unwrap(), "done");
+    }
+
+    #[tokio::test]
+    async fn test_finished_waits_for_timed_out_threads() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        let tasks = TranscriptionTasks::default();
+        let done = Arc::new(AtomicBool::new(false));
+        let running = tasks.running().await;
+        let slow = tokio::task::spawn_blocking({
+            let done = done.clone();
+            move || {
+                let _running = running;
+                std::thread::sleep(Duration::from_millis(200));
+                done.store(true, Ordering::SeqCst);
+                Ok::<_, AppError>(())
+            }
+        });
+        assert!(with_timeout(Duration::from_millis(20), async { slow.await? }).await.is_err());
+
+        // The caller gave up, but the thread is still reading
+        assert!(!done.load(Ordering::SeqCst));
+        tasks.finished().await;
+        assert!(done.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_transcription_is_counted() {
+        use std::sync::atomic::Ordering;
+
+        let before = metrics::METRICS.transcriptions.load(Ordering::Relaxed);
+        let result = with_timeout(Duration::from_secs(1), async { Ok("done".to_string()) }).await;
+        assert!(result.is_ok());
+        assert!(metrics::METRICS.transcriptions.load(Ordering::Relaxed) > before);
+        assert!(metrics::render().contains("diggy_transcriptions_total{result=\"ok\"}"));
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_transcription_keeps_file_order() {
+        let files: Vec<String> = (1..=4).map(|i| format!("speaker_{}.wav", i)).collect();
+
+        // Earlier files finish last, and one of them fails
+        let mut progress = Vec::new();
+        let results = transcribe_files_concurrently(&files, 4, 0, |file| async move {
+            let n: u64 = file.trim_start_matches("speaker_").trim_end_matches(".wav").parse().unwrap();
+            tokio::time::sleep(Duration::from_millis(40 - n * 10)).await;
+            if n == 2 {
+                Err(AppError::Transcription("decode failed".to_string()))
+            } else {
+                Ok(format!("text {}", n))
+            }
+        }, |done| progress.push(done)).await.unwrap();
+        assert_eq!(progress, vec![1, 2, 3, 4]);
+
+        let order: Vec<&str> = results.iter().map(|(file, _)| file.as_str()).collect();
+        assert_eq!(order, vec!["speaker_1.wav", "speaker_2.wav", "speaker_3.wav", "speaker_4.wav"]);
+        assert_eq!(results[0].1.as_ref().unwrap(), "text 1");
+        assert!(results[1].1.is_err());
+        assert_eq!(results[3].1.as_ref().unwrap(), "text 4");
+    }
+
+    #[tokio::test]
+    async fn test_consecutive_errors_abort_early() {
+        let files: Vec<String> = (1..=6).map(|i| format!("speaker_{}.wav", i)).collect();
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        let attempted = AtomicUsize::new(0);
+
+        // Everything after the first file fails; one at a time so completion order is file order
+        let result = transcribe_files_concurrently(&files, 1, 3, |file| {
+            attempted.fetch_add(1, Ordering::Relaxed);
+            async move {
+                if file == "speaker_1.wav" { Ok(()) } else { Err(AppError::Transcription("model failed".to_string())) }
+            }
+        }, |_| {}).await;
+
+        let aborted = result.unwrap_err();
+        assert_eq!(aborted.failures, 3);
+        assert_eq!(aborted.last_error, "Transcription failed: model failed");
+        assert_eq!(attempted.load(Ordering::Relaxed), 4);
+
+        // A success in between resets the streak
+        let mut streak = FailureStreak::new(2);
+        assert!(!streak.record_failure());
+        streak.record_success();
+        assert!(!streak.record_failure());
+        assert!(streak.record_failure());
+        assert!(!FailureStreak::new(0).record_failure());
+    }
+
+    #[test]
+    fn test_long_audio_is_stitched_across_chunks() {
+        // Each sample holds its own index, so the fake model knows where a chunk sits
+        let len = 16_000 * 100;
+        let audio: Vec<f32> = (0..len).map(|i| i as f32).collect();
+        assert_eq!(chunk_windows(len, CHUNK_SAMPLES, CHUNK_OVERLAP_SAMPLES).len(), 4);
+
+        // Emit one segment every 5 seconds of audio in the chunk
+        let segments = transcribe_chunked(&audio, CHUNK_SAMPLES, CHUNK_OVERLAP_SAMPLES, |chunk| {
+            let first_second = chunk[0] as usize / 16_000;
+            Ok((0..chunk.len() / 16_000)
+                .filter(|s| (first_second + s).is_multiple_of(5))
+                .map(|s| TranscriptSegment {
+                    start: s as i64 * 100,
+                    end: s as i64 * 100 + 150,
+                    text: format!("at {}s", first_second + s),
+                    confidence: 0.9,
+                })
+                .collect())
+        }).unwrap();
+
+        let texts: Vec<String> = segments.iter().map(|s| s.text.clone()).collect();
+        let expected: Vec<String> = (0..20).map(|i| format!("at {}s", i * 5)).collect();
+        assert_eq!(texts, expected);
+        // Timestamps are relative to the whole buffer, not the chunk
+        assert_eq!(segments[10].start, 50 * 100);
+
+        // Short audio is a single window
+        assert_eq!(chunk_windows(100, CHUNK_SAMPLES, CHUNK_OVERLAP_SAMPLES), vec![(0, 100)]);
+    }
+
+    #[test]
+    fn test_overlapping_chunk_text_is_stitched() {
+        // The second chunk repeats the last words of the first
+        let stitched = stitch_segments(vec![
+            "We agreed to ship the release on".to_string(),
+            "the release on Friday, after the review.".to_string(),
+            " After the review ".to_string(),
+        ]);
+        assert_eq!(stitched, "We agreed to ship the release on\n\nFriday, after the review.");
+
+        // Nothing in common: kept as is
+        let stitched = stitch_segments(vec!["Good morning.".to_string(), "Let's begin.".to_string()]);
+        assert_eq!(stitched, "Good morning.\n\nLet's begin.");
+        // A single shared word isn't treated as an overlap
+        assert_eq!(trim_overlap("I think so", "so we should"), "so we should");
+        // Japanese has no spaces, so characters are compared
+        assert_eq!(trim_overlap("会議を始めます", "始めます。まず"), "。まず");
+        assert_eq!(stitch_segments(Vec::new()), "");
+
+        // At a chunk boundary the later chunk's first segment loses the repeated words
+        let audio = vec![0.0f32; 16_000 * 40];
+        let segments = transcribe_chunked(&audio, CHUNK_SAMPLES, CHUNK_OVERLAP_SAMPLES, |chunk| {
+            let (start, text) = if chunk.len() == CHUNK_SAMPLES {
+                (2000, "We will ship the release on")
+            } else {
+                (150, "the release on Friday.")
+            };
+            Ok(vec![TranscriptSegment { start, end: start + 300, text: text.to_string(), confidence: 0.9 }])
+        }).unwrap();
+        let texts: Vec<&str> = segments.iter().map(|s| s.text.trim()).collect();
+        assert_eq!(texts, vec!["We will ship the release on", "Friday."]);
+    }
+
+    #[test]
+    fn test_silent_gap_splits_utterances() {
+        let tone = |secs: f32| (0..(secs * 16_000.0) as usize).map(|i| if i % 2 == 0 { 0.1 } else { -0.1 }).collect::<Vec<f32>>();
+        let silence = |secs: f32| vec![0.0f32; (secs * 16_000.0) as usize];
+
+        // 1s speech, 2s silence, 1.5s speech, then a pause shorter than the gap
+        let samples = [tone(1.0), silence(2.0), tone(1.5), silence(0.3), tone(0.5)].concat();
+        let utterances = split_at_silence(&samples, Duration::from_secs(1));
+        assert_eq!(utterances, vec![(0, 16_000), (48_000, 84_800)]);
+
+        // A longer minimum gap keeps it as one utterance
+        assert_eq!(split_at_silence(&samples, Duration::from_secs(3)), vec![(0, 84_800)]);
+        assert!(split_at_silence(&silence(2.0), Duration::from_secs(1)).is_empty());
+    }
 }
@@ -0,0 +1,171 @@
+use reqwest::Client;
+use serde::Serialize;
+use std::error::Error;
+use std::path::PathBuf;
+use std::sync::Arc;
+use twilight_model::id::{marker::{ChannelMarker, GuildMarker}, Id};
+
+use crate::persist;
+
+/// A finished meeting, handed to every transcript hook
+#[derive(Debug, Clone, Serialize)]
+pub struct TranscriptContext {
+    pub guild_id: Id<GuildMarker>,
+    /// Voice channel that was recorded
+    pub channel_id: Id<ChannelMarker>,
+    /// RFC 3339 time the recording started
+    pub started_at: String,
+    pub participants: Vec<String>,
+    pub transcript: String,
+    /// None if summarization failed or is disabled
+    pub minutes: Option<String>,
+}
+
+/// Custom post-processing run on each finished transcript
+#[async_trait::async_trait]
+pub trait TranscriptHook: Send + Sync {
+    /// Short name used in logs
+    fn name(&self) -> &str;
+
+    async fn on_transcript(&self, ctx: &TranscriptContext) -> Result<(), Box<dyn Error + Send + Sync>>;
+}
+
+/// Run every hook in order; a failing hook is logged and doesn't stop the others
+pub async fn run_hooks(hooks: &[Arc<dyn TranscriptHook>], ctx: &TranscriptContext) {
+    for hook in hooks {
+        match hook.on_transcript(ctx).await {
+            Ok(()) => println!("[INFO] Transcript hook '{}' finished for guild {}", hook.name(), ctx.guild_id),
+            Err(e) => eprintln!("[ERROR] Transcript hook '{}' failed for guild {}: {}", hook.name(), ctx.guild_id, e),
+        }
+    }
+}
+
+/// POSTs the context as JSON to a URL
+pub struct WebhookHook {
+    client: Client,
+    url: String,
+}
+
+impl WebhookHook {
+    pub fn new(client: Client, url: String) -> Self {
+        Self { client, url }
+    }
+}
+
+#[async_trait::async_trait]
+impl TranscriptHook for WebhookHook {
+    fn name(&self) -> &str {
+        "webhook"
+    }
+
+    async fn on_transcript(&self, ctx: &TranscriptContext) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let response = self.client.post(&self.url).json(ctx).send().await?;
+        if !response.status().is_success() {
+            return Err(format!("webhook returned {}", response.status()).into());
+        }
+        Ok(())
+    }
+}
+
+/// Writes the context as `{dir}/{guild_id}/{started_at}.json`
+pub struct StorageHook {
+    dir: PathBuf,
+}
+
+impl StorageHook {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn path_for(&self, ctx: &TranscriptContext) -> PathBuf {
+        let stem: String = ctx.started_at
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' })
+            .collect();
+        self.dir.join(ctx.guild_id.to_string()).join(format!("{}.json", stem))
+    }
+}
+
+#[async_trait::async_trait]
+impl TranscriptHook for StorageHook {
+    fn name(&self) -> &str {
+        "storage"
+    }
+
+    async fn on_transcript(&self, ctx: &TranscriptContext) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let path = self.path_for(ctx);
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        persist::write_atomic(&path, &serde_json::to_vec_pretty(ctx)?).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::Mutex;
+
+    #[derive(Default)]
+    struct RecordingHook {
+        seen: Mutex<Vec<TranscriptContext>>,
+    }
+
+    #[async_trait::async_trait]
+    impl TranscriptHook for RecordingHook {
+        fn name(&self) -> &str {
+            "recording"
+        }
+
+        async fn on_transcript(&self, ctx: &TranscriptContext) -> Result<(), Box<dyn Error + Send + Sync>> {
+            self.seen.lock().await.push(ctx.clone());
+            Ok(())
+        }
+    }
+
+    struct FailingHook;
+
+    #[async_trait::async_trait]
+    impl TranscriptHook for FailingHook {
+        fn name(&self) -> &str {
+            "failing"
+        }
+
+        async fn on_transcript(&self, _ctx: &TranscriptContext) -> Result<(), Box<dyn Error + Send + Sync>> {
+            Err("unreachable".into())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hooks_receive_context() {
+        let dir = std::env::temp_dir().join(format!("transcript_hooks_{}", uuid::Uuid::new_v4()));
+        let recorder = Arc::new(RecordingHook::default());
+        let hooks: Vec<Arc<dyn TranscriptHook>> = vec![
+            Arc::new(FailingHook),
+            recorder.clone(),
+            Arc::new(StorageHook::new(&dir)),
+        ];
+        let ctx = TranscriptContext {
+            guild_id: Id::new(1),
+            channel_id: Id::new(2),
+            started_at: "2024-05-01T10:00:00+09:00".to_string(),
+            participants: vec!["Alice".to_string(), "Bob".to_string()],
+            transcript: "[Alice] hello".to_string(),
+            minutes: Some("# Minutes".to_string()),
+        };
+
+        run_hooks(&hooks, &ctx).await;
+
+        // The failing hook doesn't stop the ones after it
+        let seen = recorder.seen.lock().await;
+        assert_eq!(seen.len(), 1);
+        assert_eq!(seen[0].participants, ["Alice", "Bob"]);
+        assert_eq!(seen[0].minutes.as_deref(), Some("# Minutes"));
+
+        let stored = std::fs::read_to_string(dir.join("1").join("2024-05-01T10_00_00_09_00.json")).unwrap();
+        let stored: serde_json::Value = serde_json::from_str(&stored).unwrap();
+        assert_eq!(stored["transcript"], "[Alice] hello");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
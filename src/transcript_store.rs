@@ -0,0 +1,99 @@
+use std::collections::{HashMap, VecDeque};
+use chrono::Local;
+use tokio::sync::RwLock;
+use twilight_model::id::Id;
+use twilight_model::id::marker::GuildMarker;
+
+use crate::timezone;
+
+/// Number of finished meetings kept per guild for `/transcript`
+pub const DEFAULT_TRANSCRIPT_HISTORY: usize = 5;
+
+#[derive(Debug, Clone)]
+pub struct StoredTranscript {
+    pub created_at: chrono::DateTime<Local>,
+    pub transcript: String,
+    pub minutes: Option<String>,
+}
+
+impl StoredTranscript {
+    pub fn filename(&self) -> String {
+        format!("transcript_{}.md", timezone::format(&self.created_at, "%Y%m%d_%H%M%S"))
+    }
+
+    /// Render the transcript (and minutes, if generated) as a Markdown file body
+    pub fn to_markdown(&self) -> String {
+        let mut body = format!(
+            "# Meeting Transcript ({})\n\n{}\n",
+            timezone::format(&self.created_at, "%Y-%m-%d %H:%M %Z"),
+            self.transcript.trim_end()
+        );
+        if let Some(minutes) = &self.minutes {
+            body.push_str(&format!("\n# Meeting Minutes\n\n{}\n", minutes.trim_end()));
+        }
+        body
+    }
+}
+
+/// Bounded per-guild history of finished meeting transcripts (newest last)
+pub struct LastTranscripts {
+    capacity: usize,
+    entries: RwLock<HashMap<Id<GuildMarker>, VecDeque<StoredTranscript>>>,
+}
+
+impl LastTranscripts {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            entries: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub async fn push(&self, guild_id: Id<GuildMarker>, transcript: String, minutes: Option<String>) {
+        let mut entries = self.entries.write().await;
+        let history = entries.entry(guild_id).or_insert_with(VecDeque::new);
+        history.push_back(StoredTranscript {
+            created_at: Local::now(),
+            transcript,
+            minutes,
+        });
+        while history.len() > self.capacity {
+            history.pop_front();
+        }
+    }
+
+    pub async fn latest(&self, guild_id: Id<GuildMarker>) -> Option<StoredTranscript> {
+        let entries = self.entries.read().await;
+        entries.get(&guild_id).and_then(|history| history.back().cloned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_ring_buffer_keeps_newest() {
+        let store = LastTranscripts::new(2);
+        let guild_id = Id::new(1);
+
+        store.push(guild_id, "first".to_string(), None).await;
+        store.push(guild_id, "second".to_string(), None).await;
+        store.push(guild_id, "third".to_string(), Some("minutes".to_string())).await;
+
+        let latest = store.latest(guild_id).await.unwrap();
+        assert_eq!(latest.transcript, "third");
+        assert!(latest.to_markdown().contains("# Meeting Minutes"));
+
+        let entries = store.entries.read().await;
+        let kept: Vec<&str> = entries[&guild_id].iter().map(|t| t.transcript.as_str()).collect();
+        assert_eq!(kept, vec!["second", "third"]);
+    }
+
+    #[tokio::test]
+    async fn test_guilds_are_independent() {
+        let store = LastTranscripts::new(2);
+        store.push(Id::new(1), "a".to_string(), None).await;
+        assert!(store.latest(Id::new(2)).await.is_none());
+    }
+}
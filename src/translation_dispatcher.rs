@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, Mutex};
+use crate::voice_translator::SpeakerId;
+
+/// Utterances that may wait per speaker before new ones are dropped
+pub const SPEAKER_QUEUE_CAPACITY: usize = 8;
+
+type JobHandler<T> = Arc<dyn Fn(SpeakerId, T) -> Pin<Box<dyn Future<Output = ()> + Send>> + Send + Sync>;
+
+/// Runs translation jobs one at a time per speaker, in the order they were queued.
+/// Each speaker gets a bounded FIFO queue drained by its own worker task,
+/// so different speakers are still processed concurrently.
+pub struct TranslationDispatcher<T> {
+    queues: Mutex<HashMap<SpeakerId, mpsc::Sender<(Instant, T)>>>,
+    capacity: usize,
+    /// Jobs that waited longer than this in the queue are dropped; zero keeps everything
+    max_wait: Duration,
+    handler: JobHandler<T>,
+}
+
+impl<T: Send + 'static> TranslationDispatcher<T> {
+    pub fn new<F, Fut>(capacity: usize, handler: F) -> Self
+    where
+        F: Fn(SpeakerId, T) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        Self {
+            queues: Mutex::new(HashMap::new()),
+            capacity: capacity.max(1),
+            max_wait: Duration::ZERO,
+            handler: Arc::new(move |speaker, job| Box::pin(handler(speaker, job))),
+        }
+    }
+
+    /// Drop jobs that waited in a speaker's queue longer than `max_wait` (zero disables)
+    pub fn with_max_wait(mut self, max_wait: Duration) -> Self {
+        self.max_wait = max_wait;
+        self
+    }
+
+    /// Queue a job behind the speaker's earlier jobs.
+    /// Returns false if the speaker's queue is full and the job was dropped.
+    pub async fn dispatch(&self, speaker_id: SpeakerId, job: T) -> bool {
+        let mut queues = self.queues.lock().await;
+
+        let sender = match queues.get(&speaker_id) {
+            Some(sender) if !sender.is_closed() => sender.clone(),
+            _ => {
+                let sender = self.spawn_worker(speaker_id);
+                queues.insert(speaker_id, sender.clone());
+                sender
+            }
+        };
+        drop(queues);
+
+        match sender.try_send((Instant::now(), job)) {
+            Ok(()) => true,
+            Err(mpsc::error::TrySendError::Full(_)) => {
+                println!("[WARN] Translation queue full for user {}, dropping utterance", speaker_id);
+                false
+            }
+            Err(mpsc::error::TrySendError::Closed(_)) => {
+                eprintln!("[ERROR] Translation worker for user {} stopped unexpectedly", speaker_id);
+                false
+            }
+        }
+    }
+
+    fn spawn_worker(&self, speaker_id: SpeakerId) -> mpsc::Sender<(Instant, T)> {
+        let (sender, mut receiver) = mpsc::channel::<(Instant, T)>(self.capacity);
+        let handler = self.handler.clone();
+        let max_wait = self.max_wait;
+
+        // The worker exits once the dispatcher (and with it the sender) is dropped
+        tokio::spawn(async move {
+            while let Some((queued_at, job)) = receiver.recv().await {
+                // Translating speech this late is useless in real time
+                if !max_wait.is_zero() && queued_at.elapsed() > max_wait {
+                    println!("[INFO] Dropping stale queued utterance for user {} ({:?} old)", speaker_id, queued_at.elapsed());
+                    continue;
+                }
+                handler(speaker_id, job).await;
+            }
+        });
+
+        sender
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use twilight_model::id::Id;
+
+    #[tokio::test]
+    async fn test_speaker_jobs_post_in_order() {
+        let (posted_tx, mut posted_rx) = mpsc::unbounded_channel();
+        let dispatcher = TranslationDispatcher::new(SPEAKER_QUEUE_CAPACITY, move |speaker, job: &'static str| {
+            let posted_tx = posted_tx.clone();
+            async move {
+                // The first utterance is slow to translate
+                if job == "first" {
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                }
+                posted_tx.send((speaker, job)).unwrap();
+            }
+        });
+
+        let alice: SpeakerId = Id::new(1);
+        let bob: SpeakerId = Id::new(2);
+        assert!(dispatcher.dispatch(alice, "first").await);
+        assert!(dispatcher.dispatch(alice, "second").await);
+        assert!(dispatcher.dispatch(bob, "other").await);
+
+        let mut posted = Vec::new();
+        for _ in 0..3 {
+            let item = tokio::time::timeout(Duration::from_secs(1), posted_rx.recv())
+                .await
+                .unwrap()
+                .unwrap();
+            posted.push(item);
+        }
+
+        // Bob isn't held up by Alice's slow translation, but Alice's stay in order
+        assert_eq!(posted[0], (bob, "other"));
+        assert_eq!(posted[1], (alice, "first"));
+        assert_eq!(posted[2], (alice, "second"));
+    }
+
+    #[tokio::test]
+    async fn test_jobs_stuck_in_the_queue_are_dropped() {
+        let (posted_tx, mut posted_rx) = mpsc::unbounded_channel();
+        let dispatcher = TranslationDispatcher::new(SPEAKER_QUEUE_CAPACITY, move |_, job: &'static str| {
+            let posted_tx = posted_tx.clone();
+            async move {
+                if job == "slow" {
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                }
+                posted_tx.send(job).unwrap();
+            }
+        })
+        .with_max_wait(Duration::from_millis(30));
+
+        let alice: SpeakerId = Id::new(1);
+        dispatcher.dispatch(alice, "slow").await;
+        dispatcher.dispatch(alice, "waited").await;
+        assert_eq!(posted_rx.recv().await, Some("slow"));
+        dispatcher.dispatch(alice, "fresh").await;
+
+        // "waited" sat behind the slow job past the limit
+        let next = tokio::time::timeout(Duration::from_secs(1), posted_rx.recv()).await.unwrap();
+        assert_eq!(next, Some("fresh"));
+    }
+}
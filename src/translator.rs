@@ -1,7 +1,29 @@
 use reqwest::Client;
 use serde::Deserialize;
-use std::time::Duration;
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::future::Future;
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tokio::time::sleep;
+use crate::api_client::{ClientSettings, DEFAULT_DEEPL_TIMEOUT};
+use crate::error::{classify_deepl_status, AppError, TranslationFailure};
+use crate::metrics;
+
+/// Consecutive failed translate calls before DeepL is short-circuited
+const BREAKER_FAILURE_THRESHOLD: u32 = 5;
+/// How long translate calls are short-circuited once the breaker opens
+const BREAKER_COOLDOWN: Duration = Duration::from_secs(30);
+/// Base delay for the first retry; doubled on each further attempt
+const RETRY_BASE_DELAY_MS: u64 = 200;
+/// Wait before creating a glossary again after it failed; doubled per further failure
+const GLOSSARY_RETRY_BASE: Duration = Duration::from_secs(60);
+/// Longest wait between glossary creation attempts
+const GLOSSARY_RETRY_MAX: Duration = Duration::from_secs(3600);
+
+/// Longest piece of text sent in one request (matches `sanitize_input`)
+pub const MAX_TRANSLATE_CHARS: usize = 2000;
 
 #[derive(Deserialize, Debug)]
 struct DeepLResponse {
@@ -11,14 +33,183 @@ struct DeepLResponse {
 #[derive(Deserialize, Debug)]
 struct DeepLTranslation {
     text: String,
-    #[allow(dead_code)]
     detected_source_language: Option<String>,
 }
 
+/// A translated text and the language DeepL translated it from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Translation {
+    pub text: String,
+    /// DeepL source code (e.g. `JA`), detected by DeepL when no source was given
+    pub source_lang: Option<String>,
+}
+
+impl DeepLResponse {
+    fn into_translation(self, requested_source: Option<&str>) -> Option<Translation> {
+        let translation = self.translations.into_iter().next()?;
+        Some(Translation {
+            text: translation.text.trim().to_string(),
+            source_lang: translation.detected_source_language
+                .or_else(|| requested_source.map(str::to_string)),
+        })
+    }
+}
+
+/// Parse a successful `/v2/translate` body. `Ok(None)` means DeepL answered
+/// without any translation; `Err` means the body wasn't the expected JSON.
+fn parse_translate_body(body: &str, requested_source: Option<&str>) -> Result<Option<Translation>, serde_json::Error> {
+    let response: DeepLResponse = serde_json::from_str(body)?;
+    Ok(response.into_translation(requested_source))
+}
+
+/// The speaker's configured language, if the text's script agrees with it.
+/// When it doesn't (someone switched language mid-meeting), return None so
+/// DeepL detects the source instead of mistranslating.
+pub fn confident_source<'a>(configured: &'a str, text: &str) -> Option<&'a str> {
+    let letters: Vec<char> = text.chars().filter(|c| c.is_alphabetic()).collect();
+    if letters.is_empty() {
+        return Some(configured);
+    }
+    let share = |pred: fn(&char) -> bool| letters.iter().filter(|c| pred(c)).count() * 2 > letters.len();
+
+    let matches = match configured {
+        "ja" => share(|c| matches!(c, '\u{3040}'..='\u{30FF}' | '\u{4E00}'..='\u{9FFF}')),
+        "ko" => share(|c| matches!(c, '\u{AC00}'..='\u{D7AF}' | '\u{1100}'..='\u{11FF}')),
+        "en" | "en-gb" => share(|c| c.is_ascii_alphabetic()),
+        _ => true,
+    };
+    matches.then_some(configured)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BreakerState {
+    /// Requests flow normally
+    Closed,
+    /// Requests are rejected until the cooldown ends
+    Open { until: Instant },
+    /// One trial request is in flight; its result decides the next state. If no
+    /// result arrives by `until` (the request was dropped), another trial is let through.
+    HalfOpen { until: Instant },
+}
+
+struct BreakerInner {
+    state: BreakerState,
+    consecutive_failures: u32,
+    /// Whether the "temporarily unavailable" notice was already taken for this outage
+    notified: bool,
+}
+
+/// Stops calling DeepL for a while after repeated failures
+struct CircuitBreaker {
+    failure_threshold: u32,
+    cooldown: Duration,
+    inner: Mutex<BreakerInner>,
+}
+
+impl CircuitBreaker {
+    fn new(failure_threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            failure_threshold: failure_threshold.max(1),
+            cooldown,
+            inner: Mutex::new(BreakerInner {
+                state: BreakerState::Closed,
+                consecutive_failures: 0,
+                notified: false,
+            }),
+        }
+    }
+
+    /// Whether a request may be sent now. An expired open breaker lets
+    /// exactly one trial request through (half-open), and so does a trial
+    /// that never reported back within the cooldown.
+    fn allow_request(&self, now: Instant) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        match inner.state {
+            BreakerState::Closed => true,
+            BreakerState::Open { until } | BreakerState::HalfOpen { until } if now >= until => {
+                inner.state = BreakerState::HalfOpen { until: now + self.cooldown };
+                true
+            }
+            BreakerState::Open { .. } | BreakerState::HalfOpen { .. } => false,
+        }
+    }
+
+    fn record_success(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.state != BreakerState::Closed {
+            println!("[INFO] DeepL circuit breaker closed");
+        }
+        inner.state = BreakerState::Closed;
+        inner.consecutive_failures = 0;
+        inner.notified = false;
+    }
+
+    fn record_failure(&self, now: Instant) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.consecutive_failures += 1;
+        let half_open = matches!(inner.state, BreakerState::HalfOpen { .. });
+        let should_open = half_open || inner.consecutive_failures >= self.failure_threshold;
+        if should_open {
+            if !half_open {
+                eprintln!(
+                    "[ERROR] DeepL circuit breaker opened after {} consecutive failures",
+                    inner.consecutive_failures
+                );
+            }
+            inner.state = BreakerState::Open { until: now + self.cooldown };
+        }
+    }
+
+    /// Returns true once per outage, so callers can tell users without repeating themselves
+    fn take_open_notice(&self) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        if matches!(inner.state, BreakerState::Closed) || inner.notified {
+            return false;
+        }
+        inner.notified = true;
+        true
+    }
+
+    #[cfg(test)]
+    fn state(&self) -> BreakerState {
+        self.inner.lock().unwrap().state
+    }
+}
+
+/// Exponential backoff with equal jitter, so concurrent callers don't retry in lockstep
+fn retry_delay(attempt: u32) -> Duration {
+    let base = RETRY_BASE_DELAY_MS << attempt.saturating_sub(1).min(6);
+    let half = base / 2;
+    let jitter = RandomState::new().build_hasher().finish() % (half + 1);
+    Duration::from_millis(half + jitter)
+}
+
+/// A guild's glossary for one language pair
+type GlossaryKey = (u64, String, String);
+
+/// What's known about one glossary, tagged with a hash of the entries it's for
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum GlossaryState {
+    Ready { hash: u64, id: String },
+    /// Creating it failed; not attempted again for the same entries until `retry_at`
+    Failed { hash: u64, failures: u32, retry_at: Instant },
+}
+
+/// How long to wait after the `failures`-th failed attempt to create a glossary
+fn glossary_retry_delay(failures: u32) -> Duration {
+    GLOSSARY_RETRY_BASE
+        .saturating_mul(1 << failures.saturating_sub(1).min(6))
+        .min(GLOSSARY_RETRY_MAX)
+}
+
 pub struct Translator {
     api_key: String,
     client: Client,
     api_base: String,
+    breaker: CircuitBreaker,
+    /// Glossary per guild and language pair. Each has its own lock, held while it's
+    /// created, so one slow DeepL call doesn't hold up every other guild.
+    glossaries: Mutex<HashMap<GlossaryKey, Arc<tokio::sync::Mutex<Option<GlossaryState>>>>>,
 }
 
 impl Translator {
@@ -31,110 +222,322 @@ impl Translator {
 
         Self {
             api_key,
-            client: Client::builder()
-                .timeout(Duration::from_secs(30))
-                .build()
-                .unwrap(),
+            client: ClientSettings::with_timeout(DEFAULT_DEEPL_TIMEOUT).build().unwrap(),
             api_base,
+            breaker: CircuitBreaker::new(BREAKER_FAILURE_THRESHOLD, BREAKER_COOLDOWN),
+            glossaries: Mutex::new(HashMap::new()),
         }
     }
 
+    #[cfg(test)]
+    fn with_api_base(mut self, api_base: &str) -> Self {
+        self.api_base = api_base.to_string();
+        self
+    }
+
+    /// Rebuild the HTTP client with a different timeout or pool tuning
+    pub fn with_client_settings(mut self, settings: ClientSettings) -> Self {
+        self.client = settings.build().unwrap();
+        self
+    }
+
+    /// True the first time it's called after DeepL became unavailable,
+    /// so the outage is announced once rather than per utterance
+    pub fn take_unavailable_notice(&self) -> bool {
+        self.breaker.take_open_notice()
+    }
+
     /// Sanitize user input to prevent prompt injection
     fn sanitize_input(&self, text: &str) -> String {
         text.chars()
             .filter(|c| !c.is_control() || *c == '\n' || *c == '\t')
-            .take(2000) // Limit length
+            .take(MAX_TRANSLATE_CHARS) // Limit length
             .collect::<String>()
             .replace("<", "&lt;")
             .replace(">", "&gt;")
     }
 
-    fn map_language_code(&self, lang: &str) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    fn map_language_code(&self, lang: &str) -> Result<String, AppError> {
         let normalized = lang.trim().to_lowercase();
         let code = match normalized.as_str() {
             "ja" | "japanese" | "jp" => "JA",
             "ko" | "korean" | "kr" => "KO",
             "en" | "english" | "en-us" | "en_us" => "EN-US",
-            "en-gb" | "en_gb" => "EN-GB",
+            "en-gb" | "en_gb" | "english (uk)" => "EN-GB",
             _ => {
-                return Err(format!("Unsupported language code: {}", lang).into());
+                return Err(AppError::translation(
+                    TranslationFailure::UnsupportedLanguage,
+                    None,
+                    format!("Unsupported language code: {}", lang),
+                ));
             }
         };
         Ok(code.to_string())
     }
 
-    /// Translate text using DeepL API
+    /// Translate text using DeepL API.
+    /// With no `source_lang`, DeepL detects the source language itself and
+    /// `glossary_id` (see `glossary_id`) is ignored.
     pub async fn translate(
         &self,
         text: &str,
-        source_lang: &str,
+        source_lang: Option<&str>,
         target_lang: &str,
-    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        glossary_id: Option<&str>,
+    ) -> Result<Translation, AppError> {
         let sanitized_text = self.sanitize_input(text);
 
         if sanitized_text.trim().is_empty() {
-            return Ok(String::new());
+            return Ok(Translation { text: String::new(), source_lang: None });
         }
 
-        let source_code = self.map_language_code(source_lang)?;
+        let source_code = source_lang.map(|lang| self.map_language_code(lang)).transpose()?;
         let target_code = self.map_language_code(target_lang)?;
+
+        let result = self
+            .through_breaker(self.send_translate(&sanitized_text, source_code.as_deref(), &target_code, glossary_id))
+            .await;
+        if result.is_ok() {
+            metrics::record_translation();
+        }
+        result
+    }
+
+    /// Send a DeepL request unless the circuit breaker is open, and tell the breaker how it went
+    async fn through_breaker<T>(&self, request: impl Future<Output = Result<T, AppError>>) -> Result<T, AppError> {
+        if !self.breaker.allow_request(Instant::now()) {
+            return Err(AppError::translation(
+                TranslationFailure::Unavailable,
+                None,
+                "Translation temporarily unavailable (DeepL is failing, retrying shortly)",
+            ));
+        }
+
+        let result = request.await;
+        match &result {
+            Ok(_) => self.breaker.record_success(),
+            Err(e) if e.translation_failure().is_some_and(TranslationFailure::is_outage) => {
+                self.breaker.record_failure(Instant::now())
+            }
+            // DeepL answered; a bad key, request or quota says nothing about whether it's up
+            Err(_) => self.breaker.record_success(),
+        }
+        result
+    }
+
+    async fn send_translate(
+        &self,
+        sanitized_text: &str,
+        source_code: Option<&str>,
+        target_code: &str,
+        glossary_id: Option<&str>,
+    ) -> Result<Translation, AppError> {
         let url = format!("{}/v2/translate", self.api_base);
 
-        let mut last_error: Option<String> = None;
+        let mut last_error: Option<AppError> = None;
         let max_attempts = 3;
+        // A 200 without translations is retried once, in case it was a hiccup
+        let mut retried_empty = false;
 
         for attempt in 1..=max_attempts {
             let response = self
                 .client
                 .post(&url)
                 .header("Authorization", format!("DeepL-Auth-Key {}", self.api_key))
-                .form(&[
-                    ("text", sanitized_text.as_str()),
-                    ("source_lang", source_code.as_str()),
-                    ("target_lang", target_code.as_str()),
-                ])
+                .form(&translate_form(sanitized_text, source_code, target_code, glossary_id))
                 .send()
                 .await;
 
             let response = match response {
                 Ok(resp) => resp,
                 Err(e) => {
-                    last_error = Some(format!("DeepL request failed: {}", e));
+                    metrics::record_deepl_error("network");
+                    let error = AppError::translation(TranslationFailure::Network, None, format!("DeepL request failed: {}", e));
                     if attempt < max_attempts {
-                        sleep(Duration::from_millis(200 * attempt as u64)).await;
+                        last_error = Some(error);
+                        sleep(retry_delay(attempt)).await;
                         continue;
                     }
-                    return Err(last_error.unwrap_or_else(|| "DeepL request failed".to_string()).into());
+                    return Err(error);
                 }
             };
 
             if response.status().is_success() {
-                let deepl_response: DeepLResponse = response.json().await?;
-                if let Some(translation) = deepl_response.translations.first() {
-                    return Ok(translation.text.trim().to_string());
+                let body = response.text().await.map_err(|e| {
+                    AppError::translation(TranslationFailure::Network, None, format!("DeepL response was cut off: {}", e))
+                })?;
+                match parse_translate_body(&body, source_code) {
+                    Ok(Some(translation)) => return Ok(translation),
+                    Ok(None) => {
+                        println!("[DEBUG] DeepL returned no translations: {}", body);
+                        metrics::record_deepl_error("empty");
+                        let error = AppError::translation(TranslationFailure::Other, None, "No translation returned from DeepL API");
+                        if !retried_empty && attempt < max_attempts {
+                            retried_empty = true;
+                            last_error = Some(error);
+                            sleep(retry_delay(attempt)).await;
+                            continue;
+                        }
+                        return Err(error);
+                    }
+                    Err(e) => {
+                        println!("[DEBUG] Unreadable DeepL response body: {}", body);
+                        metrics::record_deepl_error("malformed");
+                        return Err(AppError::translation(
+                            TranslationFailure::Other,
+                            None,
+                            format!("Unreadable DeepL response: {}", e),
+                        ));
+                    }
                 }
-                return Err("No translation returned from DeepL API".into());
             }
 
             let status = response.status();
             let error_text = response.text().await.unwrap_or_default();
             let status_code = status.as_u16();
+            metrics::record_deepl_error(&status_code.to_string());
 
-            let retryable = matches!(status_code, 429 | 500 | 502 | 503 | 504);
-            if retryable && attempt < max_attempts {
-                last_error = Some(format!("DeepL API error: {} - {}", status, error_text));
-                sleep(Duration::from_millis(200 * attempt as u64)).await;
+            let failure = classify_deepl_status(status_code);
+            let message = match failure {
+                TranslationFailure::Auth => {
+                    eprintln!("[ERROR] DeepL rejected the API key ({}); check DEEPL_API_KEY", status_code);
+                    format!("DeepL API key invalid ({})", status_code)
+                }
+                TranslationFailure::BadRequest => format!("DeepL rejected the request (400): {}", error_text),
+                TranslationFailure::QuotaExceeded => "DeepL API quota exceeded (456)".to_string(),
+                _ => format!("DeepL API error: {} - {}", status, error_text),
+            };
+            let error = AppError::translation(failure, Some(status_code), message);
+
+            if failure == TranslationFailure::Transient && attempt < max_attempts {
+                last_error = Some(error);
+                sleep(retry_delay(attempt)).await;
                 continue;
             }
+            return Err(error);
+        }
+
+        Err(last_error.unwrap_or_else(|| AppError::translation(TranslationFailure::Other, None, "DeepL API error")))
+    }
+
+    /// DeepL glossary holding a guild's `entries` for a language pair, created (or
+    /// recreated after the entries change) on first use. None if there's nothing to
+    /// apply or DeepL refused; translation then goes ahead without it, and creating
+    /// the glossary isn't tried again until a backoff has passed.
+    pub async fn glossary_id(
+        &self,
+        guild_id: u64,
+        source_lang: &str,
+        target_lang: &str,
+        entries: &[(String, String)],
+    ) -> Option<String> {
+        let source = glossary_language(&self.map_language_code(source_lang).ok()?);
+        let target = glossary_language(&self.map_language_code(target_lang).ok()?);
+        let key = (guild_id, source.clone(), target.clone());
+        let hash = entries_hash(entries);
+
+        let slot = self.glossaries.lock().unwrap().entry(key).or_default().clone();
+        let mut state = slot.lock().await;
+        let now = Instant::now();
+        let failures = match &*state {
+            Some(GlossaryState::Ready { hash: cached, id }) if *cached == hash => return Some(id.clone()),
+            Some(GlossaryState::Failed { hash: cached, failures, retry_at }) if *cached == hash => {
+                if now < *retry_at {
+                    return None;
+                }
+                *failures
+            }
+            _ => 0,
+        };
+
+        // The vocabulary changed (or was cleared); the old glossary is stale
+        if let Some(GlossaryState::Ready { id: old_id, .. }) = &*state
+            && let Err(e) = self.through_breaker(self.delete_glossary(old_id)).await
+        {
+            eprintln!("[WARN] Failed to delete DeepL glossary {}: {}", old_id, e);
+        }
+        *state = None;
+        if entries.is_empty() {
+            return None;
+        }
 
-            if status_code == 456 {
-                return Err("DeepL API quota exceeded (456)".into());
+        match self.through_breaker(self.create_glossary(guild_id, &source, &target, entries)).await {
+            Ok(id) => {
+                println!("[INFO] Created DeepL glossary {} for guild {} ({} → {})", id, guild_id, source, target);
+                *state = Some(GlossaryState::Ready { hash, id: id.clone() });
+                Some(id)
             }
+            Err(e) => {
+                let failures = failures + 1;
+                let delay = glossary_retry_delay(failures);
+                eprintln!(
+                    "[ERROR] Failed to create DeepL glossary for guild {}, not retrying for {:?}: {}",
+                    guild_id, delay, e
+                );
+                *state = Some(GlossaryState::Failed { hash, failures, retry_at: now + delay });
+                None
+            }
+        }
+    }
+
+    async fn create_glossary(
+        &self,
+        guild_id: u64,
+        source: &str,
+        target: &str,
+        entries: &[(String, String)],
+    ) -> Result<String, AppError> {
+        let name = format!("diggy-gizzy guild {}", guild_id);
+        let tsv = glossary_tsv(entries);
+        let response = self
+            .client
+            .post(format!("{}/v2/glossaries", self.api_base))
+            .header("Authorization", format!("DeepL-Auth-Key {}", self.api_key))
+            .form(&[
+                ("name", name.as_str()),
+                ("source_lang", source),
+                ("target_lang", target),
+                ("entries", tsv.as_str()),
+                ("entries_format", "tsv"),
+            ])
+            .send()
+            .await
+            .map_err(|e| AppError::translation(TranslationFailure::Network, None, format!("DeepL request failed: {}", e)))?;
 
-            return Err(format!("DeepL API error: {} - {}", status, error_text).into());
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(AppError::translation(
+                classify_deepl_status(status.as_u16()),
+                Some(status.as_u16()),
+                format!("DeepL glossary error: {} - {}", status, error_text),
+            ));
         }
+        let glossary: GlossaryResponse = response.json().await.map_err(|e| {
+            AppError::translation(TranslationFailure::Other, None, format!("Unreadable DeepL response: {}", e))
+        })?;
+        Ok(glossary.glossary_id)
+    }
+
+    async fn delete_glossary(&self, glossary_id: &str) -> Result<(), AppError> {
+        let response = self
+            .client
+            .delete(format!("{}/v2/glossaries/{}", self.api_base, glossary_id))
+            .header("Authorization", format!("DeepL-Auth-Key {}", self.api_key))
+            .send()
+            .await
+            .map_err(|e| AppError::translation(TranslationFailure::Network, None, format!("DeepL request failed: {}", e)))?;
 
-        Err(last_error.unwrap_or_else(|| "DeepL API error".to_string()).into())
+        let status = response.status();
+        // Already gone is as good as deleted
+        if status.is_success() || status.as_u16() == 404 {
+            return Ok(());
+        }
+        Err(AppError::translation(
+            classify_deepl_status(status.as_u16()),
+            Some(status.as_u16()),
+            format!("DeepL glossary error: {}", status),
+        ))
     }
 
     /// Detect language locally based on character analysis
@@ -164,10 +567,268 @@ impl Translator {
     }
 }
 
+/// Form fields for a `/v2/translate` request; `source_lang` is left out to let DeepL detect it.
+/// A glossary needs a known source, so it is only sent alongside one.
+fn translate_form<'a>(
+    text: &'a str,
+    source_code: Option<&'a str>,
+    target_code: &'a str,
+    glossary_id: Option<&'a str>,
+) -> Vec<(&'static str, &'a str)> {
+    let mut form = vec![("text", text)];
+    if let Some(source_code) = source_code {
+        form.push(("source_lang", source_code));
+        if let Some(glossary_id) = glossary_id {
+            form.push(("glossary_id", glossary_id));
+        }
+    }
+    form.push(("target_lang", target_code));
+    form
+}
+
+/// Glossaries are per language, not per regional variant: `EN-US` becomes `en`
+fn glossary_language(deepl_code: &str) -> String {
+    deepl_code.split('-').next().unwrap_or(deepl_code).to_lowercase()
+}
+
+/// Glossary entries in DeepL's TSV format; tabs and newlines inside a term would break it
+fn glossary_tsv(entries: &[(String, String)]) -> String {
+    let clean = |s: &str| s.replace(['\t', '\n', '\r'], " ").trim().to_string();
+    entries
+        .iter()
+        .map(|(term, translation)| format!("{}\t{}", clean(term), clean(translation)))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn entries_hash(entries: &[(String, String)]) -> u64 {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    entries.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[derive(Deserialize, Debug)]
+struct GlossaryResponse {
+    glossary_id: String,
+}
+
+/// Split text into pieces of at most `max_chars`, preferring sentence ends,
+/// then whitespace, so each piece can be translated on its own
+pub fn chunk_text(text: &str, max_chars: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut rest: Vec<char> = text.trim().chars().collect();
+
+    while rest.len() > max_chars {
+        let window = &rest[..max_chars];
+        let is_sentence_end = |c: &char| matches!(c, '.' | '!' | '?' | '。' | '！' | '？' | '\n');
+        let split = window.iter().rposition(is_sentence_end)
+            .or_else(|| window.iter().rposition(|c| c.is_whitespace()))
+            .map(|i| i + 1)
+            .unwrap_or(max_chars);
+
+        let chunk: String = rest[..split].iter().collect();
+        if !chunk.trim().is_empty() {
+            chunks.push(chunk.trim().to_string());
+        }
+        rest.drain(..split);
+    }
+
+    let last: String = rest.into_iter().collect();
+    if !last.trim().is_empty() {
+        chunks.push(last.trim().to_string());
+    }
+    chunks
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_status_classification() {
+        assert_eq!(classify_deepl_status(401), TranslationFailure::Auth);
+        assert_eq!(classify_deepl_status(403), TranslationFailure::Auth);
+        assert_eq!(classify_deepl_status(400), TranslationFailure::BadRequest);
+        assert_eq!(classify_deepl_status(429), TranslationFailure::Transient);
+        assert_eq!(classify_deepl_status(500), TranslationFailure::Transient);
+        assert_eq!(classify_deepl_status(503), TranslationFailure::Transient);
+        assert_eq!(classify_deepl_status(456), TranslationFailure::QuotaExceeded);
+        assert_eq!(classify_deepl_status(404), TranslationFailure::Other);
+
+        // Only an unreachable or struggling DeepL trips the circuit breaker
+        for (status, outage) in [(401, false), (403, false), (400, false), (456, false), (429, true), (500, true)] {
+            assert_eq!(classify_deepl_status(status).is_outage(), outage, "status {}", status);
+        }
+        assert!(TranslationFailure::Network.is_outage());
+    }
+
+    #[test]
+    fn test_translate_form_with_optional_source() {
+        assert_eq!(
+            translate_form("hello", Some("EN-US"), "JA", None),
+            vec![("text", "hello"), ("source_lang", "EN-US"), ("target_lang", "JA")]
+        );
+        assert_eq!(
+            translate_form("hello", None, "JA", None),
+            vec![("text", "hello"), ("target_lang", "JA")]
+        );
+        assert_eq!(
+            translate_form("hello", Some("EN-US"), "JA", Some("g1")),
+            vec![("text", "hello"), ("source_lang", "EN-US"), ("glossary_id", "g1"), ("target_lang", "JA")]
+        );
+        // Without a source the glossary can't apply
+        assert_eq!(translate_form("hello", None, "JA", Some("g1")).len(), 2);
+    }
+
+    #[test]
+    fn test_glossary_entries_format() {
+        let entries = vec![
+            ("Falcon".to_string(), "ファルコン".to_string()),
+            ("Big\tQuery".to_string(), "ビッグクエリ\n".to_string()),
+        ];
+        assert_eq!(glossary_tsv(&entries), "Falcon\tファルコン\nBig Query\tビッグクエリ");
+        assert_eq!(glossary_language("EN-US"), "en");
+        assert_eq!(glossary_language("JA"), "ja");
+        assert_ne!(entries_hash(&entries), entries_hash(&entries[..1]));
+    }
+
+    #[test]
+    fn test_detected_source_language() {
+        let body = r#"{"translations":[{"detected_source_language":"KO","text":" Hello "}]}"#;
+        let response: DeepLResponse = serde_json::from_str(body).unwrap();
+        assert_eq!(
+            response.into_translation(None),
+            Some(Translation { text: "Hello".to_string(), source_lang: Some("KO".to_string()) })
+        );
+
+        let body = r#"{"translations":[{"text":"Hello"}]}"#;
+        let response: DeepLResponse = serde_json::from_str(body).unwrap();
+        assert_eq!(response.into_translation(Some("JA")).unwrap().source_lang.as_deref(), Some("JA"));
+    }
+
+    /// Answers each request with the next body as a 200, counting requests
+    async fn stub_deepl(bodies: Vec<&'static str>) -> (String, std::sync::Arc<std::sync::atomic::AtomicUsize>) {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let requests = std::sync::Arc::new(AtomicUsize::new(0));
+        let counter = requests.clone();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                // Read the whole request so closing the socket doesn't reset it
+                let mut request = Vec::new();
+                let mut buf = [0u8; 4096];
+                loop {
+                    let n = socket.read(&mut buf).await.unwrap_or(0);
+                    request.extend_from_slice(&buf[..n]);
+                    let text = String::from_utf8_lossy(&request);
+                    let complete = text.find("\r\n\r\n").is_some_and(|end| {
+                        let length = text.lines()
+                            .find_map(|line| line.to_lowercase().strip_prefix("content-length:").map(|v| v.trim().parse::<usize>().unwrap_or(0)))
+                            .unwrap_or(0);
+                        request.len() >= end + 4 + length
+                    });
+                    if n == 0 || complete {
+                        break;
+                    }
+                }
+                let i = counter.fetch_add(1, Ordering::SeqCst);
+                let body = bodies[i.min(bodies.len() - 1)];
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+        (base, requests)
+    }
+
+    #[tokio::test]
+    async fn test_empty_and_malformed_responses() {
+        use std::sync::atomic::Ordering;
+        let translated = r#"{"translations":[{"detected_source_language":"JA","text":"Hello"}]}"#;
+
+        // An empty result is retried once and the second answer is used
+        let (base, requests) = stub_deepl(vec![r#"{"translations":[]}"#, translated]).await;
+        let translator = Translator::new("key".to_string()).with_api_base(&base);
+        let result = translator.translate("こんにちは", Some("ja"), "en", None).await.unwrap();
+        assert_eq!(result.text, "Hello");
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+
+        // Empty twice in a row: give up after the one retry
+        let (base, requests) = stub_deepl(vec![r#"{"translations":[]}"#]).await;
+        let translator = Translator::new("key".to_string()).with_api_base(&base);
+        let err = translator.translate("こんにちは", Some("ja"), "en", None).await.unwrap_err();
+        assert_eq!(err.to_string(), "No translation returned from DeepL API");
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+
+        // Malformed JSON is reported as such and not retried
+        let (base, requests) = stub_deepl(vec![r#"{"translations": ["#]).await;
+        let translator = Translator::new("key".to_string()).with_api_base(&base);
+        let err = translator.translate("こんにちは", Some("ja"), "en", None).await.unwrap_err();
+        assert!(err.to_string().starts_with("Unreadable DeepL response"));
+        assert_eq!(err.translation_failure(), Some(TranslationFailure::Other));
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+        assert!(parse_translate_body(r#"{"unexpected":true}"#, None).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_glossary_outcomes_are_cached() {
+        use std::sync::atomic::Ordering;
+        let entries = vec![("Falcon".to_string(), "ファルコン".to_string())];
+
+        let (base, requests) = stub_deepl(vec![r#"{"glossary_id":"g1"}"#]).await;
+        let translator = Translator::new("key".to_string()).with_api_base(&base);
+        assert_eq!(translator.glossary_id(1, "en", "ja", &entries).await.as_deref(), Some("g1"));
+        assert_eq!(translator.glossary_id(1, "en", "ja", &entries).await.as_deref(), Some("g1"));
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+        // A refused glossary isn't requested again on every utterance
+        let (base, requests) = stub_deepl(vec!["{}"]).await;
+        let translator = Translator::new("key".to_string()).with_api_base(&base);
+        assert_eq!(translator.glossary_id(1, "en", "ja", &entries).await, None);
+        assert_eq!(translator.glossary_id(1, "en", "ja", &entries).await, None);
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+        // Other guilds and changed entries aren't held back by it
+        assert_eq!(translator.glossary_id(2, "en", "ja", &entries).await, None);
+        let changed = vec![("Falcon".to_string(), "ハヤブサ".to_string())];
+        assert_eq!(translator.glossary_id(1, "en", "ja", &changed).await, None);
+        assert_eq!(requests.load(Ordering::SeqCst), 3);
+
+        assert_eq!(glossary_retry_delay(1), GLOSSARY_RETRY_BASE);
+        assert_eq!(glossary_retry_delay(2), GLOSSARY_RETRY_BASE * 2);
+        assert_eq!(glossary_retry_delay(30), GLOSSARY_RETRY_MAX);
+    }
+
+    #[test]
+    fn test_confident_source() {
+        assert_eq!(confident_source("ja", "今日はいい天気ですね"), Some("ja"));
+        assert_eq!(confident_source("ja", "Let's start the meeting"), None);
+        assert_eq!(confident_source("ko", "안녕하세요"), Some("ko"));
+        assert_eq!(confident_source("en", "こんにちは"), None);
+        // Nothing to judge by: trust the setting
+        assert_eq!(confident_source("en", "123!"), Some("en"));
+    }
+
+    #[test]
+    fn test_chunk_text_prefers_sentence_boundaries() {
+        let text = "First sentence. Second one here. Third.";
+        let chunks = chunk_text(text, 20);
+        assert_eq!(chunks, vec!["First sentence.", "Second one here.", "Third."]);
+
+        // No boundary at all: hard split at the limit
+        let chunks = chunk_text(&"あ".repeat(25), 10);
+        assert_eq!(chunks.iter().map(|c| c.chars().count()).collect::<Vec<_>>(), vec![10, 10, 5]);
+
+        assert!(chunk_text("   ", 10).is_empty());
+    }
+
     #[test]
     fn test_detect_language_japanese() {
         let text = "こんにちは世界";
@@ -203,5 +864,85 @@ mod tests {
         assert_eq!(translator.map_language_code("en").unwrap(), "EN-US");
         assert_eq!(translator.map_language_code("en-us").unwrap(), "EN-US");
         assert_eq!(translator.map_language_code("en-gb").unwrap(), "EN-GB");
+
+        // translate_utterance passes display names, so each one must map like its code
+        for code in crate::user_settings::SELECTABLE_LANGUAGES {
+            let setting = crate::user_settings::UserLanguageSetting::new(code, "ja");
+            let name = setting.to_full_name(code);
+            assert_ne!(name, code);
+            assert_eq!(translator.map_language_code(&name).unwrap(), translator.map_language_code(code).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_breaker_opens_after_threshold() {
+        let breaker = CircuitBreaker::new(3, Duration::from_secs(30));
+        let now = Instant::now();
+
+        breaker.record_failure(now);
+        breaker.record_failure(now);
+        assert_eq!(breaker.state(), BreakerState::Closed);
+        assert!(breaker.allow_request(now));
+
+        breaker.record_failure(now);
+        assert!(matches!(breaker.state(), BreakerState::Open { .. }));
+        assert!(!breaker.allow_request(now + Duration::from_secs(10)));
+
+        // The outage is announced once
+        assert!(breaker.take_open_notice());
+        assert!(!breaker.take_open_notice());
+    }
+
+    #[test]
+    fn test_breaker_half_open_trial() {
+        let breaker = CircuitBreaker::new(1, Duration::from_secs(30));
+        let now = Instant::now();
+        breaker.record_failure(now);
+
+        // After the cooldown a single trial request is allowed
+        let later = now + Duration::from_secs(31);
+        assert!(breaker.allow_request(later));
+        assert!(matches!(breaker.state(), BreakerState::HalfOpen { .. }));
+        assert!(!breaker.allow_request(later));
+
+        // A failed trial reopens the breaker for another cooldown
+        breaker.record_failure(later);
+        assert!(matches!(breaker.state(), BreakerState::Open { until } if until == later + Duration::from_secs(30)));
+
+        // A successful trial closes it
+        let much_later = later + Duration::from_secs(31);
+        assert!(breaker.allow_request(much_later));
+        breaker.record_success();
+        assert_eq!(breaker.state(), BreakerState::Closed);
+        assert!(breaker.allow_request(much_later));
+        assert!(!breaker.take_open_notice());
+    }
+
+    #[test]
+    fn test_breaker_recovers_from_an_abandoned_trial() {
+        let breaker = CircuitBreaker::new(1, Duration::from_secs(30));
+        let now = Instant::now();
+        breaker.record_failure(now);
+
+        // The trial request was dropped before it could report a result
+        let trial = now + Duration::from_secs(31);
+        assert!(breaker.allow_request(trial));
+        assert!(!breaker.allow_request(trial + Duration::from_secs(29)));
+
+        // After another cooldown a fresh trial goes through instead of blocking forever
+        let retry = trial + Duration::from_secs(30);
+        assert!(breaker.allow_request(retry));
+        assert!(!breaker.allow_request(retry));
+        breaker.record_success();
+        assert_eq!(breaker.state(), BreakerState::Closed);
+    }
+
+    #[test]
+    fn test_retry_delay_is_jittered_within_bounds() {
+        for attempt in 1..=3 {
+            let base = RETRY_BASE_DELAY_MS << (attempt - 1);
+            let delay = retry_delay(attempt).as_millis() as u64;
+            assert!(delay >= base / 2 && delay <= base, "attempt {}: {}ms", attempt, delay);
+        }
     }
 }
@@ -1,16 +1,23 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::fs;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::path::Path;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::RwLock;
+use crate::persist::{self, DebouncedSave};
 use twilight_model::id::Id;
 use twilight_model::id::marker::UserMarker;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserLanguageSetting {
-    pub source_lang: String,  // 話す言語 (ja, ko, en)
-    pub target_lang: String,  // 翻訳先言語 (ja, ko, en)
+    pub source_lang: String,  // 話す言語 (ja, ko, en, en-gb)
+    pub target_lang: String,  // 翻訳先言語 (ja, ko, en, en-gb)
+    #[serde(default)]
+    pub target_langs: Vec<String>,  // 追加の翻訳先言語
+    #[serde(default)]
+    pub romaji: bool,  // 日本語の発話にローマ字を併記する
+    #[serde(default)]
+    pub auto_swap: bool,  // 翻訳先の言語で話したら逆方向に翻訳する
 }
 
 impl UserLanguageSetting {
@@ -18,73 +25,212 @@ impl UserLanguageSetting {
         Self {
             source_lang: source.to_string(),
             target_lang: target.to_string(),
+            target_langs: Vec::new(),
+            romaji: false,
+            auto_swap: false,
         }
     }
 
+    /// All translation targets, primary first, without duplicates or the source language
+    pub fn targets(&self) -> Vec<String> {
+        let mut targets: Vec<String> = Vec::new();
+        for lang in std::iter::once(&self.target_lang).chain(&self.target_langs) {
+            if !targets.contains(lang) && (lang != &self.source_lang || targets.is_empty()) {
+                targets.push(lang.clone());
+            }
+        }
+        targets
+    }
+
+    /// Whether a romaji line should accompany this speaker's transcriptions
+    pub fn wants_romaji(&self) -> bool {
+        self.romaji && self.source_lang == "ja"
+    }
+
+    /// With auto-swap on, an utterance detected in the primary target language is
+    /// translated the other way: the target becomes the source and vice versa
+    pub fn swapped_for(&self, detected_lang: &str) -> Option<Self> {
+        let detected = speech_language(detected_lang);
+        if !self.auto_swap
+            || detected != speech_language(&self.target_lang)
+            || detected == speech_language(&self.source_lang)
+        {
+            return None;
+        }
+        Some(Self {
+            source_lang: self.target_lang.clone(),
+            target_lang: self.source_lang.clone(),
+            target_langs: Vec::new(),
+            ..self.clone()
+        })
+    }
+
+    /// Every language code in the setting is one `/translate_set` offers
+    pub fn validate(&self) -> Result<(), String> {
+        let codes: Vec<&str> = [self.source_lang.as_str(), self.target_lang.as_str()]
+            .into_iter()
+            .chain(self.target_langs.iter().map(String::as_str))
+            .collect();
+        validate_languages(&codes)
+    }
+
     pub fn to_full_name(&self, lang: &str) -> String {
-        match lang {
-            "ja" => "Japanese",
-            "ko" => "Korean",
-            "en" => "English",
-            _ => lang,
-        }.to_string()
+        language_name(lang).unwrap_or(lang).to_string()
     }
 
     pub fn get_source_full(&self) -> String {
         self.to_full_name(&self.source_lang)
     }
+}
+
+/// Codes offered by `/translate_set`
+pub const SELECTABLE_LANGUAGES: [&str; 4] = ["ja", "ko", "en", "en-gb"];
+
+/// English name of a language code, if it's one the bot knows
+pub fn language_name(code: &str) -> Option<&'static str> {
+    match code {
+        "ja" => Some("Japanese"),
+        "ko" => Some("Korean"),
+        "en" => Some("English"),
+        "en-gb" => Some("English (UK)"),
+        _ => None,
+    }
+}
 
-    pub fn get_target_full(&self) -> String {
-        self.to_full_name(&self.target_lang)
+pub fn language_flag(code: &str) -> &'static str {
+    match code {
+        "ja" => "🇯🇵",
+        "ko" => "🇰🇷",
+        "en" => "🇺🇸",
+        "en-gb" => "🇬🇧",
+        _ => "🌐",
     }
 }
 
+/// Check every code picked in `/translate_set`
+pub fn validate_languages(codes: &[&str]) -> Result<(), String> {
+    match codes.iter().find(|code| !SELECTABLE_LANGUAGES.contains(code)) {
+        Some(code) => Err(format!("Invalid language code `{}`. Use: {}", code, SELECTABLE_LANGUAGES.join(", "))),
+        None => Ok(()),
+    }
+}
+
+/// Whisper only knows base languages, so regional variants like `en-gb` are spoken as `en`
+pub fn speech_language(code: &str) -> &str {
+    code.split('-').next().unwrap_or(code)
+}
+
+/// Listener mode targets for one speaker: every other participant's language,
+/// once each, sorted so the order doesn't depend on who joined first, skipping the
+/// language already being spoken
+pub fn listener_targets(
+    speaker_id: Id<UserMarker>,
+    speaker_source: &str,
+    participants: &[(Id<UserMarker>, UserLanguageSetting)],
+) -> Vec<String> {
+    let targets: BTreeSet<&String> = participants
+        .iter()
+        .filter(|(user_id, setting)| *user_id != speaker_id && setting.source_lang != speaker_source)
+        .map(|(_, setting)| &setting.source_lang)
+        .collect();
+    targets.into_iter().cloned().collect()
+}
+
+/// Settings as `/settings_export` writes them: the `user_settings.json` format, sorted by user
+pub fn export_settings(settings: &[(Id<UserMarker>, UserLanguageSetting)]) -> Result<String, serde_json::Error> {
+    let sorted: BTreeMap<_, _> = settings.iter().map(|(user_id, setting)| (*user_id, setting)).collect();
+    serde_json::to_string_pretty(&sorted)
+}
+
+/// Entries of an exported file that can be applied, and how many were skipped
+#[derive(Debug, Default)]
+pub struct SettingsImport {
+    pub valid: Vec<(Id<UserMarker>, UserLanguageSetting)>,
+    pub skipped: usize,
+}
+
+/// Parse an exported settings file. Entries with a bad user id, a malformed
+/// setting or an unknown language are skipped; a file that isn't a JSON object fails.
+pub fn parse_settings_import(json: &str) -> Result<SettingsImport, String> {
+    let entries: BTreeMap<String, serde_json::Value> = serde_json::from_str(json)
+        .map_err(|e| format!("Not a settings export: {}", e))?;
+    let mut import = SettingsImport::default();
+    for (user_id, value) in entries {
+        let user_id = user_id.parse::<u64>().ok().and_then(Id::new_checked);
+        let setting = serde_json::from_value::<UserLanguageSetting>(value).ok()
+            .filter(|setting| setting.validate().is_ok());
+        match (user_id, setting) {
+            (Some(user_id), Some(setting)) => import.valid.push((user_id, setting)),
+            _ => import.skipped += 1,
+        }
+    }
+    Ok(import)
+}
+
 pub struct UserSettingsManager {
     settings: Arc<RwLock<HashMap<Id<UserMarker>, UserLanguageSetting>>>,
     file_path: String,
+    saves: DebouncedSave,
 }
 
 impl UserSettingsManager {
+    /// Loads synchronously; this only runs once at startup
     pub fn new(file_path: &str) -> Self {
         let settings = Self::load_from_file(file_path);
         Self {
             settings: Arc::new(RwLock::new(settings)),
             file_path: file_path.to_string(),
+            saves: DebouncedSave::default(),
         }
     }
 
     fn load_from_file(path: &str) -> HashMap<Id<UserMarker>, UserLanguageSetting> {
-        if !Path::new(path).exists() {
-            return HashMap::new();
-        }
-
-        match fs::read_to_string(path) {
-            Ok(content) => {
-                serde_json::from_str(&content).unwrap_or_default()
-            }
-            Err(_) => HashMap::new(),
-        }
+        persist::load_json_or_default(Path::new(path))
     }
 
+    /// Write the current settings without blocking the runtime; nothing is
+    /// written if the latest change is already on disk
     async fn save_to_file(&self) {
-        let settings = self.settings.read().await;
-        if let Ok(json) = serde_json::to_string_pretty(&*settings) {
-            let _ = fs::write(&self.file_path, json);
+        let result = self.saves.save(Path::new(&self.file_path), || async {
+            serde_json::to_string_pretty(&*self.settings.read().await)
+        }).await;
+        match result {
+            Ok(_) => {}
+            Err(e) => eprintln!("[ERROR] Failed to save user settings to {}: {}", self.file_path, e),
         }
     }
 
+    /// Save changes in the background at most once per `interval`
+    pub fn spawn_saver(self: &Arc<Self>, interval: Duration) {
+        let manager = self.clone();
+        tokio::spawn(async move {
+            manager.saves.run(interval, || manager.save_to_file()).await;
+        });
+    }
+
+    /// Write pending changes now, e.g. on shutdown
+    pub async fn flush(&self) {
+        self.save_to_file().await;
+    }
+
     pub async fn set_user_language(
         &self,
         user_id: Id<UserMarker>,
         source_lang: &str,
         target_lang: &str,
+        extra_targets: &[&str],
+        romaji: bool,
+        auto_swap: bool,
     ) {
-        let setting = UserLanguageSetting::new(source_lang, target_lang);
+        let mut setting = UserLanguageSetting::new(source_lang, target_lang);
+        setting.target_langs = extra_targets.iter().map(|lang| lang.to_string()).collect();
+        setting.romaji = romaji;
+        setting.auto_swap = auto_swap;
         {
             let mut settings = self.settings.write().await;
             settings.insert(user_id, setting);
+            self.saves.mark_dirty();
         }
-        self.save_to_file().await;
     }
 
     pub async fn get_user_setting(&self, user_id: Id<UserMarker>) -> Option<UserLanguageSetting> {
@@ -96,8 +242,20 @@ impl UserSettingsManager {
         {
             let mut settings = self.settings.write().await;
             settings.remove(&user_id);
+            self.saves.mark_dirty();
         }
-        self.save_to_file().await;
+    }
+
+    /// Set many users' settings at once, replacing theirs and leaving everyone else's.
+    /// Returns how many were set.
+    pub async fn set_many(&self, entries: Vec<(Id<UserMarker>, UserLanguageSetting)>) -> usize {
+        let count = entries.len();
+        if count > 0 {
+            let mut settings = self.settings.write().await;
+            settings.extend(entries);
+            self.saves.mark_dirty();
+        }
+        count
     }
 
     pub async fn list_all_settings(&self) -> Vec<(Id<UserMarker>, UserLanguageSetting)> {
@@ -109,6 +267,7 @@ impl UserSettingsManager {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::fs;
 
     #[test]
     fn test_user_language_setting() {
@@ -116,6 +275,177 @@ mod tests {
         assert_eq!(setting.source_lang, "ja");
         assert_eq!(setting.target_lang, "ko");
         assert_eq!(setting.get_source_full(), "Japanese");
-        assert_eq!(setting.get_target_full(), "Korean");
+        assert_eq!(setting.to_full_name(&setting.target_lang), "Korean");
+        assert!(!setting.wants_romaji());
+    }
+
+    #[test]
+    fn test_listener_targets_are_distinct() {
+        let participants = vec![
+            (Id::new(1), UserLanguageSetting::new("ja", "en")),
+            (Id::new(2), UserLanguageSetting::new("en", "ja")),
+            (Id::new(3), UserLanguageSetting::new("ko", "ja")),
+            (Id::new(4), UserLanguageSetting::new("en", "ko")),
+            (Id::new(5), UserLanguageSetting::new("ja", "ko")),
+        ];
+
+        // Japanese speaker: English and Korean listeners, each language once
+        assert_eq!(listener_targets(Id::new(1), "ja", &participants), vec!["en", "ko"]);
+        // The speaker's own setting never adds a target
+        assert_eq!(listener_targets(Id::new(3), "ko", &participants), vec!["en", "ja"]);
+        // Same targets whatever order the participants are listed in
+        let mut reversed = participants.clone();
+        reversed.reverse();
+        assert_eq!(listener_targets(Id::new(3), "ko", &reversed), vec!["en", "ja"]);
+        // Alone in the channel: nothing to translate into
+        assert!(listener_targets(Id::new(1), "ja", &participants[..1]).is_empty());
+    }
+
+    #[test]
+    fn test_romaji_only_for_japanese_speakers() {
+        let mut setting = UserLanguageSetting::new("ja", "en");
+        setting.romaji = true;
+        assert!(setting.wants_romaji());
+
+        let mut setting = UserLanguageSetting::new("ko", "ja");
+        setting.romaji = true;
+        assert!(!setting.wants_romaji());
+
+        // Settings saved before the option existed still load
+        let old: UserLanguageSetting = serde_json::from_str(r#"{"source_lang":"ja","target_lang":"ko"}"#).unwrap();
+        assert!(!old.romaji);
+        assert_eq!(old.targets(), vec!["ko".to_string()]);
+    }
+
+    #[test]
+    fn test_auto_swap_when_target_language_is_spoken() {
+        let mut setting = UserLanguageSetting::new("ja", "ko");
+        // Off by default: Korean speech is still treated as Japanese
+        assert!(setting.swapped_for("ko").is_none());
+
+        setting.auto_swap = true;
+        let swapped = setting.swapped_for("ko").unwrap();
+        assert_eq!(swapped.source_lang, "ko");
+        assert_eq!(swapped.targets(), vec!["ja".to_string()]);
+        // Speaking the configured source, or a third language, keeps the direction
+        assert!(setting.swapped_for("ja").is_none());
+        assert!(setting.swapped_for("en").is_none());
+
+        // Whisper reports base languages, so `en` matches an `en-gb` target
+        let mut setting = UserLanguageSetting::new("ja", "en-gb");
+        setting.auto_swap = true;
+        assert_eq!(setting.swapped_for("en").unwrap().source_lang, "en-gb");
+    }
+
+    #[tokio::test]
+    async fn test_settings_import_merges_valid_entries() {
+        let path = std::env::temp_dir().join(format!("user_settings_import_{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let manager = UserSettingsManager::new(&path.to_string_lossy());
+        manager.set_user_language(Id::new(1), "ja", "en", &[], false, false).await;
+        manager.set_user_language(Id::new(2), "ko", "ja", &[], false, false).await;
+
+        // Round trip through the export format
+        let exported = export_settings(&manager.list_all_settings().await).unwrap();
+        let import = parse_settings_import(&exported).unwrap();
+        assert_eq!((import.valid.len(), import.skipped), (2, 0));
+
+        let file = r#"{
+            "2": {"source_lang": "en", "target_lang": "ko", "romaji": true},
+            "3": {"source_lang": "ja", "target_lang": "ko"},
+            "4": {"source_lang": "fr", "target_lang": "ja"},
+            "5": {"source_lang": "ja"},
+            "six": {"source_lang": "ja", "target_lang": "en"},
+            "0": {"source_lang": "ja", "target_lang": "en"}
+        }"#;
+        let import = parse_settings_import(file).unwrap();
+        assert_eq!(import.skipped, 4);
+        assert_eq!(manager.set_many(import.valid).await, 2);
+
+        // Imported users are replaced or added, everyone else is kept
+        assert_eq!(manager.get_user_setting(Id::new(1)).await.unwrap().target_lang, "en");
+        assert_eq!(manager.get_user_setting(Id::new(2)).await.unwrap().source_lang, "en");
+        assert_eq!(manager.get_user_setting(Id::new(3)).await.unwrap().target_lang, "ko");
+        assert!(manager.get_user_setting(Id::new(4)).await.is_none());
+
+        assert!(parse_settings_import("[1, 2]").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_saves_persist_every_setting() {
+        let path = std::env::temp_dir().join(format!("user_settings_concurrent_{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let manager = UserSettingsManager::new(&path.to_string_lossy());
+
+        futures::future::join_all((1..=20u64).map(|id| {
+            manager.set_user_language(Id::new(id), "ja", "en", &[], false, false)
+        })).await;
+        futures::future::join_all((0..4).map(|_| manager.flush())).await;
+
+        let saved: HashMap<Id<UserMarker>, UserLanguageSetting> =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(saved.len(), 20);
+        assert!(saved.values().all(|setting| setting.target_lang == "en"));
+    }
+
+    #[tokio::test]
+    async fn test_british_english_is_selectable() {
+        assert!(validate_languages(&["en-gb", "ja", "en-gb"]).is_ok());
+        assert!(validate_languages(&["ja", "fr"]).unwrap_err().contains("`fr`"));
+
+        let path = std::env::temp_dir().join(format!("user_settings_en_gb_{}.json", std::process::id()));
+        let manager = UserSettingsManager::new(&path.to_string_lossy());
+        manager.set_user_language(Id::new(1), "en-gb", "ja", &["en-gb"], false, false).await;
+        manager.flush().await;
+
+        let reloaded = UserSettingsManager::new(&path.to_string_lossy());
+        let setting = reloaded.get_user_setting(Id::new(1)).await.unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(setting.source_lang, "en-gb");
+        assert_eq!(setting.get_source_full(), "English (UK)");
+        assert_eq!(setting.targets(), vec!["ja".to_string()]);
+        assert_eq!(language_flag(&setting.source_lang), "🇬🇧");
+        assert_eq!(speech_language(&setting.source_lang), "en");
+        assert_eq!(speech_language("ja"), "ja");
+    }
+
+    #[tokio::test]
+    async fn test_burst_of_changes_is_written_once() {
+        let path = std::env::temp_dir().join(format!("user_settings_debounce_{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let manager = Arc::new(UserSettingsManager::new(&path.to_string_lossy()));
+        manager.spawn_saver(Duration::from_millis(50));
+
+        for id in 1..=10u64 {
+            manager.set_user_language(Id::new(id), "ko", "ja", &[], false, false).await;
+        }
+        manager.remove_user_setting(Id::new(10)).await;
+        assert!(!path.exists());
+
+        // The saver can't run until this test yields, so the whole burst is pending by then
+        tokio::time::timeout(Duration::from_secs(10), async {
+            while manager.saves.writes() == 0 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("the burst was never saved");
+        assert_eq!(manager.saves.writes(), 1);
+        let saved: HashMap<Id<UserMarker>, UserLanguageSetting> =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved.len(), 9);
+
+        // Nothing new to write
+        manager.flush().await;
+        assert_eq!(manager.saves.writes(), 1);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_multiple_targets() {
+        let mut setting = UserLanguageSetting::new("ja", "ko");
+        setting.target_langs = vec!["en".to_string(), "ko".to_string(), "ja".to_string()];
+        assert_eq!(setting.targets(), vec!["ko".to_string(), "en".to_string()]);
     }
 }
@@ -0,0 +1,139 @@
+use songbird::events::{DisconnectReason, EventContext, EventHandler as SongbirdEventHandler};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use twilight_model::id::{marker::GuildMarker, Id};
+
+/// Rejoins tried after a dropped voice connection before the session is finalized
+pub const MAX_RECONNECT_ATTEMPTS: u32 = 3;
+/// Wait before the first rejoin; doubled for each one after
+pub const RECONNECT_BASE_DELAY: Duration = Duration::from_secs(2);
+
+/// What happened to a guild's voice connection
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VoiceConnectionEvent {
+    /// The driver connected or reconnected
+    Connected,
+    /// The connection dropped without being asked to
+    Dropped,
+    /// The bot left on purpose
+    Left,
+    /// A rejoin started by the supervisor failed
+    RejoinFailed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReconnectAction {
+    Ignore,
+    Rejoin { attempt: u32, delay: Duration },
+    /// Out of attempts: tell the channel and finalize the session
+    GiveUp,
+}
+
+/// Per-guild reconnect state: counts attempts with exponential backoff and
+/// ignores further drops while a rejoin is already underway
+#[derive(Debug, Clone)]
+pub struct ReconnectPolicy {
+    max_attempts: u32,
+    base_delay: Duration,
+    attempts: u32,
+    rejoining: bool,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self::new(MAX_RECONNECT_ATTEMPTS, RECONNECT_BASE_DELAY)
+    }
+}
+
+impl ReconnectPolicy {
+    pub fn new(max_attempts: u32, base_delay: Duration) -> Self {
+        Self { max_attempts, base_delay, attempts: 0, rejoining: false }
+    }
+
+    pub fn on_event(&mut self, event: VoiceConnectionEvent) -> ReconnectAction {
+        match event {
+            VoiceConnectionEvent::Connected | VoiceConnectionEvent::Left => {
+                self.reset();
+                ReconnectAction::Ignore
+            }
+            VoiceConnectionEvent::Dropped if self.rejoining => ReconnectAction::Ignore,
+            VoiceConnectionEvent::Dropped | VoiceConnectionEvent::RejoinFailed => self.next_attempt(),
+        }
+    }
+
+    fn next_attempt(&mut self) -> ReconnectAction {
+        if self.attempts >= self.max_attempts {
+            self.reset();
+            return ReconnectAction::GiveUp;
+        }
+        self.attempts += 1;
+        self.rejoining = true;
+        ReconnectAction::Rejoin {
+            attempt: self.attempts,
+            delay: self.base_delay * 2u32.pow(self.attempts - 1),
+        }
+    }
+
+    fn reset(&mut self) {
+        self.attempts = 0;
+        self.rejoining = false;
+    }
+}
+
+/// Forwards a call's driver connect/disconnect events to the reconnect supervisor
+#[derive(Clone)]
+pub struct VoiceConnectionHandler {
+    guild_id: Id<GuildMarker>,
+    events: mpsc::UnboundedSender<(Id<GuildMarker>, VoiceConnectionEvent)>,
+}
+
+impl VoiceConnectionHandler {
+    pub fn new(guild_id: Id<GuildMarker>, events: mpsc::UnboundedSender<(Id<GuildMarker>, VoiceConnectionEvent)>) -> Self {
+        Self { guild_id, events }
+    }
+}
+
+#[async_trait::async_trait]
+impl SongbirdEventHandler for VoiceConnectionHandler {
+    async fn act(&self, ctx: &EventContext<'_>) -> Option<songbird::Event> {
+        let event = match ctx {
+            EventContext::DriverConnect(_) | EventContext::DriverReconnect(_) => VoiceConnectionEvent::Connected,
+            EventContext::DriverDisconnect(data) if data.reason == Some(DisconnectReason::Requested) => {
+                VoiceConnectionEvent::Left
+            }
+            EventContext::DriverDisconnect(data) => {
+                println!("[WARN] Voice connection in guild {} dropped ({:?}, {:?})", self.guild_id, data.kind, data.reason);
+                VoiceConnectionEvent::Dropped
+            }
+            _ => return None,
+        };
+        let _ = self.events.send((self.guild_id, event));
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reconnect_backs_off_then_gives_up() {
+        let mut policy = ReconnectPolicy::new(3, Duration::from_secs(2));
+
+        assert_eq!(policy.on_event(VoiceConnectionEvent::Dropped), ReconnectAction::Rejoin { attempt: 1, delay: Duration::from_secs(2) });
+        // A second drop report while the rejoin is pending doesn't start another
+        assert_eq!(policy.on_event(VoiceConnectionEvent::Dropped), ReconnectAction::Ignore);
+        assert_eq!(policy.on_event(VoiceConnectionEvent::RejoinFailed), ReconnectAction::Rejoin { attempt: 2, delay: Duration::from_secs(4) });
+        assert_eq!(policy.on_event(VoiceConnectionEvent::RejoinFailed), ReconnectAction::Rejoin { attempt: 3, delay: Duration::from_secs(8) });
+        assert_eq!(policy.on_event(VoiceConnectionEvent::RejoinFailed), ReconnectAction::GiveUp);
+
+        // A successful connection starts the count over
+        policy.on_event(VoiceConnectionEvent::Dropped);
+        assert_eq!(policy.on_event(VoiceConnectionEvent::Connected), ReconnectAction::Ignore);
+        assert_eq!(policy.on_event(VoiceConnectionEvent::Dropped), ReconnectAction::Rejoin { attempt: 1, delay: Duration::from_secs(2) });
+
+        // Leaving on purpose is never retried
+        assert_eq!(policy.on_event(VoiceConnectionEvent::Left), ReconnectAction::Ignore);
+        assert_eq!(policy.on_event(VoiceConnectionEvent::Left), ReconnectAction::Ignore);
+    }
+}
@@ -1,19 +1,450 @@
-use std::collections::HashMap;
-use std::sync::Arc;
-use tokio::sync::{Mutex, RwLock};
+use std::borrow::Cow;
+use std::io::Write;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{mpsc as std_mpsc, Arc, OnceLock};
+use tokio::sync::{oneshot, watch, Mutex, RwLock};
 use twilight_model::id::Id;
 use hound::{WavSpec, WavWriter};
-use chrono::Local;
+use chrono::{DateTime, Local, NaiveDateTime};
+use regex::Regex;
+use std::sync::LazyLock;
 use songbird::events::{EventContext, EventHandler as SongbirdEventHandler};
+use songbird::events::context_data::RtpData;
+use songbird::packet::Packet;
+use crate::error::AppError;
+use crate::metrics;
+use crate::transcriber;
+use crate::ogg_opus;
+use crate::mp3;
+use crate::wav_info;
+use crate::timezone;
+use crate::preroll::Preroll;
 
 pub type SpeakerId = Id<twilight_model::id::marker::UserMarker>;
 
+/// How received voice is stored
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordingMode {
+    /// Decode to PCM and save per-speaker WAV files for transcription
+    Pcm,
+    /// Keep the received Opus packets as per-speaker `.ogg` files.
+    /// Audio is never decoded, so transcription is skipped.
+    OpusPassthrough,
+}
+
+impl RecordingMode {
+    /// Parse the `RECORDING_MODE` setting, defaulting to PCM
+    pub fn from_config(value: &str) -> Self {
+        match value.trim().to_lowercase().as_str() {
+            "opus" | "ogg" | "passthrough" => RecordingMode::OpusPassthrough,
+            _ => RecordingMode::Pcm,
+        }
+    }
+}
+
+/// File format a PCM recording is saved in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OutputFormat {
+    /// Lossless 16-bit WAV, the format transcription reads
+    Wav,
+    /// Ogg Opus, a compact copy for sharing
+    Opus,
+    /// MP3, for players and services that don't take Ogg
+    Mp3,
+}
+
+impl OutputFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::Wav => "wav",
+            OutputFormat::Opus => "ogg",
+            OutputFormat::Mp3 => "mp3",
+        }
+    }
+
+    /// Parse a comma-separated `RECORDING_FORMATS` list, ignoring unknown entries
+    pub fn parse_list(value: &str) -> Vec<OutputFormat> {
+        let mut formats = Vec::new();
+        for entry in value.split(',') {
+            let format = match entry.trim().to_lowercase().as_str() {
+                "wav" => OutputFormat::Wav,
+                "opus" | "ogg" => OutputFormat::Opus,
+                "mp3" => OutputFormat::Mp3,
+                "" => continue,
+                other => {
+                    eprintln!("[WARN] Unknown recording format '{}' ignored", other);
+                    continue;
+                }
+            };
+            if !formats.contains(&format) {
+                formats.push(format);
+            }
+        }
+        formats
+    }
+}
+
+/// Whether a mixed recording of all speakers is saved, and how
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mixdown {
+    Off,
+    /// Every speaker summed into one mono track
+    Mono,
+    /// Speakers panned round-robin across a 2-channel track
+    Stereo,
+}
+
+impl Mixdown {
+    /// Parse the `RECORD_MIXDOWN` setting: `true`/`mono`, `stereo`, anything else is off
+    pub fn from_config(value: &str) -> Self {
+        match value.trim().to_lowercase().as_str() {
+            "1" | "true" | "yes" | "mono" => Mixdown::Mono,
+            "stereo" => Mixdown::Stereo,
+            _ => Mixdown::Off,
+        }
+    }
+}
+
+/// Timestamp format used in recording filenames
+const FILENAME_TIMESTAMP_FORMAT: &str = "%Y%m%d_%H%M%S";
+
+/// `{guild}_{user}_{YYYYMMDD}_{HHMMSS}` with an optional `_{n}` collision suffix
+static RECORDING_FILENAME_PATTERN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(\d+)_(\d+)_(\d{8}_\d{6})(?:_(\d+))?$").unwrap()
+});
+
+/// The parts encoded in a recording's filename
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordingFileName {
+    pub guild_id: Id<twilight_model::id::marker::GuildMarker>,
+    pub user_id: SpeakerId,
+    pub started_at: NaiveDateTime,
+    pub suffix: u32,
+}
+
+/// File name (without directory) for a speaker's recording
+pub fn recording_filename(
+    guild_id: Id<twilight_model::id::marker::GuildMarker>,
+    user_id: SpeakerId,
+    started_at: &DateTime<Local>,
+    suffix: u32,
+    extension: &str,
+) -> String {
+    format!(
+        "{}_{}_{}_{}.{}",
+        guild_id,
+        user_id,
+        timezone::format(started_at, FILENAME_TIMESTAMP_FORMAT),
+        suffix,
+        extension
+    )
+}
+
+/// Parse a path produced by [`recording_filename`]. Older names without the
+/// collision suffix are accepted; anything else returns None.
+pub fn parse_recording_filename(path: &str) -> Option<RecordingFileName> {
+    let stem = std::path::Path::new(path).file_stem()?.to_str()?;
+    let caps = RECORDING_FILENAME_PATTERN.captures(stem)?;
+
+    Some(RecordingFileName {
+        guild_id: Id::new_checked(caps[1].parse().ok()?)?,
+        user_id: Id::new_checked(caps[2].parse().ok()?)?,
+        started_at: NaiveDateTime::parse_from_str(&caps[3], FILENAME_TIMESTAMP_FORMAT).ok()?,
+        suffix: caps.get(4).map_or(Some(0), |m| m.as_str().parse().ok())?,
+    })
+}
+
+/// Above this fraction of full scale the mixdown is soft-clipped instead of hard-clipped
+const MIX_SOFT_CLIP_KNEE: f32 = 0.8;
+
+/// Compress a summed sample into i16 range with a tanh knee
+fn soft_clip(sample: i32) -> i16 {
+    let x = sample as f32 / i16::MAX as f32;
+    let magnitude = x.abs();
+    let clipped = if magnitude <= MIX_SOFT_CLIP_KNEE {
+        magnitude
+    } else {
+        let headroom = 1.0 - MIX_SOFT_CLIP_KNEE;
+        MIX_SOFT_CLIP_KNEE + headroom * ((magnitude - MIX_SOFT_CLIP_KNEE) / headroom).tanh()
+    };
+    (clipped.copysign(x) * i16::MAX as f32).round() as i16
+}
+
+/// Sums chunks of audio placed on a timeline. Chunks added in order of their
+/// offsets let the finished start of the mix be drained as it goes, so only
+/// the overlap still being summed is kept in memory.
+struct Mixer {
+    stereo: bool,
+    /// Timeline position of the first pending frame
+    start: usize,
+    /// Running sums, interleaved left/right in stereo
+    pending: VecDeque<f32>,
+}
+
+impl Mixer {
+    fn new(stereo: bool) -> Self {
+        Self { stereo, start: 0, pending: VecDeque::new() }
+    }
+
+    fn channels(&self) -> usize {
+        if self.stereo { 2 } else { 1 }
+    }
+
+    /// Add a chunk at `offset`; `pan` only matters in stereo. A chunk starting
+    /// before what was already drained is moved up to the drained position.
+    fn add(&mut self, offset: usize, samples: &[i16], pan: f32) {
+        let channels = self.channels();
+        let from = (offset.max(self.start) - self.start) * channels;
+        let needed = from + samples.len() * channels;
+        if self.pending.len() < needed {
+            self.pending.resize(needed, 0.0);
+        }
+        let gains = if self.stereo { pan_gains(pan) } else { (1.0, 0.0) };
+        for (i, &sample) in samples.iter().enumerate() {
+            let at = from + i * channels;
+            self.pending[at] += sample as f32 * gains.0;
+            if self.stereo {
+                self.pending[at + 1] += sample as f32 * gains.1;
+            }
+        }
+    }
+
+    /// The finished mix up to timeline position `offset`, silence filling any gaps
+    fn drain_before(&mut self, offset: usize) -> Vec<i16> {
+        let frames = offset.saturating_sub(self.start);
+        let len = frames * self.channels();
+        if self.pending.len() < len {
+            self.pending.resize(len, 0.0);
+        }
+        self.start += frames;
+        self.pending.drain(..len).map(|sample| soft_clip(sample.round() as i32)).collect()
+    }
+
+    /// Everything left in the mix
+    fn finish(mut self) -> Vec<i16> {
+        let end = self.start + self.pending.len() / self.channels();
+        self.drain_before(end)
+    }
+}
+
+/// Pan positions (-1.0 left to 1.0 right) handed out to speakers in turn
+const STEREO_PAN_POSITIONS: [f32; 5] = [-0.6, 0.6, -0.3, 0.3, 0.0];
+
+/// Pan position of the `index`-th speaker in the stereo mixdown
+pub fn pan_position(index: usize) -> f32 {
+    STEREO_PAN_POSITIONS[index % STEREO_PAN_POSITIONS.len()]
+}
+
+/// Equal-power left/right gains for a pan position
+fn pan_gains(pan: f32) -> (f32, f32) {
+    let angle = (pan.clamp(-1.0, 1.0) + 1.0) * std::f32::consts::FRAC_PI_4;
+    (angle.cos(), angle.sin())
+}
+
+/// First `{output_dir}/{name(n)}` that doesn't exist yet. The file is created empty
+/// to claim it, so two sessions asking at once can't both be handed the same path.
+fn unique_path(output_dir: &str, name: impl Fn(u32) -> String) -> String {
+    let mut suffix = 0u32;
+    loop {
+        let filename = format!("{}/{}", output_dir, name(suffix));
+        match std::fs::OpenOptions::new().write(true).create_new(true).open(&filename) {
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => suffix += 1,
+            // Any other problem surfaces when the file is written
+            _ => return filename,
+        }
+    }
+}
+
+/// Default in-memory audio kept per speaker before it's spilled to disk: 30 minutes
+pub const DEFAULT_MAX_BUFFERED_SAMPLES: usize = 30 * 60 * transcriber::DECODE_SAMPLE_RATE as usize;
+
+const RECORDING_SPEC: WavSpec = WavSpec {
+    channels: 1,
+    sample_rate: transcriber::DECODE_SAMPLE_RATE,
+    bits_per_sample: 16,
+    sample_format: hound::SampleFormat::Int,
+};
+
+/// Write 16-bit samples, interleaved when `channels` is more than one
+fn write_wav(path: &str, channels: u16, samples: &[i16], tags: &[([u8; 4], String)]) -> Result<(), AppError> {
+    let mut writer = WavWriter::create(path, WavSpec { channels, ..RECORDING_SPEC })?;
+    for &sample in samples {
+        writer.write_sample(sample)?;
+    }
+    writer.finalize()?;
+    tag_wav(path, tags);
+    Ok(())
+}
+
+/// Metadata is best effort; the audio is already safely on disk
+fn tag_wav(path: &str, tags: &[([u8; 4], String)]) {
+    if let Err(e) = wav_info::append_info_chunk(std::path::Path::new(path), tags) {
+        eprintln!("[WARN] Failed to write metadata to {}: {}", path, e);
+    }
+}
+
+/// A speaker's WAV, opened once their in-memory buffer first outgrew the cap
+/// and appended to every time it does again
+struct SpillFile {
+    path: String,
+    writer: WavWriter<std::io::BufWriter<std::fs::File>>,
+    /// Samples written so far
+    samples: usize,
+    /// Sum of the squared normalized samples, for the quiet check
+    sum_squares: f64,
+}
+
+impl SpillFile {
+    fn create(path: String) -> Result<Self, AppError> {
+        let writer = WavWriter::create(&path, RECORDING_SPEC)?;
+        Ok(Self { path, writer, samples: 0, sum_squares: 0.0 })
+    }
+
+    fn append(&mut self, samples: &[i16]) -> Result<(), AppError> {
+        for &sample in samples {
+            self.writer.write_sample(sample)?;
+        }
+        // Keeps the header current, so the file stays playable if the bot dies mid-meeting
+        self.writer.flush()?;
+        self.samples += samples.len();
+        self.sum_squares += samples.iter().map(|&s| (s as f64 / 32768.0).powi(2)).sum::<f64>();
+        Ok(())
+    }
+
+    fn rms(&self) -> f32 {
+        if self.samples == 0 {
+            return 0.0;
+        }
+        (self.sum_squares / self.samples as f64).sqrt() as f32
+    }
+
+    /// Close the WAV and tag it, returning its path
+    fn finish(self, tags: &[([u8; 4], String)]) -> Result<String, AppError> {
+        self.writer.finalize()?;
+        tag_wav(&self.path, tags);
+        Ok(self.path)
+    }
+
+    fn discard(self) {
+        drop(self.writer);
+        std::fs::remove_file(&self.path).ok();
+    }
+}
+
+/// Work for a session's spill writer thread
+enum SpillJob {
+    /// Append to the speaker's WAV, creating it first if needed
+    Append(SpeakerId, Vec<i16>),
+    /// Hand over everything spilled so far, once every earlier append has landed
+    Take(oneshot::Sender<Spilled>),
+}
+
+/// What a session's spill writer holds
+#[derive(Default)]
+struct Spilled {
+    files: HashMap<SpeakerId, SpillFile>,
+    /// Audio that couldn't be written, kept in memory. Once a speaker has some,
+    /// the rest of their audio joins it so nothing is written out of order.
+    unwritten: HashMap<SpeakerId, Vec<i16>>,
+}
+
+impl Spilled {
+    fn append(&mut self, speaker_id: SpeakerId, samples: Vec<i16>, path: impl FnOnce() -> String) {
+        if let Some(unwritten) = self.unwritten.get_mut(&speaker_id) {
+            unwritten.extend_from_slice(&samples);
+            return;
+        }
+        let spill = match self.files.entry(speaker_id) {
+            std::collections::hash_map::Entry::Occupied(entry) => Ok(entry.into_mut()),
+            std::collections::hash_map::Entry::Vacant(entry) => SpillFile::create(path()).map(|spill| entry.insert(spill)),
+        };
+        if let Err(e) = spill.and_then(|spill| spill.append(&samples)) {
+            eprintln!("[ERROR] Failed to spill user {}'s audio to disk, keeping it in memory: {}", speaker_id, e);
+            self.unwritten.insert(speaker_id, samples);
+        }
+    }
+}
+
+/// Runs on its own thread so WAV writes and flushes never block the async
+/// runtime. Jobs are handled in the order they were sent; the thread ends
+/// once the session is dropped.
+fn run_spill_writer(jobs: std_mpsc::Receiver<SpillJob>, path_for: impl Fn(SpeakerId) -> String) {
+    let mut spilled = Spilled::default();
+    for job in jobs {
+        match job {
+            SpillJob::Append(speaker_id, samples) => spilled.append(speaker_id, samples, || path_for(speaker_id)),
+            SpillJob::Take(reply) => {
+                let _ = reply.send(std::mem::take(&mut spilled));
+            }
+        }
+    }
+}
+
+/// A kept speaker's finished audio
+enum SpeakerAudio<'a> {
+    Memory(Cow<'a, [i16]>),
+    /// The closed WAV of a speaker who was spilled to disk
+    File(String),
+}
+
+impl SpeakerAudio<'_> {
+    /// Every sample in order, read lazily for spilled speakers
+    fn samples(&self) -> Result<Box<dyn Iterator<Item = Result<i16, AppError>> + '_>, AppError> {
+        Ok(match self {
+            SpeakerAudio::Memory(samples) => Box::new(samples.iter().map(|&sample| Ok(sample))),
+            SpeakerAudio::File(path) => Box::new(
+                hound::WavReader::open(path)?
+                    .into_samples::<i16>()
+                    .map(|sample| sample.map_err(AppError::from)),
+            ),
+        })
+    }
+}
+
+/// Where a chunk of a speaker's buffer falls on the session timeline
+#[derive(Debug, Clone, Copy)]
+struct AudioChunk {
+    /// Samples since the session started
+    timeline_offset: usize,
+    /// Index of the chunk's first sample in the speaker buffer
+    buffer_start: usize,
+}
+
+/// Samples per whisper timestamp unit (centisecond) of decoded audio
+const SAMPLES_PER_CENTISECOND: usize = transcriber::DECODE_SAMPLE_RATE as usize / 100;
+
+/// Map a position in a speaker's file (in centiseconds, as whisper reports it) onto
+/// the session timeline, using that speaker's `(buffer_start, timeline_offset)` chunks
+pub fn file_to_timeline(chunks: &[(usize, usize)], centis: i64) -> i64 {
+    let sample = centis.max(0) as usize * SAMPLES_PER_CENTISECOND;
+    match chunks.iter().rev().find(|(buffer_start, _)| *buffer_start <= sample) {
+        Some((buffer_start, timeline_offset)) => ((timeline_offset + sample - buffer_start) / SAMPLES_PER_CENTISECOND) as i64,
+        None => centis,
+    }
+}
+
 #[derive(Clone)]
 pub struct RecordingSession {
     pub guild_id: Id<twilight_model::id::marker::GuildMarker>,
     pub channel_id: Id<twilight_model::id::marker::ChannelMarker>,
     pub start_time: chrono::DateTime<Local>,
     pub speaker_buffers: Arc<RwLock<HashMap<SpeakerId, Vec<i16>>>>,
+    pub opus_packets: Arc<RwLock<HashMap<SpeakerId, Vec<Vec<u8>>>>>,
+    pub mode: RecordingMode,
+    /// Also write a single mixed WAV of all speakers
+    pub mixdown: Mixdown,
+    /// Speakers with less (or only near-silent) audio get no WAV and aren't transcribed
+    pub min_audio: std::time::Duration,
+    /// Language chosen when the recording started; None lets whisper detect it per speaker
+    pub language: Option<String>,
+    /// A speaker's buffer is written to their WAV once it holds more than this; 0 never spills
+    pub max_buffered_samples: usize,
+    speaker_chunks: Arc<RwLock<HashMap<SpeakerId, Vec<AudioChunk>>>>,
+    /// Samples of each speaker handed to the spill writer so far
+    spilled_samples: Arc<Mutex<HashMap<SpeakerId, usize>>>,
+    /// Started by the first spill
+    spill_writer: Arc<OnceLock<std_mpsc::Sender<SpillJob>>>,
+    started_at: std::time::Instant,
     output_dir: String,
 }
 
@@ -22,6 +453,7 @@ impl RecordingSession {
         guild_id: Id<twilight_model::id::marker::GuildMarker>,
         channel_id: Id<twilight_model::id::marker::ChannelMarker>,
         output_dir: &str,
+        mode: RecordingMode,
     ) -> Self {
         std::fs::create_dir_all(output_dir).ok();
         Self {
@@ -29,51 +461,344 @@ impl RecordingSession {
             channel_id,
             start_time: Local::now(),
             speaker_buffers: Arc::new(RwLock::new(HashMap::new())),
+            opus_packets: Arc::new(RwLock::new(HashMap::new())),
+            mode,
+            mixdown: Mixdown::Off,
+            min_audio: std::time::Duration::ZERO,
+            language: None,
+            max_buffered_samples: 0,
+            speaker_chunks: Arc::new(RwLock::new(HashMap::new())),
+            spilled_samples: Arc::new(Mutex::new(HashMap::new())),
+            spill_writer: Arc::new(OnceLock::new()),
+            started_at: std::time::Instant::now(),
             output_dir: output_dir.to_string(),
         }
     }
 
+    /// Language code to pass to whisper, if one was chosen
+    pub fn transcription_language(&self) -> Option<String> {
+        self.language.as_deref().map(|code| crate::user_settings::speech_language(code).to_string())
+    }
+
     pub async fn add_audio(&self, speaker_id: SpeakerId, samples: &[i16]) {
+        self.add_audio_captured_at(speaker_id, samples, std::time::Instant::now()).await;
+    }
+
+    /// [`Self::add_audio`] for audio received earlier, such as pre-roll held until
+    /// its speaker was known; it's placed on the timeline at `captured_at`
+    pub async fn add_audio_captured_at(&self, speaker_id: SpeakerId, samples: &[i16], captured_at: std::time::Instant) {
         // Store in memory buffer (for final WAV file)
         let mut buffers = self.speaker_buffers.write().await;
         let buffer = buffers.entry(speaker_id).or_insert_with(Vec::new);
+        let mut spilled_samples = self.spilled_samples.lock().await;
+        let spilled = spilled_samples.entry(speaker_id).or_insert(0);
+        let chunk = AudioChunk {
+            timeline_offset: (captured_at.saturating_duration_since(self.started_at).as_micros() * transcriber::DECODE_SAMPLE_RATE as u128 / 1_000_000) as usize,
+            buffer_start: *spilled + buffer.len(),
+        };
         buffer.extend_from_slice(samples);
+        metrics::add_buffered_samples(samples.len() as i64);
+
+        // Bound memory in long meetings by handing the buffer to the spill writer,
+        // which appends it to the speaker's WAV off the async runtime
+        if self.max_buffered_samples > 0 && buffer.len() > self.max_buffered_samples {
+            let len = buffer.len();
+            match self.spill_writer().send(SpillJob::Append(speaker_id, std::mem::take(buffer))) {
+                Ok(()) => {
+                    *spilled += len;
+                    metrics::add_buffered_samples(-(len as i64));
+                }
+                Err(std_mpsc::SendError(job)) => {
+                    eprintln!("[ERROR] Spill writer for guild {} has stopped, keeping user {}'s audio in memory", self.guild_id, speaker_id);
+                    if let SpillJob::Append(_, samples) = job {
+                        *buffer = samples;
+                    }
+                }
+            }
+        }
+        drop(spilled_samples);
+        drop(buffers);
+
+        self.speaker_chunks.write().await.entry(speaker_id).or_insert_with(Vec::new).push(chunk);
     }
 
-    pub async fn finalize(&self, output_dir: &str) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
-        let mut output_files = Vec::new();
+    /// When each chunk of a speaker's audio arrived: `(buffer_start, timeline_offset)` in samples
+    pub async fn timeline_chunks(&self, speaker_id: SpeakerId) -> Vec<(usize, usize)> {
+        let chunks = self.speaker_chunks.read().await;
+        chunks
+            .get(&speaker_id)
+            .map(|chunks| chunks.iter().map(|chunk| (chunk.buffer_start, chunk.timeline_offset)).collect())
+            .unwrap_or_default()
+    }
+
+    fn spill_writer(&self) -> &std_mpsc::Sender<SpillJob> {
+        self.spill_writer.get_or_init(|| {
+            let (jobs, receiver) = std_mpsc::channel();
+            let (output_dir, guild_id, start_time) = (self.output_dir.clone(), self.guild_id, self.start_time);
+            std::thread::spawn(move || {
+                run_spill_writer(receiver, |speaker_id| {
+                    unique_path(&output_dir, |suffix| recording_filename(guild_id, speaker_id, &start_time, suffix, "wav"))
+                })
+            });
+            jobs
+        })
+    }
+
+    /// Throw the recording away, deleting whatever was already spilled to disk
+    pub async fn discard(&self) {
+        for (_, file) in self.take_spilled().await.files {
+            let SpillFile { path, writer, .. } = file;
+            drop(writer);
+            if let Err(e) = tokio::fs::remove_file(&path).await {
+                eprintln!("[WARN] Failed to delete discarded recording {}: {}", path, e);
+            }
+        }
+    }
+
+    /// Everything the spill writer has written, waiting for appends still in flight
+    async fn take_spilled(&self) -> Spilled {
+        let Some(writer) = self.spill_writer.get() else {
+            return Spilled::default();
+        };
+        let (reply, spilled) = oneshot::channel();
+        if writer.send(SpillJob::Take(reply)).is_err() {
+            eprintln!("[ERROR] Spill writer for guild {} has stopped; spilled audio is lost", self.guild_id);
+            return Spilled::default();
+        }
+        spilled.await.unwrap_or_default()
+    }
+
+    pub async fn add_opus_packet(&self, speaker_id: SpeakerId, packet: Vec<u8>) {
+        let mut packets = self.opus_packets.write().await;
+        packets.entry(speaker_id).or_insert_with(Vec::new).push(packet);
+    }
+
+    /// Write each speaker's audio in every requested format to the session's output
+    /// directory, returning the paths per format. Speakers that were spilled to disk
+    /// only have their WAV closed; other formats stream it back. `speaker_names` labels each WAV's
+    /// metadata; speakers missing from it are labeled `User {id}`. Opus passthrough
+    /// sessions ignore `formats` and always produce `.ogg` files.
+    pub async fn finalize(
+        &self,
+        formats: &[OutputFormat],
+        speaker_names: &HashMap<SpeakerId, String>,
+    ) -> Result<HashMap<OutputFormat, Vec<String>>, AppError> {
+        if self.mode == RecordingMode::OpusPassthrough {
+            return Ok(HashMap::from([(OutputFormat::Opus, self.finalize_opus().await?)]));
+        }
+
+        let mut outputs: HashMap<OutputFormat, Vec<String>> = HashMap::new();
         let buffers = self.speaker_buffers.read().await;
+        let Spilled { mut files, mut unwritten } = self.take_spilled().await;
+        // Speakers that weren't skipped, for the mixdown
+        let mut kept = Vec::new();
 
-        for (speaker_id, samples) in buffers.iter() {
-            if samples.is_empty() {
+        for (speaker_id, buffered) in buffers.iter() {
+            // Audio the spill writer couldn't write comes before what's still buffered
+            let buffered: Cow<[i16]> = match unwritten.remove(speaker_id) {
+                Some(mut earlier) => {
+                    earlier.extend_from_slice(buffered);
+                    Cow::Owned(earlier)
+                }
+                None => Cow::Borrowed(buffered),
+            };
+            let spill = match files.remove(speaker_id) {
+                Some(mut spill) => {
+                    spill.append(&buffered)?;
+                    Some(spill)
+                }
+                None if buffered.is_empty() => continue,
+                None => None,
+            };
+            let skip_reason = match &spill {
+                Some(spill) => transcriber::skip_reason_for(spill.samples, || spill.rms(), self.min_audio),
+                None => transcriber::recording_skip_reason(&buffered, self.min_audio),
+            };
+            if let Some(reason) = skip_reason {
+                println!(
+                    "[INFO] Skipping {:.1}s of audio from user {}: {}",
+                    spill.as_ref().map_or(buffered.len(), |spill| spill.samples) as f64 / transcriber::DECODE_SAMPLE_RATE as f64,
+                    speaker_id,
+                    reason
+                );
+                if let Some(spill) = spill {
+                    spill.discard();
+                }
                 continue;
             }
-            
-            let filename = format!(
-                "{}/{}_{}_{}.wav",
-                output_dir,
-                self.guild_id,
-                speaker_id,
-                self.start_time.format("%Y%m%d_%H%M%S")
-            );
-
-            let spec = WavSpec {
-                channels: 1,
-                sample_rate: 48000,
-                bits_per_sample: 16,
-                sample_format: hound::SampleFormat::Int,
+
+            let speaker_name = speaker_names
+                .get(speaker_id)
+                .cloned()
+                .unwrap_or_else(|| format!("User {}", speaker_id));
+            let tags = self.info_tags(Some((*speaker_id, &speaker_name)));
+
+            let audio = match spill {
+                Some(spill) => {
+                    let path = spill.finish(&tags)?;
+                    outputs.entry(OutputFormat::Wav).or_default().push(path.clone());
+                    SpeakerAudio::File(path)
+                }
+                None => SpeakerAudio::Memory(buffered),
+            };
+
+            // Every encoder works from the same samples
+            for format in formats {
+                let filename = match (format, &audio) {
+                    // Already written while spilling
+                    (OutputFormat::Wav, SpeakerAudio::File(_)) => continue,
+                    (OutputFormat::Wav, SpeakerAudio::Memory(samples)) => {
+                        let filename = self.unique_filename(*speaker_id, format.extension());
+                        write_wav(&filename, 1, samples, &tags)?;
+                        filename
+                    }
+                    (OutputFormat::Opus | OutputFormat::Mp3, _) => {
+                        let filename = self.unique_filename(*speaker_id, format.extension());
+                        let mut file = std::io::BufWriter::new(std::fs::File::create(&filename)?);
+                        let encoded = if *format == OutputFormat::Opus {
+                            ogg_opus::encode_ogg_opus(&mut file, speaker_id.get() as u32, audio.samples()?)
+                        } else {
+                            mp3::encode_mp3(&mut file, audio.samples()?)
+                        };
+                        encoded?;
+                        // Dropping the writer would swallow a failed final write
+                        file.flush()?;
+                        filename
+                    }
+                };
+                outputs.entry(*format).or_default().push(filename);
+            }
+            kept.push((*speaker_id, audio));
+        }
+
+        let saved: usize = outputs.values().map(Vec::len).sum();
+        if saved > 0 {
+            println!("[INFO] Saved {} audio files", saved);
+
+            // The mixdown is an archive copy and isn't returned for transcription
+            if self.mixdown != Mixdown::Off {
+                let filename = unique_path(&self.output_dir, |suffix| format!(
+                    "{}_mixed_{}_{}.wav",
+                    self.guild_id,
+                    timezone::format(&self.start_time, FILENAME_TIMESTAMP_FORMAT),
+                    suffix
+                ));
+                self.write_mixdown(&filename, &kept, self.mixdown == Mixdown::Stereo).await?;
+                tag_wav(&filename, &self.info_tags(None));
+                println!("[INFO] Saved mixed recording: {}", filename);
+            }
+        }
+
+        Ok(outputs)
+    }
+
+    /// `INFO` tags for a speaker's WAV, or for the mixdown when `speaker` is None
+    fn info_tags(&self, speaker: Option<(SpeakerId, &str)>) -> Vec<([u8; 4], String)> {
+        let started = timezone::format(&self.start_time, "%Y-%m-%d %H:%M %Z");
+        let mut comment = format!("guild={} channel={}", self.guild_id, self.channel_id);
+        let mut tags = Vec::new();
+        match speaker {
+            Some((speaker_id, name)) => {
+                comment.push_str(&format!(" user={}", speaker_id));
+                tags.push((wav_info::TAG_ARTIST, name.to_string()));
+                tags.push((wav_info::TAG_NAME, format!("{} ({})", name, started)));
+            }
+            None => tags.push((wav_info::TAG_NAME, format!("Mixed recording ({})", started))),
+        }
+        tags.push((wav_info::TAG_CREATED, self.start_time.to_rfc3339()));
+        tags.push((wav_info::TAG_COMMENT, comment));
+        tags.push((wav_info::TAG_SOFTWARE, "diggy-gizzy".to_string()));
+        tags
+    }
+
+    /// Place every kept speaker's chunks on the session timeline and mix them
+    /// into `path`. Speakers' audio is read and the mix written as the timeline
+    /// advances, so spilled speakers are streamed from disk. In stereo, speakers
+    /// are panned in the order they first spoke.
+    async fn write_mixdown(&self, path: &str, kept: &[(SpeakerId, SpeakerAudio<'_>)], stereo: bool) -> Result<(), AppError> {
+        struct Track<'a> {
+            chunks: &'a [AudioChunk],
+            next: usize,
+            samples: Box<dyn Iterator<Item = Result<i16, AppError>> + 'a>,
+            pan: f32,
+        }
+
+        let chunks = self.speaker_chunks.read().await;
+        let mut speakers: Vec<_> = kept
+            .iter()
+            .filter_map(|(speaker_id, audio)| chunks.get(speaker_id).map(|speaker_chunks| (speaker_id, audio, speaker_chunks)))
+            .collect();
+        speakers.sort_by_key(|(speaker_id, _, speaker_chunks)| {
+            (speaker_chunks.first().map_or(usize::MAX, |chunk| chunk.timeline_offset), **speaker_id)
+        });
+        let mut tracks = Vec::with_capacity(speakers.len());
+        for (index, (_, audio, speaker_chunks)) in speakers.into_iter().enumerate() {
+            tracks.push(Track { chunks: speaker_chunks, next: 0, samples: audio.samples()?, pan: pan_position(index) });
+        }
+
+        let mut writer = WavWriter::create(path, WavSpec { channels: if stereo { 2 } else { 1 }, ..RECORDING_SPEC })?;
+        let mut mixer = Mixer::new(stereo);
+        let next_offset = |tracks: &[Track]| {
+            tracks
+                .iter()
+                .filter_map(|track| track.chunks.get(track.next))
+                .map(|chunk| chunk.timeline_offset)
+                .min()
+        };
+        while let Some(start) = next_offset(&tracks) {
+            let track = tracks
+                .iter_mut()
+                .find(|track| track.chunks.get(track.next).is_some_and(|chunk| chunk.timeline_offset == start))
+                .expect("a track has the earliest chunk");
+            let chunk = track.chunks[track.next];
+            track.next += 1;
+            let samples: Vec<i16> = match track.chunks.get(track.next) {
+                Some(following) => track.samples.by_ref().take(following.buffer_start - chunk.buffer_start).collect::<Result<_, _>>()?,
+                None => track.samples.by_ref().collect::<Result<_, _>>()?,
             };
+            mixer.add(chunk.timeline_offset, &samples, track.pan);
 
-            let mut writer = WavWriter::create(&filename, spec)?;
-            for &sample in samples {
+            // No chunk still to come starts before the next one up
+            let done = next_offset(&tracks).unwrap_or(0);
+            for sample in mixer.drain_before(done) {
                 writer.write_sample(sample)?;
             }
-            writer.finalize()?;
+        }
+        for sample in mixer.finish() {
+            writer.write_sample(sample)?;
+        }
+        writer.finalize()?;
+        Ok(())
+    }
+
+    /// Recording path with the lowest suffix not already on disk,
+    /// so sessions started in the same second don't overwrite each other
+    fn unique_filename(&self, speaker_id: SpeakerId, extension: &str) -> String {
+        unique_path(&self.output_dir, |suffix| {
+            recording_filename(self.guild_id, speaker_id, &self.start_time, suffix, extension)
+        })
+    }
+
+    /// Write each speaker's Opus packets to an Ogg file without re-encoding
+    async fn finalize_opus(&self) -> Result<Vec<String>, AppError> {
+        let mut output_files = Vec::new();
+        let packets = self.opus_packets.read().await;
+
+        for (speaker_id, speaker_packets) in packets.iter() {
+            if speaker_packets.is_empty() {
+                continue;
+            }
+
+            let filename = self.unique_filename(*speaker_id, "ogg");
+
+            let mut file = std::io::BufWriter::new(std::fs::File::create(&filename)?);
+            ogg_opus::write_ogg_opus(&mut file, speaker_id.get() as u32, speaker_packets)?;
+            file.flush()?;
             output_files.push(filename);
         }
 
         if !output_files.is_empty() {
-            println!("[INFO] Saved {} audio files", output_files.len());
+            println!("[INFO] Saved {} Opus files", output_files.len());
         }
 
         Ok(output_files)
@@ -83,7 +808,16 @@ impl RecordingSession {
 #[derive(Clone)]
 pub struct RecordingManager {
     output_dir: String,
+    mode: RecordingMode,
+    mixdown: Mixdown,
+    min_audio: std::time::Duration,
+    max_buffered_samples: usize,
+    formats: Vec<OutputFormat>,
+    preroll: std::time::Duration,
+    session_count: Arc<watch::Sender<usize>>,
     active_sessions: Arc<RwLock<HashMap<Id<twilight_model::id::marker::GuildMarker>, RecordingSession>>>,
+    /// Users whose audio is dropped before buffering, per guild (`/record_exclude`)
+    excluded_speakers: Arc<RwLock<HashMap<Id<twilight_model::id::marker::GuildMarker>, HashSet<SpeakerId>>>>,
 }
 
 impl RecordingManager {
@@ -91,50 +825,251 @@ impl RecordingManager {
         std::fs::create_dir_all(&output_dir).ok();
         Self {
             output_dir,
+            mode: RecordingMode::Pcm,
+            mixdown: Mixdown::Off,
+            min_audio: std::time::Duration::ZERO,
+            max_buffered_samples: DEFAULT_MAX_BUFFERED_SAMPLES,
+            formats: vec![OutputFormat::Wav],
+            preroll: crate::preroll::DEFAULT_PREROLL,
+            session_count: Arc::new(watch::Sender::new(0)),
             active_sessions: Arc::new(RwLock::new(HashMap::new())),
+            excluded_speakers: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
+    pub fn with_mode(mut self, mode: RecordingMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Also write a mixed WAV of all speakers when a PCM recording is finalized
+    pub fn with_mixdown(mut self, mixdown: Mixdown) -> Self {
+        self.mixdown = mixdown;
+        self
+    }
+
+    /// Skip speakers with less than `min_audio` of audible speech when finalizing
+    pub fn with_min_audio(mut self, min_audio: std::time::Duration) -> Self {
+        self.min_audio = min_audio;
+        self
+    }
+
+    /// Samples kept in memory per speaker before they're written to disk; 0 keeps everything in memory
+    pub fn with_max_buffered_samples(mut self, max_buffered_samples: usize) -> Self {
+        self.max_buffered_samples = max_buffered_samples;
+        self
+    }
+
+    /// Formats PCM recordings are saved in. WAV is always kept because transcription reads it.
+    pub fn with_formats(mut self, formats: Vec<OutputFormat>) -> Self {
+        self.formats = vec![OutputFormat::Wav];
+        self.formats.extend(formats.into_iter().filter(|format| *format != OutputFormat::Wav));
+        self
+    }
+
+    /// Audio kept from speakers before Discord says who they are (zero disables)
+    pub fn with_preroll(mut self, preroll: std::time::Duration) -> Self {
+        self.preroll = preroll;
+        self
+    }
+
+    pub fn formats(&self) -> &[OutputFormat] {
+        &self.formats
+    }
+
+    pub fn active_session_count(&self) -> usize {
+        *self.session_count.borrow()
+    }
+
+    /// Number of active sessions, updated as recordings start and stop
+    pub fn subscribe_session_count(&self) -> watch::Receiver<usize> {
+        self.session_count.subscribe()
+    }
+
+    pub fn mode(&self) -> RecordingMode {
+        self.mode
+    }
+
+    /// Default directory for a guild's recordings: `{output_dir}/{guild_id}`
+    pub fn guild_output_dir(&self, guild_id: Id<twilight_model::id::marker::GuildMarker>) -> String {
+        format!("{}/{}", self.output_dir, guild_id)
+    }
+
+    /// Start a session writing to `output_dir`, or to the guild's subdirectory when None.
+    /// `language` forces the transcription language instead of auto-detecting it.
     pub async fn start_recording(
         &self,
         guild_id: Id<twilight_model::id::marker::GuildMarker>,
         channel_id: Id<twilight_model::id::marker::ChannelMarker>,
+        output_dir: Option<&str>,
+        language: Option<&str>,
     ) -> RecordingSession {
-        let session = RecordingSession::new(guild_id, channel_id, &self.output_dir);
+        let output_dir = output_dir.map_or_else(|| self.guild_output_dir(guild_id), str::to_string);
+        let mut session = RecordingSession::new(guild_id, channel_id, &output_dir, self.mode);
+        session.mixdown = self.mixdown;
+        session.min_audio = self.min_audio;
+        session.max_buffered_samples = self.max_buffered_samples;
+        session.language = language.map(str::to_string);
         let mut sessions = self.active_sessions.write().await;
         sessions.insert(guild_id, session.clone());
+        metrics::set_active_recordings(sessions.len());
+        self.session_count.send_replace(sessions.len());
         println!("[INFO] Started recording for guild {}", guild_id);
         session
     }
 
+    /// Start a session and create the receive handler that feeds it
+    pub async fn start_with_handler(
+        self: &Arc<Self>,
+        guild_id: Id<twilight_model::id::marker::GuildMarker>,
+        channel_id: Id<twilight_model::id::marker::ChannelMarker>,
+        output_dir: Option<&str>,
+        language: Option<&str>,
+    ) -> VoiceReceiveHandler {
+        self.start_recording(guild_id, channel_id, output_dir, language).await;
+        VoiceReceiveHandler::new(self.clone(), guild_id)
+    }
+
+    /// Guilds with an active recording
+    pub async fn recording_guilds(&self) -> Vec<Id<twilight_model::id::marker::GuildMarker>> {
+        self.active_sessions.read().await.keys().copied().collect()
+    }
+
     pub async fn stop_recording(
         &self,
         guild_id: Id<twilight_model::id::marker::GuildMarker>,
-    ) -> Result<Option<RecordingSession>, Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<Option<RecordingSession>, AppError> {
         let mut sessions = self.active_sessions.write().await;
         let session = sessions.remove(&guild_id);
+        metrics::set_active_recordings(sessions.len());
+        self.session_count.send_replace(sessions.len());
         if let Some(ref s) = session {
+            let buffered: usize = s.speaker_buffers.read().await.values().map(Vec::len).sum();
+            metrics::add_buffered_samples(-(buffered as i64));
             println!("[INFO] Stopped recording for guild {}", guild_id);
         }
         Ok(session)
     }
 
+    /// Stop or resume recording a user in this guild. Returns false if nothing changed.
+    pub async fn set_excluded(
+        &self,
+        guild_id: Id<twilight_model::id::marker::GuildMarker>,
+        user_id: SpeakerId,
+        excluded: bool,
+    ) -> bool {
+        let mut excluded_speakers = self.excluded_speakers.write().await;
+        let guild = excluded_speakers.entry(guild_id).or_default();
+        let changed = if excluded { guild.insert(user_id) } else { guild.remove(&user_id) };
+        if guild.is_empty() {
+            excluded_speakers.remove(&guild_id);
+        }
+        changed
+    }
+
+    pub async fn is_excluded(&self, guild_id: Id<twilight_model::id::marker::GuildMarker>, user_id: SpeakerId) -> bool {
+        let excluded_speakers = self.excluded_speakers.read().await;
+        excluded_speakers.get(&guild_id).is_some_and(|users| users.contains(&user_id))
+    }
+
+    /// Users excluded from recording in this guild
+    pub async fn excluded_speakers(&self, guild_id: Id<twilight_model::id::marker::GuildMarker>) -> Vec<SpeakerId> {
+        let excluded_speakers = self.excluded_speakers.read().await;
+        let mut users: Vec<SpeakerId> = excluded_speakers.get(&guild_id).into_iter().flatten().copied().collect();
+        users.sort();
+        users
+    }
+
     pub async fn add_audio_to_session(
         &self,
         guild_id: Id<twilight_model::id::marker::GuildMarker>,
         speaker_id: SpeakerId,
         samples: &[i16],
     ) {
+        self.add_audio_captured_at(guild_id, speaker_id, samples, std::time::Instant::now()).await;
+    }
+
+    pub async fn add_audio_captured_at(
+        &self,
+        guild_id: Id<twilight_model::id::marker::GuildMarker>,
+        speaker_id: SpeakerId,
+        samples: &[i16],
+        captured_at: std::time::Instant,
+    ) {
+        if self.is_excluded(guild_id, speaker_id).await {
+            return;
+        }
+        let sessions = self.active_sessions.read().await;
+        if let Some(session) = sessions.get(&guild_id) {
+            session.add_audio_captured_at(speaker_id, samples, captured_at).await;
+        }
+    }
+    
+    pub async fn add_opus_to_session(
+        &self,
+        guild_id: Id<twilight_model::id::marker::GuildMarker>,
+        speaker_id: SpeakerId,
+        packet: Vec<u8>,
+    ) {
+        if self.is_excluded(guild_id, speaker_id).await {
+            return;
+        }
         let sessions = self.active_sessions.read().await;
         if let Some(session) = sessions.get(&guild_id) {
-            session.add_audio(speaker_id, samples).await;
+            session.add_opus_packet(speaker_id, packet).await;
         }
     }
     
+    /// Point the guild's active session at a new voice channel (e.g. the bot was moved).
+    /// Returns false if nothing is being recorded or the channel is unchanged.
+    pub async fn update_channel(
+        &self,
+        guild_id: Id<twilight_model::id::marker::GuildMarker>,
+        channel_id: Id<twilight_model::id::marker::ChannelMarker>,
+    ) -> bool {
+        let mut sessions = self.active_sessions.write().await;
+        match sessions.get_mut(&guild_id) {
+            Some(session) if session.channel_id != channel_id => {
+                println!("[INFO] Recording session for guild {} moved to channel {}", guild_id, channel_id);
+                session.channel_id = channel_id;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Voice channel the guild's active session is recording
+    pub async fn session_channel(
+        &self,
+        guild_id: Id<twilight_model::id::marker::GuildMarker>,
+    ) -> Option<Id<twilight_model::id::marker::ChannelMarker>> {
+        let sessions = self.active_sessions.read().await;
+        sessions.get(&guild_id).map(|session| session.channel_id)
+    }
+
+    /// Whether the guild's active session has captured anything yet; None without a session
+    pub async fn received_audio(&self, guild_id: Id<twilight_model::id::marker::GuildMarker>) -> Option<bool> {
+        let sessions = self.active_sessions.read().await;
+        let session = sessions.get(&guild_id)?;
+        // Chunks outlive buffers that were spilled to disk
+        let has_pcm = session.speaker_chunks.read().await.values().any(|chunks| !chunks.is_empty());
+        let has_opus = session.opus_packets.read().await.values().any(|packets| !packets.is_empty());
+        Some(has_pcm || has_opus)
+    }
+
     pub async fn is_recording(&self, guild_id: Id<twilight_model::id::marker::GuildMarker>) -> bool {
         let sessions = self.active_sessions.read().await;
         sessions.contains_key(&guild_id)
     }
+
+    /// When the guild's active session started, to tell it apart from a later one
+    pub async fn session_start_time(
+        &self,
+        guild_id: Id<twilight_model::id::marker::GuildMarker>,
+    ) -> Option<chrono::DateTime<Local>> {
+        let sessions = self.active_sessions.read().await;
+        sessions.get(&guild_id).map(|session| session.start_time)
+    }
     
     pub async fn flush_audio_buffers(
         &self,
@@ -160,12 +1095,18 @@ impl RecordingManager {
     }
 }
 
+/// Decoded audio and when it arrived
+type TimedFrame = (std::time::Instant, Vec<i16>);
+
 #[derive(Clone)]
 pub struct VoiceReceiveHandler {
     pub recording_manager: Arc<RecordingManager>,
     pub guild_id: Id<twilight_model::id::marker::GuildMarker>,
     pub audio_buffers: Arc<Mutex<HashMap<u32, Vec<i16>>>>,
     pub ssrc_to_user: Arc<Mutex<HashMap<u32, SpeakerId>>>,
+    /// Decoded audio with when it arrived, or Opus frames in passthrough mode, from SSRCs not mapped yet
+    pcm_preroll: Arc<Mutex<Preroll<TimedFrame>>>,
+    opus_preroll: Arc<Mutex<Preroll<Vec<u8>>>>,
 }
 
 impl VoiceReceiveHandler {
@@ -173,15 +1114,60 @@ impl VoiceReceiveHandler {
         recording_manager: Arc<RecordingManager>,
         guild_id: Id<twilight_model::id::marker::GuildMarker>,
     ) -> Self {
+        let preroll = recording_manager.preroll;
         Self {
             recording_manager,
             guild_id,
             audio_buffers: Arc::new(Mutex::new(HashMap::new())),
             ssrc_to_user: Arc::new(Mutex::new(HashMap::new())),
+            pcm_preroll: Arc::new(Mutex::new(Preroll::new(preroll))),
+            opus_preroll: Arc::new(Mutex::new(Preroll::new(preroll))),
+        }
+    }
+
+    /// Map an SSRC to its speaker and record the audio heard before the mapping
+    async fn map_speaker(&self, ssrc: u32, user_id: SpeakerId) {
+        // Held while flushing so newer audio can't overtake the pre-roll
+        let mut ssrc_map = self.ssrc_to_user.lock().await;
+        ssrc_map.insert(ssrc, user_id);
+        println!("[DEBUG] SSRC map size: {}", ssrc_map.len());
+
+        let pcm = self.pcm_preroll.lock().await.take(ssrc);
+        let opus = self.opus_preroll.lock().await.take(ssrc);
+        if !pcm.is_empty() || !opus.is_empty() {
+            println!("[DEBUG] Recovered {} pre-roll frames for SSRC {}", pcm.len() + opus.len(), ssrc);
+        }
+        // Each frame keeps its arrival time so the mixdown places it where it was spoken
+        for (captured_at, samples) in pcm {
+            self.recording_manager.add_audio_captured_at(self.guild_id, user_id, &samples, captured_at).await;
+        }
+        for packet in opus {
+            self.recording_manager.add_opus_to_session(self.guild_id, user_id, packet).await;
         }
     }
 }
 
+/// The Opus frame in an RTP payload: songbird's `payload_offset..payload_end_pad`
+/// range (an end index, not a count), minus the header extension if there is one
+fn opus_frame(payload: &[u8], payload_offset: usize, payload_end_pad: usize, has_extension: bool) -> Option<&[u8]> {
+    let body = payload.get(payload_offset..payload_end_pad)?;
+    let start = if has_extension {
+        // RFC 8285: 2-byte profile, 2-byte length in 32-bit words, then the elements
+        let words = u16::from_be_bytes([*body.get(2)?, *body.get(3)?]) as usize;
+        4 + words * 4
+    } else {
+        0
+    };
+    body.get(start..).filter(|opus| !opus.is_empty())
+}
+
+/// Extract the raw Opus frame from a decrypted RTP packet
+fn opus_payload(packet: &RtpData) -> Option<Vec<u8>> {
+    let rtp = packet.rtp();
+    opus_frame(rtp.payload(), packet.payload_offset, packet.payload_end_pad, rtp.get_extension() != 0)
+        .map(<[u8]>::to_vec)
+}
+
 #[async_trait::async_trait]
 impl SongbirdEventHandler for VoiceReceiveHandler {
     async fn act(&self, ctx: &EventContext<'_>) -> Option<songbird::Event> {
@@ -193,13 +1179,26 @@ impl SongbirdEventHandler for VoiceReceiveHandler {
                     
                     println!("[DEBUG] SpeakingStateUpdate: SSRC {} -> User {}", ssrc, user_id);
                     
-                    let mut ssrc_map = self.ssrc_to_user.lock().await;
-                    ssrc_map.insert(ssrc, user_id);
-                    println!("[DEBUG] SSRC map size: {}", ssrc_map.len());
+                    self.map_speaker(ssrc, user_id).await;
                 } else {
                     println!("[DEBUG] SpeakingStateUpdate: user_id is None for SSRC {}", speaking.ssrc);
                 }
             }
+            EventContext::VoiceTick(tick) if self.recording_manager.mode() == RecordingMode::OpusPassthrough => {
+                for (ssrc, voice_data) in tick.speaking.iter() {
+                    let Some(opus) = voice_data.packet.as_ref().and_then(opus_payload) else {
+                        continue;
+                    };
+
+                    let ssrc_map = self.ssrc_to_user.lock().await;
+                    if let Some(&user_id) = ssrc_map.get(ssrc) {
+                        drop(ssrc_map);
+                        self.recording_manager.add_opus_to_session(self.guild_id, user_id, opus).await;
+                    } else if self.opus_preroll.lock().await.push(*ssrc, opus) {
+                        println!("[DEBUG] VoiceTick: No user mapping for SSRC {} yet, holding packets", ssrc);
+                    }
+                }
+            }
             EventContext::VoiceTick(tick) => {
                 for (ssrc, voice_data) in tick.speaking.iter() {
                     if let Some(ref audio) = voice_data.decoded_voice {
@@ -215,8 +1214,8 @@ impl SongbirdEventHandler for VoiceReceiveHandler {
                                     user_id,
                                     &samples,
                                 ).await;
-                            } else {
-                                println!("[WARN] VoiceTick: No user mapping for SSRC {}, skipping audio", ssrc);
+                            } else if self.pcm_preroll.lock().await.push(*ssrc, (std::time::Instant::now(), samples)) {
+                                println!("[DEBUG] VoiceTick: No user mapping for SSRC {} yet, holding audio", ssrc);
                             }
                         }
                     }
@@ -231,3 +1230,420 @@ impl SongbirdEventHandler for VoiceReceiveHandler {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_wav_samples(path: &str) -> Vec<i16> {
+        let mut reader = hound::WavReader::open(path).unwrap();
+        reader.samples::<i16>().collect::<Result<_, _>>().unwrap()
+    }
+
+    fn mix_tracks(chunks: &[(usize, &[i16])]) -> Vec<i16> {
+        let mut mixer = Mixer::new(false);
+        for (offset, samples) in chunks {
+            mixer.add(*offset, samples, 0.0);
+        }
+        mixer.finish()
+    }
+
+    fn mix_tracks_stereo(chunks: &[(usize, &[i16], f32)]) -> Vec<i16> {
+        let mut mixer = Mixer::new(true);
+        for (offset, samples, pan) in chunks {
+            mixer.add(*offset, samples, *pan);
+        }
+        mixer.finish()
+    }
+
+    #[test]
+    fn test_opus_frame_from_rtp_packet_decodes() {
+        use audiopus::coder::{Decoder, Encoder};
+        use audiopus::{Application, Channels, SampleRate};
+        use songbird::packet::rtp::RtpPacket;
+
+        let tone: Vec<i16> = (0..960 * 2).map(|i| ((i as f32 * 0.05).sin() * 8000.0) as i16).collect();
+        let encoder = Encoder::new(SampleRate::Hz48000, Channels::Stereo, Application::Voip).unwrap();
+        let mut encoded = vec![0u8; 4000];
+        let len = encoder.encode(&tone, &mut encoded).unwrap();
+        encoded.truncate(len);
+
+        // RTP header with the extension bit set, a one-word header extension,
+        // the Opus frame, then a 4-byte trailer songbird leaves in place
+        let mut bytes = vec![0x90, 0x78, 0x00, 0x01, 0, 0, 0x03, 0xc0, 0, 0, 0, 42];
+        bytes.extend_from_slice(&[0xbe, 0xde, 0x00, 0x01, 0x10, 0xff, 0x00, 0x00]);
+        bytes.extend_from_slice(&encoded);
+        bytes.extend_from_slice(&[0xaa; 4]);
+
+        let rtp = RtpPacket::new(&bytes).unwrap();
+        let payload = rtp.payload();
+        let frame = opus_frame(payload, 0, payload.len() - 4, rtp.get_extension() != 0).unwrap();
+        assert_eq!(frame, &encoded[..]);
+
+        let mut decoder = Decoder::new(SampleRate::Hz48000, Channels::Stereo).unwrap();
+        let mut pcm = vec![0i16; 960 * 2];
+        assert_eq!(decoder.decode(Some(frame), &mut pcm, false).unwrap(), 960);
+
+        // A truncated extension yields nothing instead of a bogus frame
+        assert_eq!(opus_frame(&[0xbe, 0xde], 0, 2, true), None);
+    }
+
+    #[tokio::test]
+    async fn test_update_channel_moves_active_session() {
+        let output_dir = std::env::temp_dir().join("diggy_gizzy_recorder_test");
+        let manager = RecordingManager::new(output_dir.to_string_lossy().to_string());
+        let guild_id = Id::new(1);
+
+        // Nothing to update without an active session
+        assert!(!manager.update_channel(guild_id, Id::new(20)).await);
+
+        manager.start_recording(guild_id, Id::new(10), None, None).await;
+        assert!(manager.update_channel(guild_id, Id::new(20)).await);
+        assert!(!manager.update_channel(guild_id, Id::new(20)).await);
+
+        let session = manager.stop_recording(guild_id).await.unwrap().unwrap();
+        assert_eq!(session.channel_id, Id::new(20));
+        assert_eq!(session.transcription_language(), None);
+    }
+
+    #[tokio::test]
+    async fn test_chosen_language_is_kept_for_transcription() {
+        let output_dir = std::env::temp_dir().join("diggy_gizzy_recorder_language_test");
+        let manager = RecordingManager::new(output_dir.to_string_lossy().to_string());
+
+        manager.start_recording(Id::new(1), Id::new(10), None, Some("ja")).await;
+        let session = manager.stop_recording(Id::new(1)).await.unwrap().unwrap();
+        assert_eq!(session.language.as_deref(), Some("ja"));
+        assert_eq!(session.transcription_language().as_deref(), Some("ja"));
+
+        // Whisper has no regional variants
+        manager.start_recording(Id::new(2), Id::new(10), None, Some("en-gb")).await;
+        let session = manager.stop_recording(Id::new(2)).await.unwrap().unwrap();
+        assert_eq!(session.transcription_language().as_deref(), Some("en"));
+    }
+
+    #[tokio::test]
+    async fn test_excluded_speakers_are_not_buffered() {
+        let output_dir = std::env::temp_dir().join("diggy_gizzy_recorder_exclude_test");
+        let manager = RecordingManager::new(output_dir.to_string_lossy().to_string());
+        let guild_id = Id::new(1);
+        let (alice, bot) = (Id::new(42), Id::new(43));
+
+        assert!(manager.set_excluded(guild_id, bot, true).await);
+        assert!(!manager.set_excluded(guild_id, bot, true).await);
+        assert_eq!(manager.excluded_speakers(guild_id).await, vec![bot]);
+
+        let session = manager.start_recording(guild_id, Id::new(10), None, None).await;
+        manager.add_audio_to_session(guild_id, alice, &[1, 2, 3]).await;
+        manager.add_audio_to_session(guild_id, bot, &[4, 5, 6]).await;
+        manager.add_opus_to_session(guild_id, bot, vec![0xF8]).await;
+        {
+            let buffers = session.speaker_buffers.read().await;
+            assert_eq!(buffers.get(&alice).map(Vec::as_slice), Some(&[1i16, 2, 3][..]));
+            assert!(!buffers.contains_key(&bot));
+            assert!(session.opus_packets.read().await.is_empty());
+        }
+
+        // Included again: recorded from then on
+        assert!(manager.set_excluded(guild_id, bot, false).await);
+        manager.add_audio_to_session(guild_id, bot, &[7]).await;
+        assert_eq!(session.speaker_buffers.read().await.get(&bot).map(Vec::len), Some(1));
+        assert!(manager.excluded_speakers(guild_id).await.is_empty());
+        manager.stop_recording(guild_id).await.unwrap();
+    }
+
+    #[test]
+    fn test_recording_filename_round_trip() {
+        let started_at = Local::now();
+        let name = recording_filename(Id::new(1), Id::new(42), &started_at, 3, "wav");
+        let parsed = parse_recording_filename(&format!("./recordings/{}", name)).unwrap();
+
+        assert_eq!(parsed.guild_id, Id::new(1));
+        assert_eq!(parsed.user_id, Id::new(42));
+        assert_eq!(parsed.started_at.format(FILENAME_TIMESTAMP_FORMAT).to_string(),
+            started_at.format(FILENAME_TIMESTAMP_FORMAT).to_string());
+        assert_eq!(parsed.suffix, 3);
+    }
+
+    #[test]
+    fn test_unique_path_claims_the_file() {
+        let dir = std::env::temp_dir().join(format!("unique_path_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let dir = dir.to_string_lossy().to_string();
+        let name = |suffix| format!("speaker_{}.wav", suffix);
+
+        // Nothing is written to the first path yet, but the next caller still gets another
+        let first = unique_path(&dir, name);
+        let second = unique_path(&dir, name);
+        assert_eq!(first, format!("{}/speaker_0.wav", dir));
+        assert_eq!(second, format!("{}/speaker_1.wav", dir));
+        assert!(std::path::Path::new(&first).exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_parse_recording_filename_edge_cases() {
+        // Names from before the collision suffix was added
+        let legacy = parse_recording_filename("1_42_20240101_120000.ogg").unwrap();
+        assert_eq!(legacy.user_id, Id::new(42));
+        assert_eq!(legacy.suffix, 0);
+
+        assert_eq!(parse_recording_filename("mixed.wav"), None);
+        assert_eq!(parse_recording_filename("1_alice_bob_20240101_120000_0.wav"), None);
+        assert_eq!(parse_recording_filename("1_42_20240101_120000_0_extra.wav"), None);
+        assert_eq!(parse_recording_filename("1_0_20240101_120000_0.wav"), None);
+        assert_eq!(parse_recording_filename("1_42_20241399_120000_0.wav"), None);
+        assert_eq!(parse_recording_filename("1_99999999999999999999_20240101_120000.wav"), None);
+    }
+
+    #[test]
+    fn test_mix_tracks_aligns_offsets_without_overflow() {
+        let loud = vec![i16::MAX; 4];
+        let quiet = vec![1000i16; 3];
+        let mixed = mix_tracks(&[(0, &loud), (2, &loud), (10, &quiet)]);
+
+        assert_eq!(mixed.len(), 13);
+        // Overlapping full-scale samples are soft-clipped rather than wrapping
+        assert!(mixed[2] > 0 && mixed[3] > 0);
+        assert!(mixed[2] >= mixed[0]);
+        assert_eq!(mixed[6], 0);
+        assert_eq!(mixed[10], 1000);
+
+        let negative = vec![i16::MIN; 2];
+        let mixed = mix_tracks(&[(0, &negative), (0, &negative)]);
+        assert!(mixed.iter().all(|&s| s < 0));
+
+        // Draining as chunks arrive gives the same mix as summing everything at once
+        let all_at_once = mix_tracks(&[(0, &loud), (2, &loud), (10, &quiet)]);
+        let mut mixer = Mixer::new(false);
+        mixer.add(0, &loud, 0.0);
+        let mut streamed = mixer.drain_before(2);
+        mixer.add(2, &loud, 0.0);
+        streamed.extend(mixer.drain_before(10));
+        mixer.add(10, &quiet, 0.0);
+        streamed.extend(mixer.finish());
+        assert_eq!(streamed, all_at_once);
+    }
+
+    #[test]
+    fn test_stereo_mix_pans_speakers_apart() {
+        let tone = vec![10_000i16; 4];
+        assert!(pan_position(0) < 0.0 && pan_position(1) > 0.0);
+        assert_eq!(pan_position(STEREO_PAN_POSITIONS.len()), pan_position(0));
+
+        // Left speaker first, right speaker after a gap: interleaved L/R frames
+        let mixed = mix_tracks_stereo(&[(0, &tone, pan_position(0)), (6, &tone, pan_position(1))]);
+        assert_eq!(mixed.len(), 20);
+        let (left, right) = (mixed[0], mixed[1]);
+        assert!(left > right && right > 0);
+        assert_eq!((mixed[12], mixed[13]), (right, left));
+        assert_eq!((mixed[8], mixed[9]), (0, 0));
+
+        // Full-scale sources on the same side stay in range
+        let loud = vec![i16::MAX; 2];
+        let mixed = mix_tracks_stereo(&[(0, &loud, -1.0), (0, &loud, -1.0)]);
+        assert!(mixed[0] > 0 && mixed[1].abs() < 10);
+        assert_eq!(Mixdown::from_config("Stereo"), Mixdown::Stereo);
+        assert_eq!(Mixdown::from_config("true"), Mixdown::Mono);
+        assert_eq!(Mixdown::from_config(""), Mixdown::Off);
+    }
+
+    #[tokio::test]
+    async fn test_finalize_same_second_does_not_overwrite() {
+        let output_dir = std::env::temp_dir().join("diggy_gizzy_recorder_collision_test");
+        let _ = std::fs::remove_dir_all(&output_dir);
+        let output_dir = output_dir.to_string_lossy().to_string();
+        let speaker: SpeakerId = Id::new(42);
+
+        let first = RecordingSession::new(Id::new(1), Id::new(10), &output_dir, RecordingMode::Pcm);
+        let mut second = RecordingSession::new(Id::new(1), Id::new(10), &output_dir, RecordingMode::Pcm);
+        second.start_time = first.start_time;
+        first.add_audio(speaker, &[1, 2, 3]).await;
+        second.add_audio(speaker, &[4, 5, 6]).await;
+
+        let first_files = first.finalize(&[OutputFormat::Wav], &HashMap::new()).await.unwrap().remove(&OutputFormat::Wav).unwrap();
+        let second_files = second.finalize(&[OutputFormat::Wav], &HashMap::from([(Id::new(2), "Alice".to_string())])).await.unwrap().remove(&OutputFormat::Wav).unwrap();
+        assert_eq!(first_files.len(), 1);
+        assert_eq!(second_files.len(), 1);
+        assert_ne!(first_files[0], second_files[0]);
+
+        for file in first_files.iter().chain(&second_files) {
+            assert_eq!(parse_recording_filename(file).map(|name| name.user_id), Some(speaker));
+        }
+        let _ = std::fs::remove_dir_all(&output_dir);
+    }
+
+    #[tokio::test]
+    async fn test_finalize_writes_every_requested_format() {
+        let output_dir = std::env::temp_dir().join("diggy_gizzy_recorder_formats_test");
+        let _ = std::fs::remove_dir_all(&output_dir);
+        let session = RecordingSession::new(Id::new(1), Id::new(10), &output_dir.to_string_lossy(), RecordingMode::Pcm);
+        session.add_audio(Id::new(42), &[100; 2000]).await;
+        session.add_audio(Id::new(43), &[200; 2000]).await;
+
+        let outputs = session.finalize(&[OutputFormat::Wav, OutputFormat::Opus, OutputFormat::Mp3], &HashMap::new()).await.unwrap();
+        assert_eq!(outputs.len(), 3);
+        for (format, extension) in [(OutputFormat::Wav, "wav"), (OutputFormat::Opus, "ogg"), (OutputFormat::Mp3, "mp3")] {
+            let files = &outputs[&format];
+            assert_eq!(files.len(), 2);
+            for file in files {
+                assert!(file.ends_with(&format!(".{}", extension)));
+                assert!(std::path::Path::new(file).exists());
+            }
+        }
+
+        let _ = std::fs::remove_dir_all(&output_dir);
+    }
+
+    #[tokio::test]
+    async fn test_buffer_over_cap_is_spilled_to_disk() {
+        let output_dir = std::env::temp_dir().join("diggy_gizzy_recorder_spill_test");
+        let _ = std::fs::remove_dir_all(&output_dir);
+        let mut session = RecordingSession::new(Id::new(1), Id::new(10), &output_dir.to_string_lossy(), RecordingMode::Pcm);
+        session.max_buffered_samples = 100;
+        session.mixdown = Mixdown::Mono;
+        let speaker = Id::new(42);
+
+        session.add_audio(speaker, &[1; 60]).await;
+        session.add_audio(speaker, &[2; 60]).await;
+        // Going over the cap handed everything buffered so far to the spill writer
+        assert!(session.speaker_buffers.read().await[&speaker].is_empty());
+        session.add_audio(speaker, &[3; 30]).await;
+        assert_eq!(session.speaker_buffers.read().await[&speaker].len(), 30);
+        // Chunk positions keep counting across the spill
+        let starts: Vec<usize> = session.timeline_chunks(speaker).await.into_iter().map(|(start, _)| start).collect();
+        assert_eq!(starts, vec![0, 60, 120]);
+
+        let outputs = session.finalize(&[OutputFormat::Wav, OutputFormat::Opus], &HashMap::new()).await.unwrap();
+        let wavs = &outputs[&OutputFormat::Wav];
+        assert_eq!(wavs.len(), 1);
+        let samples = read_wav_samples(&wavs[0]);
+        assert_eq!(samples.len(), 150);
+        assert_eq!((samples[0], samples[60], samples[149]), (1, 2, 3));
+        assert_eq!(outputs[&OutputFormat::Opus].len(), 1);
+        // Per-speaker WAV, Ogg and the mixdown
+        assert_eq!(std::fs::read_dir(&output_dir).unwrap().count(), 3);
+
+        let _ = std::fs::remove_dir_all(&output_dir);
+    }
+
+    #[tokio::test]
+    async fn test_skipped_speakers_are_left_out_of_the_mixdown() {
+        let output_dir = std::env::temp_dir().join("diggy_gizzy_recorder_mix_skip_test");
+        let _ = std::fs::remove_dir_all(&output_dir);
+        let mut session = RecordingSession::new(Id::new(1), Id::new(10), &output_dir.to_string_lossy(), RecordingMode::Pcm);
+        session.mixdown = Mixdown::Mono;
+        session.min_audio = std::time::Duration::from_millis(100);
+        session.add_audio(Id::new(42), &[1000; 9600]).await;
+        session.add_audio(Id::new(43), &[-5000; 960]).await;
+
+        let outputs = session.finalize(&[OutputFormat::Wav], &HashMap::new()).await.unwrap();
+        assert_eq!(outputs[&OutputFormat::Wav].len(), 1);
+        let mixed = std::fs::read_dir(&output_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .find(|path| path.to_string_lossy().contains("_mixed_"))
+            .unwrap();
+        let samples = read_wav_samples(&mixed.to_string_lossy());
+        assert!(samples.iter().all(|&sample| sample >= 0));
+
+        let _ = std::fs::remove_dir_all(&output_dir);
+    }
+
+    #[test]
+    fn test_output_format_list() {
+        assert_eq!(OutputFormat::parse_list("wav, OPUS,wav,mp3,flac"), vec![OutputFormat::Wav, OutputFormat::Opus, OutputFormat::Mp3]);
+        assert!(OutputFormat::parse_list("").is_empty());
+
+        // WAV stays first so transcription always has a file to read
+        let manager = RecordingManager::new(std::env::temp_dir().to_string_lossy().to_string())
+            .with_formats(vec![OutputFormat::Opus]);
+        assert_eq!(manager.formats(), &[OutputFormat::Wav, OutputFormat::Opus]);
+    }
+
+    #[tokio::test]
+    async fn test_recordings_land_in_guild_subdirectory() {
+        let output_dir = std::env::temp_dir().join("diggy_gizzy_recorder_guild_dir_test");
+        let _ = std::fs::remove_dir_all(&output_dir);
+        let manager = RecordingManager::new(output_dir.to_string_lossy().to_string());
+
+        let session = manager.start_recording(Id::new(7), Id::new(10), None, None).await;
+        session.add_audio(Id::new(42), &[1, 2, 3]).await;
+        let files = session.finalize(&[OutputFormat::Wav], &HashMap::new()).await.unwrap().remove(&OutputFormat::Wav).unwrap();
+        assert_eq!(files.len(), 1);
+        assert!(std::path::Path::new(&files[0]).starts_with(output_dir.join("7")));
+
+        // An override replaces the per-guild default
+        let custom = output_dir.join("custom");
+        let session = manager.start_recording(Id::new(8), Id::new(10), Some(&custom.to_string_lossy()), None).await;
+        session.add_audio(Id::new(42), &[1, 2, 3]).await;
+        let files = session.finalize(&[OutputFormat::Wav], &HashMap::new()).await.unwrap().remove(&OutputFormat::Wav).unwrap();
+        assert!(std::path::Path::new(&files[0]).starts_with(&custom));
+
+        let _ = std::fs::remove_dir_all(&output_dir);
+    }
+
+    #[tokio::test]
+    async fn test_finalize_uses_manager_output_dir() {
+        let output_dir = std::env::temp_dir().join("diggy_gizzy_recorder_custom_dir_test");
+        let _ = std::fs::remove_dir_all(&output_dir);
+        let manager = RecordingManager::new(output_dir.to_string_lossy().to_string()).with_mixdown(Mixdown::Mono);
+
+        let session = manager.start_recording(Id::new(3), Id::new(10), None, None).await;
+        session.add_audio(Id::new(42), &[1, 2, 3]).await;
+        let stopped = manager.stop_recording(Id::new(3)).await.unwrap().unwrap();
+        let files = stopped.finalize(&[OutputFormat::Wav], &HashMap::new()).await.unwrap().remove(&OutputFormat::Wav).unwrap();
+
+        assert_eq!(files.len(), 1);
+        assert!(std::path::Path::new(&files[0]).exists());
+        // Per-speaker files and the mixdown both go to the manager's directory
+        let written = std::fs::read_dir(output_dir.join("3")).unwrap().count();
+        assert_eq!(written, 2);
+        assert!(!std::path::Path::new("./recordings/3").exists());
+
+        let _ = std::fs::remove_dir_all(&output_dir);
+    }
+
+    #[tokio::test]
+    async fn test_start_with_handler_registers_session() {
+        let output_dir = std::env::temp_dir().join("diggy_gizzy_recorder_test");
+        let manager = Arc::new(RecordingManager::new(output_dir.to_string_lossy().to_string()));
+        let guild_id = Id::new(1);
+
+        let handler = manager.start_with_handler(guild_id, Id::new(10), None, None).await;
+        assert!(manager.is_recording(guild_id).await);
+        assert_eq!(handler.guild_id, guild_id);
+
+        // Audio the handler forwards lands in the new session
+        handler.recording_manager.add_audio_to_session(guild_id, Id::new(5), &[1, 2, 3]).await;
+        let session = manager.stop_recording(guild_id).await.unwrap().unwrap();
+        assert_eq!(session.speaker_buffers.read().await[&Id::new(5)], vec![1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_preroll_keeps_each_frame_on_its_own_timeline_slot() {
+        let output_dir = std::env::temp_dir().join("diggy_gizzy_recorder_preroll_test");
+        let manager = Arc::new(RecordingManager::new(output_dir.to_string_lossy().to_string()));
+        let guild_id = Id::new(1);
+        let handler = manager.start_with_handler(guild_id, Id::new(10), None, None).await;
+
+        // Three frames arrive 20ms apart before anyone is known to be talking
+        let first = std::time::Instant::now();
+        {
+            let mut preroll = handler.pcm_preroll.lock().await;
+            for i in 0..3u32 {
+                preroll.push(7, (first + std::time::Duration::from_millis(20) * i, vec![1; 960]));
+            }
+        }
+        handler.map_speaker(7, Id::new(5)).await;
+
+        let session = manager.stop_recording(guild_id).await.unwrap().unwrap();
+        let chunks = session.timeline_chunks(Id::new(5)).await;
+        assert_eq!(chunks.len(), 3);
+        for pair in chunks.windows(2) {
+            assert_eq!(pair[1].0 - pair[0].0, 960);
+            assert_eq!(pair[1].1 - pair[0].1, 960);
+        }
+        let _ = std::fs::remove_dir_all(&output_dir);
+    }
+}
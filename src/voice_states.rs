@@ -0,0 +1,356 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::time::{Duration, Instant};
+use reqwest::Client as ReqwestClient;
+use serde::Deserialize;
+use tokio::sync::Mutex;
+use twilight_http::Client as HttpClient;
+use twilight_model::id::Id;
+use twilight_model::id::marker::{ChannelMarker, GuildMarker, UserMarker};
+
+/// How often the sweep task looks for stale entries
+pub const VOICE_STATE_SWEEP_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// Entries without a voice state update for this long are re-verified
+pub const VOICE_STATE_STALE_AFTER: Duration = Duration::from_secs(60 * 60);
+
+/// How long the bot waits for anyone to speak after joining before it leaves again
+pub const NO_AUDIO_GRACE: Duration = Duration::from_secs(2 * 60);
+
+/// Where a freshly joined session stands in its no-audio grace period
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JoinGrace {
+    /// Nothing heard yet; check again after this long
+    Waiting(Duration),
+    Heard,
+    /// The grace period ran out without any audio
+    Silent,
+}
+
+pub fn join_grace(elapsed: Duration, received_audio: bool, grace: Duration) -> JoinGrace {
+    if received_audio {
+        JoinGrace::Heard
+    } else if elapsed >= grace {
+        JoinGrace::Silent
+    } else {
+        JoinGrace::Waiting(grace - elapsed)
+    }
+}
+
+/// `/whoami`: where the tracker puts the user and whether the bot is connected.
+/// `tracked` is the user's guild, channel and time since the last update;
+/// `bot_channel` is songbird's channel, `Some(None)` when connected to an unknown channel.
+pub fn voice_state_summary(
+    user_id: Id<UserMarker>,
+    guild_id: Id<GuildMarker>,
+    tracked: Option<(Id<GuildMarker>, Id<ChannelMarker>, Duration)>,
+    bot_channel: Option<Option<Id<ChannelMarker>>>,
+    tracked_total: usize,
+) -> String {
+    let age = |elapsed: Duration| match elapsed.as_secs() {
+        secs if secs < 60 => format!("{}s ago", secs),
+        secs if secs < 60 * 60 => format!("{} min ago", secs / 60),
+        secs => format!("{} h ago", secs / (60 * 60)),
+    };
+    let mut lines = vec![format!("🔎 **Voice state for <@{}>**", user_id)];
+    lines.push(match tracked {
+        Some((tracked_guild, channel_id, elapsed)) if tracked_guild == guild_id => {
+            format!("• **You**: in <#{}> (updated {})", channel_id, age(elapsed))
+        }
+        Some((tracked_guild, _, elapsed)) => {
+            format!("• **You**: ⚠️ tracked in another server ({}, updated {})", tracked_guild, age(elapsed))
+        }
+        None => "• **You**: ❌ not tracked — rejoin the voice channel if you are in one".to_string(),
+    });
+    lines.push(match bot_channel {
+        Some(Some(channel_id)) => format!("• **Bot**: connected to <#{}>", channel_id),
+        Some(None) => "• **Bot**: connected, channel unknown".to_string(),
+        None => "• **Bot**: not connected in this server".to_string(),
+    });
+    if let (Some((tracked_guild, user_channel, _)), Some(Some(bot_channel))) = (tracked, bot_channel)
+        && tracked_guild == guild_id
+        && user_channel != bot_channel
+    {
+        lines.push("⚠️ You're not in the bot's voice channel".to_string());
+    }
+    lines.push(format!("Tracked voice states: {}", tracked_total));
+    lines.join("\n")
+}
+
+#[derive(Debug, Clone, Copy)]
+struct TrackedVoiceState {
+    guild_id: Id<GuildMarker>,
+    channel_id: Id<ChannelMarker>,
+    updated_at: Instant,
+}
+
+/// Which voice channel each user is in, kept from gateway voice state events.
+/// Missed leave events are corrected on `GuildCreate` and by the periodic sweep.
+pub struct VoiceStateTracker {
+    states: Mutex<HashMap<Id<UserMarker>, TrackedVoiceState>>,
+}
+
+/// Users whose entry hasn't been refreshed within `max_age`
+fn find_stale(
+    states: &HashMap<Id<UserMarker>, TrackedVoiceState>,
+    now: Instant,
+    max_age: Duration,
+) -> Vec<(Id<GuildMarker>, Id<UserMarker>)> {
+    states
+        .iter()
+        .filter(|(_, state)| now.saturating_duration_since(state.updated_at) >= max_age)
+        .map(|(user_id, state)| (state.guild_id, *user_id))
+        .collect()
+}
+
+/// What the sweep does with a stale entry once it knows where the user really is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SweepAction {
+    /// Still in the cached channel
+    Keep,
+    /// In voice, but a move was missed
+    Move(Id<ChannelMarker>),
+    /// No longer in voice (or the guild)
+    Prune,
+}
+
+pub fn sweep_action(cached: Id<ChannelMarker>, current: Option<Id<ChannelMarker>>) -> SweepAction {
+    match current {
+        Some(channel_id) if channel_id == cached => SweepAction::Keep,
+        Some(channel_id) => SweepAction::Move(channel_id),
+        None => SweepAction::Prune,
+    }
+}
+
+#[derive(Deserialize)]
+struct VoiceStateChannel {
+    channel_id: Option<Id<ChannelMarker>>,
+}
+
+/// The voice channel Discord has the user in, None when they aren't in voice in the
+/// guild. twilight-http has no request for this endpoint, so it's called directly.
+pub async fn fetch_voice_channel(
+    client: &ReqwestClient,
+    http: &HttpClient,
+    guild_id: Id<GuildMarker>,
+    user_id: Id<UserMarker>,
+) -> Result<Option<Id<ChannelMarker>>, Box<dyn Error + Send + Sync>> {
+    let token = http.token().ok_or("the HTTP client has no token")?;
+    let response = client
+        .get(format!("https://discord.com/api/v10/guilds/{}/voice-states/{}", guild_id, user_id))
+        .header(reqwest::header::AUTHORIZATION, token)
+        .send()
+        .await?;
+    // Unknown voice state or unknown member
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    if !response.status().is_success() {
+        return Err(format!("Discord returned {}", response.status()).into());
+    }
+    Ok(response.json::<VoiceStateChannel>().await?.channel_id)
+}
+
+impl VoiceStateTracker {
+    pub fn new() -> Self {
+        Self {
+            states: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Apply a voice state update; `None` means the user left voice
+    pub async fn update(
+        &self,
+        guild_id: Id<GuildMarker>,
+        user_id: Id<UserMarker>,
+        channel_id: Option<Id<ChannelMarker>>,
+    ) {
+        let mut states = self.states.lock().await;
+        match channel_id {
+            Some(channel_id) => {
+                states.insert(user_id, TrackedVoiceState { guild_id, channel_id, updated_at: Instant::now() });
+            }
+            None => {
+                // Only drop the entry if it belongs to this guild
+                if states.get(&user_id).is_some_and(|state| state.guild_id == guild_id) {
+                    states.remove(&user_id);
+                }
+            }
+        }
+    }
+
+    pub async fn channel_of(&self, user_id: Id<UserMarker>) -> Option<Id<ChannelMarker>> {
+        self.states.lock().await.get(&user_id).map(|state| state.channel_id)
+    }
+
+    /// The user's guild, channel and time since their entry was last updated
+    pub async fn lookup(&self, user_id: Id<UserMarker>) -> Option<(Id<GuildMarker>, Id<ChannelMarker>, Duration)> {
+        self.states
+            .lock()
+            .await
+            .get(&user_id)
+            .map(|state| (state.guild_id, state.channel_id, state.updated_at.elapsed()))
+    }
+
+    /// Users currently in a voice channel
+    pub async fn users_in(&self, channel_id: Id<ChannelMarker>) -> Vec<Id<UserMarker>> {
+        self.states
+            .lock()
+            .await
+            .iter()
+            .filter(|(_, state)| state.channel_id == channel_id)
+            .map(|(user_id, _)| *user_id)
+            .collect()
+    }
+
+    pub async fn len(&self) -> usize {
+        self.states.lock().await.len()
+    }
+
+    /// Replace everything known about a guild with the voice states from its `GuildCreate`
+    pub async fn reconcile_guild(
+        &self,
+        guild_id: Id<GuildMarker>,
+        voice_states: impl IntoIterator<Item = (Id<UserMarker>, Id<ChannelMarker>)>,
+    ) -> usize {
+        let now = Instant::now();
+        let mut states = self.states.lock().await;
+        let before = states.len();
+        states.retain(|_, state| state.guild_id != guild_id);
+        let pruned = before - states.len();
+        for (user_id, channel_id) in voice_states {
+            states.insert(user_id, TrackedVoiceState { guild_id, channel_id, updated_at: now });
+        }
+        pruned
+    }
+
+    /// Forget a guild the bot left or lost access to
+    pub async fn remove_guild(&self, guild_id: Id<GuildMarker>) -> usize {
+        let mut states = self.states.lock().await;
+        let before = states.len();
+        states.retain(|_, state| state.guild_id != guild_id);
+        before - states.len()
+    }
+
+    /// Forget everything, e.g. when a new gateway session starts and
+    /// GuildCreate will send the current voice states again
+    pub async fn clear(&self) -> usize {
+        let mut states = self.states.lock().await;
+        let cleared = states.len();
+        states.clear();
+        cleared
+    }
+
+    /// Entries that haven't been updated within `max_age`
+    pub async fn stale_entries(&self, max_age: Duration) -> Vec<(Id<GuildMarker>, Id<UserMarker>)> {
+        find_stale(&*self.states.lock().await, Instant::now(), max_age)
+    }
+
+    /// Mark an entry as verified so it isn't checked again until it goes stale
+    pub async fn touch(&self, user_id: Id<UserMarker>) {
+        if let Some(state) = self.states.lock().await.get_mut(&user_id) {
+            state.updated_at = Instant::now();
+        }
+    }
+
+    /// Remove a user, unless a fresh update arrived in the meantime
+    pub async fn prune(&self, user_id: Id<UserMarker>, max_age: Duration) -> bool {
+        let mut states = self.states.lock().await;
+        let is_stale = states
+            .get(&user_id)
+            .is_some_and(|state| state.updated_at.elapsed() >= max_age);
+        if is_stale {
+            states.remove(&user_id);
+        }
+        is_stale
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_join_grace() {
+        let grace = Duration::from_secs(90);
+        assert_eq!(join_grace(Duration::from_secs(30), false, grace), JoinGrace::Waiting(Duration::from_secs(60)));
+        assert_eq!(join_grace(Duration::from_secs(30), true, grace), JoinGrace::Heard);
+        // Joined, but nobody spoke within the grace period
+        assert_eq!(join_grace(Duration::from_secs(90), false, grace), JoinGrace::Silent);
+        assert_eq!(join_grace(Duration::from_secs(300), true, grace), JoinGrace::Heard);
+    }
+
+    #[test]
+    fn test_find_stale_entries() {
+        let now = Instant::now();
+        let guild: Id<GuildMarker> = Id::new(1);
+        let mut states = HashMap::new();
+        let entry = |updated_at| TrackedVoiceState { guild_id: guild, channel_id: Id::new(10), updated_at };
+
+        states.insert(Id::new(100), entry(now - Duration::from_secs(2 * 60 * 60)));
+        states.insert(Id::new(101), entry(now - Duration::from_secs(60)));
+        states.insert(Id::new(102), entry(now - VOICE_STATE_STALE_AFTER));
+
+        let mut stale = find_stale(&states, now, VOICE_STATE_STALE_AFTER);
+        stale.sort();
+        assert_eq!(stale, vec![(guild, Id::new(100)), (guild, Id::new(102))]);
+    }
+
+    #[test]
+    fn test_sweep_checks_the_cached_channel() {
+        let cached = Id::new(10);
+        assert_eq!(sweep_action(cached, Some(Id::new(10))), SweepAction::Keep);
+        // Still in the guild but in another channel: the move was missed
+        assert_eq!(sweep_action(cached, Some(Id::new(11))), SweepAction::Move(Id::new(11)));
+        assert_eq!(sweep_action(cached, None), SweepAction::Prune);
+    }
+
+    #[test]
+    fn test_voice_state_summary() {
+        let (user, guild) = (Id::new(100), Id::new(1));
+        let summary = voice_state_summary(user, guild, Some((guild, Id::new(10), Duration::from_secs(125))), Some(Some(Id::new(10))), 3);
+        assert_eq!(
+            summary,
+            "🔎 **Voice state for <@100>**\n• **You**: in <#10> (updated 2 min ago)\n• **Bot**: connected to <#10>\nTracked voice states: 3"
+        );
+
+        let elsewhere = voice_state_summary(user, guild, Some((guild, Id::new(11), Duration::from_secs(5))), Some(Some(Id::new(10))), 3);
+        assert!(elsewhere.contains("⚠️ You're not in the bot's voice channel"));
+
+        let untracked = voice_state_summary(user, guild, None, None, 0);
+        assert!(untracked.contains("• **You**: ❌ not tracked"));
+        assert!(untracked.contains("• **Bot**: not connected in this server"));
+
+        let other_guild = voice_state_summary(user, guild, Some((Id::new(2), Id::new(20), Duration::from_secs(7200))), Some(None), 1);
+        assert!(other_guild.contains("tracked in another server (2, updated 2 h ago)"));
+        assert!(other_guild.contains("connected, channel unknown"));
+        assert!(!other_guild.contains("not in the bot's voice channel"));
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_and_remove_guild() {
+        let tracker = VoiceStateTracker::new();
+        let (guild_a, guild_b) = (Id::new(1), Id::new(2));
+
+        tracker.update(guild_a, Id::new(100), Some(Id::new(10))).await;
+        tracker.update(guild_a, Id::new(101), Some(Id::new(10))).await;
+        tracker.update(guild_b, Id::new(200), Some(Id::new(20))).await;
+
+        // User 101's leave was missed; the GuildCreate payload no longer lists them
+        let pruned = tracker.reconcile_guild(guild_a, [(Id::new(100), Id::new(11))]).await;
+        assert_eq!(pruned, 2);
+        assert_eq!(tracker.channel_of(Id::new(100)).await, Some(Id::new(11)));
+        assert_eq!(tracker.channel_of(Id::new(101)).await, None);
+        assert_eq!(tracker.channel_of(Id::new(200)).await, Some(Id::new(20)));
+
+        assert_eq!(tracker.remove_guild(guild_b).await, 1);
+        assert_eq!(tracker.len().await, 1);
+
+        // A leave reported by another guild doesn't remove the entry
+        tracker.update(guild_b, Id::new(100), None).await;
+        assert_eq!(tracker.channel_of(Id::new(100)).await, Some(Id::new(11)));
+
+        // Fresh entries are never pruned
+        assert!(!tracker.prune(Id::new(100), VOICE_STATE_STALE_AFTER).await);
+    }
+}
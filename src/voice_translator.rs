@@ -1,12 +1,178 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
-use tokio::sync::{Mutex, RwLock};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::sync::{Mutex, Notify, RwLock};
 use twilight_model::id::Id;
+use twilight_model::channel::message::embed::EmbedField;
 use chrono::Local;
+use serde::{Deserialize, Serialize};
 use songbird::events::{EventContext, EventHandler as SongbirdEventHandler};
 
+use crate::preroll::Preroll;
+use crate::timezone;
+
 pub type SpeakerId = Id<twilight_model::id::marker::UserMarker>;
 
+/// Utterances kept per session for `/translate_history`
+pub const TRANSLATION_HISTORY_CAPACITY: usize = 50;
+
+/// Speech older than this is dropped instead of translated late
+pub const DEFAULT_STALENESS_WINDOW: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// How often the translation loop checks for finished utterances while people talk
+pub const ACTIVE_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+/// Silence after which the translation loop stops polling until someone speaks
+pub const IDLE_AFTER: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Whether nobody has spoken within `IDLE_AFTER` of `now`
+pub fn is_idle(last_activity: chrono::DateTime<Local>, now: chrono::DateTime<Local>) -> bool {
+    let quiet_for = now.signed_duration_since(last_activity).to_std().unwrap_or_default();
+    quiet_for >= IDLE_AFTER
+}
+
+/// Accepted pause lengths that end an utterance, in milliseconds
+pub const SILENCE_MS_RANGE: std::ops::RangeInclusive<u64> = 300..=5000;
+/// Accepted minimum utterance lengths, in milliseconds
+pub const MIN_SPEECH_MS_RANGE: std::ops::RangeInclusive<u64> = 100..=5000;
+
+/// When a speaker's buffered speech is handed to translation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "StoredTuning")]
+pub struct UtteranceTuning {
+    /// Pause that ends an utterance
+    pub silence_ms: u64,
+    /// Shorter utterances are kept buffered until the speaker says more
+    pub min_speech_ms: u64,
+}
+
+impl Default for UtteranceTuning {
+    fn default() -> Self {
+        Self { silence_ms: 1500, min_speech_ms: 500 }
+    }
+}
+
+/// Tuning as read from the guild config file, which may have been edited by hand
+#[derive(Deserialize)]
+struct StoredTuning {
+    silence_ms: u64,
+    min_speech_ms: u64,
+}
+
+/// Out-of-range values are clamped to the nearest one `/translate_tune` accepts
+impl From<StoredTuning> for UtteranceTuning {
+    fn from(stored: StoredTuning) -> Self {
+        let clamp = |value: u64, range: &std::ops::RangeInclusive<u64>| value.clamp(*range.start(), *range.end());
+        Self {
+            silence_ms: clamp(stored.silence_ms, &SILENCE_MS_RANGE),
+            min_speech_ms: clamp(stored.min_speech_ms, &MIN_SPEECH_MS_RANGE),
+        }
+    }
+}
+
+impl UtteranceTuning {
+    pub fn new(silence_ms: u64, min_speech_ms: u64) -> Result<Self, String> {
+        if !SILENCE_MS_RANGE.contains(&silence_ms) {
+            return Err(format!(
+                "Silence must be between {} and {} ms",
+                SILENCE_MS_RANGE.start(), SILENCE_MS_RANGE.end()
+            ));
+        }
+        if !MIN_SPEECH_MS_RANGE.contains(&min_speech_ms) {
+            return Err(format!(
+                "Minimum speech must be between {} and {} ms",
+                MIN_SPEECH_MS_RANGE.start(), MIN_SPEECH_MS_RANGE.end()
+            ));
+        }
+        Ok(Self { silence_ms, min_speech_ms })
+    }
+
+    fn min_samples(&self) -> usize {
+        // Buffers hold mono samples at songbird's decode rate
+        self.min_speech_ms as usize * crate::transcriber::DECODE_SAMPLE_RATE as usize / 1000
+    }
+}
+
+/// One posted utterance with every translation made of it
+#[derive(Debug, Clone)]
+pub struct TranslationHistoryEntry {
+    pub speaker: SpeakerId,
+    pub original: String,
+    /// `(target language, translation)` pairs, in target order
+    pub translations: Vec<(String, String)>,
+    pub timestamp: chrono::DateTime<Local>,
+}
+
+/// Render history entries oldest first: the original, then one line per translation
+pub fn format_translation_history(entries: &[TranslationHistoryEntry]) -> String {
+    entries
+        .iter()
+        .map(|entry| {
+            let mut lines = vec![format!(
+                "`{}` <@{}>: {}",
+                timezone::format(&entry.timestamp, "%H:%M:%S"),
+                entry.speaker,
+                entry.original
+            )];
+            lines.extend(entry.translations.iter().map(|(lang, translated)| {
+                format!("→ ({}) {}", lang.to_uppercase(), translated)
+            }));
+            lines.join("\n")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// One embed field per `(target language, translation)` pair, in target order
+pub fn translation_fields(translations: &[(String, String)]) -> Vec<EmbedField> {
+    translations
+        .iter()
+        .map(|(lang, translated)| EmbedField {
+            inline: false,
+            name: format!("🌐 Translation ({})", lang.to_uppercase()),
+            value: translated.clone(),
+        })
+        .collect()
+}
+
+/// Embed title naming the speaker and language direction, e.g. `Alice · JA → EN, DE`
+pub fn translation_title(speaker: &str, source_lang: &str, translations: &[(String, String)]) -> String {
+    let targets = translations
+        .iter()
+        .map(|(lang, _)| lang.to_uppercase())
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("{} · {} → {}", speaker, source_lang.to_uppercase(), targets)
+}
+
+/// Identical transcriptions from the same speaker within this window are treated as duplicates
+const DUPLICATE_WINDOW_MS: i64 = 10_000;
+
+/// Normalize a transcription for duplicate comparison (case, punctuation and spacing ignored)
+fn normalize_for_dedup(text: &str) -> String {
+    text.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(|c| c.to_lowercase())
+        .collect()
+}
+
+/// A speaker's last posted transcription and when it was posted
+type PostedText = (String, chrono::DateTime<Local>);
+
+/// Whether `text` repeats the speaker's previous transcription within the dedup window
+pub fn is_duplicate_transcription(
+    previous: Option<&PostedText>,
+    text: &str,
+    now: chrono::DateTime<Local>,
+) -> bool {
+    let Some((last_text, last_time)) = previous else {
+        return false;
+    };
+    let normalized = normalize_for_dedup(text);
+    !normalized.is_empty()
+        && normalized == normalize_for_dedup(last_text)
+        && now.signed_duration_since(*last_time).num_milliseconds() < DUPLICATE_WINDOW_MS
+}
+
 #[derive(Debug, Clone)]
 pub struct TranslationPair {
     pub source_lang: String,
@@ -61,6 +227,13 @@ impl TranslationBuffer {
         elapsed.num_milliseconds() > silence_duration_ms as i64
     }
 
+    /// Whether the utterance has waited longer than `window` since it became ready,
+    /// `silence_ms` after the speaker's last audio. How long it ran doesn't matter.
+    pub fn is_stale(&self, window: std::time::Duration, silence_ms: u64) -> bool {
+        let ready_at = self.last_activity + chrono::Duration::milliseconds(silence_ms as i64);
+        Local::now().signed_duration_since(ready_at).to_std().is_ok_and(|waited| waited > window)
+    }
+
     /// Check if minimum speech duration is met
     pub fn has_minimum_duration(&self, min_samples: usize) -> bool {
         self.samples.len() >= min_samples
@@ -83,6 +256,20 @@ pub struct TranslationSession {
     pub speaker_buffers: Arc<RwLock<HashMap<u32, TranslationBuffer>>>,
     /// SSRC to User ID mapping
     pub ssrc_to_user: Arc<RwLock<HashMap<u32, SpeakerId>>>,
+    /// Last posted transcription per speaker, for skipping duplicates
+    pub last_text: Arc<RwLock<HashMap<SpeakerId, PostedText>>>,
+    /// Recent translations, oldest first, capped at `TRANSLATION_HISTORY_CAPACITY`
+    pub history: Arc<RwLock<VecDeque<TranslationHistoryEntry>>>,
+    /// While set, finished utterances are dropped instead of translated
+    pub paused: Arc<AtomicBool>,
+    /// Set when the bot leaves: the translation loop flushes what's buffered and ends the session
+    pub draining: Arc<AtomicBool>,
+    /// Ready speech older than this is dropped or trimmed; zero keeps everything
+    pub staleness_window: std::time::Duration,
+    /// Changed live by `/translate_tune`
+    pub tuning: Arc<RwLock<UtteranceTuning>>,
+    /// Wakes an idle translation loop when audio arrives or the session is ending
+    pub wake: Arc<Notify>,
 }
 
 impl TranslationSession {
@@ -98,7 +285,39 @@ impl TranslationSession {
             start_time: Local::now(),
             speaker_buffers: Arc::new(RwLock::new(HashMap::new())),
             ssrc_to_user: Arc::new(RwLock::new(HashMap::new())),
+            last_text: Arc::new(RwLock::new(HashMap::new())),
+            history: Arc::new(RwLock::new(VecDeque::new())),
+            paused: Arc::new(AtomicBool::new(false)),
+            draining: Arc::new(AtomicBool::new(false)),
+            staleness_window: DEFAULT_STALENESS_WINDOW,
+            tuning: Arc::new(RwLock::new(UtteranceTuning::default())),
+            wake: Arc::new(Notify::new()),
+        }
+    }
+
+    /// Remember a posted translation, dropping the oldest beyond the cap
+    pub async fn push_history(&self, speaker: SpeakerId, original: &str, translations: &[(String, String)]) {
+        let mut history = self.history.write().await;
+        history.push_back(TranslationHistoryEntry {
+            speaker,
+            original: original.to_string(),
+            translations: translations.to_vec(),
+            timestamp: Local::now(),
+        });
+        while history.len() > TRANSLATION_HISTORY_CAPACITY {
+            history.pop_front();
+        }
+    }
+
+    /// Record a speaker's transcription, returning false if it duplicates the previous one
+    pub async fn check_and_record_text(&self, user_id: SpeakerId, text: &str) -> bool {
+        let now = Local::now();
+        let mut last_text = self.last_text.write().await;
+        if is_duplicate_transcription(last_text.get(&user_id), text, now) {
+            return false;
         }
+        last_text.insert(user_id, (text.to_string(), now));
+        true
     }
 
     /// Add audio samples from a speaker
@@ -113,6 +332,7 @@ impl TranslationSession {
         let mut buffers = self.speaker_buffers.write().await;
         let buffer = buffers.entry(ssrc).or_insert_with(|| TranslationBuffer::new(user_id));
         buffer.add_samples(samples);
+        self.wake.notify_one();
     }
 
     /// Mark silence for a speaker (called when VAD detects silence)
@@ -128,14 +348,19 @@ impl TranslationSession {
         let mut ready = Vec::new();
         let mut buffers = self.speaker_buffers.write().await;
         let ssrc_map = self.ssrc_to_user.read().await;
-        
-        // Silence duration: 1.5 seconds (1500ms)
-        // Minimum duration: 0.5 seconds at 48kHz = 24000 samples
-        const SILENCE_MS: u64 = 1500;
-        const MIN_SAMPLES: usize = 24000;
+        let tuning = *self.tuning.read().await;
 
         for (ssrc, buffer) in buffers.iter_mut() {
-            if buffer.should_flush(SILENCE_MS) && buffer.has_minimum_duration(MIN_SAMPLES) {
+            if buffer.should_flush(tuning.silence_ms) && buffer.has_minimum_duration(tuning.min_samples()) {
+                // Under load the loop falls behind; translating old speech is useless in real time
+                if !self.staleness_window.is_zero() && buffer.is_stale(self.staleness_window, tuning.silence_ms) {
+                    println!(
+                        "[INFO] Dropping stale translation buffer ({} samples) for SSRC {}",
+                        buffer.samples.len(), ssrc
+                    );
+                    buffer.clear();
+                    continue;
+                }
                 if let Some(&user_id) = ssrc_map.get(ssrc) {
                     ready.push((user_id, buffer.samples.clone()));
                     buffer.clear();
@@ -145,28 +370,72 @@ impl TranslationSession {
 
         ready
     }
+
+    /// When anyone was last heard, or when the session started if nobody has been
+    pub async fn last_activity(&self) -> chrono::DateTime<Local> {
+        let buffers = self.speaker_buffers.read().await;
+        buffers.values().map(|buffer| buffer.last_activity).fold(self.start_time, std::cmp::max)
+    }
+
+    /// Take every speaker's buffered audio, whether or not they've stopped talking
+    pub async fn take_all_buffers(&self) -> Vec<(SpeakerId, Vec<i16>)> {
+        let mut buffers = self.speaker_buffers.write().await;
+        let ssrc_map = self.ssrc_to_user.read().await;
+        let mut pending = Vec::new();
+        for (ssrc, buffer) in buffers.iter_mut() {
+            if buffer.samples.is_empty() {
+                continue;
+            }
+            if let Some(&user_id) = ssrc_map.get(ssrc) {
+                pending.push((user_id, std::mem::take(&mut buffer.samples)));
+            }
+            buffer.clear();
+        }
+        pending
+    }
 }
 
 /// Manages active translation sessions
 #[derive(Clone)]
 pub struct TranslationManager {
     active_sessions: Arc<RwLock<HashMap<Id<twilight_model::id::marker::GuildMarker>, TranslationSession>>>,
+    staleness_window: std::time::Duration,
+    preroll: std::time::Duration,
 }
 
 impl TranslationManager {
     pub fn new() -> Self {
         Self {
             active_sessions: Arc::new(RwLock::new(HashMap::new())),
+            staleness_window: DEFAULT_STALENESS_WINDOW,
+            preroll: crate::preroll::DEFAULT_PREROLL,
         }
     }
 
+    /// Audio kept from speakers before Discord says who they are (zero disables)
+    pub fn with_preroll(mut self, preroll: std::time::Duration) -> Self {
+        self.preroll = preroll;
+        self
+    }
+
+    /// Drop speech older than `window` instead of translating it (zero disables)
+    pub fn with_staleness_window(mut self, window: std::time::Duration) -> Self {
+        self.staleness_window = window;
+        self
+    }
+
+    pub fn staleness_window(&self) -> std::time::Duration {
+        self.staleness_window
+    }
+
     pub async fn start_translation(
         &self,
         guild_id: Id<twilight_model::id::marker::GuildMarker>,
         channel_id: Id<twilight_model::id::marker::ChannelMarker>,
         translation_pair: TranslationPair,
     ) -> TranslationSession {
-        let session = TranslationSession::new(guild_id, channel_id, translation_pair);
+        let mut session = TranslationSession::new(guild_id, channel_id, translation_pair);
+        session.staleness_window = self.staleness_window;
         let mut sessions = self.active_sessions.write().await;
         sessions.insert(guild_id, session.clone());
         println!("[INFO] Started translation session for guild {}", guild_id);
@@ -179,12 +448,81 @@ impl TranslationManager {
     ) -> Option<TranslationSession> {
         let mut sessions = self.active_sessions.write().await;
         let session = sessions.remove(&guild_id);
-        if session.is_some() {
+        if let Some(session) = &session {
+            session.wake.notify_one();
             println!("[INFO] Stopped translation session for guild {}", guild_id);
         }
         session
     }
 
+    /// Point the guild's active session at a new voice channel (e.g. the bot was moved).
+    /// Returns false if translation isn't active or the channel is unchanged.
+    pub async fn update_channel(
+        &self,
+        guild_id: Id<twilight_model::id::marker::GuildMarker>,
+        channel_id: Id<twilight_model::id::marker::ChannelMarker>,
+    ) -> bool {
+        let mut sessions = self.active_sessions.write().await;
+        match sessions.get_mut(&guild_id) {
+            Some(session) if session.channel_id != channel_id => {
+                println!("[INFO] Translation session for guild {} moved to channel {}", guild_id, channel_id);
+                session.channel_id = channel_id;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Voice channel the guild's active session is in
+    pub async fn session_channel(
+        &self,
+        guild_id: Id<twilight_model::id::marker::GuildMarker>,
+    ) -> Option<Id<twilight_model::id::marker::ChannelMarker>> {
+        let sessions = self.active_sessions.read().await;
+        sessions.get(&guild_id).map(|session| session.channel_id)
+    }
+
+    /// When the guild's active session started, to tell it apart from a later one
+    pub async fn session_start_time(
+        &self,
+        guild_id: Id<twilight_model::id::marker::GuildMarker>,
+    ) -> Option<chrono::DateTime<Local>> {
+        let sessions = self.active_sessions.read().await;
+        sessions.get(&guild_id).map(|session| session.start_time)
+    }
+
+    /// Wait until the translation loop should check the guild's session again:
+    /// `ACTIVE_POLL_INTERVAL` while people talk, or once the channel is idle, until
+    /// audio arrives or the session is ending. Returns whether the channel was idle.
+    pub async fn wait_for_work(&self, guild_id: Id<twilight_model::id::marker::GuildMarker>) -> bool {
+        let (idle, wake) = {
+            let sessions = self.active_sessions.read().await;
+            let Some(session) = sessions.get(&guild_id) else {
+                return false;
+            };
+            (is_idle(session.last_activity().await, Local::now()), session.wake.clone())
+        };
+        if idle {
+            // A wake-up sent since the check is kept as a permit, so it isn't missed
+            wake.notified().await;
+        } else {
+            tokio::time::sleep(ACTIVE_POLL_INTERVAL).await;
+        }
+        idle
+    }
+
+    /// Whether anyone has been heard in the guild's active session; None without a session
+    pub async fn received_audio(&self, guild_id: Id<twilight_model::id::marker::GuildMarker>) -> Option<bool> {
+        let sessions = self.active_sessions.read().await;
+        let session = sessions.get(&guild_id)?;
+        let heard = !session.ssrc_to_user.read().await.is_empty();
+        Some(heard)
+    }
+
+    pub async fn active_count(&self) -> usize {
+        self.active_sessions.read().await.len()
+    }
+
     pub async fn is_translating(&self, guild_id: Id<twilight_model::id::marker::GuildMarker>) -> bool {
         let sessions = self.active_sessions.read().await;
         sessions.contains_key(&guild_id)
@@ -203,16 +541,143 @@ impl TranslationManager {
         }
     }
 
+    /// Mark a speaker as silent without touching the SSRC mapping.
+    /// Unknown SSRCs are ignored; buffers are only created once real audio arrives.
+    pub async fn mark_silence(
+        &self,
+        guild_id: Id<twilight_model::id::marker::GuildMarker>,
+        ssrc: u32,
+    ) {
+        let sessions = self.active_sessions.read().await;
+        if let Some(session) = sessions.get(&guild_id) {
+            session.mark_silence(ssrc).await;
+        }
+    }
+
+    /// Whether a speaker's new transcription should be posted (false for duplicates)
+    pub async fn should_post_transcription(
+        &self,
+        guild_id: Id<twilight_model::id::marker::GuildMarker>,
+        user_id: SpeakerId,
+        text: &str,
+    ) -> bool {
+        let sessions = self.active_sessions.read().await;
+        match sessions.get(&guild_id) {
+            Some(session) => session.check_and_record_text(user_id, text).await,
+            None => true,
+        }
+    }
+
+    pub async fn record_translation(
+        &self,
+        guild_id: Id<twilight_model::id::marker::GuildMarker>,
+        speaker: SpeakerId,
+        original: &str,
+        translations: &[(String, String)],
+    ) {
+        let sessions = self.active_sessions.read().await;
+        if let Some(session) = sessions.get(&guild_id) {
+            session.push_history(speaker, original, translations).await;
+        }
+    }
+
+    /// Recent translations for the guild's active session, oldest first
+    pub async fn recent_history(
+        &self,
+        guild_id: Id<twilight_model::id::marker::GuildMarker>,
+    ) -> Option<Vec<TranslationHistoryEntry>> {
+        let sessions = self.active_sessions.read().await;
+        let session = sessions.get(&guild_id)?;
+        let history = session.history.read().await;
+        Some(history.iter().cloned().collect())
+    }
+
+    /// Apply new utterance thresholds to the guild's session.
+    /// Returns false if translation isn't active.
+    pub async fn set_tuning(
+        &self,
+        guild_id: Id<twilight_model::id::marker::GuildMarker>,
+        tuning: UtteranceTuning,
+    ) -> bool {
+        let sessions = self.active_sessions.read().await;
+        match sessions.get(&guild_id) {
+            Some(session) => {
+                *session.tuning.write().await = tuning;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Pause or resume posting for the guild's session.
+    /// Returns false if translation isn't active.
+    pub async fn set_paused(
+        &self,
+        guild_id: Id<twilight_model::id::marker::GuildMarker>,
+        paused: bool,
+    ) -> bool {
+        let sessions = self.active_sessions.read().await;
+        match sessions.get(&guild_id) {
+            Some(session) => {
+                session.paused.store(paused, Ordering::Relaxed);
+                println!("[INFO] Translation for guild {} {}", guild_id, if paused { "paused" } else { "resumed" });
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Ask the guild's translation loop to flush buffered speech and end the session.
+    /// Returns false if translation isn't active.
+    pub async fn request_drain(&self, guild_id: Id<twilight_model::id::marker::GuildMarker>) -> bool {
+        let sessions = self.active_sessions.read().await;
+        match sessions.get(&guild_id) {
+            Some(session) => {
+                session.draining.store(true, Ordering::Relaxed);
+                session.wake.notify_one();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// If a drain was requested, end the session and return everything still buffered
+    /// (nothing if paused). Returns None while the session should keep running.
+    pub async fn take_drained(
+        &self,
+        guild_id: Id<twilight_model::id::marker::GuildMarker>,
+    ) -> Option<Vec<(SpeakerId, Vec<i16>)>> {
+        let session = {
+            let mut sessions = self.active_sessions.write().await;
+            if !sessions.get(&guild_id)?.draining.load(Ordering::Relaxed) {
+                return None;
+            }
+            sessions.remove(&guild_id)?
+        };
+        println!("[INFO] Stopped translation session for guild {} after flushing buffers", guild_id);
+
+        let pending = session.take_all_buffers().await;
+        if session.paused.load(Ordering::Relaxed) {
+            return Some(Vec::new());
+        }
+        Some(pending)
+    }
+
     pub async fn get_ready_translations(
         &self,
         guild_id: Id<twilight_model::id::marker::GuildMarker>,
     ) -> Vec<(SpeakerId, Vec<i16>)> {
         let sessions = self.active_sessions.read().await;
-        if let Some(session) = sessions.get(&guild_id) {
-            session.get_ready_buffers().await
-        } else {
-            Vec::new()
+        let Some(session) = sessions.get(&guild_id) else {
+            return Vec::new();
+        };
+
+        // Keep draining while paused so buffers don't grow, but drop what was said
+        let ready = session.get_ready_buffers().await;
+        if session.paused.load(Ordering::Relaxed) {
+            return Vec::new();
         }
+        ready
     }
 }
 
@@ -222,6 +687,7 @@ pub struct VoiceTranslateHandler {
     pub translation_manager: Arc<TranslationManager>,
     pub guild_id: Id<twilight_model::id::marker::GuildMarker>,
     pub ssrc_to_user: Arc<Mutex<HashMap<u32, SpeakerId>>>,
+    preroll: Arc<Mutex<Preroll<Vec<i16>>>>,
 }
 
 impl VoiceTranslateHandler {
@@ -229,10 +695,39 @@ impl VoiceTranslateHandler {
         translation_manager: Arc<TranslationManager>,
         guild_id: Id<twilight_model::id::marker::GuildMarker>,
     ) -> Self {
+        let preroll = Preroll::new(translation_manager.preroll);
         Self {
             translation_manager,
             guild_id,
             ssrc_to_user: Arc::new(Mutex::new(HashMap::new())),
+            preroll: Arc::new(Mutex::new(preroll)),
+        }
+    }
+
+    /// Map an SSRC to its speaker and hand over the audio heard before the mapping
+    async fn map_speaker(&self, ssrc: u32, user_id: SpeakerId) {
+        // Held while flushing so newer audio can't overtake the pre-roll
+        let mut ssrc_map = self.ssrc_to_user.lock().await;
+        ssrc_map.insert(ssrc, user_id);
+        let frames = self.preroll.lock().await.take(ssrc);
+        if !frames.is_empty() {
+            println!("[DEBUG] Translation: recovered {} pre-roll frames for SSRC {}", frames.len(), ssrc);
+        }
+        for samples in frames {
+            self.translation_manager.add_audio_to_session(self.guild_id, ssrc, user_id, &samples).await;
+        }
+    }
+
+    async fn receive_audio(&self, ssrc: u32, samples: Vec<i16>) {
+        let ssrc_map = self.ssrc_to_user.lock().await;
+        match ssrc_map.get(&ssrc) {
+            Some(&user_id) => {
+                drop(ssrc_map);
+                self.translation_manager.add_audio_to_session(self.guild_id, ssrc, user_id, &samples).await;
+            }
+            None => {
+                self.preroll.lock().await.push(ssrc, samples);
+            }
         }
     }
 }
@@ -248,8 +743,7 @@ impl SongbirdEventHandler for VoiceTranslateHandler {
                     
                     println!("[DEBUG] Translation SpeakingStateUpdate: SSRC {} -> User {}", ssrc, user_id);
                     
-                    let mut ssrc_map = self.ssrc_to_user.lock().await;
-                    ssrc_map.insert(ssrc, user_id);
+                    self.map_speaker(ssrc, user_id).await;
                 }
             }
             EventContext::VoiceTick(tick) => {
@@ -258,25 +752,11 @@ impl SongbirdEventHandler for VoiceTranslateHandler {
                         let samples: Vec<i16> = audio.clone();
                         
                         if !samples.is_empty() {
-                            let ssrc_map = self.ssrc_to_user.lock().await;
-                            if let Some(&user_id) = ssrc_map.get(ssrc) {
-                                drop(ssrc_map);
-                                self.translation_manager.add_audio_to_session(
-                                    self.guild_id,
-                                    *ssrc,
-                                    user_id,
-                                    &samples,
-                                ).await;
-                            }
+                            self.receive_audio(*ssrc, samples).await;
                         }
                     } else {
                         // No audio data - mark as silence for VAD
-                        self.translation_manager.add_audio_to_session(
-                            self.guild_id,
-                            *ssrc,
-                            Id::new(0), // Placeholder, won't be used
-                            &[],
-                        ).await;
+                        self.translation_manager.mark_silence(self.guild_id, *ssrc).await;
                     }
                 }
             }
@@ -286,3 +766,264 @@ impl SongbirdEventHandler for VoiceTranslateHandler {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_translation_fields_one_per_target() {
+        let translations = vec![
+            ("ko".to_string(), "안녕하세요".to_string()),
+            ("en".to_string(), "Hello".to_string()),
+        ];
+        let fields = translation_fields(&translations);
+        assert_eq!(fields.len(), 2);
+        assert_eq!(fields[0].name, "🌐 Translation (KO)");
+        assert_eq!(fields[1].name, "🌐 Translation (EN)");
+        assert_eq!(fields[1].value, "Hello");
+    }
+
+    #[test]
+    fn test_translation_title_names_speaker() {
+        let one = vec![("en".to_string(), "Hello".to_string())];
+        assert_eq!(translation_title("Alice", "ja", &one), "Alice · JA → EN");
+
+        let two = vec![
+            ("en".to_string(), "Hello".to_string()),
+            ("de".to_string(), "Hallo".to_string()),
+        ];
+        assert_eq!(translation_title("User 42", "ja", &two), "User 42 · JA → EN, DE");
+    }
+
+    #[test]
+    fn test_duplicate_transcription_detection() {
+        let now = Local::now();
+        let previous = ("Hello, world!".to_string(), now - chrono::Duration::seconds(2));
+
+        assert!(!is_duplicate_transcription(None, "Hello, world!", now));
+        assert!(is_duplicate_transcription(Some(&previous), "Hello, world!", now));
+        // Near-identical after normalization
+        assert!(is_duplicate_transcription(Some(&previous), "hello world", now));
+        assert!(!is_duplicate_transcription(Some(&previous), "Hello there", now));
+
+        // The same phrase said again later is not a duplicate
+        let later = now + chrono::Duration::seconds(30);
+        assert!(!is_duplicate_transcription(Some(&previous), "Hello, world!", later));
+    }
+
+    #[tokio::test]
+    async fn test_history_keeps_newest_entries() {
+        let session = TranslationSession::new(Id::new(1), Id::new(2), TranslationPair::new("ja", "en"));
+        let speaker: SpeakerId = Id::new(3);
+
+        for i in 0..TRANSLATION_HISTORY_CAPACITY + 5 {
+            session.push_history(speaker, &format!("original {}", i), &[("en".to_string(), format!("translated {}", i))]).await;
+        }
+
+        let history = session.history.read().await;
+        assert_eq!(history.len(), TRANSLATION_HISTORY_CAPACITY);
+        assert_eq!(history.front().unwrap().original, "original 5");
+        assert_eq!(history.back().unwrap().translations[0].1, format!("translated {}", TRANSLATION_HISTORY_CAPACITY + 4));
+    }
+
+    #[tokio::test]
+    async fn test_history_keeps_every_target() {
+        let session = TranslationSession::new(Id::new(1), Id::new(2), TranslationPair::new("ja", "en"));
+        let translations = vec![
+            ("en".to_string(), "Good morning".to_string()),
+            ("ko".to_string(), "좋은 아침".to_string()),
+            ("en-gb".to_string(), "Morning".to_string()),
+        ];
+        session.push_history(Id::new(3), "おはよう", &translations).await;
+
+        let entries: Vec<_> = session.history.read().await.iter().cloned().collect();
+        assert_eq!(entries[0].translations, translations);
+        let formatted = format_translation_history(&entries);
+        assert!(formatted.ends_with("<@3>: おはよう\n→ (EN) Good morning\n→ (KO) 좋은 아침\n→ (EN-GB) Morning"));
+    }
+
+    #[tokio::test]
+    async fn test_update_channel_moves_active_session() {
+        let manager = TranslationManager::new();
+        let guild_id = Id::new(1);
+
+        assert!(!manager.update_channel(guild_id, Id::new(20)).await);
+
+        manager.start_translation(guild_id, Id::new(10), TranslationPair::new("ja", "en")).await;
+        assert!(manager.update_channel(guild_id, Id::new(20)).await);
+        assert_eq!(manager.session_channel(guild_id).await, Some(Id::new(20)));
+    }
+
+    #[tokio::test]
+    async fn test_silence_tick_for_unknown_ssrc_creates_no_buffer() {
+        let session = TranslationSession::new(Id::new(1), Id::new(2), TranslationPair::new("ja", "en"));
+
+        session.mark_silence(42).await;
+        assert!(session.speaker_buffers.read().await.is_empty());
+        assert!(session.ssrc_to_user.read().await.is_empty());
+        assert!(session.get_ready_buffers().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_silence_tick_keeps_real_speaker() {
+        let manager = TranslationManager::new();
+        let guild_id = Id::new(1);
+        let speaker: SpeakerId = Id::new(3);
+        let session = manager.start_translation(guild_id, Id::new(10), TranslationPair::new("ja", "en")).await;
+
+        // Silence before the speaker's first audio doesn't claim the SSRC
+        manager.mark_silence(guild_id, 42).await;
+        manager.add_audio_to_session(guild_id, 42, speaker, &[1; 100]).await;
+        manager.mark_silence(guild_id, 42).await;
+
+        let buffers = session.speaker_buffers.read().await;
+        assert_eq!(buffers.len(), 1);
+        assert_eq!(buffers[&42].user_id, speaker);
+        assert_eq!(buffers[&42].samples.len(), 100);
+        assert!(!buffers[&42].is_speaking);
+        assert_eq!(session.ssrc_to_user.read().await[&42], speaker);
+    }
+
+    #[tokio::test]
+    async fn test_paused_session_drains_without_translating() {
+        let manager = TranslationManager::new();
+        let guild_id = Id::new(1);
+        let speaker: SpeakerId = Id::new(3);
+        let session = manager.start_translation(guild_id, Id::new(10), TranslationPair::new("ja", "en")).await;
+
+        // One finished utterance: enough samples, and silent for longer than the flush delay
+        let finish_utterance = || async {
+            manager.add_audio_to_session(guild_id, 42, speaker, &[1; 24000]).await;
+            let mut buffers = session.speaker_buffers.write().await;
+            buffers.get_mut(&42).unwrap().last_activity = Local::now() - chrono::Duration::seconds(5);
+        };
+
+        assert!(manager.set_paused(guild_id, true).await);
+        finish_utterance().await;
+        assert!(manager.get_ready_translations(guild_id).await.is_empty());
+        assert!(session.speaker_buffers.read().await[&42].samples.is_empty());
+
+        assert!(manager.set_paused(guild_id, false).await);
+        assert!(manager.get_ready_translations(guild_id).await.is_empty());
+        finish_utterance().await;
+        let ready = manager.get_ready_translations(guild_id).await;
+        assert_eq!(ready.len(), 1);
+        assert_eq!(ready[0].0, speaker);
+        assert_eq!(ready[0].1.len(), 24000);
+
+        assert!(!manager.set_paused(Id::new(2), true).await);
+    }
+
+    #[tokio::test]
+    async fn test_drain_flushes_unfinished_speech() {
+        let manager = TranslationManager::new();
+        let guild_id = Id::new(1);
+        let speaker: SpeakerId = Id::new(3);
+        manager.start_translation(guild_id, Id::new(10), TranslationPair::new("ja", "en")).await;
+
+        // Still talking: nothing is ready and no drain was requested
+        manager.add_audio_to_session(guild_id, 42, speaker, &[1; 12000]).await;
+        assert!(manager.get_ready_translations(guild_id).await.is_empty());
+        assert!(manager.take_drained(guild_id).await.is_none());
+
+        assert!(manager.request_drain(guild_id).await);
+        let flushed = manager.take_drained(guild_id).await.unwrap();
+        assert_eq!(flushed, vec![(speaker, vec![1; 12000])]);
+
+        // The session ended with the drain
+        assert!(!manager.is_translating(guild_id).await);
+        assert!(!manager.request_drain(guild_id).await);
+    }
+
+    #[tokio::test]
+    async fn test_stale_buffers_are_dropped() {
+        let session = TranslationSession::new(Id::new(1), Id::new(10), TranslationPair::new("ja", "en"));
+        let (stale, recent): (SpeakerId, SpeakerId) = (Id::new(3), Id::new(4));
+        session.add_audio(1, stale, &[1; 48_000]).await;
+        session.add_audio(2, recent, &[2; 48_000 * 12]).await;
+        {
+            let now = Local::now();
+            let mut buffers = session.speaker_buffers.write().await;
+            // Finished speaking 20s ago: older than the window
+            buffers.get_mut(&1).unwrap().last_activity = now - chrono::Duration::seconds(20);
+            // A 12s monologue that just ended is translated whole
+            buffers.get_mut(&2).unwrap().last_activity = now - chrono::Duration::seconds(2);
+        }
+
+        let ready = session.get_ready_buffers().await;
+        assert_eq!(ready.len(), 1);
+        assert_eq!(ready[0].0, recent);
+        assert_eq!(ready[0].1.len(), 48_000 * 12);
+        assert!(session.speaker_buffers.read().await[&1].samples.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_idle_loop_waits_for_audio() {
+        let now = Local::now();
+        assert!(!is_idle(now - chrono::Duration::seconds(2), now));
+        assert!(is_idle(now - chrono::Duration::seconds(31), now));
+        // Clock skew putting activity in the future counts as active
+        assert!(!is_idle(now + chrono::Duration::seconds(1), now));
+
+        let session = TranslationSession::new(Id::new(1), Id::new(10), TranslationPair::new("ja", "en"));
+        assert_eq!(session.last_activity().await, session.start_time);
+        session.add_audio(1, Id::new(3), &[1; 960]).await;
+        session.speaker_buffers.write().await.get_mut(&1).unwrap().last_activity = session.start_time + chrono::Duration::seconds(40);
+        assert_eq!(session.last_activity().await, session.start_time + chrono::Duration::seconds(40));
+
+        // An idle loop sleeps until someone speaks, however long that takes
+        let manager = Arc::new(TranslationManager::new());
+        let guild_id = Id::new(1);
+        manager.start_translation(guild_id, Id::new(10), TranslationPair::new("ja", "en")).await;
+        manager.active_sessions.write().await.get_mut(&guild_id).unwrap().start_time = now - chrono::Duration::seconds(60);
+        let waiting = tokio::spawn({
+            let manager = manager.clone();
+            async move { manager.wait_for_work(guild_id).await }
+        });
+        tokio::task::yield_now().await;
+        assert!(!waiting.is_finished());
+        manager.add_audio_to_session(guild_id, 1, Id::new(3), &[1; 960]).await;
+        assert!(waiting.await.unwrap());
+
+        // Stopping wakes it too
+        manager.active_sessions.write().await.get_mut(&guild_id).unwrap().speaker_buffers.write().await.clear();
+        let waiting = tokio::spawn({
+            let manager = manager.clone();
+            async move { manager.wait_for_work(guild_id).await }
+        });
+        tokio::task::yield_now().await;
+        manager.stop_translation(guild_id).await;
+        assert!(waiting.await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_audio_before_speaker_mapping_is_kept() {
+        let manager = Arc::new(TranslationManager::new().with_preroll(std::time::Duration::from_millis(40)));
+        let guild_id = Id::new(1);
+        manager.start_translation(guild_id, Id::new(10), TranslationPair::new("ja", "en")).await;
+        let handler = VoiceTranslateHandler::new(manager.clone(), guild_id);
+
+        // Three frames arrive before Discord says who SSRC 5 is; only the last 40ms are kept
+        for sample in [1, 2, 3] {
+            handler.receive_audio(5, vec![sample; 960]).await;
+        }
+        assert_eq!(manager.received_audio(guild_id).await, Some(false));
+
+        handler.map_speaker(5, Id::new(3)).await;
+        handler.receive_audio(5, vec![4; 960]).await;
+        let sessions = manager.active_sessions.read().await;
+        let buffers = sessions[&guild_id].speaker_buffers.read().await;
+        let samples = &buffers[&5].samples;
+        assert_eq!(samples.len(), 960 * 3);
+        assert_eq!((samples[0], samples[960], samples[1920]), (2, 3, 4));
+    }
+
+    #[test]
+    fn test_duplicate_detection_japanese() {
+        let now = Local::now();
+        let previous = ("こんにちは。".to_string(), now);
+        assert!(is_duplicate_transcription(Some(&previous), "こんにちは", now));
+        assert!(!is_duplicate_transcription(Some(&previous), "こんばんは", now));
+    }
+}
@@ -0,0 +1,101 @@
+use std::fs::OpenOptions;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+/// Speaker display name
+pub const TAG_ARTIST: [u8; 4] = *b"IART";
+/// Recording title
+pub const TAG_NAME: [u8; 4] = *b"INAM";
+/// Session start time (RFC 3339)
+pub const TAG_CREATED: [u8; 4] = *b"ICRD";
+/// Guild, channel and user ids
+pub const TAG_COMMENT: [u8; 4] = *b"ICMT";
+pub const TAG_SOFTWARE: [u8; 4] = *b"ISFT";
+
+/// Encode a `LIST` chunk of type `INFO`. Values are NUL-terminated and every
+/// subchunk is padded to an even length, as RIFF requires.
+pub fn info_chunk(tags: &[([u8; 4], String)]) -> Vec<u8> {
+    let mut body = b"INFO".to_vec();
+    for (id, value) in tags {
+        let mut data: Vec<u8> = value.bytes().filter(|&b| b != 0).collect();
+        data.push(0);
+        body.extend_from_slice(id);
+        body.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        body.extend_from_slice(&data);
+        if data.len() % 2 == 1 {
+            body.push(0);
+        }
+    }
+
+    let mut chunk = b"LIST".to_vec();
+    chunk.extend_from_slice(&(body.len() as u32).to_le_bytes());
+    chunk.extend_from_slice(&body);
+    chunk
+}
+
+/// Append an `INFO` chunk to a finalized WAV and fix up the RIFF size.
+/// Readers skip chunks they don't know, so the audio stays readable everywhere.
+pub fn append_info_chunk(path: &Path, tags: &[([u8; 4], String)]) -> std::io::Result<()> {
+    let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+
+    let mut header = [0u8; 12];
+    file.read_exact(&mut header)?;
+    if &header[0..4] != b"RIFF" || &header[8..12] != b"WAVE" {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "not a RIFF/WAVE file"));
+    }
+
+    // A data chunk with an odd length is followed by a pad byte
+    let mut end = file.seek(SeekFrom::End(0))?;
+    if end % 2 == 1 {
+        file.write_all(&[0])?;
+        end += 1;
+    }
+
+    let chunk = info_chunk(tags);
+    file.write_all(&chunk)?;
+
+    let riff_size = (end + chunk.len() as u64 - 8) as u32;
+    file.seek(SeekFrom::Start(4))?;
+    file.write_all(&riff_size.to_le_bytes())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_info_chunk_keeps_wav_readable() {
+        let path = std::env::temp_dir().join(format!("wav_info_test_{}.wav", std::process::id()));
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: 48000,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+        for i in 0..480 {
+            writer.write_sample(i as i16).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        let tags = vec![
+            (TAG_ARTIST, "Alice".to_string()),
+            (TAG_COMMENT, "guild=1 channel=2 user=3".to_string()),
+        ];
+        append_info_chunk(&path, &tags).unwrap();
+
+        let bytes = std::fs::read(&path).unwrap();
+        let mut reader = hound::WavReader::open(&path).unwrap();
+        let samples: Vec<i16> = reader.samples().map(Result::unwrap).collect();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(samples.len(), 480);
+        assert_eq!(samples[479], 479);
+        assert_eq!(u32::from_le_bytes(bytes[4..8].try_into().unwrap()) as usize, bytes.len() - 8);
+        let chunk = info_chunk(&tags);
+        assert!(bytes.ends_with(&chunk));
+        assert!(bytes.windows(10).any(|w| w == b"IART\x06\0\0\0Al"));
+        assert_eq!(chunk.len() % 2, 0);
+    }
+}